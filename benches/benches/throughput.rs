@@ -0,0 +1,207 @@
+//! Decoding throughput of the built-in codecs across buffer sizes, read sizes and shift policies,
+//! compared against the equivalent [`tokio_util`] codecs.
+//!
+//! ```not_rust
+//! cargo bench --manifest-path benches/Cargo.toml
+//! ```
+
+use std::{convert::Infallible, hint::black_box};
+
+use bytes::BytesMut;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use framez::{
+    FramedRead,
+    blocking::block_on,
+    codec::{delimiter::Delimiter, lines::Lines},
+    decode::Decoder,
+    mock::ScriptedReader,
+    next,
+    state::ShiftPolicy,
+};
+use tokio_util::codec::{AnyDelimiterCodec, LengthDelimitedCodec, LinesCodec};
+
+const FRAMES: usize = 1024;
+const PAYLOAD_LEN: usize = 48;
+
+const BUFFER_SIZES: [usize; 3] = [64, 256, 4096];
+const READ_SIZES: [usize; 3] = [1, 64, 1024];
+
+/// Frames prefixed with their length as a big-endian `u16`.
+#[derive(Debug, Clone, Default)]
+struct LengthPrefixed;
+
+impl Decoder for LengthPrefixed {
+    type Item<'buf> = &'buf [u8];
+    type Error = Infallible;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let Some(len) = self.decode_header(src) else {
+            return Ok(None);
+        };
+
+        Ok(src.get(2..len).map(|payload| (payload, len)))
+    }
+
+    fn decode_header(&mut self, src: &[u8]) -> Option<usize> {
+        let prefix = src.get(..2)?.try_into().ok()?;
+
+        Some(usize::from(u16::from_be_bytes(prefix)) + 2)
+    }
+}
+
+/// The shift policies to compare for a buffer of `buffer_size` bytes.
+fn shift_policies(buffer_size: usize) -> [(&'static str, ShiftPolicy); 3] {
+    [
+        ("when-full", ShiftPolicy::WhenFull),
+        ("eager", ShiftPolicy::Eager),
+        ("above-half", ShiftPolicy::AboveThreshold(buffer_size / 2)),
+    ]
+}
+
+fn payload(index: usize) -> impl Iterator<Item = u8> {
+    (0..PAYLOAD_LEN).map(move |offset| b'a' + ((index + offset) % 26) as u8)
+}
+
+fn delimited(delimiter: &[u8]) -> Vec<u8> {
+    (0..FRAMES)
+        .flat_map(|index| payload(index).chain(delimiter.iter().copied()))
+        .collect()
+}
+
+fn length_prefixed() -> Vec<u8> {
+    (0..FRAMES)
+        .flat_map(|index| (PAYLOAD_LEN as u16).to_be_bytes().into_iter().chain(payload(index)))
+        .collect()
+}
+
+/// Decodes every frame of `input`, read at most `read_size` bytes at a time, and returns the number of frames.
+fn framez_frames<C>(
+    codec: C,
+    input: &[u8],
+    buffer: &mut [u8],
+    read_size: usize,
+    shift_policy: ShiftPolicy,
+) -> usize
+where
+    C: Decoder,
+{
+    let chunks = [input];
+    let reader = ScriptedReader::new(&chunks).with_max_read(read_size);
+
+    let mut framer = FramedRead::new(codec, reader, buffer);
+    framer.set_shift_policy(shift_policy);
+
+    block_on(async {
+        let mut frames = 0;
+
+        while let Some(item) = next!(framer) {
+            assert!(black_box(item).is_ok(), "Must decode");
+
+            frames += 1;
+        }
+
+        frames
+    })
+}
+
+/// Decodes every frame of `input`, fed `read_size` bytes at a time, and returns the number of frames.
+fn tokio_util_frames<D>(mut codec: D, input: &[u8], read_size: usize) -> usize
+where
+    D: tokio_util::codec::Decoder,
+    D::Error: core::fmt::Debug,
+{
+    let mut buffer = BytesMut::new();
+    let mut frames = 0;
+
+    for chunk in input.chunks(read_size) {
+        buffer.extend_from_slice(chunk);
+
+        while let Some(frame) = codec.decode(&mut buffer).expect("Must decode") {
+            black_box(frame);
+
+            frames += 1;
+        }
+    }
+
+    frames
+}
+
+fn bench_codec<C, D>(
+    c: &mut Criterion,
+    name: &str,
+    input: &[u8],
+    codec: impl Fn() -> C,
+    tokio_util_codec: impl Fn() -> D,
+) where
+    C: Decoder,
+    D: tokio_util::codec::Decoder,
+    D::Error: core::fmt::Debug,
+{
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+
+    for buffer_size in BUFFER_SIZES {
+        let buffer = &mut vec![0_u8; buffer_size];
+
+        for read_size in READ_SIZES {
+            for (policy_name, shift_policy) in shift_policies(buffer_size) {
+                assert_eq!(
+                    framez_frames(codec(), input, buffer, read_size, shift_policy),
+                    FRAMES
+                );
+
+                let id = format!("buffer-{buffer_size}/read-{read_size}/{policy_name}");
+
+                group.bench_function(BenchmarkId::new("framez", id), |b| {
+                    b.iter(|| framez_frames(codec(), input, buffer, read_size, shift_policy))
+                });
+            }
+        }
+    }
+
+    for read_size in READ_SIZES {
+        assert_eq!(tokio_util_frames(tokio_util_codec(), input, read_size), FRAMES);
+
+        let id = format!("read-{read_size}");
+
+        group.bench_function(BenchmarkId::new("tokio-util", id), |b| {
+            b.iter(|| tokio_util_frames(tokio_util_codec(), input, read_size))
+        });
+    }
+
+    group.finish();
+}
+
+fn lines(c: &mut Criterion) {
+    bench_codec(c, "lines", &delimited(b"\n"), Lines::new, LinesCodec::new);
+}
+
+fn delimiter(c: &mut Criterion) {
+    bench_codec(
+        c,
+        "delimiter",
+        &delimited(b"#"),
+        || Delimiter::new(b"#"),
+        || AnyDelimiterCodec::new(b"#".to_vec(), Vec::new()),
+    );
+}
+
+fn length_delimited(c: &mut Criterion) {
+    bench_codec(
+        c,
+        "length-prefixed",
+        &length_prefixed(),
+        || LengthPrefixed,
+        || {
+            LengthDelimitedCodec::builder()
+                .length_field_length(2)
+                .new_codec()
+        },
+    );
+}
+
+criterion_group!(benches, lines, delimiter, length_delimited);
+criterion_main!(benches);
@@ -4,12 +4,12 @@
 
 use core::error::Error;
 
-use embedded_io_adapters::tokio_1::FromTokio;
 use framez::{FramedRead, FramedWrite, next};
 use framez_demo::{
     codec::PacketCodec,
-    packet::Packet,
-    payload_content::{DeviceConfig, DeviceConfigAck, Heartbeat, HeartbeatAck, Init, InitAck},
+    payload_content::{
+        DeviceConfig, DeviceConfigAck, Heartbeat, HeartbeatAck, Init, InitAck, PayloadContent,
+    },
 };
 
 #[tokio::main]
@@ -21,7 +21,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let (read, write) = tokio::io::duplex(1024);
 
     let read_buf = &mut [0u8; 1024];
-    let mut framed_read = FramedRead::new(PacketCodec::new(), FromTokio::new(read), read_buf);
+    let mut framed_read = FramedRead::new_tokio(PacketCodec::new(), read, read_buf);
 
     let reader = async move {
         while let Some(packet) = next!(framed_read).transpose()? {
@@ -32,25 +32,25 @@ async fn main() -> Result<(), Box<dyn Error>> {
     };
 
     let write_buf = &mut [0u8; 1024];
-    let mut framed_write = FramedWrite::new(PacketCodec::new(), FromTokio::new(write), write_buf);
+    let mut framed_write = FramedWrite::new_tokio(PacketCodec::new(), write, write_buf);
 
     let writer = async move {
         let packets = std::vec![
-            Packet::new(Init {
+            PayloadContent::from(Init {
                 sequence_number: 0,
                 version: "1.0.0",
             }),
-            Packet::new(InitAck {
+            PayloadContent::from(InitAck {
                 sequence_number: 0,
                 version: "1.0.0",
             }),
-            Packet::new(Heartbeat { sequence_number: 1 }),
-            Packet::new(HeartbeatAck { sequence_number: 1 }),
-            Packet::new(DeviceConfig {
+            PayloadContent::from(Heartbeat { sequence_number: 1 }),
+            PayloadContent::from(HeartbeatAck { sequence_number: 1 }),
+            PayloadContent::from(DeviceConfig {
                 sequence_number: 2,
                 config: "very-important-config",
             }),
-            Packet::new(DeviceConfigAck { sequence_number: 2 })
+            PayloadContent::from(DeviceConfigAck { sequence_number: 2 })
         ];
 
         for packet in packets {
@@ -1,47 +1,13 @@
 //! Packet codec implementation module.
 
-use framez::{
-    decode::{DecodeError, Decoder},
-    encode::Encoder,
-};
+use crate::payload_content::PayloadContent;
 
-use super::packet::{Packet, PacketFromSliceError, PacketWriteError};
-
-/// A codec for encoding and decoding packets.
-#[derive(Debug, Default)]
-pub struct PacketCodec {}
-
-impl PacketCodec {
-    /// Creates a new [`PacketCodec`].
-    pub fn new() -> Self {
-        Default::default()
-    }
-}
-
-impl DecodeError for PacketCodec {
-    type Error = PacketFromSliceError;
-}
-
-impl<'buf> Decoder<'buf> for PacketCodec {
-    type Item = Packet<'buf>;
-
-    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
-        Packet::maybe_packet_from_prefix(src)
-    }
-}
-
-impl<'buf> Encoder<Packet<'buf>> for PacketCodec {
-    type Error = PacketWriteError;
-
-    fn encode(&mut self, item: Packet<'buf>, dst: &mut [u8]) -> Result<usize, Self::Error> {
-        item.write_to(dst)
-    }
-}
+/// A codec for encoding and decoding packets carrying a [`PayloadContent`].
+pub type PacketCodec = framez::packet::PacketCodec<PayloadContent<'static>>;
 
 #[cfg(test)]
 mod test {
-    use embedded_io_adapters::tokio_1::FromTokio;
-    use framez::{FramedRead, next};
+    use framez::{FramedRead, encode::Encoder, next};
     use tokio::io::AsyncWriteExt;
 
     use crate::{
@@ -56,21 +22,21 @@ mod test {
         init_tracing();
 
         let packets = std::vec![
-            Packet::new(Init {
+            PayloadContent::from(Init {
                 sequence_number: 0,
                 version: "1.0.0",
             }),
-            Packet::new(InitAck {
+            PayloadContent::from(InitAck {
                 sequence_number: 0,
                 version: "1.0.0",
             }),
-            Packet::new(Heartbeat { sequence_number: 1 }),
-            Packet::new(HeartbeatAck { sequence_number: 1 }),
-            Packet::new(DeviceConfig {
+            PayloadContent::from(Heartbeat { sequence_number: 1 }),
+            PayloadContent::from(HeartbeatAck { sequence_number: 1 }),
+            PayloadContent::from(DeviceConfig {
                 sequence_number: 2,
                 config: "very-important-config",
             }),
-            Packet::new(DeviceConfigAck { sequence_number: 2 })
+            PayloadContent::from(DeviceConfigAck { sequence_number: 2 })
         ];
 
         let decoder = PacketCodec::new();
@@ -93,7 +59,7 @@ mod test {
         });
 
         let mut read_buf = [0; 512];
-        let mut framer = FramedRead::new(decoder, FromTokio::new(read), &mut read_buf);
+        let mut framer = FramedRead::new_tokio(decoder, read, &mut read_buf);
 
         let mut index = 0;
 
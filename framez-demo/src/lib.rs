@@ -1,12 +1,8 @@
 #![no_std]
 
 pub mod codec;
-pub mod header;
-pub mod packet;
-pub mod payload;
 pub mod payload_content;
 pub mod payload_type;
-pub mod raw_packet;
 
 #[cfg(test)]
 mod tests;
@@ -1,6 +1,7 @@
 //! Payload content module.
 
 use derive_more::derive::From;
+use framez::packet::{PayloadDecode, PayloadEncode};
 use serde::{Deserialize, Serialize};
 
 use super::payload_type::PayloadType;
@@ -84,3 +85,73 @@ impl PayloadContent<'_> {
         }
     }
 }
+
+impl PayloadEncode for PayloadContent<'_> {
+    type Error = serde_json_core::ser::Error;
+
+    fn payload_type(&self) -> u16 {
+        PayloadContent::payload_type(self) as u16
+    }
+
+    fn encode_payload(&self, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        serde_json_core::to_slice(self, dst)
+    }
+}
+
+impl PayloadDecode for PayloadContent<'static> {
+    type Payload<'buf> = PayloadContent<'buf>;
+    type Error = serde_json_core::de::Error;
+
+    fn decode_payload<'buf>(
+        payload_type: u16,
+        src: &'buf [u8],
+    ) -> Result<Option<Self::Payload<'buf>>, Self::Error> {
+        let Some(payload_type) = PayloadType::from_u16(payload_type) else {
+            return Ok(None);
+        };
+
+        let content = match payload_type {
+            PayloadType::Init => from_json_slice::<Init<'buf>>(src),
+            PayloadType::InitAck => from_json_slice::<InitAck<'buf>>(src),
+            PayloadType::Heartbeat => from_json_slice::<Heartbeat>(src),
+            PayloadType::HeartbeatAck => from_json_slice::<HeartbeatAck>(src),
+            PayloadType::DeviceConfig => from_json_slice::<DeviceConfig<'buf>>(src),
+            PayloadType::DeviceConfigAck => from_json_slice::<DeviceConfigAck>(src),
+        }?;
+
+        Ok(Some(content))
+    }
+}
+
+/// Returns the payload content (mapped from `T`) from the given JSON slice.
+fn from_json_slice<'a, T>(src: &'a [u8]) -> Result<PayloadContent<'a>, serde_json_core::de::Error>
+where
+    T: Deserialize<'a>,
+    PayloadContent<'a>: From<T>,
+{
+    serde_json_core::from_slice::<T>(src).map(|(de, _)| PayloadContent::from(de))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encode_decode() {
+        let buf = &mut [0; 100];
+
+        let content = PayloadContent::DeviceConfig(DeviceConfig {
+            sequence_number: 12,
+            config: "config",
+        });
+
+        let written = content.encode_payload(buf).expect("Must be ok");
+
+        let reconstructed =
+            PayloadContent::decode_payload(PayloadType::DeviceConfig as u16, &buf[..written])
+                .expect("Must be ok")
+                .expect("Must be some");
+
+        assert_eq!(reconstructed, content);
+    }
+}
@@ -0,0 +1,619 @@
+//! # framez-derive
+//!
+//! Derive macros for [`framez`](https://docs.rs/framez/latest/framez/)'s `FrameEncode` and `FrameDecode` traits.
+//!
+//! Use them through `framez::frame` with the `derive` feature of `framez`.
+
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    Data, DataEnum, DataStruct, DeriveInput, Expr, Fields, GenericParam, Generics, Ident, Lifetime,
+    LifetimeParam, LitInt, Member, Type, parse_macro_input, spanned::Spanned,
+};
+
+/// Derives `framez::frame::FrameEncode` for a packet struct, or `FrameEncode` and `Tagged` for a tagged payload enum.
+///
+/// See [`FrameDecode`](derive@FrameDecode) for the attributes.
+#[proc_macro_derive(FrameEncode, attributes(frame))]
+pub fn derive_frame_encode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let expanded = match &input.data {
+        Data::Struct(data) => Packet::parse(data).map(|packet| packet.encode(&input)),
+        Data::Enum(data) => Variants::parse(data).map(|variants| variants.encode(&input)),
+        Data::Union(_) => Err(syn::Error::new(
+            input.ident.span(),
+            "unions are not supported",
+        )),
+    };
+
+    expanded
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `framez::frame::FrameDecode` for a packet struct, or `TaggedDecode` for a tagged payload enum.
+///
+/// # Structs
+///
+/// The fields are laid out in order. Every field is a `zerocopy` type, e.g. `u8` or `zerocopy::big_endian::U16`, except for the payload.
+/// The attributes mark the fields that are filled in when encoding and checked when decoding:
+///
+/// - `#[frame(length)]`: The total length of the frame, including the header. The type implements `IntField`.
+/// - `#[frame(tag)]`: The type tag of the payload. The type implements `IntField`. Requires a payload.
+/// - `#[frame(checksum = <expr>)]`: The checksum of the frame, computed with the field zeroed.
+///   The expression creates a `framez::checksum::Checksum`, e.g. `Crc32::iso_hdlc()`. The type implements `IntField`.
+/// - `#[frame(payload)]`: The last field. Implements `FrameEncode` and `FrameDecode`, or `Tagged` and `TaggedDecode` if the frame has a tag.
+///   Without a length field, the payload determines where the frame ends.
+///
+/// # Enums
+///
+/// Every variant has a `#[frame(tag = <integer>)]` and holds a single payload implementing `FrameEncode` and `FrameDecode`, or nothing.
+#[proc_macro_derive(FrameDecode, attributes(frame))]
+pub fn derive_frame_decode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let expanded = match &input.data {
+        Data::Struct(data) => Packet::parse(data).and_then(|packet| packet.decode(&input)),
+        Data::Enum(data) => Variants::parse(data).and_then(|variants| variants.decode(&input)),
+        Data::Union(_) => Err(syn::Error::new(
+            input.ident.span(),
+            "unions are not supported",
+        )),
+    };
+
+    expanded
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// The role of a field of a packet struct.
+enum Kind {
+    Plain,
+    Length,
+    Tag,
+    Checksum(Expr),
+    Payload,
+}
+
+struct Field {
+    member: Member,
+    ty: Type,
+    kind: Kind,
+    /// The local variable holding the field.
+    var: Ident,
+    /// The local variable holding the offset of the field in the frame.
+    at: Ident,
+}
+
+struct Packet {
+    fields: Vec<Field>,
+    named: bool,
+}
+
+impl Packet {
+    fn parse(data: &DataStruct) -> syn::Result<Self> {
+        let mut fields = Vec::new();
+
+        for (index, field) in data.fields.iter().enumerate() {
+            let mut kind = Kind::Plain;
+
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("frame"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if !matches!(kind, Kind::Plain) {
+                        return Err(meta.error("a field can have only one role"));
+                    }
+
+                    if meta.path.is_ident("length") {
+                        kind = Kind::Length;
+                    } else if meta.path.is_ident("tag") {
+                        kind = Kind::Tag;
+                    } else if meta.path.is_ident("payload") {
+                        kind = Kind::Payload;
+                    } else if meta.path.is_ident("checksum") {
+                        kind = Kind::Checksum(meta.value()?.parse()?);
+                    } else {
+                        return Err(meta
+                            .error("expected `length`, `tag`, `checksum = <expr>` or `payload`"));
+                    }
+
+                    Ok(())
+                })?;
+            }
+
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(index.into()),
+            };
+
+            fields.push(Field {
+                member,
+                ty: field.ty.clone(),
+                kind,
+                var: format_ident!("__field_{}", index),
+                at: format_ident!("__at_{}", index),
+            });
+        }
+
+        let packet = Self {
+            fields,
+            named: matches!(data.fields, Fields::Named(_)),
+        };
+
+        packet.validate(data)?;
+
+        Ok(packet)
+    }
+
+    fn validate(&self, data: &DataStruct) -> syn::Result<()> {
+        let count =
+            |f: fn(&Kind) -> bool| self.fields.iter().filter(|field| f(&field.kind)).count();
+
+        for (name, count) in [
+            ("length", count(|kind| matches!(kind, Kind::Length))),
+            ("tag", count(|kind| matches!(kind, Kind::Tag))),
+            ("checksum", count(|kind| matches!(kind, Kind::Checksum(_)))),
+            ("payload", count(|kind| matches!(kind, Kind::Payload))),
+        ] {
+            if count > 1 {
+                return Err(syn::Error::new(
+                    data.fields.span(),
+                    format!("at most one `{name}` field is allowed"),
+                ));
+            }
+        }
+
+        if let Some(position) = self
+            .fields
+            .iter()
+            .position(|field| matches!(field.kind, Kind::Payload))
+        {
+            if position + 1 != self.fields.len() {
+                return Err(syn::Error::new(
+                    self.fields[position].ty.span(),
+                    "the `payload` field must be the last field",
+                ));
+            }
+        }
+
+        if self.tag().is_some() && self.payload().is_none() {
+            return Err(syn::Error::new(
+                data.fields.span(),
+                "a `tag` field requires a `payload` field",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn header(&self) -> impl Iterator<Item = &Field> {
+        self.fields
+            .iter()
+            .filter(|field| !matches!(field.kind, Kind::Payload))
+    }
+
+    fn find(&self, f: impl Fn(&Kind) -> bool) -> Option<&Field> {
+        self.fields.iter().find(|field| f(&field.kind))
+    }
+
+    fn length(&self) -> Option<&Field> {
+        self.find(|kind| matches!(kind, Kind::Length))
+    }
+
+    fn tag(&self) -> Option<&Field> {
+        self.find(|kind| matches!(kind, Kind::Tag))
+    }
+
+    fn checksum(&self) -> Option<(&Field, &Expr)> {
+        self.fields.iter().find_map(|field| match &field.kind {
+            Kind::Checksum(expr) => Some((field, expr)),
+            _ => None,
+        })
+    }
+
+    fn payload(&self) -> Option<&Field> {
+        self.find(|kind| matches!(kind, Kind::Payload))
+    }
+
+    fn encode(&self, input: &DeriveInput) -> TokenStream2 {
+        let name = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let header = self.header().map(|field| {
+            let Field { member, ty, at, .. } = field;
+
+            let write = match &field.kind {
+                Kind::Plain => quote! {
+                    ::framez::frame::__private::write::<#ty>(dst, #at, &self.#member)?
+                },
+                Kind::Tag => {
+                    let payload = self.payload().map(|payload| &payload.member);
+
+                    quote! {
+                        ::framez::frame::__private::write_int::<#ty>(
+                            dst,
+                            #at,
+                            ::framez::frame::Tagged::tag(&self.#payload),
+                        )?
+                    }
+                }
+                // Filled in once the whole frame is encoded.
+                Kind::Length | Kind::Checksum(_) => quote! {
+                    ::framez::frame::__private::write_int::<#ty>(dst, #at, 0)?
+                },
+                Kind::Payload => unreachable!("the payload is not a part of the header"),
+            };
+
+            quote! {
+                let #at = __offset;
+                __offset += #write;
+            }
+        });
+
+        let payload = self.payload().map(|Field { member, .. }| {
+            quote! {
+                __offset += ::framez::frame::FrameEncode::encode_frame(
+                    &self.#member,
+                    dst.get_mut(__offset..).unwrap_or_default(),
+                )?;
+            }
+        });
+
+        let length = self.length().map(|Field { ty, at, .. }| {
+            quote! {
+                ::framez::frame::__private::write_int::<#ty>(dst, #at, __offset as u64)?;
+            }
+        });
+
+        let checksum = self.checksum().map(|(Field { ty, at, .. }, expr)| {
+            quote! {
+                let __checksum = ::framez::frame::__private::checksum(
+                    #expr,
+                    dst.get(..__offset).unwrap_or_default(),
+                    #at,
+                    ::core::mem::size_of::<#ty>(),
+                );
+
+                ::framez::frame::__private::write_int::<#ty>(dst, #at, __checksum)?;
+            }
+        });
+
+        quote! {
+            impl #impl_generics ::framez::frame::FrameEncode for #name #ty_generics #where_clause {
+                #[allow(unused_mut, unused_variables)]
+                fn encode_frame(&self, dst: &mut [u8]) -> ::core::result::Result<usize, ::framez::frame::FrameError> {
+                    let mut __offset = 0_usize;
+
+                    #(#header)*
+                    #payload
+                    #length
+                    #checksum
+
+                    ::core::result::Result::Ok(__offset)
+                }
+            }
+        }
+    }
+
+    fn decode(&self, input: &DeriveInput) -> syn::Result<TokenStream2> {
+        let name = &input.ident;
+        let (generics, lifetime) = decode_generics(&input.generics)?;
+        let (impl_generics, _, _) = generics.split_for_impl();
+        let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let header = self.header().map(|Field { ty, var, at, .. }| {
+            quote! {
+                let #at = __offset;
+                let ::core::option::Option::Some(#var) = ::framez::frame::__private::read::<#ty>(src, #at) else {
+                    return ::core::result::Result::Ok(::core::option::Option::None);
+                };
+                __offset += ::core::mem::size_of::<#ty>();
+            }
+        });
+
+        let verify = self.checksum().map(|(Field { ty, var, at, .. }, expr)| {
+            quote! {
+                let __checksum = ::framez::frame::__private::checksum(
+                    #expr,
+                    __frame,
+                    #at,
+                    ::core::mem::size_of::<#ty>(),
+                );
+
+                if ::framez::frame::IntField::to_u64(&#var) != __checksum {
+                    return ::core::result::Result::Err(::framez::frame::FrameError::Checksum);
+                }
+            }
+        });
+
+        let decode_payload = self.payload().map(|Field { ty, .. }| match self.tag() {
+            Some(Field { var: tag, .. }) => quote! {
+                <#ty as ::framez::frame::TaggedDecode<#lifetime>>::decode_tagged(
+                    ::framez::frame::IntField::to_u64(&#tag),
+                    __payload,
+                )?
+            },
+            None => quote! {
+                <#ty as ::framez::frame::FrameDecode<#lifetime>>::decode_frame(__payload)?
+            },
+        });
+
+        let body = match (self.length(), self.payload()) {
+            (Some(Field { var: length, .. }), payload) => {
+                let payload = payload.map(|Field { var, .. }| {
+                    quote! {
+                        let __payload = __frame.get(__offset..).unwrap_or_default();
+
+                        // The frame is complete, so the payload must be too.
+                        let ::core::option::Option::Some((#var, _)) = #decode_payload else {
+                            return ::core::result::Result::Err(::framez::frame::FrameError::InvalidLength);
+                        };
+                    }
+                });
+
+                quote! {
+                    let __frame_len = usize::try_from(::framez::frame::IntField::to_u64(&#length))
+                        .map_err(|_| ::framez::frame::FrameError::InvalidLength)?;
+
+                    if __frame_len < __offset {
+                        return ::core::result::Result::Err(::framez::frame::FrameError::InvalidLength);
+                    }
+
+                    let ::core::option::Option::Some(__frame) = src.get(..__frame_len) else {
+                        return ::core::result::Result::Ok(::core::option::Option::None);
+                    };
+
+                    #verify
+                    #payload
+                }
+            }
+            (None, Some(Field { var, .. })) => quote! {
+                let __payload = src.get(__offset..).unwrap_or_default();
+
+                let ::core::option::Option::Some((#var, __payload_len)) = #decode_payload else {
+                    return ::core::result::Result::Ok(::core::option::Option::None);
+                };
+
+                let __frame_len = __offset + __payload_len;
+                let __frame = src.get(..__frame_len).unwrap_or_default();
+
+                #verify
+            },
+            (None, None) => quote! {
+                let __frame_len = __offset;
+                let __frame = src.get(..__frame_len).unwrap_or_default();
+
+                #verify
+            },
+        };
+
+        let vars = self
+            .fields
+            .iter()
+            .map(|Field { member, var, .. }| match member {
+                Member::Named(ident) => quote! { #ident: #var },
+                Member::Unnamed(_) => quote! { #var },
+            });
+
+        let item = if self.named {
+            quote! { Self { #(#vars),* } }
+        } else {
+            quote! { Self ( #(#vars),* ) }
+        };
+
+        let frame_len = self.length().map(|Field { ty, .. }| {
+            let before = self
+                .fields
+                .iter()
+                .take_while(|field| !matches!(field.kind, Kind::Length))
+                .map(|Field { ty, .. }| quote! { + ::core::mem::size_of::<#ty>() });
+
+            quote! {
+                fn frame_len(src: &[u8]) -> ::core::option::Option<usize> {
+                    let __field = ::framez::frame::__private::read::<#ty>(src, 0 #(#before)*)?;
+
+                    usize::try_from(::framez::frame::IntField::to_u64(&__field)).ok()
+                }
+            }
+        });
+
+        Ok(quote! {
+            impl #impl_generics ::framez::frame::FrameDecode<#lifetime> for #name #ty_generics #where_clause {
+                #[allow(unused_mut, unused_variables)]
+                fn decode_frame(src: &#lifetime [u8]) -> ::core::result::Result<::core::option::Option<(Self, usize)>, ::framez::frame::FrameError> {
+                    let mut __offset = 0_usize;
+
+                    #(#header)*
+                    #body
+
+                    ::core::result::Result::Ok(::core::option::Option::Some((#item, __frame_len)))
+                }
+
+                #frame_len
+            }
+        })
+    }
+}
+
+struct Variant {
+    ident: Ident,
+    tag: LitInt,
+    ty: Option<Type>,
+}
+
+struct Variants {
+    variants: Vec<Variant>,
+}
+
+impl Variants {
+    fn parse(data: &DataEnum) -> syn::Result<Self> {
+        let mut variants = Vec::<Variant>::new();
+
+        for variant in &data.variants {
+            let mut tag = None;
+
+            for attr in variant
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("frame"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("tag") {
+                        let lit: LitInt = meta.value()?.parse()?;
+                        let value = lit.base10_parse::<u64>()?;
+
+                        tag = Some(LitInt::new(&format!("{value}_u64"), lit.span()));
+
+                        return Ok(());
+                    }
+
+                    Err(meta.error("expected `tag = <integer>`"))
+                })?;
+            }
+
+            let Some(tag) = tag else {
+                return Err(syn::Error::new(
+                    variant.ident.span(),
+                    "every variant needs a `#[frame(tag = <integer>)]`",
+                ));
+            };
+
+            if let Some(other) = variants
+                .iter()
+                .find(|other| other.tag.to_string() == tag.to_string())
+            {
+                return Err(syn::Error::new(
+                    tag.span(),
+                    format!("the tag is already used by `{}`", other.ident),
+                ));
+            }
+
+            let ty = match &variant.fields {
+                Fields::Unit => None,
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    fields.unnamed.first().map(|field| field.ty.clone())
+                }
+                fields => {
+                    return Err(syn::Error::new(
+                        fields.span(),
+                        "a variant holds a single unnamed payload or nothing",
+                    ));
+                }
+            };
+
+            variants.push(Variant {
+                ident: variant.ident.clone(),
+                tag,
+                ty,
+            });
+        }
+
+        Ok(Self { variants })
+    }
+
+    fn encode(&self, input: &DeriveInput) -> TokenStream2 {
+        let name = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let tags = self
+            .variants
+            .iter()
+            .map(|Variant { ident, tag, ty }| match ty {
+                Some(_) => quote! { Self::#ident(_) => #tag },
+                None => quote! { Self::#ident => #tag },
+            });
+
+        let encodes = self.variants.iter().map(|Variant { ident, ty, .. }| match ty {
+            Some(_) => quote! { Self::#ident(payload) => ::framez::frame::FrameEncode::encode_frame(payload, dst) },
+            None => quote! { Self::#ident => ::core::result::Result::Ok(0) },
+        });
+
+        // An enum without variants has no values, so neither method is ever called.
+        let (tag, encode) = if self.variants.is_empty() {
+            (quote! { match *self {} }, quote! { match *self {} })
+        } else {
+            (
+                quote! { match self { #(#tags,)* } },
+                quote! { match self { #(#encodes,)* } },
+            )
+        };
+
+        quote! {
+            impl #impl_generics ::framez::frame::Tagged for #name #ty_generics #where_clause {
+                fn tag(&self) -> u64 {
+                    #tag
+                }
+            }
+
+            impl #impl_generics ::framez::frame::FrameEncode for #name #ty_generics #where_clause {
+                fn encode_frame(&self, dst: &mut [u8]) -> ::core::result::Result<usize, ::framez::frame::FrameError> {
+                    #encode
+                }
+            }
+        }
+    }
+
+    fn decode(&self, input: &DeriveInput) -> syn::Result<TokenStream2> {
+        let name = &input.ident;
+        let (generics, lifetime) = decode_generics(&input.generics)?;
+        let (impl_generics, _, _) = generics.split_for_impl();
+        let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let arms = self.variants.iter().map(|Variant { ident, tag, ty }| match ty {
+            Some(ty) => quote! {
+                #tag => ::core::result::Result::Ok(
+                    <#ty as ::framez::frame::FrameDecode<#lifetime>>::decode_frame(src)?
+                        .map(|(payload, size)| (Self::#ident(payload), size)),
+                )
+            },
+            None => quote! {
+                #tag => ::core::result::Result::Ok(::core::option::Option::Some((Self::#ident, 0)))
+            },
+        });
+
+        Ok(quote! {
+            impl #impl_generics ::framez::frame::TaggedDecode<#lifetime> for #name #ty_generics #where_clause {
+                fn decode_tagged(tag: u64, src: &#lifetime [u8]) -> ::core::result::Result<::core::option::Option<(Self, usize)>, ::framez::frame::FrameError> {
+                    match tag {
+                        #(#arms,)*
+                        _ => ::core::result::Result::Err(::framez::frame::FrameError::UnknownTag(tag)),
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Returns the generics of the decode impl and the lifetime of the decoded bytes.
+///
+/// The lifetime parameter of the type, if any, is the lifetime of the decoded bytes. Otherwise, a new one is added.
+fn decode_generics(generics: &Generics) -> syn::Result<(Generics, Lifetime)> {
+    let mut lifetimes = generics.lifetimes();
+
+    match (lifetimes.next(), lifetimes.next()) {
+        (None, _) => {
+            let lifetime = Lifetime::new("'__buf", Span::call_site());
+
+            let mut generics = generics.clone();
+            generics.params.insert(
+                0,
+                GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())),
+            );
+
+            Ok((generics, lifetime))
+        }
+        (Some(param), None) => Ok((generics.clone(), param.lifetime.clone())),
+        (Some(_), Some(param)) => Err(syn::Error::new(
+            param.span(),
+            "at most one lifetime parameter is supported",
+        )),
+    }
+}
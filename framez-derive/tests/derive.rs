@@ -0,0 +1,227 @@
+use framez::{
+    FramedRead, FramedWrite,
+    checksum::Crc32,
+    frame::{FrameCodec, FrameDecode, FrameEncode, FrameError},
+    mock::Duplex,
+    next,
+};
+use zerocopy::{
+    FromBytes, Immutable, IntoBytes, KnownLayout,
+    big_endian::{U16, U32},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, FromBytes, IntoBytes, Immutable, KnownLayout)]
+#[repr(C)]
+struct Reading {
+    sensor: u8,
+    _reserved: u8,
+    value: U16,
+}
+
+impl FrameEncode for Reading {
+    fn encode_frame(&self, dst: &mut [u8]) -> Result<usize, FrameError> {
+        self.as_bytes().encode_frame(dst)
+    }
+}
+
+impl FrameDecode<'_> for Reading {
+    fn decode_frame(src: &[u8]) -> Result<Option<(Self, usize)>, FrameError> {
+        Ok(Self::read_from_prefix(src)
+            .ok()
+            .map(|(reading, _)| (reading, size_of::<Self>())))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FrameEncode, FrameDecode)]
+enum Message<'a> {
+    #[frame(tag = 1)]
+    Ping,
+    #[frame(tag = 2)]
+    Reading(Reading),
+    #[frame(tag = 0x10)]
+    Raw(&'a [u8]),
+}
+
+#[derive(Debug, Clone, PartialEq, FrameEncode, FrameDecode)]
+struct Packet<'a> {
+    version: u8,
+    #[frame(tag)]
+    kind: u8,
+    #[frame(length)]
+    len: U16,
+    #[frame(checksum = Crc32::iso_hdlc())]
+    crc: U32,
+    #[frame(payload)]
+    message: Message<'a>,
+}
+
+impl<'a> Packet<'a> {
+    fn new(message: Message<'a>) -> Self {
+        Self {
+            version: 1,
+            kind: 0,
+            len: U16::ZERO,
+            crc: U32::ZERO,
+            message,
+        }
+    }
+}
+
+/// A packet without a length field, ending with its payload.
+#[derive(Debug, Clone, PartialEq, FrameEncode, FrameDecode)]
+struct Sample(u8, #[frame(payload)] Reading);
+
+const HEADER: usize = 8;
+
+fn encode(item: impl FrameEncode) -> Vec<u8> {
+    let mut buffer = [0_u8; 64];
+    let size = item.encode_frame(&mut buffer).expect("Must encode");
+
+    buffer[..size].to_vec()
+}
+
+#[test]
+fn packet_round_trips() {
+    let reading = Reading {
+        sensor: 3,
+        _reserved: 0,
+        value: U16::new(0x1234),
+    };
+
+    for message in [
+        Message::Ping,
+        Message::Reading(reading),
+        Message::Raw(b"Hello"),
+    ] {
+        let bytes = encode(Packet::new(message.clone()));
+
+        assert_eq!(Packet::frame_len(&bytes), Some(bytes.len()));
+
+        let (packet, size) = Packet::decode_frame(&bytes)
+            .expect("Must decode")
+            .expect("Must be some");
+
+        assert_eq!(size, bytes.len());
+        assert_eq!(packet.message, message);
+        assert_eq!(usize::from(packet.len.get()), bytes.len());
+    }
+}
+
+#[test]
+fn header_fields_are_filled_in() {
+    let bytes = encode(Packet::new(Message::Raw(b"Hi")));
+
+    assert_eq!(bytes[0], 1);
+    assert_eq!(bytes[1], 0x10);
+    assert_eq!(&bytes[2..4], &[0, HEADER as u8 + 2]);
+    assert_eq!(&bytes[HEADER..], b"Hi");
+
+    let mut zeroed = bytes.clone();
+    zeroed[4..8].fill(0);
+
+    let crc = u32::from_be_bytes(bytes[4..8].try_into().expect("Must be 4 bytes"));
+    assert_eq!(
+        crc,
+        framez::checksum::Checksum::checksum(&mut Crc32::iso_hdlc(), &zeroed)
+    );
+}
+
+#[test]
+fn incomplete_packet_needs_more_bytes() {
+    let bytes = encode(Packet::new(Message::Raw(b"Hello")));
+
+    for len in 0..bytes.len() {
+        assert!(matches!(Packet::decode_frame(&bytes[..len]), Ok(None)));
+    }
+}
+
+#[test]
+fn corrupted_packet_is_rejected() {
+    let mut bytes = encode(Packet::new(Message::Raw(b"Hello")));
+    bytes[HEADER] ^= 0xFF;
+
+    assert!(matches!(
+        Packet::decode_frame(&bytes),
+        Err(FrameError::Checksum)
+    ));
+}
+
+#[test]
+fn unknown_tag_is_rejected() {
+    let mut bytes = encode(Packet::new(Message::Ping));
+    bytes[1] = 0x42;
+
+    let mut zeroed = bytes.clone();
+    zeroed[4..8].fill(0);
+
+    let crc = framez::checksum::Checksum::checksum(&mut Crc32::iso_hdlc(), &zeroed);
+    bytes[4..8].copy_from_slice(&crc.to_be_bytes());
+
+    assert!(matches!(
+        Packet::decode_frame(&bytes),
+        Err(FrameError::UnknownTag(0x42))
+    ));
+}
+
+#[test]
+fn short_length_is_rejected() {
+    let mut bytes = encode(Packet::new(Message::Ping));
+    bytes[3] = 2;
+
+    assert!(matches!(
+        Packet::decode_frame(&bytes),
+        Err(FrameError::InvalidLength)
+    ));
+}
+
+#[test]
+fn small_buffer_is_rejected() {
+    let mut buffer = [0_u8; HEADER + 2];
+
+    assert!(matches!(
+        Packet::new(Message::Raw(b"Hello")).encode_frame(&mut buffer),
+        Err(FrameError::BufferTooSmall)
+    ));
+}
+
+#[tokio::test]
+async fn packets_without_length_are_framed() {
+    let duplex = Duplex::<64>::new();
+    let (a, b) = duplex.split();
+
+    let w_buf = &mut [0_u8; 32];
+    let mut writer = FramedWrite::new(FrameCodec::<Sample>::new(), a, w_buf);
+
+    let samples = [
+        Sample(
+            1,
+            Reading {
+                sensor: 2,
+                _reserved: 0,
+                value: U16::new(3),
+            },
+        ),
+        Sample(
+            4,
+            Reading {
+                sensor: 5,
+                _reserved: 0,
+                value: U16::new(6),
+            },
+        ),
+    ];
+
+    for sample in samples.clone() {
+        writer.send(sample).await.expect("Must send");
+    }
+
+    drop(writer);
+
+    let buffer = &mut [0_u8; 32];
+    let mut reader = FramedRead::new(FrameCodec::<Sample>::new(), b, buffer);
+
+    for sample in samples {
+        let item = next!(reader).expect("Must be some").expect("Must decode");
+        assert_eq!(item, sample);
+    }
+}
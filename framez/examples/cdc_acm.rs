@@ -0,0 +1,138 @@
+//! This example shows how to frame a USB CDC-ACM serial port using the `CdcAcm` adapter.
+//!
+//! On a device, the `CdcAcm` wraps an `embassy_usb::class::cdc_acm::CdcAcmClass`.
+//! Here, both ends are simulated by channels that carry packets of at most 64 bytes, like a full-speed USB bulk endpoint.
+//!
+//! ```not_rust
+//! cargo run --example cdc_acm --features="embassy-usb tokio"
+//! ```
+
+use core::error::Error;
+
+use embassy_usb::driver::EndpointError;
+use framez::{
+    Framed,
+    cdc_acm::{CdcAcm, ReadPacket, WritePacket},
+    codec::lines::StrLines,
+    next, send,
+};
+use tokio::sync::mpsc::{Receiver, Sender, channel};
+
+const MAX_PACKET_SIZE: usize = 64;
+
+/// One end of a simulated USB link.
+struct Endpoint {
+    rx: Receiver<Vec<u8>>,
+    tx: Sender<Vec<u8>>,
+}
+
+impl Endpoint {
+    fn pair() -> (Self, Self) {
+        let (host_tx, device_rx) = channel(8);
+        let (device_tx, host_rx) = channel(8);
+
+        (
+            Self {
+                rx: host_rx,
+                tx: host_tx,
+            },
+            Self {
+                rx: device_rx,
+                tx: device_tx,
+            },
+        )
+    }
+}
+
+impl ReadPacket for Endpoint {
+    fn max_packet_size(&self) -> usize {
+        MAX_PACKET_SIZE
+    }
+
+    async fn read_packet(&mut self, buf: &mut [u8]) -> Result<usize, EndpointError> {
+        let packet = self.rx.recv().await.ok_or(EndpointError::Disabled)?;
+
+        buf[..packet.len()].copy_from_slice(&packet);
+
+        Ok(packet.len())
+    }
+}
+
+impl WritePacket for Endpoint {
+    fn max_packet_size(&self) -> usize {
+        MAX_PACKET_SIZE
+    }
+
+    async fn write_packet(&mut self, data: &[u8]) -> Result<(), EndpointError> {
+        tracing::debug!(len = data.len(), "packet");
+
+        self.tx
+            .send(data.to_vec())
+            .await
+            .map_err(|_| EndpointError::Disabled)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    tracing_subscriber::fmt()
+        .with_env_filter("cdc_acm=debug,device=info,host=info")
+        .init();
+
+    let (host, device) = Endpoint::pair();
+
+    let read_buf = &mut [0u8; 256];
+    let write_buf = &mut [0u8; 256];
+    let mut device = Framed::new(
+        StrLines::new(),
+        CdcAcm::<_>::new(device),
+        read_buf,
+        write_buf,
+    );
+
+    let device = async move {
+        while let Some(item) = next!(device).transpose()? {
+            tracing::info!(target: "device", item, "received frame");
+
+            // echo the item back
+            send!(device, item)?;
+
+            if item == "Close" {
+                break;
+            }
+        }
+
+        Ok::<(), Box<dyn Error>>(())
+    };
+
+    let read_buf = &mut [0u8; 256];
+    let write_buf = &mut [0u8; 256];
+    let mut host = Framed::new(StrLines::new(), CdcAcm::<_>::new(host), read_buf, write_buf);
+
+    let host = async move {
+        // Together with its line end, the second item fills exactly two packets, so a zero-length packet ends its transfer.
+        let long = "A".repeat(2 * MAX_PACKET_SIZE - 2);
+        let items = ["Hello, world!", &long, "Close"];
+
+        for item in items {
+            tracing::info!(target: "host", item, "sending frame");
+
+            host.send(item).await?;
+        }
+
+        for _ in items {
+            if let Some(item) = next!(host).transpose()? {
+                tracing::info!(target: "host", item, "received frame");
+            }
+        }
+
+        Ok::<(), Box<dyn Error>>(())
+    };
+
+    let (device_result, host_result) = tokio::join!(device, host);
+
+    device_result?;
+    host_result?;
+
+    Ok(())
+}
@@ -6,7 +6,6 @@
 
 use core::error::Error;
 
-use embedded_io_adapters::tokio_1::FromTokio;
 use framez::{Framed, codec::lines::StrLines, next, send};
 
 #[tokio::main]
@@ -19,7 +18,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     let read_buf = &mut [0u8; 1024];
     let write_buf = &mut [0u8; 1024];
-    let mut server = Framed::new(StrLines::new(), FromTokio::new(server), read_buf, write_buf);
+    let mut server = Framed::new_tokio(StrLines::new(), server, read_buf, write_buf);
 
     let server = async move {
         while let Some(item) = next!(server).transpose()? {
@@ -40,7 +39,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     let read_buf = &mut [0u8; 1024];
     let write_buf = &mut [0u8; 1024];
-    let mut client = Framed::new(StrLines::new(), FromTokio::new(client), read_buf, write_buf);
+    let mut client = Framed::new_tokio(StrLines::new(), client, read_buf, write_buf);
 
     let client = async move {
         let items = ["Hello, world!", "How are you?", "Goodbye!", "Close"];
@@ -5,7 +5,6 @@
 use core::{error::Error, pin::pin};
 use std::str::FromStr;
 
-use embedded_io_adapters::tokio_1::FromTokio;
 use framez::{FramedRead, FramedWrite, codec::lines::StrLines};
 use futures::{SinkExt, StreamExt};
 
@@ -18,7 +17,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let (read, write) = tokio::io::duplex(1024);
 
     let read_buf = &mut [0u8; 1024];
-    let mut framed_read = FramedRead::new(StrLines::new(), FromTokio::new(read), read_buf);
+    let mut framed_read = FramedRead::new_tokio(StrLines::new(), read, read_buf);
 
     let reader = async move {
         let stream = framed_read.stream(String::from_str);
@@ -32,7 +31,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     };
 
     let write_buf = &mut [0u8; 1024];
-    let mut framed_write = FramedWrite::new(StrLines::new(), FromTokio::new(write), write_buf);
+    let mut framed_write = FramedWrite::new_tokio(StrLines::new(), write, write_buf);
 
     let writer = async move {
         let items = ["Hello, world!", "How are you?", "Goodbye!"];
@@ -4,7 +4,6 @@
 
 use core::error::Error;
 
-use embedded_io_adapters::tokio_1::FromTokio;
 use framez::{FramedRead, FramedWrite, codec::lines::StrLines, next};
 
 #[tokio::main]
@@ -16,7 +15,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let (read, write) = tokio::io::duplex(8);
 
     let read_buf = &mut [0u8; 1024];
-    let mut framed_read = FramedRead::new(StrLines::new(), FromTokio::new(read), read_buf);
+    let mut framed_read = FramedRead::new_tokio(StrLines::new(), read, read_buf);
 
     let reader = async move {
         while let Some(item) = next!(framed_read).transpose()? {
@@ -27,7 +26,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     };
 
     let write_buf = &mut [0u8; 1024];
-    let mut framed_write = FramedWrite::new(StrLines::new(), FromTokio::new(write), write_buf);
+    let mut framed_write = FramedWrite::new_tokio(StrLines::new(), write, write_buf);
 
     let writer = async move {
         let items = ["Hello, world!", "How are you?", "Goodbye!"];
@@ -0,0 +1,10 @@
+//! Adapters from the IO traits of async runtimes to [`embedded_io_async`](https://docs.rs/embedded-io-async/latest/embedded_io_async/)'s traits.
+//!
+//! Re-exported from [`embedded_io_adapters`](https://docs.rs/embedded-io-adapters/latest/embedded_io_adapters/),
+//! so the framers can name them without depending on the adapter crate, e.g. `Framed::new_tokio` or `Framed::new_futures`.
+
+#[cfg(feature = "tokio")]
+pub use embedded_io_adapters::tokio_1::FromTokio;
+
+#[cfg(feature = "futures-io")]
+pub use embedded_io_adapters::futures_03::FromFutures;
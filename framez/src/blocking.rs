@@ -0,0 +1,515 @@
+//! Blocking framers over [`std::io::Read`] and [`std::io::Write`].
+//!
+//! Meant for host-side tools that share their codecs with the firmware, but do not need an async runtime.
+//! The framers wrap their async counterparts using the [`FromStd`] adapter and drive them to completion using [`block_on`].
+
+use core::{
+    convert::Infallible,
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use embedded_io_async::{ErrorType, Read, Write};
+
+use crate::{ReadError, WriteError, buffer::Buffer, decode::Decoder, encode::Encoder};
+
+/// Adapter from [`std::io::Read`] and [`std::io::Write`] to [`embedded_io_async::Read`] and [`embedded_io_async::Write`].
+///
+/// The async methods block until the underlying IO operation completes, so they are never pending.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FromStd<T> {
+    inner: T,
+}
+
+impl<T> FromStd<T> {
+    /// Creates a new [`FromStd`] wrapping the given `inner` reader/writer.
+    #[inline]
+    pub const fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    /// Returns reference to the inner reader/writer.
+    #[inline]
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns mutable reference to the inner reader/writer.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the [`FromStd`] and returns the inner reader/writer.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> ErrorType for FromStd<T> {
+    type Error = std::io::Error;
+}
+
+impl<T> Read for FromStd<T>
+where
+    T: std::io::Read,
+{
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        loop {
+            match self.inner.read(buf) {
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<T> Write for FromStd<T>
+where
+    T: std::io::Write,
+{
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        loop {
+            match self.inner.write(buf) {
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                result => return result,
+            }
+        }
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+/// Runs the `future` to completion on the current thread.
+///
+/// Futures that only wait for [`FromStd`] complete on the first poll.
+/// Other futures are polled in a loop, yielding the thread in between.
+pub fn block_on<F>(future: F) -> F::Output
+where
+    F: Future,
+{
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+
+        std::thread::yield_now();
+    }
+}
+
+/// A blocking [`Framed`](crate::Framed) over a [`std::io::Read`] and [`std::io::Write`] stream.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Framed<'buf, C, RW, RB = &'buf mut [u8], WB = &'buf mut [u8]> {
+    framed: crate::Framed<'buf, C, FromStd<RW>, RB, WB>,
+}
+
+impl<'buf, C, RW> Framed<'buf, C, RW> {
+    /// Creates a new [`Framed`] with the given `codec` and `reader/writer`.
+    #[inline]
+    pub const fn new(
+        codec: C,
+        inner: RW,
+        read_buffer: &'buf mut [u8],
+        write_buffer: &'buf mut [u8],
+    ) -> Self {
+        Self::with_buffers(codec, inner, read_buffer, write_buffer)
+    }
+}
+
+impl<'buf, C, RW, RB, WB> Framed<'buf, C, RW, RB, WB> {
+    /// Creates a new [`Framed`] with the given `codec`, `reader/writer` and [`Buffer`]s.
+    #[inline]
+    pub const fn with_buffers(codec: C, inner: RW, read_buffer: RB, write_buffer: WB) -> Self {
+        Self {
+            framed: crate::Framed::with_buffers(
+                codec,
+                FromStd::new(inner),
+                read_buffer,
+                write_buffer,
+            ),
+        }
+    }
+
+    /// Returns reference to the codec.
+    #[inline]
+    pub const fn codec(&self) -> &C {
+        self.framed.codec()
+    }
+
+    /// Returns mutable reference to the codec.
+    #[inline]
+    pub const fn codec_mut(&mut self) -> &mut C {
+        self.framed.codec_mut()
+    }
+
+    /// Returns reference to the reader/writer.
+    #[inline]
+    pub const fn inner(&self) -> &RW {
+        self.framed.inner().inner()
+    }
+
+    /// Returns mutable reference to the reader/writer.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut RW {
+        self.framed.inner_mut().inner_mut()
+    }
+
+    /// Returns reference to the async [`Framed`](crate::Framed).
+    #[inline]
+    pub const fn framed(&self) -> &crate::Framed<'buf, C, FromStd<RW>, RB, WB> {
+        &self.framed
+    }
+
+    /// Returns mutable reference to the async [`Framed`](crate::Framed).
+    ///
+    /// Its methods can be run using [`block_on`].
+    #[inline]
+    pub const fn framed_mut(&mut self) -> &mut crate::Framed<'buf, C, FromStd<RW>, RB, WB> {
+        &mut self.framed
+    }
+
+    /// Consumes the [`Framed`] and returns the async [`Framed`](crate::Framed).
+    #[inline]
+    pub fn into_framed(self) -> crate::Framed<'buf, C, FromStd<RW>, RB, WB> {
+        self.framed
+    }
+
+    /// See [`Framed::maybe_next`](crate::Framed::maybe_next).
+    #[allow(clippy::type_complexity)]
+    pub fn maybe_next(
+        &mut self,
+    ) -> Option<Result<Option<C::Item<'_>>, ReadError<std::io::Error, C::Error>>>
+    where
+        C: Decoder,
+        RW: std::io::Read,
+        RB: Buffer,
+    {
+        block_on(self.framed.maybe_next())
+    }
+
+    /// See [`Framed::next`](crate::Framed::next).
+    pub fn next<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<std::io::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: std::io::Read,
+        RB: Buffer,
+    {
+        block_on(self.framed.next(map))
+    }
+
+    /// See [`Framed::send`](crate::Framed::send).
+    pub fn send<I>(&mut self, item: I) -> Result<(), WriteError<std::io::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: std::io::Write,
+        WB: Buffer,
+    {
+        block_on(self.framed.send(item))
+    }
+
+    /// See [`Framed::send_all`](crate::Framed::send_all).
+    pub fn send_all<I, T>(&mut self, items: T) -> Result<(), WriteError<std::io::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: std::io::Write,
+        WB: Buffer,
+        T: IntoIterator<Item = I>,
+    {
+        block_on(self.framed.send_all(items))
+    }
+
+    /// See [`Framed::flush`](crate::Framed::flush).
+    pub fn flush(&mut self) -> Result<(), WriteError<std::io::Error, Infallible>>
+    where
+        RW: std::io::Write,
+        WB: Buffer,
+    {
+        block_on(self.framed.flush())
+    }
+}
+
+/// A blocking [`FramedRead`](crate::FramedRead) over a [`std::io::Read`] source.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FramedRead<'buf, C, R, B = &'buf mut [u8]> {
+    framed: crate::FramedRead<'buf, C, FromStd<R>, B>,
+}
+
+impl<'buf, C, R> FramedRead<'buf, C, R> {
+    /// Creates a new [`FramedRead`] with the given `codec` and `reader`.
+    #[inline]
+    pub const fn new(codec: C, reader: R, buffer: &'buf mut [u8]) -> Self {
+        Self::with_buffer(codec, reader, buffer)
+    }
+}
+
+impl<'buf, C, R, B> FramedRead<'buf, C, R, B> {
+    /// Creates a new [`FramedRead`] with the given `codec`, `reader` and [`Buffer`].
+    #[inline]
+    pub const fn with_buffer(codec: C, reader: R, buffer: B) -> Self {
+        Self {
+            framed: crate::FramedRead::with_buffer(codec, FromStd::new(reader), buffer),
+        }
+    }
+
+    /// Returns reference to the codec.
+    #[inline]
+    pub const fn codec(&self) -> &C {
+        self.framed.codec()
+    }
+
+    /// Returns mutable reference to the codec.
+    #[inline]
+    pub const fn codec_mut(&mut self) -> &mut C {
+        self.framed.codec_mut()
+    }
+
+    /// Returns reference to the reader.
+    #[inline]
+    pub const fn inner(&self) -> &R {
+        self.framed.inner().inner()
+    }
+
+    /// Returns mutable reference to the reader.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut R {
+        self.framed.inner_mut().inner_mut()
+    }
+
+    /// Returns reference to the async [`FramedRead`](crate::FramedRead).
+    #[inline]
+    pub const fn framed(&self) -> &crate::FramedRead<'buf, C, FromStd<R>, B> {
+        &self.framed
+    }
+
+    /// Returns mutable reference to the async [`FramedRead`](crate::FramedRead).
+    ///
+    /// Its methods can be run using [`block_on`].
+    #[inline]
+    pub const fn framed_mut(&mut self) -> &mut crate::FramedRead<'buf, C, FromStd<R>, B> {
+        &mut self.framed
+    }
+
+    /// Consumes the [`FramedRead`] and returns the async [`FramedRead`](crate::FramedRead).
+    #[inline]
+    pub fn into_framed(self) -> crate::FramedRead<'buf, C, FromStd<R>, B> {
+        self.framed
+    }
+
+    /// See [`Framed::maybe_next`](crate::Framed::maybe_next).
+    #[allow(clippy::type_complexity)]
+    pub fn maybe_next(
+        &mut self,
+    ) -> Option<Result<Option<C::Item<'_>>, ReadError<std::io::Error, C::Error>>>
+    where
+        C: Decoder,
+        R: std::io::Read,
+        B: Buffer,
+    {
+        block_on(self.framed.maybe_next())
+    }
+
+    /// See [`Framed::next`](crate::Framed::next).
+    pub fn next<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<std::io::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        R: std::io::Read,
+        B: Buffer,
+    {
+        block_on(self.framed.next(map))
+    }
+}
+
+/// A blocking [`FramedWrite`](crate::FramedWrite) over a [`std::io::Write`] sink.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FramedWrite<'buf, C, W, B = &'buf mut [u8]> {
+    framed: crate::FramedWrite<'buf, C, FromStd<W>, B>,
+}
+
+impl<'buf, C, W> FramedWrite<'buf, C, W> {
+    /// Creates a new [`FramedWrite`] with the given `codec` and `writer`.
+    #[inline]
+    pub const fn new(codec: C, writer: W, buffer: &'buf mut [u8]) -> Self {
+        Self::with_buffer(codec, writer, buffer)
+    }
+}
+
+impl<'buf, C, W, B> FramedWrite<'buf, C, W, B> {
+    /// Creates a new [`FramedWrite`] with the given `codec`, `writer` and [`Buffer`].
+    #[inline]
+    pub const fn with_buffer(codec: C, writer: W, buffer: B) -> Self {
+        Self {
+            framed: crate::FramedWrite::with_buffer(codec, FromStd::new(writer), buffer),
+        }
+    }
+
+    /// Returns reference to the codec.
+    #[inline]
+    pub const fn codec(&self) -> &C {
+        self.framed.codec()
+    }
+
+    /// Returns mutable reference to the codec.
+    #[inline]
+    pub const fn codec_mut(&mut self) -> &mut C {
+        self.framed.codec_mut()
+    }
+
+    /// Returns reference to the writer.
+    #[inline]
+    pub const fn inner(&self) -> &W {
+        self.framed.inner().inner()
+    }
+
+    /// Returns mutable reference to the writer.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut W {
+        self.framed.inner_mut().inner_mut()
+    }
+
+    /// Returns reference to the async [`FramedWrite`](crate::FramedWrite).
+    #[inline]
+    pub const fn framed(&self) -> &crate::FramedWrite<'buf, C, FromStd<W>, B> {
+        &self.framed
+    }
+
+    /// Returns mutable reference to the async [`FramedWrite`](crate::FramedWrite).
+    ///
+    /// Its methods can be run using [`block_on`].
+    #[inline]
+    pub const fn framed_mut(&mut self) -> &mut crate::FramedWrite<'buf, C, FromStd<W>, B> {
+        &mut self.framed
+    }
+
+    /// Consumes the [`FramedWrite`] and returns the async [`FramedWrite`](crate::FramedWrite).
+    #[inline]
+    pub fn into_framed(self) -> crate::FramedWrite<'buf, C, FromStd<W>, B> {
+        self.framed
+    }
+
+    /// See [`Framed::send`](crate::Framed::send).
+    pub fn send<I>(&mut self, item: I) -> Result<(), WriteError<std::io::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        W: std::io::Write,
+        B: Buffer,
+    {
+        block_on(self.framed.send(item))
+    }
+
+    /// See [`Framed::send_all`](crate::Framed::send_all).
+    pub fn send_all<I, T>(&mut self, items: T) -> Result<(), WriteError<std::io::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        W: std::io::Write,
+        B: Buffer,
+        T: IntoIterator<Item = I>,
+    {
+        block_on(self.framed.send_all(items))
+    }
+
+    /// See [`Framed::flush`](crate::Framed::flush).
+    pub fn flush(&mut self) -> Result<(), WriteError<std::io::Error, Infallible>>
+    where
+        W: std::io::Write,
+        B: Buffer,
+    {
+        block_on(self.framed.flush())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        net::{TcpListener, TcpStream},
+        string::String,
+        vec::Vec,
+    };
+
+    use crate::codec::lines::{Lines, StrLines};
+
+    use super::*;
+
+    #[test]
+    fn read_and_write_without_runtime() {
+        let buffer = &mut [0_u8; 32];
+        let mut writer = FramedWrite::new(Lines::new(), Vec::new(), buffer);
+
+        writer.send(b"Hello".as_slice()).expect("Must send");
+        writer
+            .send_all([b"world".as_slice(), b"!"])
+            .expect("Must send");
+
+        let written = writer.into_framed().into_parts().1.into_inner();
+
+        assert_eq!(written, b"Hello\r\nworld\r\n!\r\n");
+
+        let buffer = &mut [0_u8; 32];
+        let mut reader = FramedRead::new(StrLines::new(), written.as_slice(), buffer);
+
+        let mut lines = Vec::new();
+
+        while let Some(line) = reader.next(|line| String::from(line)) {
+            lines.push(line.expect("Must decode"));
+        }
+
+        assert_eq!(lines, ["Hello", "world", "!"]);
+    }
+
+    #[test]
+    fn framed_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Must bind");
+        let client = TcpStream::connect(listener.local_addr().expect("Must have address"))
+            .expect("Must connect");
+        let (server, _) = listener.accept().expect("Must accept");
+
+        let server = std::thread::spawn(move || {
+            let read_buf = &mut [0_u8; 32];
+            let write_buf = &mut [0_u8; 32];
+            let mut framed = Framed::new(StrLines::new(), server, read_buf, write_buf);
+
+            loop {
+                match framed.maybe_next() {
+                    Some(Ok(Some("PING"))) => break,
+                    Some(Ok(_)) => continue,
+                    _ => panic!("Must receive PING"),
+                }
+            }
+
+            framed.send("PONG").expect("Must send");
+        });
+
+        let read_buf = &mut [0_u8; 32];
+        let write_buf = &mut [0_u8; 32];
+        let mut framed = Framed::new(StrLines::new(), client, read_buf, write_buf);
+
+        framed.send("PING").expect("Must send");
+
+        let line = framed
+            .next(|line| String::from(line))
+            .expect("Must be some")
+            .expect("Must decode");
+
+        assert_eq!(line, "PONG");
+
+        server.join().expect("Must join");
+    }
+}
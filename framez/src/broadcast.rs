@@ -0,0 +1,395 @@
+//! Sending of frames to multiple writers.
+//!
+//! A [`Broadcast`] encodes every item once and writes the encoded frame to each of its sinks,
+//! e.g. to fan out telemetry to a USB serial port, a radio and a log storage.
+
+#![allow(async_fn_in_trait)]
+
+use core::convert::Infallible;
+
+use embedded_io_async::{Error, ErrorKind, Write};
+
+use crate::{
+    WriteError,
+    encode::{Encoder, write_all},
+};
+
+/// The maximum number of sinks whose errors are reported in [`Failed`].
+pub const MAX_REPORTED_SINKS: usize = 32;
+
+/// The sinks of a [`Broadcast`] that failed to write a frame.
+///
+/// Sinks are identified by their index, in the order they were added with [`Broadcast::sink`].
+/// Only the first [`MAX_REPORTED_SINKS`] sinks are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Failed {
+    mask: u32,
+    kinds: [ErrorKind; MAX_REPORTED_SINKS],
+}
+
+impl Failed {
+    const fn new() -> Self {
+        Self {
+            mask: 0,
+            kinds: [ErrorKind::Other; MAX_REPORTED_SINKS],
+        }
+    }
+
+    fn insert(&mut self, index: usize, kind: ErrorKind) {
+        if let Some(slot) = self.kinds.get_mut(index) {
+            *slot = kind;
+            self.mask |= 1 << index;
+        }
+    }
+
+    /// Returns the number of sinks that failed.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.mask.count_ones() as usize
+    }
+
+    /// Returns `true` if no sink failed.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.mask == 0
+    }
+
+    /// Returns the kind of the error of the sink at `index`, if it failed.
+    #[inline]
+    pub fn kind(&self, index: usize) -> Option<ErrorKind> {
+        if index >= MAX_REPORTED_SINKS || self.mask & (1 << index) == 0 {
+            return None;
+        }
+
+        self.kinds.get(index).copied()
+    }
+
+    /// Returns an iterator over the indices of the failed sinks and the kinds of their errors.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (usize, ErrorKind)> + '_ {
+        (0..MAX_REPORTED_SINKS).filter_map(|index| self.kind(index).map(|kind| (index, kind)))
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Failed {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "Failed {{ mask: {=u32:#b} }}", self.mask)
+    }
+}
+
+impl core::fmt::Display for Failed {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} sink(s) failed", self.len())
+    }
+}
+
+/// An error that can occur while sending a frame with [`Broadcast::send`].
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BroadcastError<E> {
+    /// The item could not be encoded. Nothing was written.
+    Encode(E),
+    /// Some sinks failed to write or flush the frame. The other sinks received the whole frame.
+    Write(Failed),
+}
+
+impl<E> core::fmt::Display for BroadcastError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Encode(err) => write!(f, "Encode error: {err}"),
+            Self::Write(failed) => write!(f, "Write error: {failed}"),
+        }
+    }
+}
+
+impl<E> core::error::Error for BroadcastError<E> where E: core::fmt::Display + core::fmt::Debug {}
+
+/// A set of sinks that receive every frame.
+///
+/// Built using [`Broadcast::sink`].
+pub trait Sinks {
+    /// Returns the number of sinks.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there are no sinks.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Writes and flushes `frame` to every sink, in the order they were added, and records the ones that failed in `failed`.
+    async fn write_frame(&mut self, frame: &[u8], failed: &mut Failed);
+}
+
+/// A sink, preceded by the previously added sinks.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sink<W, N> {
+    write: W,
+    next: N,
+}
+
+impl<W, N> Sink<W, N> {
+    /// Returns reference to the writer.
+    #[inline]
+    pub const fn inner(&self) -> &W {
+        &self.write
+    }
+
+    /// Returns mutable reference to the writer.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut W {
+        &mut self.write
+    }
+
+    /// Returns reference to the previously added sinks.
+    #[inline]
+    pub const fn next(&self) -> &N {
+        &self.next
+    }
+
+    /// Returns mutable reference to the previously added sinks.
+    #[inline]
+    pub const fn next_mut(&mut self) -> &mut N {
+        &mut self.next
+    }
+}
+
+/// Writes and flushes `frame` to `write`.
+async fn write_frame<W>(write: &mut W, frame: &[u8]) -> Result<(), ErrorKind>
+where
+    W: Write,
+{
+    match write_all::<W, Infallible>(write, frame).await {
+        Ok(()) => {}
+        Err(WriteError::IO(err)) => return Err(err.kind()),
+        Err(_) => return Err(ErrorKind::WriteZero),
+    }
+
+    write.flush().await.map_err(|err| err.kind())
+}
+
+impl<W, N> Sinks for Sink<W, N>
+where
+    W: Write,
+    N: Sinks,
+{
+    fn len(&self) -> usize {
+        self.next.len().saturating_add(1)
+    }
+
+    async fn write_frame(&mut self, frame: &[u8], failed: &mut Failed) {
+        self.next.write_frame(frame, failed).await;
+
+        if let Err(kind) = write_frame(&mut self.write, frame).await {
+            failed.insert(self.next.len(), kind);
+        }
+    }
+}
+
+/// No sinks.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoSinks;
+
+impl Sinks for NoSinks {
+    fn len(&self) -> usize {
+        0
+    }
+
+    async fn write_frame(&mut self, _frame: &[u8], _failed: &mut Failed) {}
+}
+
+/// Encodes every item once and writes the encoded frame to all of its sinks.
+///
+/// A sink that fails does not stop the frame from being written to the other sinks.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{broadcast::Broadcast, codec::lines::StrLines, mock::Noop};
+///
+/// # async fn run() {
+/// let buffer = &mut [0_u8; 32];
+///
+/// let mut broadcast = Broadcast::new(StrLines::new(), buffer)
+///     .sink(Noop)
+///     .sink(Vec::new());
+///
+/// broadcast.send("Hello").await.unwrap();
+///
+/// assert_eq!(broadcast.sinks().inner(), b"Hello\r\n");
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Broadcast<'buf, C, S> {
+    codec: C,
+    buffer: &'buf mut [u8],
+    sinks: S,
+}
+
+impl<'buf, C> Broadcast<'buf, C, NoSinks> {
+    /// Creates a new [`Broadcast`] that encodes into `buffer`, without sinks.
+    #[inline]
+    pub const fn new(codec: C, buffer: &'buf mut [u8]) -> Self {
+        Self {
+            codec,
+            buffer,
+            sinks: NoSinks,
+        }
+    }
+}
+
+impl<'buf, C, S> Broadcast<'buf, C, S> {
+    /// Adds `write` as the next sink.
+    #[inline]
+    pub fn sink<W>(self, write: W) -> Broadcast<'buf, C, Sink<W, S>> {
+        Broadcast {
+            codec: self.codec,
+            buffer: self.buffer,
+            sinks: Sink {
+                write,
+                next: self.sinks,
+            },
+        }
+    }
+
+    /// Returns reference to the codec.
+    #[inline]
+    pub const fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    /// Returns mutable reference to the codec.
+    #[inline]
+    pub const fn codec_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    /// Returns reference to the sinks. The last added sink comes first.
+    #[inline]
+    pub const fn sinks(&self) -> &S {
+        &self.sinks
+    }
+
+    /// Returns mutable reference to the sinks. The last added sink comes first.
+    #[inline]
+    pub const fn sinks_mut(&mut self) -> &mut S {
+        &mut self.sinks
+    }
+
+    /// Consumes the [`Broadcast`] and returns the codec and the sinks.
+    #[inline]
+    pub fn into_parts(self) -> (C, S) {
+        (self.codec, self.sinks)
+    }
+
+    /// Encodes `item` once, then writes and flushes the frame to every sink, in the order they were added.
+    ///
+    /// # Errors
+    ///
+    /// - [`BroadcastError::Encode`] if encoding failed. Nothing was written.
+    /// - [`BroadcastError::Write`] with the sinks that failed to write or flush the frame.
+    pub async fn send<I>(&mut self, item: I) -> Result<(), BroadcastError<C::Error>>
+    where
+        C: Encoder<I>,
+        S: Sinks,
+    {
+        let size = self
+            .codec
+            .encode(item, self.buffer)
+            .map_err(BroadcastError::Encode)?;
+
+        let frame = self.buffer.get(..size).unwrap_or_default();
+
+        let mut failed = Failed::new();
+
+        self.sinks.write_frame(frame, &mut failed).await;
+
+        if failed.is_empty() {
+            return Ok(());
+        }
+
+        Err(BroadcastError::Write(failed))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use crate::{
+        codec::lines::Lines,
+        mock::{FailAfter, FaultyWriter, Noop},
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn frame_is_written_to_every_sink() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 16];
+        let mut broadcast = Broadcast::new(Lines::new(), buffer)
+            .sink(Vec::new())
+            .sink(Vec::new());
+
+        broadcast
+            .send(b"Hello".as_slice())
+            .await
+            .expect("Must send");
+        broadcast
+            .send(b"world!".as_slice())
+            .await
+            .expect("Must send");
+
+        assert_eq!(broadcast.sinks().len(), 2);
+        assert_eq!(broadcast.sinks().inner(), b"Hello\r\nworld!\r\n");
+        assert_eq!(broadcast.sinks().next().inner(), b"Hello\r\nworld!\r\n");
+    }
+
+    #[tokio::test]
+    async fn failed_sinks_are_reported() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 16];
+        let mut broadcast = Broadcast::new(Lines::new(), buffer)
+            .sink(FaultyWriter::new(
+                Noop,
+                FailAfter::Operations(0),
+                ErrorKind::BrokenPipe,
+            ))
+            .sink(Vec::new());
+
+        let Err(BroadcastError::Write(failed)) = broadcast.send(b"Hello".as_slice()).await else {
+            panic!("Must fail");
+        };
+
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed.kind(0), Some(ErrorKind::BrokenPipe));
+        assert_eq!(failed.kind(1), None);
+
+        // The other sinks still receive the frame.
+        assert_eq!(broadcast.sinks().inner(), b"Hello\r\n");
+    }
+
+    #[tokio::test]
+    async fn nothing_is_written_if_encoding_fails() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 4];
+        let mut broadcast = Broadcast::new(Lines::new(), buffer).sink(Vec::new());
+
+        assert!(matches!(
+            broadcast.send(b"Hello".as_slice()).await,
+            Err(BroadcastError::Encode(_))
+        ));
+
+        assert!(broadcast.sinks().inner().is_empty());
+    }
+}
@@ -0,0 +1,315 @@
+//! Buffer storage for reading and writing frames.
+
+/// Storage for the bytes of a [`ReadState`](crate::state::ReadState) or [`WriteState`](crate::state::WriteState).
+///
+/// Implemented for borrowed slices and owned arrays, so the framer can either borrow or own its buffers.
+///
+/// The length of the returned slices is the capacity of the buffer and must not change between calls.
+pub trait Buffer {
+    /// Returns the buffer as a slice.
+    fn as_slice(&self) -> &[u8];
+
+    /// Returns the buffer as a mutable slice.
+    fn as_mut_slice(&mut self) -> &mut [u8];
+}
+
+impl Buffer for [u8] {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl<const N: usize> Buffer for [u8; N] {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl<B> Buffer for &mut B
+where
+    B: Buffer + ?Sized,
+{
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        (**self).as_slice()
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        (**self).as_mut_slice()
+    }
+}
+
+/// Uses the current contents of the [`heapless::Vec`] as the buffer.
+///
+/// Resize the vector to the desired capacity before handing it to a framer, e.g. using [`heapless::Vec::resize_default`].
+#[cfg(feature = "heapless")]
+impl<const N: usize> Buffer for heapless::Vec<u8, N> {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+/// Uses the whole boxed slice as the buffer.
+#[cfg(feature = "alloc")]
+impl Buffer for alloc::boxed::Box<[u8]> {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+/// Uses the current contents of the [`Vec`](alloc::vec::Vec) as the buffer.
+///
+/// Resize the vector to the desired capacity before handing it to a framer, e.g. using [`Vec::resize`](alloc::vec::Vec::resize).
+#[cfg(feature = "alloc")]
+impl Buffer for alloc::vec::Vec<u8> {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+/// A [`Buffer`] that is wiped when it is dropped, so credentials and key material passing through frames do not linger in memory.
+///
+/// Wraps any [`Buffer`], e.g. a borrowed slice, using [`zeroize`](https://docs.rs/zeroize/latest/zeroize/), so the wipe is not optimized away.
+/// The wrapper moves with the buffer, e.g. through [`Framed::into_parts`](crate::Framed::into_parts) and [`detect`](crate::detect::detect),
+/// so the bytes are wiped once the buffer is finally dropped.
+/// See [`Framed::zeroize_buffers`](crate::Framed::zeroize_buffers) to wipe the buffers of a framer that is still in use.
+///
+/// Formatting the buffer only shows its capacity, never its contents.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{Framed, buffer::Sensitive, codec::lines::StrLines, mock::Noop};
+///
+/// let r_buf = &mut [0u8; 64];
+/// let w_buf = &mut [0u8; 64];
+///
+/// let framed = Framed::with_buffers(StrLines::new(), Noop, Sensitive::new(r_buf), Sensitive::new(w_buf));
+/// ```
+#[cfg(feature = "zeroize")]
+pub struct Sensitive<B: Buffer> {
+    buffer: B,
+}
+
+#[cfg(feature = "zeroize")]
+impl<B: Buffer> Sensitive<B> {
+    /// Creates a new [`Sensitive`] wrapping the given `buffer`.
+    #[inline]
+    pub const fn new(buffer: B) -> Self {
+        Self { buffer }
+    }
+
+    /// Wipes the whole buffer.
+    #[inline]
+    pub fn zeroize(&mut self) {
+        zeroize::Zeroize::zeroize(self.buffer.as_mut_slice());
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<B: Buffer> Buffer for Sensitive<B> {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self.buffer.as_slice()
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self.buffer.as_mut_slice()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<B: Buffer> Drop for Sensitive<B> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<B: Buffer> core::fmt::Debug for Sensitive<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Sensitive")
+            .field("capacity", &self.buffer.as_slice().len())
+            .finish()
+    }
+}
+
+#[cfg(all(feature = "zeroize", feature = "defmt"))]
+impl<B: Buffer> defmt::Format for Sensitive<B> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "Sensitive {{ capacity: {} }}",
+            self.buffer.as_slice().len()
+        )
+    }
+}
+
+/// The capacity of a [`FrameBuffer`] was exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrameBufferFull;
+
+impl core::fmt::Display for FrameBufferFull {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Frame buffer full")
+    }
+}
+
+impl core::error::Error for FrameBufferFull {}
+
+/// A container that owns a copy of a decoded frame of type `T`.
+///
+/// Used by the [`Copied`](crate::codec::copied::Copied) codec adapter, so owned frames can be stored in any container,
+/// e.g. [`heapless::Vec`](https://docs.rs/heapless/latest/heapless/struct.Vec.html) or `arrayvec::ArrayVec`.
+pub trait FrameBuffer<T: ?Sized>: Default + AsRef<T> {
+    /// Appends `frame` to the container.
+    fn push(&mut self, frame: &T) -> Result<(), FrameBufferFull>;
+}
+
+#[cfg(feature = "alloc")]
+impl FrameBuffer<[u8]> for alloc::vec::Vec<u8> {
+    #[inline]
+    fn push(&mut self, frame: &[u8]) -> Result<(), FrameBufferFull> {
+        self.extend_from_slice(frame);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FrameBuffer<str> for alloc::string::String {
+    #[inline]
+    fn push(&mut self, frame: &str) -> Result<(), FrameBufferFull> {
+        self.push_str(frame);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> FrameBuffer<[u8]> for heapless::Vec<u8, N> {
+    #[inline]
+    fn push(&mut self, frame: &[u8]) -> Result<(), FrameBufferFull> {
+        self.extend_from_slice(frame).map_err(|_| FrameBufferFull)
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> FrameBuffer<str> for heapless::String<N> {
+    #[inline]
+    fn push(&mut self, frame: &str) -> Result<(), FrameBufferFull> {
+        self.push_str(frame).map_err(|_| FrameBufferFull)
+    }
+}
+
+#[cfg(all(test, feature = "zeroize"))]
+mod test {
+    use crate::{
+        FramedRead, RingFramedRead,
+        codec::lines::{Lines, StrLines},
+        mock::ScriptedReader,
+        next, next_ring,
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn sensitive_buffer_is_wiped() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+
+        {
+            let mut framer = FramedRead::with_buffer(
+                StrLines::new(),
+                ScriptedReader::new(&[b"secret\n", b"password\n"]),
+                Sensitive::new(&mut *buffer),
+            );
+
+            let item = next!(framer).expect("Must be some").expect("Must decode");
+            assert_eq!(item, "secret");
+
+            framer.zeroize_buffers();
+
+            assert_eq!(framer.framable(), 0);
+            assert!(
+                framer
+                    .core
+                    .state
+                    .read
+                    .buffer
+                    .as_slice()
+                    .iter()
+                    .all(|b| *b == 0)
+            );
+
+            // Reads the rest of the stream into the wiped buffer.
+            let item = next!(framer).expect("Must be some").expect("Must decode");
+            assert_eq!(item, "password");
+        }
+
+        assert_eq!(buffer, &[0_u8; 32]);
+    }
+
+    #[tokio::test]
+    async fn ring_buffer_is_wiped() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = RingFramedRead::new(
+            Lines::new(),
+            ScriptedReader::new(&[b"secret\npass", b"word\n"]),
+            buffer,
+        );
+
+        let item = next_ring!(framer)
+            .expect("Must be some")
+            .expect("Must decode");
+        assert_eq!(item, b"secret".as_slice());
+
+        framer.zeroize_buffers();
+
+        assert_eq!(framer.framable(), 0);
+        assert!(framer.state.buffer.iter().all(|b| *b == 0));
+
+        // The discarded part of the frame is gone, the rest of the stream is read into the wiped buffer.
+        let item = next_ring!(framer)
+            .expect("Must be some")
+            .expect("Must decode");
+        assert_eq!(item, b"word".as_slice());
+    }
+}
@@ -0,0 +1,404 @@
+//! Adapter from [`embassy_usb`](https://docs.rs/embassy-usb/latest/embassy_usb/)'s CDC-ACM class to [`Read`] and [`Write`].
+//!
+//! The CDC-ACM class reads and writes whole USB packets, while the framers read into whatever space is left in their buffer
+//! and write frames of any length. [`CdcAcm`] bridges the two:
+//!
+//! - Reads into less space than a packet go through an internal packet buffer, so no bytes are lost.
+//! - Zero-length packets sent by the host are skipped, instead of being reported as EOF.
+//! - Writes are split into packets, and a flush after a full packet sends a zero-length packet, so the host does not wait for the end of the transfer.
+//!
+//! # Example
+//!
+//! ```rust
+//! use embassy_usb::{class::cdc_acm::CdcAcmClass, driver::Driver};
+//! use framez::{Framed, cdc_acm::CdcAcm, codec::lines::StrLines, next, send};
+//!
+//! async fn echo<'d, D: Driver<'d>>(mut class: CdcAcmClass<'d, D>) {
+//!     class.wait_connection().await;
+//!
+//!     let read_buf = &mut [0_u8; 256];
+//!     let write_buf = &mut [0_u8; 256];
+//!     let mut framed = Framed::new(StrLines::new(), CdcAcm::<_>::new(class), read_buf, write_buf);
+//!
+//!     while let Some(Ok(line)) = next!(framed) {
+//!         if send!(framed, line).is_err() {
+//!             break;
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The halves of [`CdcAcmClass::split`] are adapted the same way, e.g. for a [`FramedRead`](crate::FramedRead) and a [`FramedWrite`](crate::FramedWrite) in different tasks.
+
+#![allow(async_fn_in_trait)]
+
+use embassy_usb::{
+    class::cdc_acm::{CdcAcmClass, Receiver, Sender},
+    driver::{Driver, EndpointError},
+};
+use embedded_io_async::{ErrorKind, ErrorType, Read, Write};
+
+/// A source of USB packets.
+pub trait ReadPacket {
+    /// Returns the maximum packet size in bytes.
+    fn max_packet_size(&self) -> usize;
+
+    /// Reads a single packet into `buf`, which holds at least [`ReadPacket::max_packet_size`] bytes.
+    async fn read_packet(&mut self, buf: &mut [u8]) -> Result<usize, EndpointError>;
+}
+
+/// A sink of USB packets.
+pub trait WritePacket {
+    /// Returns the maximum packet size in bytes.
+    fn max_packet_size(&self) -> usize;
+
+    /// Writes `data`, which holds at most [`WritePacket::max_packet_size`] bytes, as a single packet.
+    async fn write_packet(&mut self, data: &[u8]) -> Result<(), EndpointError>;
+}
+
+impl<'d, D: Driver<'d>> ReadPacket for CdcAcmClass<'d, D> {
+    fn max_packet_size(&self) -> usize {
+        self.max_packet_size() as usize
+    }
+
+    async fn read_packet(&mut self, buf: &mut [u8]) -> Result<usize, EndpointError> {
+        self.read_packet(buf).await
+    }
+}
+
+impl<'d, D: Driver<'d>> WritePacket for CdcAcmClass<'d, D> {
+    fn max_packet_size(&self) -> usize {
+        self.max_packet_size() as usize
+    }
+
+    async fn write_packet(&mut self, data: &[u8]) -> Result<(), EndpointError> {
+        self.write_packet(data).await
+    }
+}
+
+impl<'d, D: Driver<'d>> ReadPacket for Receiver<'d, D> {
+    fn max_packet_size(&self) -> usize {
+        self.max_packet_size() as usize
+    }
+
+    async fn read_packet(&mut self, buf: &mut [u8]) -> Result<usize, EndpointError> {
+        self.read_packet(buf).await
+    }
+}
+
+impl<'d, D: Driver<'d>> WritePacket for Sender<'d, D> {
+    fn max_packet_size(&self) -> usize {
+        self.max_packet_size() as usize
+    }
+
+    async fn write_packet(&mut self, data: &[u8]) -> Result<(), EndpointError> {
+        self.write_packet(data).await
+    }
+}
+
+/// An error that can occur while reading or writing USB packets.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CdcAcmError {
+    /// The endpoint is disabled, e.g. because the USB host disconnected.
+    Disconnected,
+    /// A received packet does not fit into the packet buffer.
+    ///
+    /// The packet buffer of the [`CdcAcm`] must hold at least the maximum packet size.
+    BufferOverflow,
+}
+
+impl From<EndpointError> for CdcAcmError {
+    fn from(err: EndpointError) -> Self {
+        match err {
+            EndpointError::Disabled => Self::Disconnected,
+            EndpointError::BufferOverflow => Self::BufferOverflow,
+        }
+    }
+}
+
+impl core::fmt::Display for CdcAcmError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Disconnected => write!(f, "Disconnected"),
+            Self::BufferOverflow => write!(f, "Buffer overflow"),
+        }
+    }
+}
+
+impl core::error::Error for CdcAcmError {}
+
+impl embedded_io_async::Error for CdcAcmError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Disconnected => ErrorKind::NotConnected,
+            Self::BufferOverflow => ErrorKind::OutOfMemory,
+        }
+    }
+}
+
+/// Adapts a [`CdcAcmClass`], or one of its split halves, to [`Read`] and [`Write`].
+///
+/// The packet buffer holds `N` bytes, which must be at least the maximum packet size of the class. Defaults to `64`, the maximum for full-speed devices.
+/// See the [module](crate::cdc_acm) documentation.
+#[derive(Debug)]
+pub struct CdcAcm<C, const N: usize = 64> {
+    class: C,
+    packet: [u8; N],
+    /// The start of the bytes in the packet buffer that have not been read yet.
+    start: usize,
+    /// The end of the bytes in the packet buffer.
+    end: usize,
+    /// Whether the last written packet was full, so a zero-length packet must end the transfer.
+    zlp: bool,
+}
+
+impl<C, const N: usize> CdcAcm<C, N> {
+    /// Creates a new [`CdcAcm`].
+    #[inline]
+    pub const fn new(class: C) -> Self {
+        Self {
+            class,
+            packet: [0; N],
+            start: 0,
+            end: 0,
+            zlp: false,
+        }
+    }
+
+    /// Returns reference to the class.
+    #[inline]
+    pub const fn inner(&self) -> &C {
+        &self.class
+    }
+
+    /// Returns mutable reference to the class.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut C {
+        &mut self.class
+    }
+
+    /// Consumes the [`CdcAcm`] and returns the class.
+    ///
+    /// Bytes of a received packet that have not been read yet are dropped.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.class
+    }
+
+    /// Copies the buffered bytes of the last received packet into `buf`.
+    fn read_buffered(&mut self, buf: &mut [u8]) -> usize {
+        let available = self.packet.get(self.start..self.end).unwrap_or_default();
+        let len = available.len().min(buf.len());
+
+        if let (Some(dst), Some(src)) = (buf.get_mut(..len), available.get(..len)) {
+            dst.copy_from_slice(src);
+        }
+
+        self.start = self.start.saturating_add(len);
+
+        len
+    }
+}
+
+impl<C, const N: usize> ErrorType for CdcAcm<C, N> {
+    type Error = CdcAcmError;
+}
+
+impl<C, const N: usize> Read for CdcAcm<C, N>
+where
+    C: ReadPacket,
+{
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if self.start < self.end {
+            return Ok(self.read_buffered(buf));
+        }
+
+        loop {
+            // Reads directly into `buf` if it can hold a whole packet.
+            if buf.len() >= self.class.max_packet_size() {
+                match self.class.read_packet(buf).await? {
+                    0 => continue,
+                    n => return Ok(n),
+                }
+            }
+
+            let n = self.class.read_packet(&mut self.packet).await?;
+
+            // Skips zero-length packets, which end a transfer and must not be mistaken for EOF.
+            if n == 0 {
+                continue;
+            }
+
+            self.end = n;
+            self.start = 0;
+
+            return Ok(self.read_buffered(buf));
+        }
+    }
+}
+
+impl<C, const N: usize> Write for CdcAcm<C, N>
+where
+    C: WritePacket,
+{
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let max_packet_size = self.class.max_packet_size();
+        let len = buf.len().min(max_packet_size);
+
+        self.class
+            .write_packet(buf.get(..len).unwrap_or_default())
+            .await?;
+
+        self.zlp = len == max_packet_size;
+
+        Ok(len)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        if self.zlp {
+            self.class.write_packet(&[]).await?;
+
+            self.zlp = false;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use crate::{
+        Framed, FramedRead, FramedWrite, codec::lines::StrLines, next, tests::init_tracing,
+    };
+
+    use super::*;
+
+    /// Packets of at most `4` bytes.
+    #[derive(Default)]
+    struct Packets {
+        received: Vec<Vec<u8>>,
+        sent: Vec<Vec<u8>>,
+    }
+
+    impl ReadPacket for Packets {
+        fn max_packet_size(&self) -> usize {
+            4
+        }
+
+        async fn read_packet(&mut self, buf: &mut [u8]) -> Result<usize, EndpointError> {
+            if self.received.is_empty() {
+                return Err(EndpointError::Disabled);
+            }
+
+            let packet = self.received.remove(0);
+
+            buf.get_mut(..packet.len())
+                .ok_or(EndpointError::BufferOverflow)?
+                .copy_from_slice(&packet);
+
+            Ok(packet.len())
+        }
+    }
+
+    impl WritePacket for Packets {
+        fn max_packet_size(&self) -> usize {
+            4
+        }
+
+        async fn write_packet(&mut self, data: &[u8]) -> Result<(), EndpointError> {
+            assert!(data.len() <= 4);
+
+            self.sent.push(data.to_vec());
+
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_packets_into_small_buffers() {
+        init_tracing();
+
+        let packets = Packets {
+            received: std::vec![
+                b"Hell".to_vec(),
+                b"o\nHe".to_vec(),
+                std::vec![],
+                b"y\n".to_vec()
+            ],
+            ..Default::default()
+        };
+
+        // Only 3 bytes fit after the first buffered bytes, less than a packet.
+        let buffer = &mut [0_u8; 7];
+        let mut framer = FramedRead::new(StrLines::new(), CdcAcm::<_, 4>::new(packets), buffer);
+
+        assert_eq!(
+            next!(framer).expect("Must be some").expect("Must decode"),
+            "Hello"
+        );
+        assert_eq!(
+            next!(framer).expect("Must be some").expect("Must decode"),
+            "Hey"
+        );
+        assert!(matches!(
+            next!(framer),
+            Some(Err(crate::ReadError::IO(CdcAcmError::Disconnected)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn full_packet_is_followed_by_zero_length_packet() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedWrite::new(
+            StrLines::new(),
+            CdcAcm::<_, 4>::new(Packets::default()),
+            buffer,
+        );
+
+        // Both frames end with `\r\n`. The first one fills its last packet.
+        framer.send("Hello!").await.expect("Must send");
+        framer.send("Hey").await.expect("Must send");
+
+        let sent: &[&[u8]] = &[b"Hell", b"o!\r\n", b"", b"Hey\r", b"\n"];
+
+        assert_eq!(framer.inner().inner().sent, sent);
+    }
+
+    #[tokio::test]
+    async fn packet_buffer_too_small() {
+        init_tracing();
+
+        let packets = Packets {
+            received: std::vec![b"Hell".to_vec()],
+            ..Default::default()
+        };
+
+        let read_buf = &mut [0_u8; 2];
+        let write_buf = &mut [0_u8; 2];
+        let mut framer = Framed::new(
+            StrLines::new(),
+            CdcAcm::<_, 2>::new(packets),
+            read_buf,
+            write_buf,
+        );
+
+        assert!(matches!(
+            next!(framer),
+            Some(Err(crate::ReadError::IO(CdcAcmError::BufferOverflow)))
+        ));
+    }
+}
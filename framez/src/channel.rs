@@ -0,0 +1,408 @@
+//! In-process transports backed by channels.
+//!
+//! Route bytes between tasks, or feed a framer in tests, without a real IO object:
+//!
+//! - [`SpscRead`] and [`SpscWrite`] wrap the ends of a [`heapless::spsc::Queue`](https://docs.rs/heapless/latest/heapless/spsc/struct.Queue.html) of bytes, with the `heapless` feature.
+//! - [`pipe`] creates a [`ChannelRead`] and a [`ChannelWrite`] over a [`futures::channel::mpsc`](https://docs.rs/futures/latest/futures/channel/mpsc/index.html) channel of byte chunks,
+//!   with the `futures-channel` feature.
+
+#[cfg(feature = "heapless")]
+pub use spsc::{SpscRead, SpscWrite};
+
+#[cfg(feature = "futures-channel")]
+pub use mpsc::{ChannelRead, ChannelWrite, Closed, pipe};
+
+#[cfg(feature = "heapless")]
+mod spsc {
+    use core::convert::Infallible;
+
+    use embedded_io_async::{ErrorType, Read, ReadReady, Write, WriteReady};
+    use heapless::spsc::{Consumer, Producer};
+
+    use crate::future::yield_now;
+
+    /// Reads bytes from the [`Consumer`] of a [`heapless::spsc::Queue`].
+    ///
+    /// The queue has no way to wake a waiting task. While it is empty, reading yields to the executor and tries again.
+    /// The queue is never closed, so reading never reaches EOF.
+    pub struct SpscRead<'a, const N: usize> {
+        consumer: Consumer<'a, u8, N>,
+    }
+
+    impl<'a, const N: usize> SpscRead<'a, N> {
+        /// Creates a new [`SpscRead`].
+        #[inline]
+        pub const fn new(consumer: Consumer<'a, u8, N>) -> Self {
+            Self { consumer }
+        }
+
+        /// Consumes the [`SpscRead`] and returns the [`Consumer`].
+        #[inline]
+        pub fn into_inner(self) -> Consumer<'a, u8, N> {
+            self.consumer
+        }
+    }
+
+    impl<const N: usize> core::fmt::Debug for SpscRead<'_, N> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.debug_struct("SpscRead")
+                .field("len", &self.consumer.len())
+                .field("capacity", &self.consumer.capacity())
+                .finish()
+        }
+    }
+
+    impl<const N: usize> ErrorType for SpscRead<'_, N> {
+        type Error = Infallible;
+    }
+
+    impl<const N: usize> Read for SpscRead<'_, N> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+
+            while !self.consumer.ready() {
+                yield_now().await;
+            }
+
+            let mut read = 0_usize;
+
+            for slot in buf.iter_mut() {
+                let Some(byte) = self.consumer.dequeue() else {
+                    break;
+                };
+
+                *slot = byte;
+                read = read.saturating_add(1);
+            }
+
+            Ok(read)
+        }
+    }
+
+    impl<const N: usize> ReadReady for SpscRead<'_, N> {
+        fn read_ready(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.consumer.ready())
+        }
+    }
+
+    /// Writes bytes to the [`Producer`] of a [`heapless::spsc::Queue`].
+    ///
+    /// The queue has no way to wake a waiting task. While it is full, writing yields to the executor and tries again.
+    pub struct SpscWrite<'a, const N: usize> {
+        producer: Producer<'a, u8, N>,
+    }
+
+    impl<'a, const N: usize> SpscWrite<'a, N> {
+        /// Creates a new [`SpscWrite`].
+        #[inline]
+        pub const fn new(producer: Producer<'a, u8, N>) -> Self {
+            Self { producer }
+        }
+
+        /// Consumes the [`SpscWrite`] and returns the [`Producer`].
+        #[inline]
+        pub fn into_inner(self) -> Producer<'a, u8, N> {
+            self.producer
+        }
+    }
+
+    impl<const N: usize> core::fmt::Debug for SpscWrite<'_, N> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.debug_struct("SpscWrite")
+                .field("len", &self.producer.len())
+                .field("capacity", &self.producer.capacity())
+                .finish()
+        }
+    }
+
+    impl<const N: usize> ErrorType for SpscWrite<'_, N> {
+        type Error = Infallible;
+    }
+
+    impl<const N: usize> Write for SpscWrite<'_, N> {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+
+            while !self.producer.ready() {
+                yield_now().await;
+            }
+
+            let mut written = 0_usize;
+
+            for byte in buf {
+                if self.producer.enqueue(*byte).is_err() {
+                    break;
+                }
+
+                written = written.saturating_add(1);
+            }
+
+            Ok(written)
+        }
+    }
+
+    impl<const N: usize> WriteReady for SpscWrite<'_, N> {
+        fn write_ready(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.producer.ready())
+        }
+    }
+}
+
+#[cfg(feature = "futures-channel")]
+mod mpsc {
+    use core::future::poll_fn;
+    use std::vec::Vec;
+
+    use embedded_io_async::{ErrorKind, ErrorType, Read, Write};
+    use futures::{
+        StreamExt,
+        channel::mpsc::{Receiver, Sender, channel},
+    };
+
+    /// Creates a [`ChannelRead`] and a [`ChannelWrite`] connected by a channel that buffers `buffer` chunks, see [`channel`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use framez::{FramedRead, FramedWrite, channel::pipe, codec::lines::StrLines, next};
+    ///
+    /// # async fn run() {
+    /// let (read, write) = pipe(8);
+    ///
+    /// let write_buf = &mut [0_u8; 32];
+    /// let mut writer = FramedWrite::new(StrLines::new(), write, write_buf);
+    ///
+    /// writer.send("Hello").await.unwrap();
+    ///
+    /// // Dropping the writer closes the channel, so the reader reaches EOF.
+    /// drop(writer);
+    ///
+    /// let read_buf = &mut [0_u8; 32];
+    /// let mut reader = FramedRead::new(StrLines::new(), read, read_buf);
+    ///
+    /// assert_eq!(next!(reader).unwrap().unwrap(), "Hello");
+    /// assert!(next!(reader).is_none());
+    /// # }
+    /// ```
+    pub fn pipe(buffer: usize) -> (ChannelRead, ChannelWrite) {
+        let (sender, receiver) = channel(buffer);
+
+        (ChannelRead::new(receiver), ChannelWrite::new(sender))
+    }
+
+    /// The error returned by a [`ChannelWrite`] when the receiving end of its channel is dropped.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub struct Closed;
+
+    impl core::fmt::Display for Closed {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "Channel closed")
+        }
+    }
+
+    impl core::error::Error for Closed {}
+
+    impl embedded_io_async::Error for Closed {
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::BrokenPipe
+        }
+    }
+
+    /// Reads the byte chunks received from a [`Receiver`].
+    ///
+    /// A chunk that does not fit into the read buffer is kept and read by the next calls.
+    /// Reading reaches EOF once all the senders are dropped and every chunk is read.
+    #[derive(Debug)]
+    pub struct ChannelRead {
+        receiver: Receiver<Vec<u8>>,
+        chunk: Vec<u8>,
+        /// The number of bytes of the chunk that have been read.
+        read: usize,
+    }
+
+    impl ChannelRead {
+        /// Creates a new [`ChannelRead`].
+        #[inline]
+        pub const fn new(receiver: Receiver<Vec<u8>>) -> Self {
+            Self {
+                receiver,
+                chunk: Vec::new(),
+                read: 0,
+            }
+        }
+
+        /// Consumes the [`ChannelRead`] and returns the [`Receiver`].
+        ///
+        /// The bytes of the current chunk that have not been read yet are dropped.
+        #[inline]
+        pub fn into_inner(self) -> Receiver<Vec<u8>> {
+            self.receiver
+        }
+    }
+
+    impl ErrorType for ChannelRead {
+        type Error = core::convert::Infallible;
+    }
+
+    impl Read for ChannelRead {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+
+            while self.read >= self.chunk.len() {
+                match self.receiver.next().await {
+                    Some(chunk) => {
+                        self.chunk = chunk;
+                        self.read = 0;
+                    }
+                    None => return Ok(0),
+                }
+            }
+
+            let available = self.chunk.get(self.read..).unwrap_or_default();
+            let len = available.len().min(buf.len());
+
+            if let (Some(dst), Some(src)) = (buf.get_mut(..len), available.get(..len)) {
+                dst.copy_from_slice(src);
+            }
+
+            self.read = self.read.saturating_add(len);
+
+            Ok(len)
+        }
+    }
+
+    /// Sends every written buffer as a byte chunk to a [`Sender`].
+    ///
+    /// Writing waits while the channel is full, and is cancel safe. Dropping the [`ChannelWrite`] closes the channel, unless the [`Sender`] was cloned.
+    #[derive(Debug, Clone)]
+    pub struct ChannelWrite {
+        sender: Sender<Vec<u8>>,
+    }
+
+    impl ChannelWrite {
+        /// Creates a new [`ChannelWrite`].
+        #[inline]
+        pub const fn new(sender: Sender<Vec<u8>>) -> Self {
+            Self { sender }
+        }
+
+        /// Consumes the [`ChannelWrite`] and returns the [`Sender`].
+        #[inline]
+        pub fn into_inner(self) -> Sender<Vec<u8>> {
+            self.sender
+        }
+    }
+
+    impl ErrorType for ChannelWrite {
+        type Error = Closed;
+    }
+
+    impl Write for ChannelWrite {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+
+            // The chunk is handed to the channel without suspending, so cancelling never sends it twice.
+            poll_fn(|cx| self.sender.poll_ready(cx))
+                .await
+                .map_err(|_| Closed)?;
+
+            self.sender.start_send(buf.to_vec()).map_err(|_| Closed)?;
+
+            Ok(buf.len())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "heapless")]
+    #[tokio::test]
+    async fn spsc() {
+        use heapless::spsc::Queue;
+
+        use crate::{FramedRead, FramedWrite, codec::lines::StrLines, next, tests::init_tracing};
+
+        use super::*;
+
+        init_tracing();
+
+        // Smaller than a frame, so the writer waits for the reader.
+        let mut queue = Queue::<u8, 4>::new();
+        let (producer, consumer) = queue.split();
+
+        let items = ["Hello", "Hey", "How are you?"];
+
+        let write = async move {
+            let buffer = &mut [0_u8; 32];
+            let mut writer = FramedWrite::new(StrLines::new(), SpscWrite::new(producer), buffer);
+
+            for item in items {
+                writer.send(item).await.expect("Must send");
+            }
+        };
+
+        let read = async move {
+            let buffer = &mut [0_u8; 32];
+            let mut reader = FramedRead::new(StrLines::new(), SpscRead::new(consumer), buffer);
+
+            for item in items {
+                assert_eq!(
+                    next!(reader).expect("Must be some").expect("Must decode"),
+                    item
+                );
+            }
+        };
+
+        tokio::join!(write, read);
+    }
+
+    #[cfg(feature = "futures-channel")]
+    #[tokio::test]
+    async fn pipe() {
+        use std::vec::Vec;
+
+        use futures::{SinkExt, StreamExt, pin_mut};
+
+        use crate::{FramedRead, FramedWrite, codec::lines::StrLines, tests::init_tracing};
+
+        init_tracing();
+
+        let (read, write) = super::pipe(1);
+
+        let items = ["Hello", "Hey", "How are you?"];
+
+        let writer = tokio::spawn(async move {
+            let buffer = &mut [0_u8; 32];
+            let mut writer = FramedWrite::new(StrLines::new(), write, buffer);
+            let sink = writer.sink();
+
+            pin_mut!(sink);
+
+            for item in items {
+                sink.send(item).await.expect("Must send");
+            }
+        });
+
+        let buffer = &mut [0_u8; 16];
+        let mut reader = FramedRead::new(StrLines::new(), read, buffer);
+
+        let collected = reader
+            .stream(|item| std::string::String::from(item))
+            .map(|item| item.expect("Must decode"))
+            .collect::<Vec<_>>()
+            .await;
+
+        writer.await.expect("Must join");
+
+        assert_eq!(collected, items);
+    }
+}
@@ -0,0 +1,245 @@
+//! Checksums of frames.
+//!
+//! The [`Checksum`] trait lets codecs compute the checksum of a frame without depending on a specific algorithm,
+//! so a software CRC can be swapped for a hardware CRC engine of the target.
+//!
+//! Software implementations are provided for common CRC-8, CRC-16 and CRC-32 variants, see [`Crc8`], [`Crc16`] and [`Crc32`].
+//! They compute the CRC bitwise, trading speed for not requiring any lookup table.
+
+/// An incrementally computed checksum.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::checksum::{Checksum, Crc16};
+///
+/// let mut crc = Crc16::modbus();
+///
+/// crc.update(b"1234");
+/// crc.update(b"56789");
+///
+/// assert_eq!(crc.finalize(), 0x4B37);
+/// ```
+pub trait Checksum {
+    /// The computed checksum.
+    type Output;
+
+    /// Feeds `bytes` into the checksum.
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Returns the checksum of all bytes fed since the creation or the last [`Checksum::reset`].
+    fn finalize(&self) -> Self::Output;
+
+    /// Resets the checksum to its initial value.
+    fn reset(&mut self);
+
+    /// Resets the checksum and returns the checksum of `bytes`.
+    fn checksum(&mut self, bytes: &[u8]) -> Self::Output {
+        self.reset();
+        self.update(bytes);
+        self.finalize()
+    }
+}
+
+impl<C> Checksum for &mut C
+where
+    C: Checksum + ?Sized,
+{
+    type Output = C::Output;
+
+    #[inline]
+    fn update(&mut self, bytes: &[u8]) {
+        C::update(self, bytes)
+    }
+
+    #[inline]
+    fn finalize(&self) -> Self::Output {
+        C::finalize(self)
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        C::reset(self)
+    }
+}
+
+macro_rules! crc {
+    ($(#[$meta:meta])* $name:ident, $ty:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        pub struct $name {
+            /// The polynomial, reflected if the input and the output are reflected.
+            poly: $ty,
+            /// The initial value of the register, reflected if the input and the output are reflected.
+            init: $ty,
+            reflected: bool,
+            xorout: $ty,
+            register: $ty,
+        }
+
+        impl $name {
+            /// Creates a new CRC with the given parameters.
+            ///
+            /// `poly` and `init` are given in their normal, unreflected form. If `reflected` is `true`, both the input bytes and the output are reflected.
+            /// The output is XORed with `xorout`.
+            #[inline]
+            pub const fn new(poly: $ty, init: $ty, reflected: bool, xorout: $ty) -> Self {
+                let (poly, init) = if reflected {
+                    (poly.reverse_bits(), init.reverse_bits())
+                } else {
+                    (poly, init)
+                };
+
+                Self {
+                    poly,
+                    init,
+                    reflected,
+                    xorout,
+                    register: init,
+                }
+            }
+        }
+
+        impl Checksum for $name {
+            type Output = $ty;
+
+            fn update(&mut self, bytes: &[u8]) {
+                const TOP: $ty = 1 << (<$ty>::BITS - 1);
+
+                for &byte in bytes {
+                    if self.reflected {
+                        self.register ^= byte as $ty;
+
+                        for _ in 0..8 {
+                            self.register = if self.register & 1 != 0 {
+                                (self.register >> 1) ^ self.poly
+                            } else {
+                                self.register >> 1
+                            };
+                        }
+                    } else {
+                        self.register ^= (byte as $ty) << (<$ty>::BITS - 8);
+
+                        for _ in 0..8 {
+                            self.register = if self.register & TOP != 0 {
+                                (self.register << 1) ^ self.poly
+                            } else {
+                                self.register << 1
+                            };
+                        }
+                    }
+                }
+            }
+
+            #[inline]
+            fn finalize(&self) -> Self::Output {
+                self.register ^ self.xorout
+            }
+
+            #[inline]
+            fn reset(&mut self) {
+                self.register = self.init;
+            }
+        }
+    };
+}
+
+crc!(
+    /// A CRC with an 8-bit register.
+    Crc8,
+    u8
+);
+
+crc!(
+    /// A CRC with a 16-bit register.
+    Crc16,
+    u16
+);
+
+crc!(
+    /// A CRC with a 32-bit register.
+    Crc32,
+    u32
+);
+
+impl Crc8 {
+    /// CRC-8/SMBUS, used by SMBus packet error checking.
+    #[inline]
+    pub const fn smbus() -> Self {
+        Self::new(0x07, 0x00, false, 0x00)
+    }
+
+    /// CRC-8/MAXIM-DOW, used by Dallas 1-Wire devices.
+    #[inline]
+    pub const fn maxim() -> Self {
+        Self::new(0x31, 0x00, true, 0x00)
+    }
+}
+
+impl Crc16 {
+    /// CRC-16/MODBUS, used by Modbus RTU.
+    #[inline]
+    pub const fn modbus() -> Self {
+        Self::new(0x8005, 0xFFFF, true, 0x0000)
+    }
+
+    /// CRC-16/IBM-SDLC, also known as CRC-16/X-25, used as the frame check sequence of HDLC.
+    #[inline]
+    pub const fn x25() -> Self {
+        Self::new(0x1021, 0xFFFF, true, 0xFFFF)
+    }
+
+    /// CRC-16/IBM-3740, also known as CRC-16/CCITT-FALSE.
+    #[inline]
+    pub const fn ccitt_false() -> Self {
+        Self::new(0x1021, 0xFFFF, false, 0x0000)
+    }
+}
+
+impl Crc32 {
+    /// CRC-32/ISO-HDLC, the CRC-32 of Ethernet, zip and the 32-bit frame check sequence of HDLC.
+    #[inline]
+    pub const fn iso_hdlc() -> Self {
+        Self::new(0x04C1_1DB7, 0xFFFF_FFFF, true, 0xFFFF_FFFF)
+    }
+
+    /// CRC-32/ISCSI, also known as CRC-32C or Castagnoli.
+    #[inline]
+    pub const fn castagnoli() -> Self {
+        Self::new(0x1EDC_6F41, 0xFFFF_FFFF, true, 0xFFFF_FFFF)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CHECK: &[u8] = b"123456789";
+
+    #[test]
+    fn check_values() {
+        assert_eq!(Crc8::smbus().checksum(CHECK), 0xF4);
+        assert_eq!(Crc8::maxim().checksum(CHECK), 0xA1);
+        assert_eq!(Crc16::modbus().checksum(CHECK), 0x4B37);
+        assert_eq!(Crc16::x25().checksum(CHECK), 0x906E);
+        assert_eq!(Crc16::ccitt_false().checksum(CHECK), 0x29B1);
+        assert_eq!(Crc32::iso_hdlc().checksum(CHECK), 0xCBF4_3926);
+        assert_eq!(Crc32::castagnoli().checksum(CHECK), 0xE306_9283);
+    }
+
+    #[test]
+    fn incremental_update_and_reset() {
+        let mut crc = Crc32::iso_hdlc();
+
+        for chunk in CHECK.chunks(2) {
+            crc.update(chunk);
+        }
+
+        assert_eq!(crc.finalize(), 0xCBF4_3926);
+
+        crc.reset();
+
+        assert_eq!(crc.finalize(), 0x0000_0000);
+    }
+}
@@ -0,0 +1,65 @@
+//! Clocks to timestamp frames.
+//!
+//! A [`Clock`] stamps every frame read with [`next_timestamped`](crate::functions::next_timestamped) with the time it was received,
+//! e.g. to correlate the frames of a data logger with sensor events.
+
+/// A monotonic source of tick counts.
+///
+/// The length of a tick is up to the implementation, e.g. the ticks of `embassy_time::Instant` or the cycle counter of the target.
+///
+/// Implemented for closures returning a tick count:
+///
+/// ```rust
+/// use framez::clock::Clock;
+///
+/// let clock = || 42_u64;
+///
+/// assert_eq!(clock.now(), 42);
+/// ```
+pub trait Clock {
+    /// Returns the current tick count.
+    fn now(&self) -> u64;
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> u64,
+{
+    #[inline]
+    fn now(&self) -> u64 {
+        self()
+    }
+}
+
+/// A [`Clock`] counting microseconds since its creation, using [`std::time::Instant`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct StdClock {
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl StdClock {
+    /// Creates a new [`StdClock`] starting at `0`.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    #[inline]
+    fn now(&self) -> u64 {
+        u64::try_from(self.start.elapsed().as_micros()).unwrap_or(u64::MAX)
+    }
+}
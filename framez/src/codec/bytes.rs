@@ -2,9 +2,12 @@
 
 use core::convert::Infallible;
 
+use embedded_io_async::Write;
+
 use crate::{
-    decode::{DecodeError, Decoder},
-    encode::Encoder,
+    WriteError,
+    decode::{Decoder, RingDecoder, RingSlice},
+    encode::{EncodeBufferTooSmall, EncodeParts, Encoder, Parts, write_all},
 };
 
 /// A codec that decodes bytes into bytes and encodes bytes into bytes.
@@ -20,48 +23,146 @@ impl Bytes {
     }
 }
 
-impl DecodeError for Bytes {
+impl Decoder for Bytes {
+    type Item<'buf> = &'buf [u8];
     type Error = Infallible;
-}
-
-impl<'buf> Decoder<'buf> for Bytes {
-    type Item = &'buf [u8];
 
-    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
         Ok(Some((src, src.len())))
     }
 }
 
-/// Error returned by [`Bytes::encode`].
-#[derive(Debug)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub enum BytesEncodeError {
-    /// The input buffer is too small to fit the bytes.
-    BufferTooSmall,
-}
+impl RingDecoder for Bytes {
+    type Item<'buf> = RingSlice<'buf>;
+    type Error = Infallible;
 
-impl core::fmt::Display for BytesEncodeError {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        match self {
-            Self::BufferTooSmall => write!(f, "buffer too small"),
+    fn decode_ring<'buf>(
+        &mut self,
+        head: &'buf mut [u8],
+        tail: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let src = RingSlice::new(head, tail);
+
+        if src.is_empty() {
+            return Ok(None);
         }
+
+        Ok(Some((src, src.len())))
     }
 }
 
-impl core::error::Error for BytesEncodeError {}
-
 impl Encoder<&[u8]> for Bytes {
-    type Error = BytesEncodeError;
+    type Error = EncodeBufferTooSmall;
 
     fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
         let size = item.len();
 
-        if dst.len() < size {
-            return Err(BytesEncodeError::BufferTooSmall);
-        }
+        let available = dst.len();
 
-        dst[..item.len()].copy_from_slice(item);
+        let Some(dst) = dst.get_mut(..size) else {
+            return Err(EncodeBufferTooSmall {
+                required: size,
+                available,
+            });
+        };
+
+        dst.copy_from_slice(item);
 
         Ok(size)
     }
+
+    fn encoded_len(&self, item: &&[u8]) -> Option<usize> {
+        Some(item.len())
+    }
+
+    async fn encode_to_writer<W>(
+        &mut self,
+        item: &[u8],
+        _buf: &mut [u8],
+        write: &mut W,
+    ) -> Result<usize, WriteError<W::Error, Self::Error>>
+    where
+        W: Write,
+    {
+        write_all(write, item).await?;
+
+        Ok(item.len())
+    }
+}
+
+impl<'a> EncodeParts<'a, &'a [u8]> for Bytes {
+    fn encode_parts(&mut self, item: &'a [u8], _dst: &mut [u8]) -> Result<Parts<'a>, Self::Error> {
+        Ok(Parts {
+            header: 0,
+            payload: item,
+            trailer: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use tokio::io::AsyncWriteExt;
+
+    use crate::{RingFramedRead, next_ring, tests::init_tracing};
+
+    use super::*;
+
+    #[test]
+    fn decode_ring() {
+        let head = &mut [1, 2, 3];
+        let tail = &mut [4, 5];
+
+        let (item, size) = Bytes::new()
+            .decode_ring(head, tail)
+            .expect("Must decode")
+            .expect("Must be some");
+
+        assert_eq!(size, 5);
+        assert_eq!(item, [1, 2, 3, 4, 5].as_slice());
+        assert_eq!(item.head(), [1, 2, 3]);
+        assert_eq!(item.tail(), [4, 5]);
+
+        let decoded = Bytes::new()
+            .decode_ring(&mut [], &mut [])
+            .expect("Must decode");
+
+        assert!(decoded.is_none());
+    }
+
+    #[tokio::test]
+    async fn framed_read_ring() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(3);
+
+        tokio::spawn(async move {
+            write.write_all(b"Hello, world!").await.expect("Must write");
+        });
+
+        let buffer = &mut [0_u8; 4];
+        let mut framer = RingFramedRead::new(
+            Bytes::new(),
+            embedded_io_adapters::tokio_1::FromTokio::new(read),
+            buffer,
+        );
+
+        let mut collected = Vec::new();
+
+        while let Some(item) = next_ring!(framer) {
+            let item = item.expect("Must decode");
+
+            assert!(!item.is_empty());
+            assert!(item.len() <= 4);
+
+            collected.extend(item.iter());
+        }
+
+        assert_eq!(collected, b"Hello, world!");
+    }
 }
@@ -0,0 +1,389 @@
+//! COBS codec for encoding and decoding zero terminated frames.
+//!
+//! [Consistent Overhead Byte Stuffing](https://en.wikipedia.org/wiki/Consistent_Overhead_Byte_Stuffing) removes every zero byte from a frame,
+//! so a single zero byte can terminate it. A receiver that joins a stream at an arbitrary point resynchronizes at the next zero byte.
+
+use core::convert::Infallible;
+
+use crate::{
+    decode::Decoder,
+    encode::{EncodeBufferTooSmall, Encoder},
+};
+
+/// The largest code byte. Starts a block of `254` non-zero bytes that is not followed by a zero byte.
+const MAX_CODE: u8 = 0xFF;
+
+/// A codec that decodes COBS encoded bytes ending with a zero byte into bytes and encodes bytes into COBS encoded bytes ending with a zero byte.
+///
+/// Frames are decoded in place, so the decoded item borrows the read buffer mutably.
+/// Empty and malformed frames, e.g. garbage received before the first zero byte, are skipped and consumed together with the next valid frame.
+///
+/// # Note
+///
+/// This codec tracks progress using an internal state of the underlying buffer, and it must not be used across multiple framing sessions.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Cobs {
+    /// The start of the current frame, after the skipped frames.
+    start: usize,
+    /// The number of bytes of the slice that have been seen so far.
+    seen: usize,
+}
+
+impl Cobs {
+    /// Creates a new [`Cobs`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self { start: 0, seen: 0 }
+    }
+
+    /// Returns the number of bytes the frame of `item` occupies, terminating zero byte included.
+    pub fn encoded_len(item: &[u8]) -> usize {
+        let mut len = 2_usize;
+        let mut code = 1_u8;
+
+        for byte in item {
+            len = len.saturating_add(1);
+
+            if *byte == 0 {
+                code = 1;
+
+                continue;
+            }
+
+            code = code.saturating_add(1);
+
+            if code == MAX_CODE {
+                len = len.saturating_add(1);
+                code = 1;
+            }
+        }
+
+        len
+    }
+
+    /// Skips the frame of `size` bytes at the start of the next slice, like a malformed frame.
+    ///
+    /// Used by adapters that fail to process a decoded frame, so the frame is consumed together with the next valid frame.
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub(crate) const fn skip(&mut self, size: usize) {
+        self.start = size;
+        self.seen = size;
+    }
+}
+
+/// Returns the number of bytes to reserve before the bytes encoded by [`encode_in_place`] in a buffer of `len` bytes.
+#[cfg(any(test, feature = "serde"))]
+#[inline]
+pub(crate) const fn in_place_offset(len: usize) -> usize {
+    /// The number of data bytes in a full block.
+    const BLOCK: usize = MAX_CODE as usize - 1;
+
+    (len / BLOCK).saturating_add(1)
+}
+
+/// COBS encodes the `len` bytes at `buf[offset..]` to the start of `buf` and appends the terminating zero byte.
+///
+/// The `offset` must be at least [`in_place_offset`] of the length of `buf`, so every byte is read before it is overwritten.
+/// Returns the size of the frame.
+#[cfg(any(test, feature = "serde"))]
+pub(crate) fn encode_in_place(buf: &mut [u8], offset: usize, len: usize) -> usize {
+    let mut code_index = 0;
+    let mut write = 1;
+    let mut code = 1_u8;
+
+    for read in offset..offset.saturating_add(len) {
+        let byte = buf.get(read).copied().unwrap_or_default();
+
+        if byte != 0 {
+            if let Some(slot) = buf.get_mut(write) {
+                *slot = byte;
+            }
+
+            write = write.saturating_add(1);
+            code = code.saturating_add(1);
+
+            if code != MAX_CODE {
+                continue;
+            }
+        }
+
+        if let Some(slot) = buf.get_mut(code_index) {
+            *slot = code;
+        }
+
+        code_index = write;
+        write = write.saturating_add(1);
+        code = 1;
+    }
+
+    if let Some(slot) = buf.get_mut(code_index) {
+        *slot = code;
+    }
+
+    if let Some(slot) = buf.get_mut(write) {
+        *slot = 0;
+    }
+
+    write.saturating_add(1)
+}
+
+/// Decodes the COBS encoded `frame`, without its terminating zero byte, in place.
+///
+/// Returns the length of the decoded bytes at the start of `frame`, or `None` if the `frame` is empty or malformed.
+fn decode_in_place(frame: &mut [u8]) -> Option<usize> {
+    let mut read = 0;
+    let mut write = 0;
+
+    while let Some(code) = frame.get(read).copied() {
+        let end = read.saturating_add(code as usize);
+
+        if code == 0 || end > frame.len() {
+            return None;
+        }
+
+        // Copied byte by byte, so every access is checked. The decoded bytes never overtake the encoded ones.
+        for index in read.saturating_add(1)..end {
+            *frame.get_mut(write)? = *frame.get(index)?;
+
+            write = write.saturating_add(1);
+        }
+
+        read = end;
+
+        if code != MAX_CODE && read < frame.len() {
+            *frame.get_mut(write)? = 0;
+
+            write = write.saturating_add(1);
+        }
+    }
+
+    (read > 0).then_some(write)
+}
+
+impl Decoder for Cobs {
+    type Item<'buf> = &'buf mut [u8];
+    type Error = Infallible;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        while let Some(byte) = src.get(self.seen).copied() {
+            self.seen = self.seen.saturating_add(1);
+
+            if byte != 0 {
+                continue;
+            }
+
+            let start = self.start;
+            let end = self.seen.saturating_sub(1);
+
+            self.start = self.seen;
+
+            let Some(len) = src.get_mut(start..end).and_then(decode_in_place) else {
+                continue;
+            };
+
+            let size = self.seen;
+
+            self.start = 0;
+            self.seen = 0;
+
+            return Ok(src
+                .get_mut(start..start.saturating_add(len))
+                .map(|bytes| (bytes, size)));
+        }
+
+        Ok(None)
+    }
+}
+
+impl Encoder<&[u8]> for Cobs {
+    type Error = EncodeBufferTooSmall;
+
+    fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let size = Self::encoded_len(item);
+
+        let available = dst.len();
+
+        let Some(dst) = dst.get_mut(..size) else {
+            return Err(EncodeBufferTooSmall {
+                required: size,
+                available,
+            });
+        };
+
+        let mut code_index = 0;
+        let mut write = 1;
+        let mut code = 1_u8;
+
+        for byte in item {
+            if *byte != 0 {
+                if let Some(slot) = dst.get_mut(write) {
+                    *slot = *byte;
+                }
+
+                write = write.saturating_add(1);
+                code = code.saturating_add(1);
+
+                if code != MAX_CODE {
+                    continue;
+                }
+            }
+
+            if let Some(slot) = dst.get_mut(code_index) {
+                *slot = code;
+            }
+
+            code_index = write;
+            write = write.saturating_add(1);
+            code = 1;
+        }
+
+        if let Some(slot) = dst.get_mut(code_index) {
+            *slot = code;
+        }
+
+        if let Some(slot) = dst.get_mut(write) {
+            *slot = 0;
+        }
+
+        Ok(size)
+    }
+
+    fn encoded_len(&self, item: &&[u8]) -> Option<usize> {
+        Some(Self::encoded_len(item))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use futures::{SinkExt, StreamExt, pin_mut};
+    use tokio::io::AsyncWriteExt;
+
+    use crate::{
+        ReadError,
+        tests::{framed_read, init_tracing, sink_stream},
+    };
+
+    use super::*;
+
+    fn encode(item: &[u8]) -> Vec<u8> {
+        let mut dst = std::vec![0; Cobs::encoded_len(item)];
+
+        let size = Cobs::new().encode(item, &mut dst).expect("Must encode");
+
+        assert_eq!(size, dst.len());
+
+        dst
+    }
+
+    #[test]
+    fn encode_known_frames() {
+        assert_eq!(encode(b""), [0x01, 0x00]);
+        assert_eq!(encode(b"\0"), [0x01, 0x01, 0x00]);
+        assert_eq!(encode(b"\0\0"), [0x01, 0x01, 0x01, 0x00]);
+        assert_eq!(
+            encode(b"\x11\x22\0\x33"),
+            [0x03, 0x11, 0x22, 0x02, 0x33, 0x00]
+        );
+        assert_eq!(encode(b"\x11\0\0\0"), [0x02, 0x11, 0x01, 0x01, 0x01, 0x00]);
+
+        let item = [0xAA; 254];
+        let encoded = encode(&item);
+
+        assert_eq!(encoded.len(), 257);
+        assert_eq!(encoded[0], 0xFF);
+        assert_eq!(encoded[255..], [0x01, 0x00]);
+    }
+
+    #[test]
+    fn encode_in_place_matches_encode() {
+        let items: &[&[u8]] = &[b"", b"\0", b"\x11\x22\0\x33", &[0xAA; 254], &[0xAA; 600]];
+
+        for item in items {
+            let len = 1024;
+            let offset = in_place_offset(len);
+
+            let mut buf = std::vec![0; len];
+            buf[offset..offset + item.len()].copy_from_slice(item);
+
+            let size = encode_in_place(&mut buf, offset, item.len());
+
+            assert_eq!(buf[..size], encode(item));
+        }
+    }
+
+    #[test]
+    fn encode_buffer_too_small() {
+        let dst = &mut [0_u8; 4];
+
+        assert_eq!(
+            Cobs::new().encode(b"\x11\x22\0\x33", dst),
+            Err(EncodeBufferTooSmall {
+                required: 6,
+                available: 4
+            })
+        );
+    }
+
+    #[test]
+    fn decode_skips_malformed_frames() {
+        // An empty frame, a frame with a code byte past its end, then a valid frame.
+        let src = &mut [0x00, 0x05, 0x11, 0x00, 0x03, 0x11, 0x22, 0x00, 0xAA];
+
+        let (item, size) = Cobs::new()
+            .decode(src)
+            .expect("Must not fail")
+            .expect("Must decode");
+
+        assert_eq!(item, b"\x11\x22");
+        assert_eq!(size, 8);
+    }
+
+    #[tokio::test]
+    async fn framed_read() {
+        init_tracing();
+
+        let items: &[&[u8]] = &[
+            b"\x03\x11\x22\x02\x33\x00\x01",
+            b"\x00\x02\x11\x01\x01",
+            b"\x01\x00\x00\x05\x44",
+            b"\x00\x02\x55",
+        ];
+
+        let decoder = Cobs::new();
+
+        let expected: &[&[u8]] = &[];
+        framed_read!(items, expected, decoder, 2, BufferTooSmall);
+
+        let expected: &[&[u8]] = &[b"\x11\x22\0\x33", b"", b"\x11\0\0\0"];
+        framed_read!(items, expected, decoder, 32, BytesRemainingOnStream);
+        framed_read!(items, expected, decoder, 32, 1, BytesRemainingOnStream);
+        framed_read!(items, expected, decoder, 32, 2, BytesRemainingOnStream);
+        framed_read!(items, expected, decoder, 32, 4, BytesRemainingOnStream);
+    }
+
+    #[tokio::test]
+    async fn sink_stream() {
+        init_tracing();
+
+        let items: Vec<Vec<u8>> = std::vec![
+            b"Hello".to_vec(),
+            b"\0Hello\0".to_vec(),
+            b"".to_vec(),
+            b"\0".to_vec(),
+            [0xAA; 300].to_vec(),
+        ];
+
+        let decoder = Cobs::new();
+        let encoder = Cobs::new();
+        let map = |item: &mut [u8]| item.to_vec();
+
+        sink_stream!(encoder, decoder, items, map);
+    }
+}
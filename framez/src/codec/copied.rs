@@ -0,0 +1,236 @@
+//! Codec adapter that decodes into owned items stored in a [`FrameBuffer`].
+
+use core::marker::PhantomData;
+
+use embedded_io_async::Write;
+
+use crate::{
+    WriteError,
+    buffer::{FrameBuffer, FrameBufferFull},
+    decode::Decoder,
+    encode::Encoder,
+};
+
+/// A codec that copies the borrowed items of the inner decoder into a [`FrameBuffer`] `F`.
+///
+/// E.g. `Copied<Lines, heapless::Vec<u8, 64>>` decodes into [`heapless::Vec`](https://docs.rs/heapless/latest/heapless/struct.Vec.html)
+/// and `Copied<StrLines, heapless::String<64>>` decodes into [`heapless::String`](https://docs.rs/heapless/latest/heapless/struct.String.html).
+/// Like [`Owned`](crate::codec::owned::Owned), but works without `alloc` and with any container.
+///
+/// Encoding is delegated to the inner encoder.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Copied<C, F> {
+    inner: C,
+    _frame: PhantomData<fn() -> F>,
+}
+
+impl<C, F> Copied<C, F> {
+    /// Creates a new [`Copied`] wrapping the given `codec`.
+    #[inline]
+    pub const fn new(codec: C) -> Self {
+        Self {
+            inner: codec,
+            _frame: PhantomData,
+        }
+    }
+
+    /// Returns reference to the inner codec.
+    #[inline]
+    pub const fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns mutable reference to the inner codec.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the [`Copied`] and returns the inner codec.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+/// Error returned by [`Copied::decode`].
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CopiedError<E> {
+    /// The inner decoder failed to decode a frame.
+    Decode(E),
+    /// The frame does not fit into the [`FrameBuffer`].
+    Full(FrameBufferFull),
+}
+
+impl<E> core::fmt::Display for CopiedError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Decode(err) => write!(f, "Decode error: {err}"),
+            Self::Full(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl<E> core::error::Error for CopiedError<E> where E: core::fmt::Display + core::fmt::Debug {}
+
+/// Copies `frame` into a new `F`.
+fn copy<T, F, E>(frame: &T) -> Result<F, CopiedError<E>>
+where
+    T: ?Sized,
+    F: FrameBuffer<T>,
+{
+    let mut buffer = F::default();
+
+    buffer.push(frame).map_err(CopiedError::Full)?;
+
+    Ok(buffer)
+}
+
+impl<C, T, F> Decoder for Copied<C, F>
+where
+    C: for<'buf> Decoder<Item<'buf> = &'buf T>,
+    T: ?Sized + 'static,
+    F: FrameBuffer<T>,
+{
+    type Item<'buf> = F;
+    type Error = CopiedError<C::Error>;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        match self.inner.decode(src).map_err(CopiedError::Decode)? {
+            Some((item, size)) => Ok(Some((copy(item)?, size))),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        match self.inner.decode_eof(src).map_err(CopiedError::Decode)? {
+            Some((item, size)) => Ok(Some((copy(item)?, size))),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_header(&mut self, src: &[u8]) -> Option<usize> {
+        self.inner.decode_header(src)
+    }
+}
+
+impl<C, F, I> Encoder<I> for Copied<C, F>
+where
+    C: Encoder<I>,
+{
+    type Error = C::Error;
+
+    fn encode(&mut self, item: I, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encoded_len(&self, item: &I) -> Option<usize> {
+        self.inner.encoded_len(item)
+    }
+
+    async fn encode_to_writer<W>(
+        &mut self,
+        item: I,
+        buf: &mut [u8],
+        write: &mut W,
+    ) -> Result<usize, WriteError<W::Error, Self::Error>>
+    where
+        W: Write,
+    {
+        self.inner.encode_to_writer(item, buf, write).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{FramedRead, ReadError, codec::lines::Lines, tests::init_tracing};
+
+    use super::*;
+
+    /// A minimal fixed capacity container, like `arrayvec::ArrayVec`.
+    #[derive(Debug, Default)]
+    struct Array {
+        bytes: [u8; 8],
+        len: usize,
+    }
+
+    impl AsRef<[u8]> for Array {
+        fn as_ref(&self) -> &[u8] {
+            &self.bytes[..self.len]
+        }
+    }
+
+    impl FrameBuffer<[u8]> for Array {
+        fn push(&mut self, frame: &[u8]) -> Result<(), FrameBufferFull> {
+            let dst = self
+                .bytes
+                .get_mut(self.len..self.len + frame.len())
+                .ok_or(FrameBufferFull)?;
+
+            dst.copy_from_slice(frame);
+            self.len += frame.len();
+
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn items_outlive_the_next_call() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(
+            Copied::<_, Array>::new(Lines::new()),
+            b"Hello\nworld!\nHello, world!\n".as_slice(),
+            buffer,
+        );
+
+        let first = framer
+            .next(|item| item)
+            .await
+            .expect("Must be some")
+            .expect("Must decode");
+
+        let second = framer
+            .next(|item| item)
+            .await
+            .expect("Must be some")
+            .expect("Must decode");
+
+        assert_eq!(first.as_ref(), b"Hello");
+        assert_eq!(second.as_ref(), b"world!");
+
+        assert!(matches!(
+            framer.next(|item| item).await,
+            Some(Err(ReadError::Decode(CopiedError::Full(FrameBufferFull))))
+        ));
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn decode_into_heapless_string() {
+        use crate::codec::lines::StrLines;
+
+        let src = &mut *b"Hello\r\n".to_vec();
+
+        let (item, size) = Copied::<_, heapless::String<8>>::new(StrLines::new())
+            .decode(src)
+            .expect("Must decode")
+            .expect("Must be some");
+
+        assert_eq!(item, "Hello");
+        assert_eq!(size, 7);
+    }
+}
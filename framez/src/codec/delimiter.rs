@@ -3,8 +3,8 @@
 use core::convert::Infallible;
 
 use crate::{
-    decode::{DecodeError, Decoder},
-    encode::Encoder,
+    decode::{Decoder, RingDecoder, RingSlice},
+    encode::{EncodeBufferTooSmall, EncodeParts, Encoder, Parts},
 };
 
 /// A codec that decodes bytes ending with a `delimiter` into bytes and encodes bytes into bytes ending with a `delimiter`.
@@ -35,34 +35,74 @@ impl<'a> Delimiter<'a> {
     }
 }
 
-impl DecodeError for Delimiter<'_> {
+impl Decoder for Delimiter<'_> {
+    type Item<'buf> = &'buf [u8];
     type Error = Infallible;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let src: &'buf [u8] = src;
+
+        if self.delimiter.is_empty() {
+            return Ok(src.get(..1).map(|bytes| (bytes, 1)));
+        }
+
+        // A delimiter may start in the bytes seen so far and end in the new ones.
+        let from = self
+            .seen
+            .saturating_sub(self.delimiter.len().saturating_sub(1));
+
+        let Some(start) = src
+            .get(from..)
+            .and_then(|unseen| memchr::memmem::find(unseen, self.delimiter))
+            .map(|index| from.saturating_add(index))
+        else {
+            self.seen = src.len();
+
+            return Ok(None);
+        };
+
+        self.seen = 0;
+
+        Ok(src
+            .get(..start)
+            .map(|bytes| (bytes, start.saturating_add(self.delimiter.len()))))
+    }
 }
 
-impl<'buf> Decoder<'buf> for Delimiter<'_> {
-    type Item = &'buf [u8];
+impl RingDecoder for Delimiter<'_> {
+    type Item<'buf> = RingSlice<'buf>;
+    type Error = Infallible;
+
+    fn decode_ring<'buf>(
+        &mut self,
+        head: &'buf mut [u8],
+        tail: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let src = RingSlice::new(head, tail);
 
-    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
         if src.len() < self.delimiter.len() {
             return Ok(None);
         }
 
         match self.delimiter.last() {
-            None => {
-                let bytes = &src[..self.seen + 1];
-                let item = (bytes, self.seen + 1);
-
-                Ok(Some(item))
-            }
+            None => Ok((!src.is_empty()).then(|| (src.prefix(1), 1))),
             Some(last_byte) => {
-                while self.seen < src.len() {
-                    if src[self.seen] == *last_byte {
-                        let src_delimiter =
-                            &src[self.seen + 1 - self.delimiter.len()..self.seen + 1];
+                while let Some(index) = src.position(self.seen, *last_byte) {
+                    let end = index.saturating_add(1);
 
-                        if src_delimiter == self.delimiter {
-                            let bytes = &src[..self.seen + 1 - self.delimiter.len()];
-                            let item = (bytes, self.seen + 1);
+                    if end >= self.delimiter.len() {
+                        let start = end.saturating_sub(self.delimiter.len());
+
+                        let is_delimiter =
+                            self.delimiter.iter().enumerate().all(|(offset, byte)| {
+                                src.get(start.saturating_add(offset)) == Some(*byte)
+                            });
+
+                        if is_delimiter {
+                            let item = (src.prefix(start), end);
 
                             self.seen = 0;
 
@@ -70,48 +110,65 @@ impl<'buf> Decoder<'buf> for Delimiter<'_> {
                         }
                     }
 
-                    self.seen += 1;
+                    self.seen = end;
                 }
 
+                self.seen = src.len();
+
                 Ok(None)
             }
         }
     }
 }
 
-/// Error returned by [`Delimiter::encode`].
-#[derive(Debug)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub enum DelimiterEncodeError {
-    /// The input buffer is too small to fit the encoded bytes.
-    BufferTooSmall,
-}
-
-impl core::fmt::Display for DelimiterEncodeError {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        match self {
-            DelimiterEncodeError::BufferTooSmall => write!(f, "buffer too small"),
-        }
-    }
-}
-
-impl core::error::Error for DelimiterEncodeError {}
-
 impl Encoder<&[u8]> for Delimiter<'_> {
-    type Error = DelimiterEncodeError;
+    type Error = EncodeBufferTooSmall;
 
     fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
-        let size = item.len() + self.delimiter.len();
+        let size = item.len().saturating_add(self.delimiter.len());
 
-        if dst.len() < size {
-            return Err(DelimiterEncodeError::BufferTooSmall);
-        }
+        let available = dst.len();
+
+        let Some((payload, delimiter)) = dst
+            .split_at_mut_checked(item.len())
+            .and_then(|(payload, rest)| Some((payload, rest.get_mut(..self.delimiter.len())?)))
+        else {
+            return Err(EncodeBufferTooSmall {
+                required: size,
+                available,
+            });
+        };
 
-        dst[..item.len()].copy_from_slice(item);
-        dst[item.len()..size].copy_from_slice(self.delimiter);
+        payload.copy_from_slice(item);
+        delimiter.copy_from_slice(self.delimiter);
 
         Ok(size)
     }
+
+    fn encoded_len(&self, item: &&[u8]) -> Option<usize> {
+        Some(item.len().saturating_add(self.delimiter.len()))
+    }
+}
+
+impl<'a> EncodeParts<'a, &'a [u8]> for Delimiter<'_> {
+    fn encode_parts(&mut self, item: &'a [u8], dst: &mut [u8]) -> Result<Parts<'a>, Self::Error> {
+        let available = dst.len();
+
+        let Some(delimiter) = dst.get_mut(..self.delimiter.len()) else {
+            return Err(EncodeBufferTooSmall {
+                required: self.delimiter.len(),
+                available,
+            });
+        };
+
+        delimiter.copy_from_slice(self.delimiter);
+
+        Ok(Parts {
+            header: 0,
+            payload: item,
+            trailer: self.delimiter.len(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -123,7 +180,7 @@ mod test {
 
     use crate::{
         ReadError,
-        tests::{framed_read, init_tracing, sink_stream},
+        tests::{framed_read, framed_read_ring, init_tracing, sink_stream},
     };
 
     use super::*;
@@ -190,6 +247,80 @@ mod test {
         framed_read!(items, expected, decoder);
     }
 
+    #[tokio::test]
+    async fn long_delimiter_split_across_reads() {
+        use crate::{FramedRead, mock::ScriptedReader, next};
+
+        init_tracing();
+
+        // The delimiter starts with a repeated byte, so partial matches overlap.
+        let chunks: &[&[u8]] = &[b"xaaaaab", b"yaa", b"ab", b"aaabaaa"];
+
+        for max_read in 1..=4 {
+            let buffer = &mut [0_u8; 32];
+            let mut framer = FramedRead::new(
+                Delimiter::new(b"aaab"),
+                ScriptedReader::new(chunks).with_max_read(max_read),
+                buffer,
+            );
+
+            let mut frames = Vec::new();
+
+            while let Some(Ok(frame)) = next!(framer) {
+                frames.push(frame.to_vec());
+            }
+
+            assert_eq!(frames, [b"xaa".as_slice(), b"y", b""], "{max_read}");
+        }
+    }
+
+    #[tokio::test]
+    async fn framed_read_ring() {
+        init_tracing();
+
+        // cspell: disable
+        let items: &[&[u8]] = &[
+            b"jh asjd##ppppppppppppppp##",
+            b"k hb##jsjuwjal kadj##jsadhjiu##w",
+            b"##jal kadjjsadhjiuwqens ##",
+            b"nd ",
+            b"yxxcjajsdi##askdn as",
+            b"jdasd##iouqw es",
+            b"sd##k",
+        ];
+        // cspell: enable
+
+        let decoder = Delimiter::new(b"##");
+
+        let expected: &[&[u8]] = &[];
+        framed_read_ring!(items, expected, decoder, 1, BufferTooSmall);
+        framed_read_ring!(items, expected, decoder, 4, 2, BufferTooSmall);
+
+        // cspell: disable
+
+        let expected: &[&[u8]] = &[
+            b"jh asjd",
+            b"ppppppppppppppp",
+            b"k hb",
+            b"jsjuwjal kadj",
+            b"jsadhjiu",
+            b"w",
+            b"jal kadjjsadhjiuwqens ",
+            b"nd yxxcjajsdi",
+            b"askdn asjdasd",
+            b"iouqw essd",
+        ];
+
+        // cspell: enable
+
+        framed_read_ring!(items, expected, decoder, 32, BytesRemainingOnStream);
+        framed_read_ring!(items, expected, decoder, 32, 1, BytesRemainingOnStream);
+        framed_read_ring!(items, expected, decoder, 33, 3, BytesRemainingOnStream);
+        framed_read_ring!(items, expected, decoder, 37, 4, BytesRemainingOnStream);
+
+        framed_read_ring!(items, expected, decoder);
+    }
+
     #[tokio::test]
     async fn sink_stream() {
         init_tracing();
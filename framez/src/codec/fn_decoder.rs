@@ -0,0 +1,119 @@
+//! A decoder built from a closure.
+
+use crate::decode::Decoder;
+
+/// Creates a [`FnDecoder`] that decodes frames using the closure `f`.
+///
+/// The closure behaves like [`Decoder::decode`]. It returns `Ok(Some((item, size)))` if `item` was decoded from the first `size` bytes of `src`,
+/// `Ok(None)` if more bytes are needed, or an error.
+///
+/// # Example
+///
+/// ```rust
+/// use core::convert::Infallible;
+///
+/// use framez::{FramedRead, codec::from_fn, next};
+///
+/// # async fn run() {
+/// // A frame is a length byte followed by that many bytes, decoded into their sum.
+/// let decoder = from_fn(|src: &mut [u8]| {
+///     let Some((&len, rest)) = src.split_first() else {
+///         return Ok::<_, Infallible>(None);
+///     };
+///
+///     let len = len as usize;
+///
+///     Ok(rest
+///         .get(..len)
+///         .map(|bytes| (bytes.iter().map(|b| *b as u32).sum::<u32>(), 1 + len)))
+/// });
+///
+/// let buffer = &mut [0_u8; 16];
+/// let mut framer = FramedRead::new(decoder, b"\x02\x01\x02\x01\x07".as_slice(), buffer);
+///
+/// assert_eq!(next!(framer).unwrap().unwrap(), 3);
+/// assert_eq!(next!(framer).unwrap().unwrap(), 7);
+/// # }
+/// ```
+#[inline]
+pub const fn from_fn<F>(f: F) -> FnDecoder<F> {
+    FnDecoder { f }
+}
+
+/// A [`Decoder`] that decodes frames using a closure. Created by [`from_fn`].
+///
+/// The decoded items are owned, since the closure can not name the lifetime of the buffer.
+/// Decoders of borrowed items implement [`Decoder`] for a type of their own.
+#[derive(Clone, Copy)]
+pub struct FnDecoder<F> {
+    f: F,
+}
+
+impl<F> FnDecoder<F> {
+    /// Consumes the [`FnDecoder`] and returns the closure.
+    #[inline]
+    pub fn into_inner(self) -> F {
+        self.f
+    }
+}
+
+impl<F> core::fmt::Debug for FnDecoder<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FnDecoder").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<F> defmt::Format for FnDecoder<F> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "FnDecoder {{ .. }}")
+    }
+}
+
+impl<F, T, E> Decoder for FnDecoder<F>
+where
+    F: FnMut(&mut [u8]) -> Result<Option<(T, usize)>, E>,
+{
+    type Item<'buf> = T;
+    type Error = E;
+
+    #[inline]
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        (self.f)(src)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{FramedRead, ReadError, mock::ScriptedReader, next, tests::init_tracing};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn decodes_with_closure() {
+        init_tracing();
+
+        // Frames of two bytes, decoded into a big endian u16. Zero is invalid.
+        let decoder = from_fn(|src: &mut [u8]| match src {
+            [0, 0, ..] => Err("zero"),
+            [a, b, ..] => Ok(Some((u16::from_be_bytes([*a, *b]), 2))),
+            _ => Ok(None),
+        });
+
+        let read = ScriptedReader::new(&[b"\x01", b"\x02\x00", b"\x00"]);
+
+        let buffer = &mut [0_u8; 8];
+        let mut framer = FramedRead::new(decoder, read, buffer);
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, 0x0102);
+
+        assert!(matches!(
+            next!(framer),
+            Some(Err(ReadError::Decode("zero")))
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+//! An encoder built from a closure.
+
+use crate::encode::Encoder;
+
+/// Creates a [`FnEncoder`] that encodes items using the closure `f`.
+///
+/// The closure behaves like [`Encoder::encode`]. It encodes the item into the start of `dst` and returns the number of written bytes, or an error.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{FramedWrite, codec::encoder_from_fn};
+///
+/// # async fn run() {
+/// // Prepends a two byte tag and appends a terminator.
+/// let encoder = encoder_from_fn(|item: &[u8], dst: &mut [u8]| {
+///     let len = item.len() + 3;
+///     let dst = dst.get_mut(..len).ok_or("buffer too small")?;
+///
+///     dst[..2].copy_from_slice(b"<<");
+///     dst[2..len - 1].copy_from_slice(item);
+///     dst[len - 1] = b'\n';
+///
+///     Ok::<_, &str>(len)
+/// });
+///
+/// let buffer = &mut [0_u8; 16];
+/// let mut framer = FramedWrite::new(encoder, Vec::new(), buffer);
+///
+/// framer.send(b"Hello".as_slice()).await.unwrap();
+///
+/// let (_, written, _) = framer.into_parts();
+///
+/// assert_eq!(written, b"<<Hello\n");
+/// # }
+/// ```
+#[inline]
+pub const fn encoder_from_fn<F>(f: F) -> FnEncoder<F> {
+    FnEncoder { f }
+}
+
+/// An [`Encoder`] that encodes items using a closure. Created by [`encoder_from_fn`].
+#[derive(Clone, Copy)]
+pub struct FnEncoder<F> {
+    f: F,
+}
+
+impl<F> FnEncoder<F> {
+    /// Consumes the [`FnEncoder`] and returns the closure.
+    #[inline]
+    pub fn into_inner(self) -> F {
+        self.f
+    }
+}
+
+impl<F> core::fmt::Debug for FnEncoder<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FnEncoder").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<F> defmt::Format for FnEncoder<F> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "FnEncoder {{ .. }}")
+    }
+}
+
+impl<F, I, E> Encoder<I> for FnEncoder<F>
+where
+    F: FnMut(I, &mut [u8]) -> Result<usize, E>,
+{
+    type Error = E;
+
+    #[inline]
+    fn encode(&mut self, item: I, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        (self.f)(item, dst)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use crate::{FramedWrite, WriteError, tests::init_tracing};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn encodes_with_closure() {
+        init_tracing();
+
+        // Encodes a u16 as two big endian bytes. Zero is invalid.
+        let encoder = encoder_from_fn(|item: u16, dst: &mut [u8]| {
+            if item == 0 {
+                return Err("zero");
+            }
+
+            dst.get_mut(..2)
+                .ok_or("buffer too small")?
+                .copy_from_slice(&item.to_be_bytes());
+
+            Ok(2)
+        });
+
+        let buffer = &mut [0_u8; 8];
+        let mut framer = FramedWrite::new(encoder, Vec::new(), buffer);
+
+        framer.send(0x0102).await.expect("Must send");
+
+        assert!(matches!(
+            framer.send(0).await,
+            Err(WriteError::Encode("zero"))
+        ));
+
+        let (_, written, _) = framer.into_parts();
+
+        assert_eq!(written, [1, 2]);
+    }
+}
@@ -0,0 +1,247 @@
+//! Codec adapter that decodes into mutable items.
+
+use core::ops::Range;
+
+use embedded_io_async::Write;
+
+use crate::{WriteError, decode::Decoder, encode::Encoder};
+
+/// A codec that converts the borrowed items of the inner decoder into mutable items.
+///
+/// E.g. `InPlace<Lines>` decodes into `&mut [u8]`, so the frame can be decrypted or unescaped in place in the read buffer.
+/// The item of the inner decoder must be a part of the buffer it was given.
+///
+/// Encoding is delegated to the inner encoder.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InPlace<C> {
+    inner: C,
+}
+
+impl<C> InPlace<C> {
+    /// Creates a new [`InPlace`] wrapping the given `codec`.
+    #[inline]
+    pub const fn new(codec: C) -> Self {
+        Self { inner: codec }
+    }
+
+    /// Returns reference to the inner codec.
+    #[inline]
+    pub const fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns mutable reference to the inner codec.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the [`InPlace`] and returns the inner codec.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+/// Error returned by [`InPlace::decode`].
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InPlaceError<E> {
+    /// The inner decoder failed to decode a frame.
+    Decode(E),
+    /// The item of the inner decoder is not a part of the buffer it was given.
+    OutOfBuffer,
+}
+
+impl<E> core::fmt::Display for InPlaceError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Decode(err) => write!(f, "Decode error: {err}"),
+            Self::OutOfBuffer => write!(f, "Item out of buffer"),
+        }
+    }
+}
+
+impl<E> core::error::Error for InPlaceError<E> where E: core::fmt::Display + core::fmt::Debug {}
+
+/// Returns the range of `item` in the buffer starting at the address `base`.
+pub(crate) fn span(base: usize, item: &[u8]) -> Option<Range<usize>> {
+    if item.is_empty() {
+        return Some(0..0);
+    }
+
+    let start = item.as_ptr().addr().checked_sub(base)?;
+
+    Some(start..start.checked_add(item.len())?)
+}
+
+/// Reborrows the `span` of `src` mutably.
+fn in_place<E>(src: &mut [u8], span: Option<Range<usize>>) -> Result<&mut [u8], InPlaceError<E>> {
+    span.and_then(|span| src.get_mut(span))
+        .ok_or(InPlaceError::OutOfBuffer)
+}
+
+impl<C> Decoder for InPlace<C>
+where
+    C: for<'a> Decoder<Item<'a> = &'a [u8]>,
+{
+    type Item<'buf> = &'buf mut [u8];
+    type Error = InPlaceError<C::Error>;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let base = src.as_ptr().addr();
+
+        let frame = self
+            .inner
+            .decode(&mut *src)
+            .map_err(InPlaceError::Decode)?
+            .map(|(item, size)| (span(base, item), size));
+
+        match frame {
+            Some((span, size)) => Ok(Some((in_place(src, span)?, size))),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let base = src.as_ptr().addr();
+
+        let frame = self
+            .inner
+            .decode_eof(&mut *src)
+            .map_err(InPlaceError::Decode)?
+            .map(|(item, size)| (span(base, item), size));
+
+        match frame {
+            Some((span, size)) => Ok(Some((in_place(src, span)?, size))),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_header(&mut self, src: &[u8]) -> Option<usize> {
+        self.inner.decode_header(src)
+    }
+}
+
+impl<C, I> Encoder<I> for InPlace<C>
+where
+    C: Encoder<I>,
+{
+    type Error = C::Error;
+
+    fn encode(&mut self, item: I, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encoded_len(&self, item: &I) -> Option<usize> {
+        self.inner.encoded_len(item)
+    }
+
+    async fn encode_to_writer<W>(
+        &mut self,
+        item: I,
+        buf: &mut [u8],
+        write: &mut W,
+    ) -> Result<usize, WriteError<W::Error, Self::Error>>
+    where
+        W: Write,
+    {
+        self.inner.encode_to_writer(item, buf, write).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use crate::{
+        FramedRead,
+        codec::{bytes::Bytes, lines::Lines},
+        next,
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn items_are_transformed_in_place() {
+        init_tracing();
+
+        // "Hello\n" and "world!\n" with every payload byte xor-ed with 0x20.
+        let read = b"hELLO\nWORLD\x01\n".as_slice();
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(InPlace::new(Lines::new()), read, buffer);
+
+        let mut collected = Vec::<Vec<u8>>::new();
+
+        while let Some(item) = next!(framer) {
+            let item = item.expect("Must decode");
+
+            item.iter_mut().for_each(|byte| *byte ^= 0x20);
+
+            collected.push(item.to_vec());
+        }
+
+        assert_eq!(collected, [b"Hello".as_slice(), b"world!"]);
+    }
+
+    #[test]
+    fn decode_into_mut_slice() {
+        let src = &mut *b"Hello\r\n".to_vec();
+
+        let (item, size) = InPlace::new(Lines::new())
+            .decode(src)
+            .expect("Must decode")
+            .expect("Must be some");
+
+        item.make_ascii_uppercase();
+
+        assert_eq!(size, 7);
+        assert_eq!(src, b"HELLO\r\n");
+
+        let (item, size) = InPlace::new(Bytes::new())
+            .decode(&mut [])
+            .expect("Must decode")
+            .expect("Must be some");
+
+        assert!(item.is_empty());
+        assert_eq!(size, 0);
+    }
+
+    /// Returns a frame that is not a part of the buffer.
+    struct Static;
+
+    impl Decoder for Static {
+        type Item<'buf> = &'buf [u8];
+        type Error = core::convert::Infallible;
+
+        fn decode<'buf>(
+            &mut self,
+            src: &'buf mut [u8],
+        ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+            Ok(Some((b"Hello", src.len())))
+        }
+    }
+
+    #[test]
+    fn item_out_of_buffer() {
+        let src = &mut *b"Hello".to_vec();
+
+        assert!(matches!(
+            InPlace::new(Static).decode(src),
+            Err(InPlaceError::OutOfBuffer)
+        ));
+    }
+}
@@ -2,9 +2,12 @@
 
 use core::convert::Infallible;
 
+use embedded_io_async::Write;
+
 use crate::{
-    decode::{DecodeError, Decoder},
-    encode::Encoder,
+    WriteError,
+    decode::{Decoder, RingDecoder, RingSlice},
+    encode::{EncodeBufferTooSmall, EncodeFmt, EncodeParts, Encoder, Parts, write_all},
 };
 
 /// A codec that decodes `bytes` into a `line of bytes` and encodes a `line of bytes` into `bytes`.
@@ -27,70 +30,188 @@ impl Lines {
     }
 }
 
-impl DecodeError for Lines {
+impl Decoder for Lines {
+    type Item<'buf> = &'buf [u8];
     type Error = Infallible;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let src: &'buf [u8] = src;
+
+        let Some(index) = src
+            .get(self.seen..)
+            .and_then(|unseen| memchr::memchr(b'\n', unseen))
+            .map(|index| self.seen.saturating_add(index))
+        else {
+            self.seen = src.len();
+
+            return Ok(None);
+        };
+
+        let line_bytes = src.get(..index).unwrap_or_default();
+        let line_bytes = match line_bytes.split_last() {
+            Some((b'\r', line_bytes)) => line_bytes,
+            _ => line_bytes,
+        };
+
+        self.seen = 0;
+
+        Ok(Some((line_bytes, index.saturating_add(1))))
+    }
 }
 
-impl<'buf> Decoder<'buf> for Lines {
-    type Item = &'buf [u8];
+impl RingDecoder for Lines {
+    type Item<'buf> = RingSlice<'buf>;
+    type Error = Infallible;
 
-    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
-        while self.seen < src.len() {
-            if src[self.seen] == b'\n' {
-                let line_bytes = match &src[..self.seen].last() {
-                    Some(b'\r') => &src[..self.seen - 1],
-                    _ => &src[..self.seen],
+    fn decode_ring<'buf>(
+        &mut self,
+        head: &'buf mut [u8],
+        tail: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let src = RingSlice::new(head, tail);
+
+        match src.position(self.seen, b'\n') {
+            Some(index) => {
+                let line_bytes = match index.checked_sub(1).and_then(|index| src.get(index)) {
+                    Some(b'\r') => src.prefix(index.saturating_sub(1)),
+                    _ => src.prefix(index),
                 };
 
-                let item = (line_bytes, self.seen + 1);
-
                 self.seen = 0;
 
-                return Ok(Some(item));
+                Ok(Some((line_bytes, index.saturating_add(1))))
             }
+            None => {
+                self.seen = src.len();
 
-            self.seen += 1;
+                Ok(None)
+            }
         }
-
-        Ok(None)
     }
 }
 
-/// Error returned by [`Lines::encode`].
-#[derive(Debug)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub enum LinesEncodeError {
-    /// The input buffer is too small to fit the encoded line.
-    BufferTooSmall,
+impl Encoder<&[u8]> for Lines {
+    type Error = EncodeBufferTooSmall;
+
+    fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let size = item.len().saturating_add(2);
+
+        let available = dst.len();
+
+        let Some((payload, terminator)) = dst
+            .get_mut(..size)
+            .and_then(|dst| dst.split_at_mut_checked(item.len()))
+        else {
+            return Err(EncodeBufferTooSmall {
+                required: size,
+                available,
+            });
+        };
+
+        payload.copy_from_slice(item);
+        terminator.copy_from_slice(b"\r\n");
+
+        Ok(size)
+    }
+
+    fn encoded_len(&self, item: &&[u8]) -> Option<usize> {
+        Some(item.len().saturating_add(2))
+    }
+
+    async fn encode_to_writer<W>(
+        &mut self,
+        item: &[u8],
+        _buf: &mut [u8],
+        write: &mut W,
+    ) -> Result<usize, WriteError<W::Error, Self::Error>>
+    where
+        W: Write,
+    {
+        write_all(write, item).await?;
+        write_all(write, b"\r\n").await?;
+
+        Ok(item.len().saturating_add(2))
+    }
 }
 
-impl core::fmt::Display for LinesEncodeError {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        match self {
-            Self::BufferTooSmall => write!(f, "buffer too small"),
-        }
+impl<'a> EncodeParts<'a, &'a [u8]> for Lines {
+    fn encode_parts(&mut self, item: &'a [u8], dst: &mut [u8]) -> Result<Parts<'a>, Self::Error> {
+        let available = dst.len();
+
+        let Some(terminator) = dst.get_mut(..2) else {
+            return Err(EncodeBufferTooSmall {
+                required: 2,
+                available,
+            });
+        };
+
+        terminator.copy_from_slice(b"\r\n");
+
+        Ok(Parts {
+            header: 0,
+            payload: item,
+            trailer: 2,
+        })
     }
 }
 
-impl core::error::Error for LinesEncodeError {}
+impl EncodeFmt for Lines {
+    type Error = EncodeBufferTooSmall;
 
-impl Encoder<&[u8]> for Lines {
-    type Error = LinesEncodeError;
+    fn encode_fmt(
+        &mut self,
+        args: core::fmt::Arguments<'_>,
+        dst: &mut [u8],
+    ) -> Result<usize, Self::Error> {
+        let mut writer = FmtWriter { dst, len: 0 };
 
-    fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
-        let size = item.len() + 2;
+        // The writer never fails, so an error can only come from a misbehaving formatting trait implementation.
+        // The bytes formatted so far are kept, like `core::fmt::write` would.
+        let _ = core::fmt::write(&mut writer, args);
 
-        if dst.len() < size {
-            return Err(LinesEncodeError::BufferTooSmall);
-        }
+        let len = writer.len;
+        let size = len.saturating_add(2);
+
+        let available = dst.len();
 
-        dst[..item.len()].copy_from_slice(item);
-        dst[item.len()..size].copy_from_slice(b"\r\n");
+        let Some(terminator) = dst.get_mut(len..size) else {
+            return Err(EncodeBufferTooSmall {
+                required: size,
+                available,
+            });
+        };
+
+        terminator.copy_from_slice(b"\r\n");
 
         Ok(size)
     }
 }
 
+/// Writes formatted bytes into a buffer.
+///
+/// Bytes that do not fit are counted but not written, so the required size is known once formatting is done.
+struct FmtWriter<'a> {
+    dst: &'a mut [u8],
+    len: usize,
+}
+
+impl core::fmt::Write for FmtWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len.saturating_add(s.len());
+
+        if let Some(dst) = self.dst.get_mut(self.len..end) {
+            dst.copy_from_slice(s.as_bytes());
+        }
+
+        self.len = end;
+
+        Ok(())
+    }
+}
+
 /// A codec that decodes `bytes` into an [`str`] line and encodes an [`str`] line into `bytes`.
 ///
 /// # Note
@@ -144,14 +265,14 @@ impl core::fmt::Display for StrLinesDecodeError {
 
 impl core::error::Error for StrLinesDecodeError {}
 
-impl DecodeError for StrLines {
+impl Decoder for StrLines {
+    type Item<'buf> = &'buf str;
     type Error = StrLinesDecodeError;
-}
-
-impl<'buf> Decoder<'buf> for StrLines {
-    type Item = &'buf str;
 
-    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
         match Decoder::decode(&mut self.inner, src) {
             Ok(Some((bytes, size))) => {
                 let item = core::str::from_utf8(bytes).map_err(StrLinesDecodeError::Utf8)?;
@@ -159,17 +280,51 @@ impl<'buf> Decoder<'buf> for StrLines {
                 Ok(Some((item, size)))
             }
             Ok(None) => Ok(None),
-            Err(_) => unreachable!(),
+            Err(err) => match err {},
         }
     }
 }
 
 impl<'a> Encoder<&'a str> for StrLines {
-    type Error = LinesEncodeError;
+    type Error = EncodeBufferTooSmall;
 
     fn encode(&mut self, item: &'a str, dst: &mut [u8]) -> Result<usize, Self::Error> {
         Encoder::encode(&mut self.inner, item.as_bytes(), dst)
     }
+
+    fn encoded_len(&self, item: &&'a str) -> Option<usize> {
+        Encoder::encoded_len(&self.inner, &item.as_bytes())
+    }
+
+    async fn encode_to_writer<W>(
+        &mut self,
+        item: &'a str,
+        buf: &mut [u8],
+        write: &mut W,
+    ) -> Result<usize, WriteError<W::Error, Self::Error>>
+    where
+        W: Write,
+    {
+        Encoder::encode_to_writer(&mut self.inner, item.as_bytes(), buf, write).await
+    }
+}
+
+impl<'a> EncodeParts<'a, &'a str> for StrLines {
+    fn encode_parts(&mut self, item: &'a str, dst: &mut [u8]) -> Result<Parts<'a>, Self::Error> {
+        self.inner.encode_parts(item.as_bytes(), dst)
+    }
+}
+
+impl EncodeFmt for StrLines {
+    type Error = EncodeBufferTooSmall;
+
+    fn encode_fmt(
+        &mut self,
+        args: core::fmt::Arguments<'_>,
+        dst: &mut [u8],
+    ) -> Result<usize, Self::Error> {
+        self.inner.encode_fmt(args, dst)
+    }
 }
 
 #[cfg(test)]
@@ -179,16 +334,85 @@ mod test {
         vec::Vec,
     };
 
+    use embedded_io_adapters::tokio_1::FromTokio;
     use futures::{SinkExt, StreamExt, pin_mut};
     use tokio::io::AsyncWriteExt;
 
     use crate::{
         ReadError,
-        tests::{framed_read, init_tracing, sink_stream},
+        tests::{framed_read, framed_read_ring, init_tracing, sink_stream},
     };
 
     use super::*;
 
+    #[test]
+    fn encode_buffer_too_small() {
+        let dst = &mut [0_u8; 6];
+
+        let err = Lines::new()
+            .encode(b"Hello".as_slice(), dst)
+            .expect_err("Must not fit");
+
+        assert_eq!(
+            err,
+            EncodeBufferTooSmall {
+                required: 7,
+                available: 6,
+            }
+        );
+    }
+
+    #[test]
+    fn encode_fmt() {
+        let dst = &mut [0_u8; 16];
+
+        let size = Lines::new()
+            .encode_fmt(format_args!("{}-{}", "Hello", 42), dst)
+            .expect("Must encode");
+
+        assert_eq!(&dst[..size], b"Hello-42\r\n");
+
+        let dst = &mut [0_u8; 8];
+
+        let err = Lines::new()
+            .encode_fmt(format_args!("{}-{}", "Hello", 42), dst)
+            .expect_err("Must not fit");
+
+        assert_eq!(
+            err,
+            EncodeBufferTooSmall {
+                required: 10,
+                available: 8,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn send_fmt() {
+        init_tracing();
+
+        let (read, write) = tokio::io::duplex(1024);
+
+        let buffer = &mut [0_u8; 16];
+        let mut writer = crate::FramedWrite::new(StrLines::new(), FromTokio::new(write), buffer);
+
+        crate::send_fmt!(writer, "Hello, {}!", "world").expect("Must send");
+        crate::send_fmt!(writer, "{}", 42).expect("Must send");
+
+        drop(writer);
+
+        let buffer = &mut [0_u8; 16];
+        let mut reader = crate::FramedRead::new(StrLines::new(), FromTokio::new(read), buffer);
+
+        let mut collected = Vec::<String>::new();
+
+        while let Some(item) = crate::next!(reader) {
+            collected.push(item.expect("Must decode").into());
+        }
+
+        assert_eq!(collected, ["Hello, world!", "42"]);
+    }
+
     #[tokio::test]
     async fn framed_read() {
         init_tracing();
@@ -238,6 +462,44 @@ mod test {
         framed_read!(items, expected, decoder);
     }
 
+    #[tokio::test]
+    async fn framed_read_ring() {
+        init_tracing();
+
+        let items: &[&[u8]] = &[
+            b"Hel",
+            b"lo\n",
+            b"Hell",
+            b"o, world!\n",
+            b"H",
+            b"ei\r\n",
+            b"sup",
+            b"\n",
+            b"Hey\r",
+            b"\n",
+            b"How ",
+            b"are y",
+        ];
+
+        let decoder = Lines::new();
+
+        let expected: &[&[u8]] = &[];
+        framed_read_ring!(items, expected, decoder, 1, BufferTooSmall);
+        framed_read_ring!(items, expected, decoder, 2, 1, BufferTooSmall);
+        framed_read_ring!(items, expected, decoder, 4, 2, BufferTooSmall);
+
+        let expected: &[&[u8]] = &[b"Hello"];
+        framed_read_ring!(items, expected, decoder, 8, BufferTooSmall);
+
+        let expected: &[&[u8]] = &[b"Hello", b"Hello, world!", b"Hei", b"sup", b"Hey"];
+        framed_read_ring!(items, expected, decoder, 16, BytesRemainingOnStream);
+        framed_read_ring!(items, expected, decoder, 16, 1, BytesRemainingOnStream);
+        framed_read_ring!(items, expected, decoder, 16, 3, BytesRemainingOnStream);
+        framed_read_ring!(items, expected, decoder, 17, 4, BytesRemainingOnStream);
+
+        framed_read_ring!(items, expected, decoder);
+    }
+
     #[tokio::test]
     async fn sink_stream() {
         init_tracing();
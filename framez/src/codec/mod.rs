@@ -1,5 +1,29 @@
 //! A ready to use set of codecs.
 
 pub mod bytes;
+pub mod cobs;
+pub mod copied;
 pub mod delimiter;
+pub mod fn_decoder;
+pub mod fn_encoder;
+pub mod in_place;
 pub mod lines;
+pub mod with_raw;
+
+pub use fn_decoder::from_fn;
+pub use fn_encoder::encoder_from_fn;
+
+#[cfg(feature = "alloc")]
+pub mod owned;
+
+#[cfg(feature = "nom")]
+pub mod nom;
+
+#[cfg(feature = "serde")]
+pub mod serde;
+
+#[cfg(feature = "tokio-util")]
+pub mod tokio_util;
+
+#[cfg(feature = "winnow")]
+pub mod winnow;
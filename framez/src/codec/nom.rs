@@ -0,0 +1,303 @@
+//! Codec adapter for [`nom`](https://docs.rs/nom/latest/nom/) streaming parsers.
+
+use nom::{
+    IResult,
+    error::{Error, ErrorKind},
+};
+
+use crate::decode::Decoder;
+
+/// A `nom` parser that parses a frame from the start of its input.
+///
+/// Implemented for functions and closures returning owned outputs, e.g. `fn(&[u8]) -> IResult<&[u8], Message>`.
+/// Parsers returning outputs that borrow from their input implement it for a type of their own:
+///
+/// ```rust
+/// use framez::codec::nom::FrameParser;
+/// use nom::{IResult, Parser, multi::length_data, number::streaming::be_u8};
+///
+/// struct LengthPrefixed;
+///
+/// impl FrameParser for LengthPrefixed {
+///     type Output<'buf> = &'buf [u8];
+///
+///     fn parse_frame<'buf>(&mut self, input: &'buf [u8]) -> IResult<&'buf [u8], &'buf [u8]> {
+///         length_data(be_u8).parse(input)
+///     }
+/// }
+/// ```
+pub trait FrameParser {
+    /// The parsed frame.
+    type Output<'buf>;
+
+    /// Parses a frame from the start of `input` and returns the remaining input with the frame.
+    ///
+    /// Parsers built from `nom`'s `streaming` combinators return [`nom::Err::Incomplete`] if more bytes are needed.
+    fn parse_frame<'buf>(&mut self, input: &'buf [u8]) -> IResult<&'buf [u8], Self::Output<'buf>>;
+}
+
+impl<F, O> FrameParser for F
+where
+    F: for<'a> FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    type Output<'buf> = O;
+
+    #[inline]
+    fn parse_frame<'buf>(&mut self, input: &'buf [u8]) -> IResult<&'buf [u8], Self::Output<'buf>> {
+        self(input)
+    }
+}
+
+/// A failure of the [`FrameParser`] of a [`Nom`] codec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NomError {
+    /// The kind of the failure.
+    pub kind: ErrorKind,
+    /// The offset of the failure from the start of the decoded bytes.
+    pub offset: usize,
+}
+
+impl core::fmt::Display for NomError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Parser failed: {} at offset {}",
+            self.kind.description(),
+            self.offset
+        )
+    }
+}
+
+impl core::error::Error for NomError {}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for NomError {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "NomError {{ kind: {}, offset: {} }}",
+            self.kind.description(),
+            self.offset
+        )
+    }
+}
+
+/// A codec that decodes frames using a `nom` streaming [`FrameParser`].
+///
+/// - A parsed frame consumes the bytes the parser consumed.
+/// - [`nom::Err::Incomplete`] means more bytes are needed.
+/// - [`nom::Err::Error`] means the bytes do not start a frame. The first byte is skipped and parsing is retried,
+///   so the codec resynchronizes with the stream, e.g. after garbage received before the first frame.
+/// - [`nom::Err::Failure`] is returned as a [`NomError`]. Its first byte is skipped, so reading can continue.
+///
+/// Skipped bytes are consumed together with the next frame, so they must fit into the read buffer with it.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{FramedRead, codec::nom::Nom, next};
+/// use nom::{IResult, Parser, number::streaming::be_u16, sequence::preceded, bytes::streaming::tag};
+///
+/// /// A reading starts with `R` followed by a big endian value.
+/// fn reading(input: &[u8]) -> IResult<&[u8], u16> {
+///     preceded(tag(&b"R"[..]), be_u16).parse(input)
+/// }
+///
+/// # async fn run() {
+/// let buffer = &mut [0_u8; 16];
+/// let mut framer = FramedRead::new(Nom::new(reading), b"R\x01\x02R\x03\x04".as_slice(), buffer);
+///
+/// assert_eq!(next!(framer).unwrap().unwrap(), 0x0102);
+/// assert_eq!(next!(framer).unwrap().unwrap(), 0x0304);
+/// # }
+/// ```
+///
+/// # Note
+///
+/// This codec tracks progress using an internal state of the underlying buffer, and it must not be used across multiple framing sessions.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Nom<P> {
+    parser: P,
+    /// The number of skipped bytes at the start of the slice.
+    skip: usize,
+}
+
+impl<P> Nom<P> {
+    /// Creates a new [`Nom`] codec wrapping the given `parser`.
+    #[inline]
+    pub const fn new(parser: P) -> Self {
+        Self { parser, skip: 0 }
+    }
+
+    /// Returns reference to the parser.
+    #[inline]
+    pub const fn parser(&self) -> &P {
+        &self.parser
+    }
+
+    /// Returns mutable reference to the parser.
+    #[inline]
+    pub const fn parser_mut(&mut self) -> &mut P {
+        &mut self.parser
+    }
+
+    /// Consumes the [`Nom`] codec and returns the parser.
+    #[inline]
+    pub fn into_parser(self) -> P {
+        self.parser
+    }
+}
+
+impl<P> Decoder for Nom<P>
+where
+    P: FrameParser,
+{
+    type Item<'buf> = P::Output<'buf>;
+    type Error = NomError;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let src: &'buf [u8] = src;
+
+        loop {
+            let input = src.get(self.skip..).unwrap_or_default();
+
+            match self.parser.parse_frame(input) {
+                Ok((rest, item)) => {
+                    let size = self
+                        .skip
+                        .saturating_add(input.len().saturating_sub(rest.len()));
+
+                    self.skip = 0;
+
+                    return Ok(Some((item, size)));
+                }
+                Err(nom::Err::Incomplete(_)) => return Ok(None),
+                Err(nom::Err::Error(_)) if self.skip < src.len() => {
+                    self.skip = self.skip.saturating_add(1);
+                }
+                Err(nom::Err::Error(_)) => return Ok(None),
+                Err(nom::Err::Failure(Error { input: at, code })) => {
+                    let offset = self
+                        .skip
+                        .saturating_add(input.len().saturating_sub(at.len()));
+
+                    self.skip = self.skip.saturating_add(1);
+
+                    return Err(NomError { kind: code, offset });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nom::{
+        Parser, bytes::streaming::tag, combinator::cut, multi::length_data,
+        number::streaming::be_u8, sequence::preceded,
+    };
+
+    use crate::{FramedRead, ReadError, mock::ScriptedReader, next, tests::init_tracing};
+
+    use super::*;
+
+    /// A frame starts with `#` followed by a length prefixed payload.
+    struct Framed;
+
+    impl FrameParser for Framed {
+        type Output<'buf> = &'buf [u8];
+
+        fn parse_frame<'buf>(&mut self, input: &'buf [u8]) -> IResult<&'buf [u8], &'buf [u8]> {
+            preceded(tag(&b"#"[..]), length_data(be_u8)).parse(input)
+        }
+    }
+
+    fn pair(input: &[u8]) -> IResult<&[u8], (u8, u8)> {
+        preceded(tag(&b"P"[..]), cut((be_u8, be_u8))).parse(input)
+    }
+
+    #[tokio::test]
+    async fn borrowed_frames_across_chunks() {
+        init_tracing();
+
+        let read = ScriptedReader::new(&[b"#\x05He", b"llo#", b"\x00#\x03Hey"]);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Nom::new(Framed), read, buffer);
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b"Hello");
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b"");
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b"Hey");
+
+        assert!(next!(framer).is_none());
+    }
+
+    #[tokio::test]
+    async fn garbage_is_skipped() {
+        init_tracing();
+
+        let read = ScriptedReader::new(&[b"xyz#\x02Hi"]);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Nom::new(Framed), read, buffer);
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b"Hi");
+
+        assert!(next!(framer).is_none());
+    }
+
+    #[tokio::test]
+    async fn failure_is_reported() {
+        init_tracing();
+
+        fn strict(input: &[u8]) -> IResult<&[u8], u8> {
+            preceded(tag(&b"P"[..]), cut(tag(&b"!"[..])))
+                .map(|_| 0)
+                .parse(input)
+        }
+
+        let read = ScriptedReader::new(&[b"P?P!"]);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Nom::new(strict), read, buffer);
+
+        assert!(matches!(
+            next!(framer),
+            Some(Err(ReadError::Decode(NomError {
+                kind: ErrorKind::Tag,
+                offset: 1
+            })))
+        ));
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, 0);
+
+        assert!(next!(framer).is_none());
+    }
+
+    #[tokio::test]
+    async fn owned_frames() {
+        init_tracing();
+
+        let read = ScriptedReader::new(&[b"P\x01", b"\x02P\x03\x04"]);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Nom::new(pair), read, buffer);
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, (1, 2));
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, (3, 4));
+    }
+}
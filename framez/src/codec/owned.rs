@@ -0,0 +1,165 @@
+//! Codec adapter that decodes into owned items.
+
+use alloc::borrow::ToOwned;
+
+use embedded_io_async::Write;
+
+use crate::{WriteError, decode::Decoder, encode::Encoder};
+
+/// A codec that converts the borrowed items of the inner decoder into owned items.
+///
+/// E.g. `Owned<Lines>` decodes into [`Vec<u8>`](alloc::vec::Vec) and `Owned<StrLines>` decodes into [`String`](alloc::string::String).
+/// The owned items do not borrow the read buffer, so they can be kept across calls to `next`.
+///
+/// Encoding is delegated to the inner encoder.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Owned<C> {
+    inner: C,
+}
+
+impl<C> Owned<C> {
+    /// Creates a new [`Owned`] wrapping the given `codec`.
+    #[inline]
+    pub const fn new(codec: C) -> Self {
+        Self { inner: codec }
+    }
+
+    /// Returns reference to the inner codec.
+    #[inline]
+    pub const fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns mutable reference to the inner codec.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the [`Owned`] and returns the inner codec.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C, T> Decoder for Owned<C>
+where
+    C: for<'buf> Decoder<Item<'buf> = &'buf T>,
+    T: ToOwned + ?Sized + 'static,
+{
+    type Item<'buf> = T::Owned;
+    type Error = C::Error;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        match self.inner.decode(src)? {
+            Some((item, size)) => Ok(Some((item.to_owned(), size))),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        match self.inner.decode_eof(src)? {
+            Some((item, size)) => Ok(Some((item.to_owned(), size))),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_header(&mut self, src: &[u8]) -> Option<usize> {
+        self.inner.decode_header(src)
+    }
+}
+
+impl<C, I> Encoder<I> for Owned<C>
+where
+    C: Encoder<I>,
+{
+    type Error = C::Error;
+
+    fn encode(&mut self, item: I, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encoded_len(&self, item: &I) -> Option<usize> {
+        self.inner.encoded_len(item)
+    }
+
+    async fn encode_to_writer<W>(
+        &mut self,
+        item: I,
+        buf: &mut [u8],
+        write: &mut W,
+    ) -> Result<usize, WriteError<W::Error, Self::Error>>
+    where
+        W: Write,
+    {
+        self.inner.encode_to_writer(item, buf, write).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{string::String, vec::Vec};
+
+    use embedded_io_adapters::tokio_1::FromTokio;
+    use tokio::io::AsyncWriteExt;
+
+    use crate::{
+        Framed,
+        codec::lines::{Lines, StrLines},
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn owned_items_outlive_the_next_call() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        write
+            .write_all(b"Hello\nworld!\n")
+            .await
+            .expect("Must write");
+        drop(write);
+
+        let mut framed =
+            Framed::new_alloc(Owned::new(StrLines::new()), FromTokio::new(read), 16, 16);
+
+        let first = framed
+            .next(|item| item)
+            .await
+            .expect("Must be some")
+            .expect("Must decode");
+
+        let second = framed
+            .next(|item| item)
+            .await
+            .expect("Must be some")
+            .expect("Must decode");
+
+        assert_eq!(first, String::from("Hello"));
+        assert_eq!(second, String::from("world!"));
+    }
+
+    #[test]
+    fn decode_into_vec() {
+        let src = &mut *b"Hello\r\n".to_vec();
+
+        let (item, size) = Owned::new(Lines::new())
+            .decode(src)
+            .expect("Must decode")
+            .expect("Must be some");
+
+        assert_eq!(item, Vec::from(b"Hello".as_slice()));
+        assert_eq!(size, 7);
+    }
+}
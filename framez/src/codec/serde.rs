@@ -0,0 +1,388 @@
+//! Serde codec for encoding and decoding typed items with a pluggable [`WireFormat`].
+//!
+//! The provided wire formats are:
+//!
+//! - [`Postcard`] with the `postcard` feature.
+//! - [`SerdeJsonCore`] with the `serde-json-core` feature.
+//! - [`Cbor`] with the `cbor` feature.
+
+use core::marker::PhantomData;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    codec::cobs::{self, Cobs},
+    decode::Decoder,
+    encode::Encoder,
+};
+
+/// A serialization format that serializes items into a buffer and deserializes items from a buffer.
+///
+/// Implement it to use another format with the [`SerdeCodec`].
+pub trait WireFormat {
+    /// The type of error returned when serializing fails, e.g. because the buffer is too small.
+    type SerializeError;
+
+    /// The type of error returned when deserializing fails.
+    type DeserializeError;
+
+    /// Serializes the `item` into the start of `dst` and returns the number of bytes written.
+    fn serialize<T>(item: &T, dst: &mut [u8]) -> Result<usize, Self::SerializeError>
+    where
+        T: Serialize + ?Sized;
+
+    /// Deserializes an item from the whole `src`.
+    fn deserialize<T>(src: &[u8]) -> Result<T, Self::DeserializeError>
+    where
+        T: DeserializeOwned;
+}
+
+/// A codec that decodes frames into items of type `T` and encodes items of type `T` into frames, using the [`WireFormat`] `F`.
+///
+/// Frames are delimited using the [`Cobs`] codec, so every format can be used on byte streams, e.g. serial links.
+/// The serialized item is COBS encoded in place, so encoding needs no intermediate buffer.
+/// In exchange, the serialized item may use at most `len - 2 - len / 254` bytes of a write buffer of `len` bytes.
+///
+/// A frame that fails to deserialize is reported once and skipped, so reading can continue with the next frame.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{
+///     FramedRead, FramedWrite,
+///     codec::serde::{Postcard, SerdeCodec},
+///     next,
+/// };
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Reading {
+///     id: u8,
+///     value: i32,
+/// }
+///
+/// # async fn run() {
+/// let write_buf = &mut [0_u8; 32];
+/// let mut writer = FramedWrite::new(SerdeCodec::<Reading, Postcard>::new(), Vec::new(), write_buf);
+///
+/// writer.send(&Reading { id: 1, value: -7 }).await.unwrap();
+///
+/// let (_, bytes, _) = writer.into_parts();
+///
+/// let read_buf = &mut [0_u8; 32];
+/// let mut reader = FramedRead::new(SerdeCodec::<Reading, Postcard>::new(), bytes.as_slice(), read_buf);
+///
+/// let reading = next!(reader).unwrap().unwrap();
+///
+/// assert_eq!(reading, Reading { id: 1, value: -7 });
+/// # }
+/// ```
+///
+/// # Note
+///
+/// This codec tracks progress using an internal state of the underlying buffer, and it must not be used across multiple framing sessions.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SerdeCodec<T, F> {
+    cobs: Cobs,
+    _item: PhantomData<fn() -> (T, F)>,
+}
+
+impl<T, F> SerdeCodec<T, F> {
+    /// Creates a new [`SerdeCodec`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            cobs: Cobs::new(),
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<T, F> Decoder for SerdeCodec<T, F>
+where
+    T: DeserializeOwned,
+    F: WireFormat,
+{
+    type Item<'buf> = T;
+    type Error = F::DeserializeError;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let Ok(Some((frame, size))) = self.cobs.decode(src) else {
+            return Ok(None);
+        };
+
+        match F::deserialize(frame) {
+            Ok(item) => Ok(Some((item, size))),
+            Err(err) => {
+                self.cobs.skip(size);
+
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<T, F> Encoder<&T> for SerdeCodec<T, F>
+where
+    T: Serialize,
+    F: WireFormat,
+{
+    type Error = F::SerializeError;
+
+    fn encode(&mut self, item: &T, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let offset = cobs::in_place_offset(dst.len());
+
+        // Keeps room for the terminating zero byte.
+        let end = dst.len().saturating_sub(1).max(offset);
+
+        let len = F::serialize(item, dst.get_mut(offset..end).unwrap_or_default())?;
+
+        Ok(cobs::encode_in_place(dst, offset, len))
+    }
+}
+
+/// The [`postcard`](https://docs.rs/postcard/latest/postcard/) [`WireFormat`].
+#[cfg(feature = "postcard")]
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Postcard;
+
+#[cfg(feature = "postcard")]
+impl WireFormat for Postcard {
+    type SerializeError = postcard::Error;
+    type DeserializeError = postcard::Error;
+
+    fn serialize<T>(item: &T, dst: &mut [u8]) -> Result<usize, Self::SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        postcard::to_slice(item, dst).map(|used| used.len())
+    }
+
+    fn deserialize<T>(src: &[u8]) -> Result<T, Self::DeserializeError>
+    where
+        T: DeserializeOwned,
+    {
+        postcard::from_bytes(src)
+    }
+}
+
+/// The [`serde-json-core`](https://docs.rs/serde-json-core/latest/serde_json_core/) [`WireFormat`].
+#[cfg(feature = "serde-json-core")]
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SerdeJsonCore;
+
+#[cfg(feature = "serde-json-core")]
+impl WireFormat for SerdeJsonCore {
+    type SerializeError = serde_json_core::ser::Error;
+    type DeserializeError = serde_json_core::de::Error;
+
+    fn serialize<T>(item: &T, dst: &mut [u8]) -> Result<usize, Self::SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        serde_json_core::to_slice(item, dst)
+    }
+
+    fn deserialize<T>(src: &[u8]) -> Result<T, Self::DeserializeError>
+    where
+        T: DeserializeOwned,
+    {
+        match serde_json_core::from_slice(src)? {
+            (item, used) if used == src.len() => Ok(item),
+            _ => Err(serde_json_core::de::Error::TrailingCharacters),
+        }
+    }
+}
+
+/// The [`CBOR`](https://cbor.io) [`WireFormat`], using [`minicbor-serde`](https://docs.rs/minicbor-serde/latest/minicbor_serde/).
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl WireFormat for Cbor {
+    type SerializeError = minicbor_serde::error::EncodeError<minicbor::encode::write::EndOfSlice>;
+    type DeserializeError = minicbor_serde::error::DecodeError;
+
+    fn serialize<T>(item: &T, dst: &mut [u8]) -> Result<usize, Self::SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut serializer =
+            minicbor_serde::Serializer::new(minicbor::encode::write::Cursor::new(dst));
+
+        item.serialize(&mut serializer)?;
+
+        Ok(serializer.into_encoder().into_writer().position())
+    }
+
+    fn deserialize<T>(src: &[u8]) -> Result<T, Self::DeserializeError>
+    where
+        T: DeserializeOwned,
+    {
+        minicbor_serde::from_slice(src)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use futures::{SinkExt, StreamExt, pin_mut};
+    use serde::Deserialize;
+
+    use crate::{FramedRead, FramedWrite, ReadError, next, tests::init_tracing};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Reading {
+        id: u8,
+        value: i32,
+        valid: bool,
+    }
+
+    fn readings() -> Vec<Reading> {
+        (0..8)
+            .map(|id| Reading {
+                id,
+                value: i32::from(id) * -1000,
+                valid: id % 2 == 0,
+            })
+            .collect()
+    }
+
+    /// Writes every reading using the format `F`, then reads them back.
+    async fn round_trip<F>()
+    where
+        F: WireFormat,
+        F::SerializeError: core::fmt::Debug,
+        F::DeserializeError: core::fmt::Debug,
+    {
+        let items = readings();
+
+        let (read, write) = tokio::io::duplex(16);
+        let items_clone = items.clone();
+
+        tokio::spawn(async move {
+            let buffer = &mut [0_u8; 64];
+            let mut writer = FramedWrite::new(
+                SerdeCodec::<Reading, F>::new(),
+                embedded_io_adapters::tokio_1::FromTokio::new(write),
+                buffer,
+            );
+            let sink = writer.sink();
+
+            pin_mut!(sink);
+
+            for item in items_clone.iter() {
+                sink.send(item).await.expect("Must send");
+            }
+        });
+
+        let buffer = &mut [0_u8; 64];
+        let mut reader = FramedRead::new(
+            SerdeCodec::<Reading, F>::new(),
+            embedded_io_adapters::tokio_1::FromTokio::new(read),
+            buffer,
+        );
+
+        let collected = reader
+            .stream(|item| item)
+            .map(|item| item.expect("Must decode"))
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(items, collected);
+    }
+
+    #[cfg(feature = "postcard")]
+    #[tokio::test]
+    async fn postcard_round_trip() {
+        init_tracing();
+
+        round_trip::<Postcard>().await;
+    }
+
+    #[cfg(feature = "serde-json-core")]
+    #[tokio::test]
+    async fn serde_json_core_round_trip() {
+        init_tracing();
+
+        round_trip::<SerdeJsonCore>().await;
+    }
+
+    #[cfg(feature = "cbor")]
+    #[tokio::test]
+    async fn cbor_round_trip() {
+        init_tracing();
+
+        round_trip::<Cbor>().await;
+    }
+
+    #[cfg(feature = "postcard")]
+    #[tokio::test]
+    async fn skips_frames_that_fail_to_deserialize() {
+        init_tracing();
+
+        let reading = Reading {
+            id: 1,
+            value: 2,
+            valid: true,
+        };
+
+        // `2` is not a valid `bool`.
+        let invalid = &mut [0_u8; 16];
+        let size = Cobs::new()
+            .encode(b"\x01\x02\x02".as_slice(), invalid)
+            .expect("Must encode");
+
+        let valid = &mut [0_u8; 16];
+        let valid_size = SerdeCodec::<Reading, Postcard>::new()
+            .encode(&reading, valid)
+            .expect("Must encode");
+
+        let mut bytes = invalid[..size].to_vec();
+        bytes.extend_from_slice(&valid[..valid_size]);
+
+        let buffer = &mut [0_u8; 32];
+        let mut reader = FramedRead::new(
+            SerdeCodec::<Reading, Postcard>::new(),
+            bytes.as_slice(),
+            buffer,
+        );
+
+        assert!(matches!(next!(reader), Some(Err(ReadError::Decode(_)))));
+        assert_eq!(
+            next!(reader).expect("Must be some").expect("Must decode"),
+            reading
+        );
+        assert!(next!(reader).is_none());
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn encode_buffer_too_small() {
+        let dst = &mut [0_u8; 4];
+
+        assert!(
+            SerdeCodec::<Reading, Postcard>::new()
+                .encode(
+                    &Reading {
+                        id: 1,
+                        value: 2,
+                        valid: true,
+                    },
+                    dst,
+                )
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,529 @@
+//! Codec adapters from and to [`tokio_util`](https://docs.rs/tokio-util/latest/tokio_util/codec/index.html) codecs.
+
+use bytes::{Buf, BytesMut};
+
+use crate::{
+    decode::Decoder,
+    encode::{EncodeBufferTooSmall, Encoder},
+};
+
+/// A codec that drives a [`tokio_util::codec::Decoder`] and [`tokio_util::codec::Encoder`] as a [`Decoder`] and [`Encoder`].
+///
+/// The bytes of the read buffer are copied into an internal [`BytesMut`] before they are passed to the inner decoder,
+/// and the frames encoded by the inner encoder are copied from an internal [`BytesMut`] into the write buffer.
+///
+/// # Note
+///
+/// This codec tracks progress using an internal state of the underlying buffer, and it must not be used across multiple framing sessions.
+#[derive(Debug, Clone, Default)]
+pub struct FromTokioUtil<C> {
+    inner: C,
+    /// The bytes passed to the inner decoder that it has not consumed yet.
+    read: BytesMut,
+    /// The number of bytes at the start of the read buffer that have been copied into `read`.
+    fed: usize,
+    /// The frame encoded by the inner encoder.
+    write: BytesMut,
+}
+
+impl<C> FromTokioUtil<C> {
+    /// Creates a new [`FromTokioUtil`] wrapping the given `codec`.
+    #[inline]
+    pub fn new(codec: C) -> Self {
+        Self {
+            inner: codec,
+            read: BytesMut::new(),
+            fed: 0,
+            write: BytesMut::new(),
+        }
+    }
+
+    /// Returns reference to the inner codec.
+    #[inline]
+    pub const fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns mutable reference to the inner codec.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the [`FromTokioUtil`] and returns the inner codec.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    /// Copies the bytes of `src` that have not been passed to the inner decoder yet.
+    fn feed(&mut self, src: &[u8]) {
+        match src.get(self.fed..) {
+            Some(new) => self.read.extend_from_slice(new),
+            None => {
+                // The framer dropped bytes that were not consumed, start over.
+                self.read.clear();
+                self.read.extend_from_slice(src);
+            }
+        }
+
+        self.fed = src.len();
+    }
+
+    /// Returns the number of bytes of `src` that the inner decoder consumed to decode a frame.
+    fn consumed(&mut self, src_len: usize) -> usize {
+        self.fed = self.read.len();
+
+        src_len.saturating_sub(self.read.len())
+    }
+}
+
+impl<C> Decoder for FromTokioUtil<C>
+where
+    C: tokio_util::codec::Decoder,
+{
+    type Item<'buf> = C::Item;
+    type Error = C::Error;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        self.feed(src);
+
+        match self.inner.decode(&mut self.read)? {
+            Some(item) => Ok(Some((item, self.consumed(src.len())))),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        self.feed(src);
+
+        match self.inner.decode_eof(&mut self.read)? {
+            Some(item) => Ok(Some((item, self.consumed(src.len())))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Error returned by [`FromTokioUtil::encode`].
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum FromTokioUtilEncodeError<E> {
+    /// The inner encoder failed to encode a frame.
+    Encode(E),
+    /// The encoded frame does not fit into the write buffer.
+    BufferTooSmall(EncodeBufferTooSmall),
+}
+
+impl<E> core::fmt::Display for FromTokioUtilEncodeError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Encode(err) => write!(f, "Encode error: {err}"),
+            Self::BufferTooSmall(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl<E> core::error::Error for FromTokioUtilEncodeError<E> where
+    E: core::fmt::Display + core::fmt::Debug
+{
+}
+
+impl<C, I> Encoder<I> for FromTokioUtil<C>
+where
+    C: tokio_util::codec::Encoder<I>,
+{
+    type Error = FromTokioUtilEncodeError<C::Error>;
+
+    fn encode(&mut self, item: I, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        self.write.clear();
+
+        self.inner
+            .encode(item, &mut self.write)
+            .map_err(FromTokioUtilEncodeError::Encode)?;
+
+        let available = dst.len();
+        let required = self.write.len();
+
+        let dst = dst
+            .get_mut(..required)
+            .ok_or(FromTokioUtilEncodeError::BufferTooSmall(
+                EncodeBufferTooSmall {
+                    required,
+                    available,
+                },
+            ))?;
+
+        dst.copy_from_slice(&self.write);
+
+        Ok(required)
+    }
+}
+
+/// The default maximum length of a frame encoded by [`ToTokioUtil`].
+pub const DEFAULT_MAX_FRAME_LEN: usize = 8 * 1024;
+
+/// A codec that drives a [`Decoder`] and [`Encoder`] as a [`tokio_util::codec::Decoder`] and [`tokio_util::codec::Encoder`].
+///
+/// Lets the same codec frame a stream on an embedded device and on a tokio host.
+///
+/// The items of the inner decoder must not borrow the buffer, because [`tokio_util`](https://docs.rs/tokio-util/latest/tokio_util/codec/index.html)'s items are owned.
+/// Wrap decoders of borrowed items in [`Owned`](crate::codec::owned::Owned), e.g. `ToTokioUtil::new(Owned::new(Lines::new()))`.
+///
+/// The frames are encoded directly into the [`BytesMut`] of the [`tokio_util`](https://docs.rs/tokio-util/latest/tokio_util/codec/index.html) framer.
+/// [`Encoder::encoded_len`] bytes are reserved for every frame if known, [`ToTokioUtil::max_frame_len`] bytes otherwise.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ToTokioUtil<C> {
+    inner: C,
+    /// The number of bytes reserved for a frame of unknown length.
+    max_frame_len: usize,
+}
+
+impl<C> ToTokioUtil<C> {
+    /// Creates a new [`ToTokioUtil`] wrapping the given `codec`.
+    #[inline]
+    pub const fn new(codec: C) -> Self {
+        Self {
+            inner: codec,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    /// Sets the number of bytes reserved to encode a frame, if the inner encoder does not know its [`Encoder::encoded_len`].
+    ///
+    /// Defaults to [`DEFAULT_MAX_FRAME_LEN`].
+    #[inline]
+    pub const fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+
+    /// Returns the number of bytes reserved to encode a frame of unknown length.
+    #[inline]
+    pub const fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Returns reference to the inner codec.
+    #[inline]
+    pub const fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns mutable reference to the inner codec.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the [`ToTokioUtil`] and returns the inner codec.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C> Default for ToTokioUtil<C>
+where
+    C: Default,
+{
+    fn default() -> Self {
+        Self::new(C::default())
+    }
+}
+
+/// Error returned by [`ToTokioUtil`].
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ToTokioUtilError<E> {
+    /// An IO error occurred while reading from or writing to the underlying stream.
+    IO(std::io::Error),
+    /// The inner codec failed to decode or encode a frame.
+    Codec(E),
+    /// The inner decoder reported consuming more bytes than it was given.
+    InvalidState,
+}
+
+impl<E> From<std::io::Error> for ToTokioUtilError<E> {
+    fn from(err: std::io::Error) -> Self {
+        Self::IO(err)
+    }
+}
+
+impl<E> core::fmt::Display for ToTokioUtilError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::IO(err) => write!(f, "IO error: {err}"),
+            Self::Codec(err) => write!(f, "Codec error: {err}"),
+            Self::InvalidState => write!(f, "Invalid state"),
+        }
+    }
+}
+
+impl<E> core::error::Error for ToTokioUtilError<E> where E: core::fmt::Display + core::fmt::Debug {}
+
+/// Advances `src` past the decoded frame and returns its item.
+fn advance<T, E>(
+    src: &mut BytesMut,
+    decoded: Option<(T, usize)>,
+) -> Result<Option<T>, ToTokioUtilError<E>> {
+    match decoded {
+        Some((item, size)) => {
+            if size > src.len() {
+                return Err(ToTokioUtilError::InvalidState);
+            }
+
+            src.advance(size);
+
+            Ok(Some(item))
+        }
+        None => Ok(None),
+    }
+}
+
+impl<C, T> tokio_util::codec::Decoder for ToTokioUtil<C>
+where
+    C: for<'buf> Decoder<Item<'buf> = T>,
+{
+    type Item = T;
+    type Error = ToTokioUtilError<C::Error>;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let decoded = self
+            .inner
+            .decode(src.as_mut())
+            .map_err(ToTokioUtilError::Codec)?;
+
+        advance(src, decoded)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let decoded = self
+            .inner
+            .decode_eof(src.as_mut())
+            .map_err(ToTokioUtilError::Codec)?;
+
+        match advance(src, decoded)? {
+            Some(item) => Ok(Some(item)),
+            None if src.is_empty() => Ok(None),
+            None => Err(std::io::Error::other("bytes remaining on stream").into()),
+        }
+    }
+}
+
+impl<C, I> tokio_util::codec::Encoder<I> for ToTokioUtil<C>
+where
+    C: Encoder<I>,
+{
+    type Error = ToTokioUtilError<C::Error>;
+
+    fn encode(&mut self, item: I, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let start = dst.len();
+        let len = self.inner.encoded_len(&item).unwrap_or(self.max_frame_len);
+
+        dst.resize(start.saturating_add(len), 0);
+
+        let encoded = dst
+            .get_mut(start..)
+            .ok_or(ToTokioUtilError::InvalidState)
+            .and_then(|buf| {
+                self.inner
+                    .encode(item, buf)
+                    .map_err(ToTokioUtilError::Codec)
+            });
+
+        match encoded {
+            Ok(size) if size <= len => {
+                dst.truncate(start.saturating_add(size));
+
+                Ok(())
+            }
+            Ok(_) => {
+                dst.truncate(start);
+
+                Err(ToTokioUtilError::InvalidState)
+            }
+            Err(err) => {
+                dst.truncate(start);
+
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{string::String, vec::Vec};
+
+    use embedded_io_async::{ErrorType, Read};
+    use futures::{SinkExt, StreamExt};
+    use tokio_util::codec::{Framed, LengthDelimitedCodec, LinesCodec};
+
+    use crate::{
+        FramedRead, FramedWrite,
+        codec::{
+            lines::{Lines, StrLines},
+            owned::Owned,
+        },
+        next,
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    /// Reads one byte at a time.
+    struct OneByOne<'a>(&'a [u8]);
+
+    impl ErrorType for OneByOne<'_> {
+        type Error = core::convert::Infallible;
+    }
+
+    impl Read for OneByOne<'_> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let len = buf.len().min(1);
+
+            self.0.read(&mut buf[..len]).await
+        }
+    }
+
+    #[tokio::test]
+    async fn lines_round_trip() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut writer =
+            FramedWrite::new(FromTokioUtil::new(LinesCodec::new()), Vec::new(), buffer);
+
+        writer.send("Hello").await.expect("Must send");
+        writer.send("world!").await.expect("Must send");
+
+        let written = writer.core.inner;
+        assert_eq!(written, b"Hello\nworld!\n");
+
+        let buffer = &mut [0_u8; 32];
+        let mut reader = FramedRead::new(
+            FromTokioUtil::new(LinesCodec::new()),
+            written.as_slice(),
+            buffer,
+        );
+
+        let mut lines = Vec::<String>::new();
+
+        while let Some(line) = next!(reader) {
+            lines.push(line.expect("Must decode"));
+        }
+
+        assert_eq!(lines, ["Hello", "world!"]);
+    }
+
+    #[tokio::test]
+    async fn stateful_decoder_sees_every_byte_once() {
+        init_tracing();
+
+        // LengthDelimitedCodec consumes the header before the payload is complete.
+        let read = b"\x00\x00\x00\x05Hello\x00\x00\x00\x06world!";
+        let buffer = &mut [0_u8; 32];
+        let mut reader = FramedRead::new(
+            FromTokioUtil::new(LengthDelimitedCodec::new()),
+            OneByOne(read),
+            buffer,
+        );
+
+        let mut frames = Vec::<Vec<u8>>::new();
+
+        while let Some(frame) = next!(reader) {
+            frames.push(frame.expect("Must decode").to_vec());
+        }
+
+        assert_eq!(frames, [b"Hello".as_slice(), b"world!"]);
+    }
+
+    #[test]
+    fn encode_buffer_too_small() {
+        let mut codec = FromTokioUtil::new(LinesCodec::new());
+
+        assert!(matches!(
+            Encoder::encode(&mut codec, "Hello", &mut [0_u8; 4]),
+            Err(FromTokioUtilEncodeError::BufferTooSmall(
+                EncodeBufferTooSmall {
+                    required: 6,
+                    available: 4
+                }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn framez_codec_in_tokio_util_framed() {
+        init_tracing();
+
+        let (client, server) = tokio::io::duplex(1024);
+
+        let mut client = Framed::new(client, ToTokioUtil::new(Owned::new(StrLines::new())));
+        let mut server = Framed::new(server, ToTokioUtil::new(Owned::new(StrLines::new())));
+
+        client.send("Hello").await.expect("Must send");
+        client.send("world!").await.expect("Must send");
+        drop(client);
+
+        let mut lines = Vec::<String>::new();
+
+        while let Some(line) = server.next().await {
+            lines.push(line.expect("Must decode"));
+        }
+
+        assert_eq!(lines, ["Hello", "world!"]);
+    }
+
+    #[test]
+    fn decode_eof_with_bytes_remaining() {
+        let mut codec = ToTokioUtil::new(Owned::new(Lines::new()));
+        let mut src = BytesMut::from(b"Hello\r\nworld!".as_slice());
+
+        let item = tokio_util::codec::Decoder::decode_eof(&mut codec, &mut src)
+            .expect("Must decode")
+            .expect("Must be some");
+
+        assert_eq!(item, b"Hello");
+        assert_eq!(src, b"world!".as_slice());
+
+        assert!(matches!(
+            tokio_util::codec::Decoder::decode_eof(&mut codec, &mut src),
+            Err(ToTokioUtilError::IO(_))
+        ));
+    }
+
+    #[test]
+    fn encode_frame_of_unknown_length() {
+        // FromTokioUtil does not know the encoded length of its frames.
+        let mut codec =
+            ToTokioUtil::new(FromTokioUtil::new(LinesCodec::new())).with_max_frame_len(8);
+        let mut dst = BytesMut::from(b"Hi\n".as_slice());
+
+        tokio_util::codec::Encoder::encode(&mut codec, "Hello", &mut dst).expect("Must encode");
+
+        assert_eq!(dst, b"Hi\nHello\n".as_slice());
+
+        assert!(matches!(
+            tokio_util::codec::Encoder::encode(&mut codec, "Hello world!", &mut dst),
+            Err(ToTokioUtilError::Codec(
+                FromTokioUtilEncodeError::BufferTooSmall(_)
+            ))
+        ));
+
+        assert_eq!(dst, b"Hi\nHello\n".as_slice());
+    }
+}
@@ -0,0 +1,273 @@
+//! Codec adapter for [`winnow`](https://docs.rs/winnow/latest/winnow/) partial parsers.
+
+use winnow::{ModalResult, Partial, error::ErrMode};
+
+use crate::decode::Decoder;
+
+/// A `winnow` parser that parses a frame from the start of its [`Partial`] input.
+///
+/// Implemented for functions and closures returning owned outputs, e.g. `fn(&mut Partial<&[u8]>) -> ModalResult<Message>`.
+/// Parsers returning outputs that borrow from their input implement it for a type of their own:
+///
+/// ```rust
+/// use framez::codec::winnow::FrameParser;
+/// use winnow::{ModalResult, Parser, Partial, binary::{be_u8, length_take}, error::ContextError};
+///
+/// struct LengthPrefixed;
+///
+/// impl FrameParser for LengthPrefixed {
+///     type Output<'buf> = &'buf [u8];
+///     type Error = ContextError;
+///
+///     fn parse_frame<'buf>(&mut self, input: &mut Partial<&'buf [u8]>) -> ModalResult<&'buf [u8]> {
+///         length_take(be_u8).parse_next(input)
+///     }
+/// }
+/// ```
+pub trait FrameParser {
+    /// The parsed frame.
+    type Output<'buf>;
+
+    /// The error of the parser, e.g. [`ContextError`](winnow::error::ContextError).
+    type Error;
+
+    /// Parses a frame from the start of `input`, advancing it past the frame.
+    ///
+    /// Parsers of [`Partial`] input return [`ErrMode::Incomplete`] if more bytes are needed.
+    fn parse_frame<'buf>(
+        &mut self,
+        input: &mut Partial<&'buf [u8]>,
+    ) -> ModalResult<Self::Output<'buf>, Self::Error>;
+}
+
+impl<F, O, E> FrameParser for F
+where
+    F: for<'a> FnMut(&mut Partial<&'a [u8]>) -> ModalResult<O, E>,
+{
+    type Output<'buf> = O;
+    type Error = E;
+
+    #[inline]
+    fn parse_frame<'buf>(
+        &mut self,
+        input: &mut Partial<&'buf [u8]>,
+    ) -> ModalResult<Self::Output<'buf>, Self::Error> {
+        self(input)
+    }
+}
+
+/// A codec that decodes frames using a `winnow` [`FrameParser`] of [`Partial`] input.
+///
+/// - A parsed frame consumes the bytes the parser consumed.
+/// - [`ErrMode::Incomplete`] means more bytes are needed.
+/// - [`ErrMode::Backtrack`] means the bytes do not start a frame. The first byte is skipped and parsing is retried,
+///   so the codec resynchronizes with the stream, e.g. after garbage received before the first frame.
+/// - [`ErrMode::Cut`] is returned as the error of the parser. Its first byte is skipped, so reading can continue.
+///
+/// Skipped bytes are consumed together with the next frame, so they must fit into the read buffer with it.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{FramedRead, codec::winnow::Winnow, next};
+/// use winnow::{ModalResult, Parser, Partial, binary::be_u16, combinator::preceded};
+///
+/// /// A reading starts with `R` followed by a big endian value.
+/// fn reading(input: &mut Partial<&[u8]>) -> ModalResult<u16> {
+///     preceded(b'R', be_u16).parse_next(input)
+/// }
+///
+/// # async fn run() {
+/// let buffer = &mut [0_u8; 16];
+/// let mut framer = FramedRead::new(Winnow::new(reading), b"R\x01\x02R\x03\x04".as_slice(), buffer);
+///
+/// assert_eq!(next!(framer).unwrap().unwrap(), 0x0102);
+/// assert_eq!(next!(framer).unwrap().unwrap(), 0x0304);
+/// # }
+/// ```
+///
+/// # Note
+///
+/// This codec tracks progress using an internal state of the underlying buffer, and it must not be used across multiple framing sessions.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Winnow<P> {
+    parser: P,
+    /// The number of skipped bytes at the start of the slice.
+    skip: usize,
+}
+
+impl<P> Winnow<P> {
+    /// Creates a new [`Winnow`] codec wrapping the given `parser`.
+    #[inline]
+    pub const fn new(parser: P) -> Self {
+        Self { parser, skip: 0 }
+    }
+
+    /// Returns reference to the parser.
+    #[inline]
+    pub const fn parser(&self) -> &P {
+        &self.parser
+    }
+
+    /// Returns mutable reference to the parser.
+    #[inline]
+    pub const fn parser_mut(&mut self) -> &mut P {
+        &mut self.parser
+    }
+
+    /// Consumes the [`Winnow`] codec and returns the parser.
+    #[inline]
+    pub fn into_parser(self) -> P {
+        self.parser
+    }
+}
+
+impl<P> Decoder for Winnow<P>
+where
+    P: FrameParser,
+{
+    type Item<'buf> = P::Output<'buf>;
+    type Error = P::Error;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let src: &'buf [u8] = src;
+
+        loop {
+            let start = src.get(self.skip..).unwrap_or_default();
+            let mut input = Partial::new(start);
+
+            match self.parser.parse_frame(&mut input) {
+                Ok(item) => {
+                    let rest = input.into_inner();
+                    let size = self
+                        .skip
+                        .saturating_add(start.len().saturating_sub(rest.len()));
+
+                    self.skip = 0;
+
+                    return Ok(Some((item, size)));
+                }
+                Err(ErrMode::Incomplete(_)) => return Ok(None),
+                Err(ErrMode::Backtrack(_)) if self.skip < src.len() => {
+                    self.skip = self.skip.saturating_add(1);
+                }
+                Err(ErrMode::Backtrack(_)) => return Ok(None),
+                Err(ErrMode::Cut(err)) => {
+                    self.skip = self.skip.saturating_add(1);
+
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use winnow::{
+        Parser,
+        binary::{be_u8, length_take},
+        combinator::{cut_err, preceded},
+        error::ContextError,
+    };
+
+    use crate::{FramedRead, ReadError, mock::ScriptedReader, next, tests::init_tracing};
+
+    use super::*;
+
+    /// A frame starts with `#` followed by a length prefixed payload.
+    struct Framed;
+
+    impl FrameParser for Framed {
+        type Output<'buf> = &'buf [u8];
+        type Error = ContextError;
+
+        fn parse_frame<'buf>(
+            &mut self,
+            input: &mut Partial<&'buf [u8]>,
+        ) -> ModalResult<&'buf [u8]> {
+            preceded(b'#', length_take(be_u8)).parse_next(input)
+        }
+    }
+
+    fn pair(input: &mut Partial<&[u8]>) -> ModalResult<(u8, u8)> {
+        preceded(b'P', cut_err((be_u8, be_u8))).parse_next(input)
+    }
+
+    #[tokio::test]
+    async fn borrowed_frames_across_chunks() {
+        init_tracing();
+
+        let read = ScriptedReader::new(&[b"#\x05He", b"llo#", b"\x00#\x03Hey"]);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Winnow::new(Framed), read, buffer);
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b"Hello");
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b"");
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b"Hey");
+
+        assert!(next!(framer).is_none());
+    }
+
+    #[tokio::test]
+    async fn garbage_is_skipped() {
+        init_tracing();
+
+        let read = ScriptedReader::new(&[b"xyz#\x02Hi"]);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Winnow::new(Framed), read, buffer);
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b"Hi");
+
+        assert!(next!(framer).is_none());
+    }
+
+    #[tokio::test]
+    async fn cut_is_reported() {
+        init_tracing();
+
+        fn strict(input: &mut Partial<&[u8]>) -> ModalResult<u8> {
+            preceded(b'P', cut_err(b'!')).parse_next(input)
+        }
+
+        let read = ScriptedReader::new(&[b"P?P!"]);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Winnow::new(strict), read, buffer);
+
+        assert!(matches!(next!(framer), Some(Err(ReadError::Decode(_)))));
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b'!');
+
+        assert!(next!(framer).is_none());
+    }
+
+    #[tokio::test]
+    async fn owned_frames() {
+        init_tracing();
+
+        let read = ScriptedReader::new(&[b"P\x01", b"\x02P\x03\x04"]);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Winnow::new(pair), read, buffer);
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, (1, 2));
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, (3, 4));
+    }
+}
@@ -0,0 +1,237 @@
+//! Codec adapter that decodes items together with the raw bytes of their frames.
+
+use core::ops::Range;
+
+use embedded_io_async::Write;
+
+use crate::{WriteError, codec::in_place::span, decode::Decoder, encode::Encoder};
+
+/// A codec that returns the borrowed items of the inner decoder together with the raw bytes of their frames.
+///
+/// E.g. `WithRaw<Lines>` decodes `Hello\r\n` into `(b"Hello", b"Hello\r\n")`, so a frame can be forwarded, logged or checksummed as it was received, without encoding it again.
+/// The raw bytes are all the bytes consumed by the inner decoder for the frame.
+/// The item of the inner decoder must be a part of the buffer it was given, so decoders that transform the frame in place, like [`Cobs`](crate::codec::cobs::Cobs), are not supported.
+///
+/// Encoding is delegated to the inner encoder.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WithRaw<C> {
+    inner: C,
+}
+
+impl<C> WithRaw<C> {
+    /// Creates a new [`WithRaw`] wrapping the given `codec`.
+    #[inline]
+    pub const fn new(codec: C) -> Self {
+        Self { inner: codec }
+    }
+
+    /// Returns reference to the inner codec.
+    #[inline]
+    pub const fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns mutable reference to the inner codec.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the [`WithRaw`] and returns the inner codec.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+/// Error returned by [`WithRaw::decode`].
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WithRawError<E> {
+    /// The inner decoder failed to decode a frame.
+    Decode(E),
+    /// The item of the inner decoder is not a part of the buffer it was given.
+    OutOfBuffer,
+}
+
+impl<E> core::fmt::Display for WithRawError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Decode(err) => write!(f, "Decode error: {err}"),
+            Self::OutOfBuffer => write!(f, "Item out of buffer"),
+        }
+    }
+}
+
+impl<E> core::error::Error for WithRawError<E> where E: core::fmt::Display + core::fmt::Debug {}
+
+/// Returns the `span` of the item and the first `size` bytes of `src`.
+fn with_raw<E>(
+    src: &[u8],
+    span: Option<Range<usize>>,
+    size: usize,
+) -> Result<(&[u8], &[u8]), WithRawError<E>> {
+    match (span.and_then(|span| src.get(span)), src.get(..size)) {
+        (Some(item), Some(raw)) => Ok((item, raw)),
+        _ => Err(WithRawError::OutOfBuffer),
+    }
+}
+
+impl<C> Decoder for WithRaw<C>
+where
+    C: for<'a> Decoder<Item<'a> = &'a [u8]>,
+{
+    type Item<'buf> = (&'buf [u8], &'buf [u8]);
+    type Error = WithRawError<C::Error>;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let base = src.as_ptr().addr();
+
+        let frame = self
+            .inner
+            .decode(&mut *src)
+            .map_err(WithRawError::Decode)?
+            .map(|(item, size)| (span(base, item), size));
+
+        match frame {
+            Some((span, size)) => Ok(Some((with_raw(src, span, size)?, size))),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let base = src.as_ptr().addr();
+
+        let frame = self
+            .inner
+            .decode_eof(&mut *src)
+            .map_err(WithRawError::Decode)?
+            .map(|(item, size)| (span(base, item), size));
+
+        match frame {
+            Some((span, size)) => Ok(Some((with_raw(src, span, size)?, size))),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_header(&mut self, src: &[u8]) -> Option<usize> {
+        self.inner.decode_header(src)
+    }
+}
+
+impl<C, I> Encoder<I> for WithRaw<C>
+where
+    C: Encoder<I>,
+{
+    type Error = C::Error;
+
+    fn encode(&mut self, item: I, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encoded_len(&self, item: &I) -> Option<usize> {
+        self.inner.encoded_len(item)
+    }
+
+    async fn encode_to_writer<W>(
+        &mut self,
+        item: I,
+        buf: &mut [u8],
+        write: &mut W,
+    ) -> Result<usize, WriteError<W::Error, Self::Error>>
+    where
+        W: Write,
+    {
+        self.inner.encode_to_writer(item, buf, write).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use crate::{
+        FramedRead,
+        codec::{delimiter::Delimiter, lines::Lines},
+        next,
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn items_come_with_their_frames() {
+        init_tracing();
+
+        let read = b"Hello\r\nworld!\n".as_slice();
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(WithRaw::new(Lines::new()), read, buffer);
+
+        let mut collected = Vec::<(Vec<u8>, Vec<u8>)>::new();
+
+        while let Some(item) = next!(framer) {
+            let (item, raw) = item.expect("Must decode");
+
+            collected.push((item.to_vec(), raw.to_vec()));
+        }
+
+        assert_eq!(
+            collected,
+            [
+                (b"Hello".to_vec(), b"Hello\r\n".to_vec()),
+                (b"world!".to_vec(), b"world!\n".to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_bytes_include_the_delimiter() {
+        let src = &mut *b"Hello#world!".to_vec();
+
+        let ((item, raw), size) = WithRaw::new(Delimiter::new(b"#"))
+            .decode(src)
+            .expect("Must decode")
+            .expect("Must be some");
+
+        assert_eq!(item, b"Hello");
+        assert_eq!(raw, b"Hello#");
+        assert_eq!(size, 6);
+    }
+
+    /// Returns a frame that is not a part of the buffer.
+    struct Static;
+
+    impl Decoder for Static {
+        type Item<'buf> = &'buf [u8];
+        type Error = core::convert::Infallible;
+
+        fn decode<'buf>(
+            &mut self,
+            src: &'buf mut [u8],
+        ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+            Ok(Some((b"Hello", src.len())))
+        }
+    }
+
+    #[test]
+    fn item_out_of_buffer() {
+        let src = &mut *b"Hello".to_vec();
+
+        assert!(matches!(
+            WithRaw::new(Static).decode(src),
+            Err(WithRawError::OutOfBuffer)
+        ));
+    }
+}
@@ -1,49 +1,378 @@
 //! Decoder trait definition.
 
-/// An error that can occur while decoding a frame.
-pub trait DecodeError {
-    /// The type of error that a decoder returns.
-    type Error;
-}
-
-impl<D> DecodeError for &mut D
-where
-    D: DecodeError,
-{
-    type Error = D::Error;
-}
-
 /// A decoder that decodes a frame from a buffer.
-pub trait Decoder<'buf>: DecodeError {
+pub trait Decoder {
     /// The type of item that this decoder decodes.
-    type Item;
+    ///
+    /// The item may borrow the buffer mutably, e.g. `&'buf mut [u8]`, so the frame can be transformed in place.
+    /// See [`InPlace`](crate::codec::in_place::InPlace).
+    type Item<'buf>;
+
+    /// The type of error that this decoder returns.
+    type Error;
 
     /// Decodes a frame from the provided buffer.
-    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error>;
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error>;
 
     /// Decodes a frame from the provided buffer at the end of the stream.
-    fn decode_eof(
+    fn decode_eof<'buf>(
         &mut self,
         src: &'buf mut [u8],
-    ) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
         self.decode(src)
     }
+
+    /// Returns the total length of the frame at the start of `src`, header included, if the header is complete.
+    ///
+    /// Called before the first attempt to decode a frame, until it returns `Some`. The framer then keeps reading
+    /// without calling [`Decoder::decode`] until the whole frame is buffered.
+    /// Length-prefixed protocols implement it to avoid speculative decodes of partial frames.
+    ///
+    /// Returns `None` by default.
+    fn decode_header(&mut self, src: &[u8]) -> Option<usize> {
+        let _ = src;
+
+        None
+    }
 }
 
-impl<'buf, D> Decoder<'buf> for &mut D
+impl<D> Decoder for &mut D
 where
-    D: Decoder<'buf>,
+    D: Decoder,
 {
-    type Item = D::Item;
+    type Item<'buf> = D::Item<'buf>;
+    type Error = D::Error;
 
-    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
         (*self).decode(src)
     }
 
-    fn decode_eof(
+    fn decode_eof<'buf>(
         &mut self,
         src: &'buf mut [u8],
-    ) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
         (*self).decode_eof(src)
     }
+
+    fn decode_header(&mut self, src: &[u8]) -> Option<usize> {
+        (*self).decode_header(src)
+    }
+}
+
+/// A decoded frame header that is followed by a payload in the stream.
+///
+/// Implemented by the items of decoders that decode only the header of a frame and leave its payload in the stream.
+/// The payload is then read in chunks using [`next_payload_chunk`](crate::functions::next_payload_chunk).
+pub trait PayloadHeader {
+    /// Returns the number of payload bytes that follow the header.
+    fn payload_len(&self) -> usize;
+}
+
+impl<H> PayloadHeader for &H
+where
+    H: PayloadHeader,
+{
+    fn payload_len(&self) -> usize {
+        (*self).payload_len()
+    }
+}
+
+/// A decoder that decodes a frame from a ring buffer.
+///
+/// The buffered bytes of a ring buffer may wrap around the end of the underlying buffer.
+/// They are passed to the decoder as two sequential slices: `head` holds the oldest bytes
+/// and `tail` holds the bytes that wrapped around to the start of the buffer. `tail` is empty if the bytes do not wrap.
+///
+/// Used with [`RingFramedRead`](crate::RingFramedRead), or [`RingReadState`](crate::state::RingReadState) and [`maybe_next_ring`](crate::functions::maybe_next_ring).
+pub trait RingDecoder {
+    /// The type of item that this decoder decodes.
+    type Item<'buf>;
+
+    /// The type of error that this decoder returns.
+    type Error;
+
+    /// Decodes a frame from the provided sequential slices.
+    ///
+    /// The returned size is the number of bytes consumed, counted from the start of `head`.
+    fn decode_ring<'buf>(
+        &mut self,
+        head: &'buf mut [u8],
+        tail: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error>;
+
+    /// Decodes a frame from the provided sequential slices at the end of the stream.
+    fn decode_ring_eof<'buf>(
+        &mut self,
+        head: &'buf mut [u8],
+        tail: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        self.decode_ring(head, tail)
+    }
+}
+
+impl<D> RingDecoder for &mut D
+where
+    D: RingDecoder,
+{
+    type Item<'buf> = D::Item<'buf>;
+    type Error = D::Error;
+
+    fn decode_ring<'buf>(
+        &mut self,
+        head: &'buf mut [u8],
+        tail: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        (*self).decode_ring(head, tail)
+    }
+
+    fn decode_ring_eof<'buf>(
+        &mut self,
+        head: &'buf mut [u8],
+        tail: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        (*self).decode_ring_eof(head, tail)
+    }
+}
+
+/// A byte slice that may wrap around the end of a ring buffer.
+///
+/// Consists of two sequential slices, `head` followed by `tail`.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RingSlice<'a> {
+    head: &'a [u8],
+    tail: &'a [u8],
+}
+
+impl<'a> RingSlice<'a> {
+    /// Creates a new [`RingSlice`] from two sequential slices.
+    #[inline]
+    pub const fn new(head: &'a [u8], tail: &'a [u8]) -> Self {
+        Self { head, tail }
+    }
+
+    /// Returns the first part of the slice.
+    #[inline]
+    pub const fn head(&self) -> &'a [u8] {
+        self.head
+    }
+
+    /// Returns the second part of the slice. Empty if the slice does not wrap.
+    #[inline]
+    pub const fn tail(&self) -> &'a [u8] {
+        self.tail
+    }
+
+    /// Returns the total number of bytes.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.head.len().saturating_add(self.tail.len())
+    }
+
+    /// Returns `true` if the slice has no bytes.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bytes as one contiguous slice if the slice does not wrap.
+    #[inline]
+    pub const fn as_contiguous(&self) -> Option<&'a [u8]> {
+        match (self.head.is_empty(), self.tail.is_empty()) {
+            (_, true) => Some(self.head),
+            (true, false) => Some(self.tail),
+            (false, false) => None,
+        }
+    }
+
+    /// Returns the byte at `index`.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<u8> {
+        match self.head.get(index) {
+            Some(byte) => Some(*byte),
+            None => self.tail.get(index.checked_sub(self.head.len())?).copied(),
+        }
+    }
+
+    /// Returns the index of the first `byte` at or after `from`.
+    ///
+    /// Scans `head` and then `tail` as plain slices.
+    #[inline]
+    pub fn position(&self, from: usize, byte: u8) -> Option<usize> {
+        let head_len = self.head.len();
+
+        if let Some(head) = self.head.get(from..) {
+            if let Some(index) = memchr::memchr(byte, head) {
+                return Some(from.saturating_add(index));
+            }
+        }
+
+        let from = from.saturating_sub(head_len);
+
+        memchr::memchr(byte, self.tail.get(from..)?)
+            .map(|index| head_len.saturating_add(from).saturating_add(index))
+    }
+
+    /// Returns the first `len` bytes.
+    ///
+    /// Returns all bytes if `len` is greater than [`RingSlice::len`].
+    #[inline]
+    pub fn prefix(&self, len: usize) -> Self {
+        match self.head.split_at_checked(len) {
+            Some((head, _)) => Self::new(head, &[]),
+            None => Self::new(
+                self.head,
+                self.tail
+                    .split_at_checked(len.saturating_sub(self.head.len()))
+                    .map_or(self.tail, |(tail, _)| tail),
+            ),
+        }
+    }
+
+    /// Returns an iterator over the bytes.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = u8> + 'a {
+        self.head.iter().chain(self.tail.iter()).copied()
+    }
+
+    /// Copies the bytes into `dst` and returns the number of bytes copied.
+    ///
+    /// Returns `None` if `dst` is too small.
+    pub fn copy_to_slice(&self, dst: &mut [u8]) -> Option<usize> {
+        let len = self.len();
+
+        let (head, tail) = dst.get_mut(..len)?.split_at_mut_checked(self.head.len())?;
+
+        head.copy_from_slice(self.head);
+        tail.copy_from_slice(self.tail);
+
+        Some(len)
+    }
+}
+
+impl PartialEq for RingSlice<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl PartialEq<[u8]> for RingSlice<'_> {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter().copied())
+    }
+}
+
+impl PartialEq<&[u8]> for RingSlice<'_> {
+    fn eq(&self, other: &&[u8]) -> bool {
+        self == *other
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HEAD: &[u8] = b"Hello";
+    const TAIL: &[u8] = b", world!";
+
+    #[test]
+    fn ring_slice_get_across_wrap() {
+        let src = RingSlice::new(HEAD, TAIL);
+
+        assert_eq!(src.len(), 13);
+        assert_eq!(src.get(0), Some(b'H'));
+        assert_eq!(src.get(4), Some(b'o'));
+        assert_eq!(src.get(5), Some(b','));
+        assert_eq!(src.get(12), Some(b'!'));
+        assert_eq!(src.get(13), None);
+
+        let src = RingSlice::new(&[], TAIL);
+
+        assert_eq!(src.get(0), Some(b','));
+    }
+
+    #[test]
+    fn ring_slice_position() {
+        let src = RingSlice::new(HEAD, TAIL);
+
+        assert_eq!(src.position(0, b'l'), Some(2));
+        assert_eq!(src.position(4, b'l'), Some(10));
+        assert_eq!(src.position(5, b'H'), None);
+        assert_eq!(src.position(0, b'!'), Some(12));
+        assert_eq!(src.position(13, b'!'), None);
+        assert_eq!(src.position(42, b'!'), None);
+    }
+
+    #[test]
+    fn ring_slice_prefix() {
+        let src = RingSlice::new(HEAD, TAIL);
+
+        let prefix = src.prefix(0);
+        assert!(prefix.is_empty());
+
+        let prefix = src.prefix(3);
+        assert_eq!(prefix.head(), b"Hel");
+        assert!(prefix.tail().is_empty());
+
+        let prefix = src.prefix(5);
+        assert_eq!(prefix.head(), HEAD);
+        assert!(prefix.tail().is_empty());
+
+        let prefix = src.prefix(7);
+        assert_eq!(prefix.head(), HEAD);
+        assert_eq!(prefix.tail(), b", ");
+
+        assert_eq!(src.prefix(13), src);
+    }
+
+    #[test]
+    fn ring_slice_prefix_out_of_bounds() {
+        let slice = RingSlice::new(HEAD, TAIL);
+
+        assert_eq!(slice.prefix(14).len(), slice.len());
+        assert!(slice.prefix(14).iter().eq(slice.iter()));
+    }
+
+    #[test]
+    fn ring_slice_as_contiguous() {
+        assert_eq!(RingSlice::new(HEAD, &[]).as_contiguous(), Some(HEAD));
+        assert_eq!(RingSlice::new(&[], TAIL).as_contiguous(), Some(TAIL));
+        assert_eq!(
+            RingSlice::new(&[], &[]).as_contiguous(),
+            Some([].as_slice())
+        );
+        assert_eq!(RingSlice::new(HEAD, TAIL).as_contiguous(), None);
+    }
+
+    #[test]
+    fn ring_slice_copy_to_slice() {
+        let src = RingSlice::new(HEAD, TAIL);
+
+        let dst = &mut [0_u8; 16];
+        assert_eq!(src.copy_to_slice(dst), Some(13));
+        assert_eq!(&dst[..13], b"Hello, world!");
+
+        let dst = &mut [0_u8; 12];
+        assert_eq!(src.copy_to_slice(dst), None);
+    }
+
+    #[test]
+    fn ring_slice_eq() {
+        let src = RingSlice::new(HEAD, TAIL);
+
+        assert_eq!(src, RingSlice::new(b"Hello, ", b"world!"));
+        assert_eq!(src, RingSlice::new(b"Hello, world!", &[]));
+        assert_ne!(src, RingSlice::new(b"Hello, world", &[]));
+        assert_ne!(src, RingSlice::new(b"Hello, world?", &[]));
+
+        assert_eq!(src, *b"Hello, world!".as_slice());
+        assert_eq!(src, b"Hello, world!".as_slice());
+        assert_ne!(src, b"Hello".as_slice());
+    }
 }
@@ -0,0 +1,296 @@
+//! Protocol auto-detection at connection start.
+//!
+//! Devices that speak either a text console or a binary protocol on the same port pick the codec based on the first bytes they receive.
+//! [`detect`] reads until one of several [`Candidate`]s matches the buffered bytes, without consuming them,
+//! and returns a [`Framed`] that decodes them with the codec of the winner.
+
+use embedded_io_async::Read;
+
+use crate::{
+    DetectError, Framed, ReadError,
+    buffer::Buffer,
+    functions,
+    logging::{debug, error},
+    state::ReadState,
+};
+
+#[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
+use crate::logging::Name;
+
+#[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
+const READ: &str = "framez::read";
+
+/// The result of matching the first buffered bytes against a [`Candidate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Match {
+    /// The bytes start a frame of the candidate.
+    Yes,
+    /// The bytes do not start a frame of the candidate.
+    No,
+    /// More bytes are needed to decide.
+    NeedMore,
+}
+
+/// A matcher of the first buffered bytes.
+pub type Matcher = fn(&[u8]) -> Match;
+
+/// A codec that is selected by [`detect`] if its `matcher` matches the first buffered bytes.
+#[derive(Debug, Clone)]
+pub struct Candidate<C> {
+    /// Matches the first buffered bytes.
+    pub matcher: Matcher,
+    /// The codec that decodes the frames if the candidate wins.
+    pub codec: C,
+}
+
+impl<C> Candidate<C> {
+    /// Creates a new [`Candidate`] with the given `matcher` and `codec`.
+    #[inline]
+    pub const fn new(matcher: Matcher, codec: C) -> Self {
+        Self { matcher, codec }
+    }
+}
+
+/// Reads from the `framed`'s reader until one of the `candidates` matches the buffered bytes,
+/// and returns a [`Framed`] that decodes them with the codec of the winner.
+///
+/// The candidates are tried in order. A candidate wins if it returns [`Match::Yes`] and all candidates before it return [`Match::No`].
+/// Otherwise, more bytes are read. At eof, [`Match::NeedMore`] counts as [`Match::No`].
+///
+/// The codec of the `framed` is only a placeholder, e.g. `()`, and is dropped.
+/// The buffered bytes are not consumed, so the first frame is decoded from them.
+///
+/// Frames with different codec types are detected using an enum of codecs, or with [`detect_index`].
+///
+/// # Errors
+///
+/// - [`DetectError::NoMatch`] if all candidates return [`Match::No`].
+/// - [`DetectError::UnexpectedEof`] if eof was reached while a candidate still returns [`Match::NeedMore`] and none matched.
+/// - [`DetectError::Read`] if reading failed, e.g. [`ReadError::BufferTooSmall`] if the buffer is full before a candidate matched.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{
+///     Framed,
+///     codec::delimiter::Delimiter,
+///     detect::{Candidate, Match, detect},
+///     next,
+/// };
+///
+/// /// A console line starts with a printable character.
+/// fn console(bytes: &[u8]) -> Match {
+///     match bytes.first() {
+///         None => Match::NeedMore,
+///         Some(byte) if byte.is_ascii_graphic() => Match::Yes,
+///         Some(_) => Match::No,
+///     }
+/// }
+///
+/// /// A binary frame starts with a magic byte.
+/// fn binary(bytes: &[u8]) -> Match {
+///     match bytes.first() {
+///         None => Match::NeedMore,
+///         Some(0x7E) => Match::Yes,
+///         Some(_) => Match::No,
+///     }
+/// }
+///
+/// # async fn run() {
+/// let r_buf = &mut [0_u8; 32];
+/// let w_buf = &mut [0_u8; 32];
+///
+/// let framed = Framed::new((), b"\x7E\x01\x02\x00".as_slice(), r_buf, w_buf);
+///
+/// let candidates = [
+///     Candidate::new(console, Delimiter::new(b"\r\n")),
+///     Candidate::new(binary, Delimiter::new(&[0])),
+/// ];
+///
+/// let mut framed = detect(framed, candidates).await.unwrap();
+///
+/// assert_eq!(next!(framed).unwrap().unwrap(), b"\x7E\x01\x02");
+/// # }
+/// ```
+pub async fn detect<'buf, D, C, RW, RB, WB, const N: usize>(
+    framed: Framed<'buf, D, RW, RB, WB>,
+    candidates: [Candidate<C>; N],
+) -> Result<Framed<'buf, C, RW, RB, WB>, DetectError<RW::Error>>
+where
+    RW: Read,
+    RB: Buffer,
+{
+    let (_, mut read_write, mut state) = framed.into_parts();
+
+    let matchers = candidates.each_ref().map(|candidate| candidate.matcher);
+    let index = detect_index(&mut state.read, &mut read_write, &matchers).await?;
+
+    let Some(candidate) = candidates.into_iter().nth(index) else {
+        return Err(DetectError::Read(ReadError::InvalidState));
+    };
+
+    Ok(Framed::from_parts(candidate.codec, read_write, state))
+}
+
+/// Reads from `read` until one of the `matchers` matches the buffered bytes of the `state`,
+/// and returns the index of the winner, without consuming the bytes.
+///
+/// Meant for framers other than [`Framed`] and for candidates with different codec types.
+/// See [`detect`] for how the winner is selected.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`](crate::functions::maybe_next).
+/// The bytes read so far stay buffered.
+pub async fn detect_index<R, B>(
+    state: &mut ReadState<'_, B>,
+    read: &mut R,
+    matchers: &[Matcher],
+) -> Result<usize, DetectError<R::Error>>
+where
+    R: Read,
+    B: Buffer,
+{
+    let mut eof = false;
+
+    loop {
+        let buffered = state
+            .buffer
+            .as_slice()
+            .get(state.total_consumed..state.index)
+            .unwrap_or_default();
+
+        let mut need_more = false;
+
+        for (index, matcher) in matchers.iter().enumerate() {
+            match matcher(buffered) {
+                Match::Yes => {
+                    debug!(target: READ, "{}Protocol detected. candidate: {}, buffered: {}", Name(state.name), index, buffered.len());
+
+                    return Ok(index);
+                }
+                Match::No => {}
+                Match::NeedMore => {
+                    need_more = true;
+
+                    if !eof {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if !need_more {
+            error!(target: READ, "{}No protocol matched", Name(state.name));
+
+            return Err(DetectError::NoMatch);
+        }
+
+        if eof {
+            error!(target: READ, "{}Got EOF before a protocol matched", Name(state.name));
+
+            return Err(DetectError::UnexpectedEof);
+        }
+
+        eof = functions::fill(state, read).await? == 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{codec::delimiter::Delimiter, mock::ScriptedReader, next, tests::init_tracing};
+
+    use super::*;
+
+    /// A console command starts with `AT`.
+    fn console(bytes: &[u8]) -> Match {
+        match bytes {
+            [b'A', b'T', ..] => Match::Yes,
+            [] | [b'A'] => Match::NeedMore,
+            _ => Match::No,
+        }
+    }
+
+    /// A binary frame starts with a magic byte.
+    fn binary(bytes: &[u8]) -> Match {
+        match bytes.first() {
+            None => Match::NeedMore,
+            Some(0x7E) => Match::Yes,
+            Some(_) => Match::No,
+        }
+    }
+
+    fn any(_: &[u8]) -> Match {
+        Match::Yes
+    }
+
+    #[tokio::test]
+    async fn reads_until_a_candidate_matches() {
+        init_tracing();
+
+        let read = ScriptedReader::new(&[b"A", b"T+RST\r\nAT\r\n"]);
+
+        let (r_buf, w_buf) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+        let framed = Framed::new((), read, r_buf, w_buf);
+
+        let candidates = [
+            Candidate::new(binary, Delimiter::new(&[0])),
+            Candidate::new(console, Delimiter::new(b"\r\n")),
+        ];
+
+        let mut framed = detect(framed, candidates).await.expect("Must detect");
+
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b"AT+RST");
+
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b"AT");
+    }
+
+    #[tokio::test]
+    async fn no_match() {
+        init_tracing();
+
+        let read = ScriptedReader::new(&[b"GET / HTTP/1.1\r\n"]);
+
+        let (r_buf, w_buf) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+        let framed = Framed::new((), read, r_buf, w_buf);
+
+        let candidates = [
+            Candidate::new(binary, Delimiter::new(&[0])),
+            Candidate::new(console, Delimiter::new(b"\r\n")),
+        ];
+
+        assert!(matches!(
+            detect(framed, candidates).await,
+            Err(DetectError::NoMatch)
+        ));
+    }
+
+    #[tokio::test]
+    async fn need_more_at_eof() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut state = ReadState::new(buffer);
+        let mut read = ScriptedReader::new(&[b"A"]);
+
+        assert!(matches!(
+            detect_index(&mut state, &mut read, &[binary, console]).await,
+            Err(DetectError::UnexpectedEof)
+        ));
+
+        // Counts as no match, so a later candidate wins.
+        let buffer = &mut [0_u8; 32];
+        let mut state = ReadState::new(buffer);
+        let mut read = ScriptedReader::new(&[b"A"]);
+
+        let index = detect_index(&mut state, &mut read, &[console, any])
+            .await
+            .expect("Must detect");
+
+        assert_eq!(index, 1);
+        assert_eq!(state.framable(), 1);
+    }
+}
@@ -1,5 +1,35 @@
 //! Encoder trait definition.
 
+#![allow(async_fn_in_trait)]
+
+use embedded_io_async::Write;
+
+use crate::WriteError;
+
+/// Error returned by encoders when the provided buffer is too small to fit the encoded frame.
+///
+/// Shared by the built-in encoders, so callers can handle it generically, e.g. by growing the write buffer or splitting the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EncodeBufferTooSmall {
+    /// The number of bytes needed to encode the frame.
+    pub required: usize,
+    /// The number of bytes available in the buffer.
+    pub available: usize,
+}
+
+impl core::fmt::Display for EncodeBufferTooSmall {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "buffer too small. required: {}, available: {}",
+            self.required, self.available
+        )
+    }
+}
+
+impl core::error::Error for EncodeBufferTooSmall {}
+
 /// An encoder that encodes a frame into a buffer.
 pub trait Encoder<Item> {
     /// The type of error that this encoder returns.
@@ -7,6 +37,139 @@ pub trait Encoder<Item> {
 
     /// Encodes an item into the provided buffer.
     fn encode(&mut self, item: Item, dst: &mut [u8]) -> Result<usize, Self::Error>;
+
+    /// Returns the number of bytes that [`Encoder::encode`] would write for the given `item`, if known without encoding it.
+    ///
+    /// Framers use this hint to reject frames that can not fit the write buffer before doing any work.
+    ///
+    /// Returns `None` by default.
+    fn encoded_len(&self, item: &Item) -> Option<usize> {
+        let _ = item;
+
+        None
+    }
+
+    /// Encodes an item and writes it directly to `write`, returning the number of bytes written.
+    ///
+    /// Codecs that can stream their output, e.g. a length prefix followed by the payload borrowed from the item,
+    /// override it to skip the intermediate write buffer `buf`. See [`send_direct`](crate::functions::send_direct).
+    /// Encoding errors should be returned before any bytes are written, so the peer never receives a torn frame.
+    ///
+    /// By default, the item is encoded into `buf` using [`Encoder::encode`], then written.
+    async fn encode_to_writer<W>(
+        &mut self,
+        item: Item,
+        buf: &mut [u8],
+        write: &mut W,
+    ) -> Result<usize, WriteError<W::Error, Self::Error>>
+    where
+        W: Write,
+    {
+        let size = self.encode(item, buf).map_err(WriteError::Encode)?;
+
+        write_all(write, buf.get(..size).ok_or(WriteError::InvalidState)?).await?;
+
+        Ok(size)
+    }
+}
+
+/// Writes all the `bytes` to `write`, e.g. in an implementation of [`Encoder::encode_to_writer`].
+///
+/// # Errors
+///
+/// - [`WriteError::WriteZero`] if `write` returned `Ok(0)`.
+/// - [`WriteError::IO`] if writing failed.
+pub async fn write_all<W, E>(write: &mut W, mut bytes: &[u8]) -> Result<(), WriteError<W::Error, E>>
+where
+    W: Write,
+{
+    while !bytes.is_empty() {
+        match write.write(bytes).await {
+            Ok(0) => return Err(WriteError::WriteZero),
+            Ok(n) => bytes = bytes.get(n..).unwrap_or_default(),
+            Err(err) => return Err(WriteError::IO(err)),
+        }
+    }
+
+    Ok(())
+}
+
+/// The parts of a frame encoded by [`EncodeParts::encode_parts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Parts<'a> {
+    /// The length of the header, encoded at the start of the buffer.
+    pub header: usize,
+    /// The payload, borrowed from the item.
+    pub payload: &'a [u8],
+    /// The length of the trailer, encoded right after the header.
+    pub trailer: usize,
+}
+
+/// An encoder that encodes only the header and trailer of a frame into a buffer and borrows the payload from the item.
+///
+/// Avoids copying large payloads into the write buffer. See [`send_parts`](crate::functions::send_parts).
+pub trait EncodeParts<'a, Item>: Encoder<Item> {
+    /// Encodes the header of the `item` at the start of `dst`, followed by its trailer, and returns the parts of the frame.
+    ///
+    /// The frame is sent as the header, the payload, then the trailer.
+    fn encode_parts(&mut self, item: Item, dst: &mut [u8]) -> Result<Parts<'a>, Self::Error>;
+}
+
+impl<'a, E, Item> EncodeParts<'a, Item> for &mut E
+where
+    E: EncodeParts<'a, Item>,
+{
+    fn encode_parts(&mut self, item: Item, dst: &mut [u8]) -> Result<Parts<'a>, Self::Error> {
+        (*self).encode_parts(item, dst)
+    }
+}
+
+/// An encoder that formats [`core::fmt::Arguments`] directly into a buffer.
+///
+/// Avoids formatting into an intermediate buffer before encoding. See [`send_fmt!`](crate::send_fmt!).
+pub trait EncodeFmt {
+    /// The type of error that this encoder returns.
+    type Error;
+
+    /// Formats the arguments into the provided buffer and encodes them as a frame.
+    fn encode_fmt(
+        &mut self,
+        args: core::fmt::Arguments<'_>,
+        dst: &mut [u8],
+    ) -> Result<usize, Self::Error>;
+}
+
+/// Adapts an [`EncodeFmt`] into an [`Encoder`] of [`core::fmt::Arguments`].
+///
+/// The [`EncodeFmt`] is not an [`Encoder`] itself, so that the items of the codec's [`Encoder`] implementations can still be inferred,
+/// e.g. `&Vec<u8>` coercing to `&[u8]`.
+#[derive(Debug)]
+pub struct FmtEncoder<'a, C> {
+    inner: &'a mut C,
+}
+
+impl<'a, C> FmtEncoder<'a, C> {
+    /// Creates a new [`FmtEncoder`] wrapping the given `codec`.
+    #[inline]
+    pub const fn new(codec: &'a mut C) -> Self {
+        Self { inner: codec }
+    }
+}
+
+impl<C> Encoder<core::fmt::Arguments<'_>> for FmtEncoder<'_, C>
+where
+    C: EncodeFmt,
+{
+    type Error = C::Error;
+
+    fn encode(
+        &mut self,
+        item: core::fmt::Arguments<'_>,
+        dst: &mut [u8],
+    ) -> Result<usize, Self::Error> {
+        self.inner.encode_fmt(item, dst)
+    }
 }
 
 impl<E, Item> Encoder<Item> for &mut E
@@ -18,4 +181,20 @@ where
     fn encode(&mut self, item: Item, dst: &mut [u8]) -> Result<usize, Self::Error> {
         (*self).encode(item, dst)
     }
+
+    fn encoded_len(&self, item: &Item) -> Option<usize> {
+        (**self).encoded_len(item)
+    }
+
+    async fn encode_to_writer<W>(
+        &mut self,
+        item: Item,
+        buf: &mut [u8],
+        write: &mut W,
+    ) -> Result<usize, WriteError<W::Error, Self::Error>>
+    where
+        W: Write,
+    {
+        (*self).encode_to_writer(item, buf, write).await
+    }
 }
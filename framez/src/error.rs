@@ -1,3 +1,9 @@
+use core::convert::Infallible;
+
+use embedded_io_async::ErrorKind;
+
+use crate::encode::EncodeBufferTooSmall;
+
 /// An error that can occur while reading a frame.
 #[non_exhaustive]
 #[derive(Debug)]
@@ -11,6 +17,30 @@ pub enum ReadError<I, D> {
     BufferTooSmall,
     /// There are bytes remaining on the stream after decoding.
     BytesRemainingOnStream,
+    /// The timeout elapsed before a frame was decoded.
+    ///
+    /// Returned by [`next_timeout`](crate::functions::next_timeout). The buffered bytes are kept, so reading can be resumed.
+    Timeout,
+    /// The read state is inconsistent, e.g. its indices point outside of the buffer.
+    InvalidState,
+    /// The decoder returned a frame, but reported consuming zero bytes or more bytes than it was given.
+    ///
+    /// Contains the reported size. The buffered bytes are kept as they are, since the decoder can not be trusted.
+    BadDecoder(usize),
+    /// The decoded frame does not fit into the destination buffer.
+    ///
+    /// Returned by [`next_into`](crate::functions::next_into). Contains the length of the frame, which is dropped.
+    FrameTooLarge(usize),
+    /// The frame did not complete within the deadline measured from its first byte.
+    ///
+    /// Returned by [`next_deadline`](crate::functions::next_deadline). The partial frame is discarded, so reading can continue with the next frame.
+    FrameDeadline,
+    /// No bytes arrived within the idle timeout, e.g. because the peer was unplugged.
+    ///
+    /// Returned by [`next_idle`](crate::functions::next_idle). The buffered bytes are kept, so reading can be resumed.
+    Idle,
+    /// The read bytes exceed the [`Quota`](crate::quota::Quota) of the read state. The bytes are dropped.
+    QuotaExceeded,
 }
 
 impl<I, D> core::fmt::Display for ReadError<I, D>
@@ -23,6 +53,13 @@ where
             Self::BufferTooSmall => write!(f, "Buffer too small"),
             Self::IO(err) => write!(f, "IO error: {err}"),
             Self::BytesRemainingOnStream => write!(f, "Bytes remaining on stream"),
+            Self::Timeout => write!(f, "Timeout"),
+            Self::InvalidState => write!(f, "Invalid state"),
+            Self::BadDecoder(size) => write!(f, "Bad decoder: consumed {size} bytes"),
+            Self::FrameTooLarge(len) => write!(f, "Frame too large: {len} bytes"),
+            Self::FrameDeadline => write!(f, "Frame deadline elapsed"),
+            Self::Idle => write!(f, "Idle timeout elapsed"),
+            Self::QuotaExceeded => write!(f, "Quota exceeded"),
             Self::Decode(err) => write!(f, "Decode error: {err}"),
         }
     }
@@ -35,6 +72,97 @@ where
 {
 }
 
+impl<I, D> embedded_io_async::Error for ReadError<I, D>
+where
+    I: embedded_io_async::Error,
+    D: core::fmt::Debug,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::IO(err) => err.kind(),
+            Self::Decode(_) | Self::BytesRemainingOnStream | Self::BadDecoder(_) => {
+                ErrorKind::InvalidData
+            }
+            Self::BufferTooSmall => ErrorKind::OutOfMemory,
+            Self::Timeout | Self::FrameDeadline | Self::Idle => ErrorKind::TimedOut,
+            Self::InvalidState | Self::QuotaExceeded => ErrorKind::Other,
+            Self::FrameTooLarge(_) => ErrorKind::InvalidInput,
+        }
+    }
+}
+
+impl<I> ReadError<I, Infallible> {
+    /// Converts an error that can not be a decode error, e.g. returned by code that does not depend on the codec.
+    pub(crate) fn cast_decode<D>(self) -> ReadError<I, D> {
+        match self {
+            Self::IO(err) => ReadError::IO(err),
+            Self::Decode(never) => match never {},
+            Self::BufferTooSmall => ReadError::BufferTooSmall,
+            Self::BytesRemainingOnStream => ReadError::BytesRemainingOnStream,
+            Self::Timeout => ReadError::Timeout,
+            Self::InvalidState => ReadError::InvalidState,
+            Self::BadDecoder(size) => ReadError::BadDecoder(size),
+            Self::FrameTooLarge(len) => ReadError::FrameTooLarge(len),
+            Self::FrameDeadline => ReadError::FrameDeadline,
+            Self::Idle => ReadError::Idle,
+            Self::QuotaExceeded => ReadError::QuotaExceeded,
+        }
+    }
+}
+
+impl<D> ReadError<Infallible, D> {
+    /// Converts an error that can not be an IO error, e.g. returned by code that does not depend on the reader.
+    pub(crate) fn cast_io<I>(self) -> ReadError<I, D> {
+        match self {
+            Self::IO(never) => match never {},
+            Self::Decode(err) => ReadError::Decode(err),
+            Self::BufferTooSmall => ReadError::BufferTooSmall,
+            Self::BytesRemainingOnStream => ReadError::BytesRemainingOnStream,
+            Self::Timeout => ReadError::Timeout,
+            Self::InvalidState => ReadError::InvalidState,
+            Self::BadDecoder(size) => ReadError::BadDecoder(size),
+            Self::FrameTooLarge(len) => ReadError::FrameTooLarge(len),
+            Self::FrameDeadline => ReadError::FrameDeadline,
+            Self::Idle => ReadError::Idle,
+            Self::QuotaExceeded => ReadError::QuotaExceeded,
+        }
+    }
+}
+
+/// An error that can occur while reading the payload of a frame.
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReadPayloadError<I> {
+    /// An IO error occurred while reading from the underlying source.
+    IO(I),
+    /// The buffer is too small to read a payload chunk.
+    BufferTooSmall,
+    /// EOF was reached before the whole payload was read.
+    UnexpectedEof,
+    /// The read state is inconsistent, e.g. its indices point outside of the buffer.
+    InvalidState,
+    /// The read bytes exceed the [`Quota`](crate::quota::Quota) of the read state. The bytes are dropped.
+    QuotaExceeded,
+}
+
+impl<I> core::fmt::Display for ReadPayloadError<I>
+where
+    I: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::IO(err) => write!(f, "IO error: {err}"),
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+            Self::UnexpectedEof => write!(f, "Unexpected EOF"),
+            Self::InvalidState => write!(f, "Invalid state"),
+            Self::QuotaExceeded => write!(f, "Quota exceeded"),
+        }
+    }
+}
+
+impl<I> core::error::Error for ReadPayloadError<I> where I: core::fmt::Display + core::fmt::Debug {}
+
 /// An error that can occur while writing a frame.
 #[non_exhaustive]
 #[derive(Debug)]
@@ -44,6 +172,22 @@ pub enum WriteError<I, E> {
     IO(I),
     /// An error occurred while encoding a frame.
     Encode(E),
+    /// The underlying sink returned `Ok(0)` while writing a frame.
+    WriteZero,
+    /// A previous frame with a streamed payload failed or was cancelled after some of its bytes were written.
+    ///
+    /// The peer received a torn frame.
+    IncompleteStreamedFrame,
+    /// The underlying sink is not ready to be written to without blocking.
+    ///
+    /// Returned by [`try_send`](crate::functions::try_send).
+    NotReady,
+    /// The write buffer is too small to fit the frame.
+    ///
+    /// Returned before encoding, if the [`Encoder::encoded_len`](crate::encode::Encoder::encoded_len) of the frame exceeds the capacity of the write buffer.
+    BufferTooSmall(EncodeBufferTooSmall),
+    /// The write state is inconsistent, e.g. an encoder reported writing more bytes than the buffer holds.
+    InvalidState,
 }
 
 impl<I, E> core::fmt::Display for WriteError<I, E>
@@ -55,6 +199,11 @@ where
         match self {
             Self::IO(err) => write!(f, "IO error: {err}"),
             Self::Encode(err) => write!(f, "Encode error: {err}"),
+            Self::WriteZero => write!(f, "Write zero"),
+            Self::IncompleteStreamedFrame => write!(f, "Incomplete streamed frame"),
+            Self::NotReady => write!(f, "Not ready"),
+            Self::BufferTooSmall(err) => write!(f, "Buffer too small: {err}"),
+            Self::InvalidState => write!(f, "Invalid state"),
         }
     }
 }
@@ -65,3 +214,418 @@ where
     E: core::fmt::Display + core::fmt::Debug,
 {
 }
+
+impl<I, E> embedded_io_async::Error for WriteError<I, E>
+where
+    I: embedded_io_async::Error,
+    E: core::fmt::Debug,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::IO(err) => err.kind(),
+            Self::Encode(_) => ErrorKind::InvalidInput,
+            Self::WriteZero => ErrorKind::WriteZero,
+            // Would block, so the frame can be sent again later.
+            Self::NotReady => ErrorKind::Interrupted,
+            Self::BufferTooSmall(_) => ErrorKind::OutOfMemory,
+            Self::IncompleteStreamedFrame | Self::InvalidState => ErrorKind::Other,
+        }
+    }
+}
+
+impl<I> WriteError<I, Infallible> {
+    /// Converts an error that can not be an encode error, e.g. returned by code that does not depend on the codec.
+    pub(crate) fn cast_encode<E>(self) -> WriteError<I, E> {
+        match self {
+            Self::IO(err) => WriteError::IO(err),
+            Self::Encode(never) => match never {},
+            Self::WriteZero => WriteError::WriteZero,
+            Self::IncompleteStreamedFrame => WriteError::IncompleteStreamedFrame,
+            Self::NotReady => WriteError::NotReady,
+            Self::BufferTooSmall(err) => WriteError::BufferTooSmall(err),
+            Self::InvalidState => WriteError::InvalidState,
+        }
+    }
+}
+
+/// An error that can occur while writing a frame with a streamed payload.
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SendStreamedError<I, E, P> {
+    /// An error occurred while writing the frame.
+    Write(WriteError<I, E>),
+    /// An error occurred while reading the payload from the source.
+    Payload(P),
+    /// The write buffer has no room to copy the payload through.
+    BufferTooSmall,
+    /// EOF was reached before the whole payload was read from the source.
+    UnexpectedEof,
+}
+
+impl<I, E, P> From<WriteError<I, E>> for SendStreamedError<I, E, P> {
+    fn from(err: WriteError<I, E>) -> Self {
+        Self::Write(err)
+    }
+}
+
+impl<I, E, P> core::fmt::Display for SendStreamedError<I, E, P>
+where
+    I: core::fmt::Display,
+    E: core::fmt::Display,
+    P: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Write(err) => write!(f, "Write error: {err}"),
+            Self::Payload(err) => write!(f, "Payload error: {err}"),
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+            Self::UnexpectedEof => write!(f, "Unexpected EOF"),
+        }
+    }
+}
+
+impl<I, E, P> core::error::Error for SendStreamedError<I, E, P>
+where
+    I: core::fmt::Display + core::fmt::Debug,
+    E: core::fmt::Display + core::fmt::Debug,
+    P: core::fmt::Display + core::fmt::Debug,
+{
+}
+
+/// An error that can occur while closing a session with [`Framed::close_with`](crate::Framed::close_with).
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CloseError<I, E, D> {
+    /// An error occurred while sending the close frame.
+    Write(WriteError<I, E>),
+    /// An error occurred while draining the incoming frames.
+    ///
+    /// [`ReadError::Timeout`] if the peer did not close the connection in time.
+    Read(ReadError<I, D>),
+}
+
+impl<I, E, D> From<WriteError<I, E>> for CloseError<I, E, D> {
+    fn from(err: WriteError<I, E>) -> Self {
+        Self::Write(err)
+    }
+}
+
+impl<I, E, D> From<ReadError<I, D>> for CloseError<I, E, D> {
+    fn from(err: ReadError<I, D>) -> Self {
+        Self::Read(err)
+    }
+}
+
+impl<I, E, D> core::fmt::Display for CloseError<I, E, D>
+where
+    I: core::fmt::Display,
+    E: core::fmt::Display,
+    D: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Write(err) => write!(f, "Write error: {err}"),
+            Self::Read(err) => write!(f, "Read error: {err}"),
+        }
+    }
+}
+
+impl<I, E, D> core::error::Error for CloseError<I, E, D>
+where
+    I: core::fmt::Display + core::fmt::Debug,
+    E: core::fmt::Display + core::fmt::Debug,
+    D: core::fmt::Display + core::fmt::Debug,
+{
+}
+
+/// An error that can occur while establishing a session with [`Framed::handshake`](crate::Framed::handshake).
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HandshakeError<I, E, D> {
+    /// An error occurred while sending the hello frame.
+    Write(WriteError<I, E>),
+    /// An error occurred while reading the ack frame.
+    ///
+    /// [`ReadError::Timeout`] if no ack was received after the last attempt.
+    Read(ReadError<I, D>),
+    /// The peer closed the connection before sending an ack frame.
+    Closed,
+}
+
+impl<I, E, D> From<WriteError<I, E>> for HandshakeError<I, E, D> {
+    fn from(err: WriteError<I, E>) -> Self {
+        Self::Write(err)
+    }
+}
+
+impl<I, E, D> From<ReadError<I, D>> for HandshakeError<I, E, D> {
+    fn from(err: ReadError<I, D>) -> Self {
+        Self::Read(err)
+    }
+}
+
+impl<I, E, D> core::fmt::Display for HandshakeError<I, E, D>
+where
+    I: core::fmt::Display,
+    E: core::fmt::Display,
+    D: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Write(err) => write!(f, "Write error: {err}"),
+            Self::Read(err) => write!(f, "Read error: {err}"),
+            Self::Closed => write!(f, "Connection closed"),
+        }
+    }
+}
+
+impl<I, E, D> core::error::Error for HandshakeError<I, E, D>
+where
+    I: core::fmt::Display + core::fmt::Debug,
+    E: core::fmt::Display + core::fmt::Debug,
+    D: core::fmt::Display + core::fmt::Debug,
+{
+}
+
+/// An error that can occur during a request-response exchange with [`HalfDuplex::transfer`](crate::half_duplex::HalfDuplex::transfer).
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TransferError<I, E, D> {
+    /// An error occurred while sending the request.
+    Write(WriteError<I, E>),
+    /// An error occurred while reading the response.
+    ///
+    /// [`ReadError::Timeout`] if no response was received in time.
+    Read(ReadError<I, D>),
+    /// The peer closed the connection before sending a response.
+    Closed,
+}
+
+impl<I, E, D> From<WriteError<I, E>> for TransferError<I, E, D> {
+    fn from(err: WriteError<I, E>) -> Self {
+        Self::Write(err)
+    }
+}
+
+impl<I, E, D> From<ReadError<I, D>> for TransferError<I, E, D> {
+    fn from(err: ReadError<I, D>) -> Self {
+        Self::Read(err)
+    }
+}
+
+impl<I, E, D> core::fmt::Display for TransferError<I, E, D>
+where
+    I: core::fmt::Display,
+    E: core::fmt::Display,
+    D: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Write(err) => write!(f, "Write error: {err}"),
+            Self::Read(err) => write!(f, "Read error: {err}"),
+            Self::Closed => write!(f, "Connection closed"),
+        }
+    }
+}
+
+impl<I, E, D> core::error::Error for TransferError<I, E, D>
+where
+    I: core::fmt::Display + core::fmt::Debug,
+    E: core::fmt::Display + core::fmt::Debug,
+    D: core::fmt::Display + core::fmt::Debug,
+{
+}
+
+/// An error that can occur while routing frames with [`route`](crate::functions::route).
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RouteError<I, D, H> {
+    /// An error occurred while reading a frame.
+    Read(ReadError<I, D>),
+    /// A handler failed to handle a frame.
+    Handle(H),
+}
+
+impl<I, D, H> From<ReadError<I, D>> for RouteError<I, D, H> {
+    fn from(err: ReadError<I, D>) -> Self {
+        Self::Read(err)
+    }
+}
+
+impl<I, D, H> core::fmt::Display for RouteError<I, D, H>
+where
+    I: core::fmt::Display,
+    D: core::fmt::Display,
+    H: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Read(err) => write!(f, "Read error: {err}"),
+            Self::Handle(err) => write!(f, "Handle error: {err}"),
+        }
+    }
+}
+
+impl<I, D, H> core::error::Error for RouteError<I, D, H>
+where
+    I: core::fmt::Display + core::fmt::Debug,
+    D: core::fmt::Display + core::fmt::Debug,
+    H: core::fmt::Display + core::fmt::Debug,
+{
+}
+
+/// An error that can occur while forwarding frames with [`forward`](crate::functions::forward).
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ForwardError<RI, D, WI, E> {
+    /// An error occurred while reading a frame from the source.
+    Read(ReadError<RI, D>),
+    /// An error occurred while sending a frame to the destination.
+    Write(WriteError<WI, E>),
+}
+
+impl<RI, D, WI, E> From<ReadError<RI, D>> for ForwardError<RI, D, WI, E> {
+    fn from(err: ReadError<RI, D>) -> Self {
+        Self::Read(err)
+    }
+}
+
+impl<RI, D, WI, E> From<WriteError<WI, E>> for ForwardError<RI, D, WI, E> {
+    fn from(err: WriteError<WI, E>) -> Self {
+        Self::Write(err)
+    }
+}
+
+impl<RI, D, WI, E> core::fmt::Display for ForwardError<RI, D, WI, E>
+where
+    RI: core::fmt::Display,
+    D: core::fmt::Display,
+    WI: core::fmt::Display,
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Read(err) => write!(f, "Read error: {err}"),
+            Self::Write(err) => write!(f, "Write error: {err}"),
+        }
+    }
+}
+
+impl<RI, D, WI, E> core::error::Error for ForwardError<RI, D, WI, E>
+where
+    RI: core::fmt::Display + core::fmt::Debug,
+    D: core::fmt::Display + core::fmt::Debug,
+    WI: core::fmt::Display + core::fmt::Debug,
+    E: core::fmt::Display + core::fmt::Debug,
+{
+}
+
+/// An error that can occur while bridging two transports with [`bridge`](crate::forward::bridge).
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BridgeError<A, B> {
+    /// Forwarding the frames of `a_read` to `b_write` failed.
+    AToB(A),
+    /// Forwarding the frames of `b_read` to `a_write` failed.
+    BToA(B),
+}
+
+impl<A, B> core::fmt::Display for BridgeError<A, B>
+where
+    A: core::fmt::Display,
+    B: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::AToB(err) => write!(f, "Forwarding from a to b failed: {err}"),
+            Self::BToA(err) => write!(f, "Forwarding from b to a failed: {err}"),
+        }
+    }
+}
+
+impl<A, B> core::error::Error for BridgeError<A, B>
+where
+    A: core::fmt::Display + core::fmt::Debug,
+    B: core::fmt::Display + core::fmt::Debug,
+{
+}
+
+/// An error that can occur while detecting the protocol with [`detect`](crate::detect::detect).
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DetectError<I> {
+    /// An error occurred while reading.
+    ///
+    /// [`ReadError::BufferTooSmall`] if the buffer is full before a candidate matched.
+    Read(ReadError<I, Infallible>),
+    /// None of the candidates matched the buffered bytes.
+    NoMatch,
+    /// EOF was reached before a candidate matched.
+    UnexpectedEof,
+}
+
+impl<I> From<ReadError<I, Infallible>> for DetectError<I> {
+    fn from(err: ReadError<I, Infallible>) -> Self {
+        Self::Read(err)
+    }
+}
+
+impl<I> core::fmt::Display for DetectError<I>
+where
+    I: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Read(err) => write!(f, "Read error: {err}"),
+            Self::NoMatch => write!(f, "No protocol matched"),
+            Self::UnexpectedEof => write!(f, "Unexpected EOF"),
+        }
+    }
+}
+
+impl<I> core::error::Error for DetectError<I> where I: core::fmt::Display + core::fmt::Debug {}
+
+#[cfg(test)]
+mod test {
+    use core::convert::Infallible;
+
+    use embedded_io_async::Error;
+
+    use super::*;
+
+    #[test]
+    fn read_error_kind() {
+        type E = ReadError<ErrorKind, Infallible>;
+
+        assert_eq!(E::IO(ErrorKind::BrokenPipe).kind(), ErrorKind::BrokenPipe);
+        assert_eq!(E::BufferTooSmall.kind(), ErrorKind::OutOfMemory);
+        assert_eq!(E::BytesRemainingOnStream.kind(), ErrorKind::InvalidData);
+        assert_eq!(E::Timeout.kind(), ErrorKind::TimedOut);
+        assert_eq!(E::FrameTooLarge(16).kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn write_error_kind() {
+        type E = WriteError<ErrorKind, Infallible>;
+
+        assert_eq!(E::IO(ErrorKind::TimedOut).kind(), ErrorKind::TimedOut);
+        assert_eq!(E::WriteZero.kind(), ErrorKind::WriteZero);
+        assert_eq!(E::NotReady.kind(), ErrorKind::Interrupted);
+        assert_eq!(
+            E::BufferTooSmall(EncodeBufferTooSmall {
+                required: 8,
+                available: 4
+            })
+            .kind(),
+            ErrorKind::OutOfMemory
+        );
+    }
+}
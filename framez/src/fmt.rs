@@ -0,0 +1,173 @@
+//! Formatters for logging buffers consistently with the crate's own logs.
+//!
+//! Custom codecs can wrap their buffers in [`Bytes`] and log them with `log`, `tracing` or `defmt`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use framez::fmt::Bytes;
+//!
+//! let buffer = b"Hi\n";
+//!
+//! # #[cfg(all(feature = "char-fmt", not(feature = "pretty-hex-fmt"), not(feature = "redact")))]
+//! assert_eq!(format!("{:?}", Bytes(buffer)), "['H', 'i', '\\n']");
+//! ```
+
+/// Formats a byte slice for debugging.
+///
+/// The [`Debug`](core::fmt::Debug) output depends on the enabled features:
+///
+/// - `char-fmt`: `['0', '0', '0', 'o']`. Enabled by default.
+/// - `pretty-hex-fmt`: `[0x00, 0x00, 0x00, 0x6F]`.
+/// - Neither or both: `[00, 00, 00, 6F]`.
+///
+/// With `defmt`, the bytes are logged as a compact hex dump of at most [`DEFMT_MAX_BYTES`] bytes.
+///
+/// With `redact`, only the length is logged, e.g. `<4 bytes>`, never the bytes themselves.
+#[derive(Clone, Copy)]
+pub struct Bytes<'a>(pub &'a [u8]);
+
+/// The maximum number of bytes logged by [`Bytes`] with `defmt`.
+///
+/// Set at build time using the `FRAMEZ_DEFMT_MAX_BYTES` environment variable. Defaults to `32`.
+#[cfg(feature = "defmt")]
+pub const DEFMT_MAX_BYTES: usize = parse_usize(option_env!("FRAMEZ_DEFMT_MAX_BYTES"), 32);
+
+/// Parses a decimal number at compile time, or returns `default` if it is missing or invalid.
+#[cfg(feature = "defmt")]
+const fn parse_usize(value: Option<&str>, default: usize) -> usize {
+    let Some(value) = value else {
+        return default;
+    };
+
+    let mut digits = value.as_bytes();
+    let mut number: usize = 0;
+
+    if digits.is_empty() {
+        return default;
+    }
+
+    while let [digit, rest @ ..] = digits {
+        if !digit.is_ascii_digit() {
+            return default;
+        }
+
+        number = match number.checked_mul(10) {
+            Some(number) => match number.checked_add(digit.wrapping_sub(b'0') as usize) {
+                Some(number) => number,
+                None => return default,
+            },
+            None => return default,
+        };
+
+        digits = rest;
+    }
+
+    number
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Bytes<'_> {
+    /// Produces: [00, 00, 00, 6f] or [00, 00, 00, 6f].. (64 bytes)
+    fn format(&self, f: defmt::Formatter<'_>) {
+        if cfg!(feature = "redact") {
+            return defmt::write!(f, "<{=usize} bytes>", self.0.len());
+        }
+
+        match self.0.get(..DEFMT_MAX_BYTES) {
+            Some(head) if self.0.len() > DEFMT_MAX_BYTES => {
+                defmt::write!(f, "{=[u8]:02x}.. ({=usize} bytes)", head, self.0.len())
+            }
+            _ => defmt::write!(f, "{=[u8]:02x}", self.0),
+        }
+    }
+}
+
+impl Bytes<'_> {
+    /// Produces: [0x00, 0x00, 0x00, 0x6F]
+    #[cfg(all(feature = "pretty-hex-fmt", not(feature = "char-fmt")))]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Some((last, rest)) = self.0.split_last() else {
+            return write!(f, "[]");
+        };
+
+        write!(f, "[")?;
+        for byte in rest {
+            write!(f, "0x{:02X?}, ", byte)?;
+        }
+        write!(f, "0x{:02X}]", last)?;
+
+        Ok(())
+    }
+
+    /// Produces: ['0', '0', '0', 'o']
+    #[cfg(all(feature = "char-fmt", not(feature = "pretty-hex-fmt")))]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Some((last, rest)) = self.0.split_last() else {
+            return write!(f, "[]");
+        };
+
+        write!(f, "[")?;
+        for byte in rest {
+            write!(f, "{:?}, ", *byte as char)?;
+        }
+        write!(f, "{:?}]", *last as char)?;
+
+        Ok(())
+    }
+
+    /// Produces: [00, 00, 00, 6F]
+    #[cfg(any(
+        all(not(feature = "pretty-hex-fmt"), not(feature = "char-fmt")),
+        all(feature = "pretty-hex-fmt", feature = "char-fmt")
+    ))]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:02X?}", self.0)
+    }
+}
+
+impl core::fmt::Debug for Bytes<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if cfg!(feature = "redact") {
+            return write!(f, "<{} bytes>", self.0.len());
+        }
+
+        self.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::format;
+
+    use super::*;
+
+    #[test]
+    #[cfg(all(
+        feature = "char-fmt",
+        not(feature = "pretty-hex-fmt"),
+        not(feature = "redact")
+    ))]
+    fn char_fmt() {
+        assert_eq!(format!("{:?}", Bytes(b"")), "[]");
+        assert_eq!(format!("{:?}", Bytes(b"\0o")), "['\\0', 'o']");
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "pretty-hex-fmt",
+        not(feature = "char-fmt"),
+        not(feature = "redact")
+    ))]
+    fn pretty_hex_fmt() {
+        assert_eq!(format!("{:?}", Bytes(b"")), "[]");
+        assert_eq!(format!("{:?}", Bytes(b"\0o")), "[0x00, 0x6F]");
+    }
+
+    #[test]
+    #[cfg(feature = "redact")]
+    fn redact() {
+        assert_eq!(format!("{:?}", Bytes(b"")), "<0 bytes>");
+        assert_eq!(format!("{:?}", Bytes(b"secret")), "<6 bytes>");
+    }
+}
@@ -0,0 +1,216 @@
+//! Forwarding of frames from one framer to another.
+//!
+//! [`forward`] pumps the frames of a [`FramedRead`] into a [`FramedWrite`] until eof, e.g. from a serial port to a TCP socket.
+//! [`bridge`] forwards the frames in both directions between two transports at once.
+//!
+//! The frames are decoded and encoded again, so both ends may use different codecs.
+//! See [`functions::forward`](crate::functions::forward) to forward frames using the decoupled states.
+
+use core::pin::pin;
+
+use embedded_io_async::{Read, Write};
+
+use crate::{
+    BridgeError, ForwardError, FramedRead, FramedWrite,
+    buffer::Buffer,
+    decode::Decoder,
+    encode::Encoder,
+    functions,
+    future::{Either, select},
+};
+
+/// Reads the frames of `src` until eof and sends each one to `dst`.
+///
+/// Returns the number of forwarded frames. See [`functions::forward`](crate::functions::forward).
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{
+///     FramedRead, FramedWrite,
+///     codec::{bytes::Bytes, lines::Lines},
+///     forward::forward,
+///     mock::Noop,
+/// };
+///
+/// # async fn run() {
+/// let read_buf = &mut [0_u8; 32];
+/// let mut src = FramedRead::new(Lines::new(), b"Hello\nworld!\n".as_slice(), read_buf);
+///
+/// let write_buf = &mut [0_u8; 32];
+/// let mut dst = FramedWrite::new(Bytes::new(), Noop, write_buf);
+///
+/// assert_eq!(forward(&mut src, &mut dst).await.unwrap(), 2);
+/// # }
+/// ```
+pub async fn forward<D, R, RB, C, W, WB, E>(
+    src: &mut FramedRead<'_, D, R, RB>,
+    dst: &mut FramedWrite<'_, C, W, WB>,
+) -> Result<usize, ForwardError<R::Error, D::Error, W::Error, E>>
+where
+    D: Decoder,
+    R: Read,
+    RB: Buffer,
+    C: for<'a> Encoder<D::Item<'a>, Error = E>,
+    W: Write,
+    WB: Buffer,
+{
+    functions::forward(
+        &mut src.core.state.read,
+        &mut src.core.codec,
+        &mut src.core.inner,
+        &mut dst.core.state.write,
+        &mut dst.core.codec,
+        &mut dst.core.inner,
+    )
+    .await
+}
+
+/// Forwards the frames of `a_read` to `b_write` and the frames of `b_read` to `a_write` at once, until either `a_read` or `b_read` reaches eof.
+///
+/// `a_read` and `a_write` are the read and write halves of one transport, `b_read` and `b_write` the halves of the other one.
+/// The arguments are ordered by direction, like two calls to [`forward`].
+/// Once one direction ends, the other one is dropped. Bytes of a frame that were only partially sent are kept
+/// in the write state, see the cancel safety of [`send`](crate::functions::send).
+///
+/// # Return value
+///
+/// - `Ok(())` if `a_read` or `b_read` reached eof.
+/// - `Err(BridgeError::AToB(error))` if forwarding the frames of `a_read` to `b_write` failed.
+/// - `Err(BridgeError::BToA(error))` if forwarding the frames of `b_read` to `a_write` failed.
+#[allow(clippy::type_complexity)]
+pub async fn bridge<DA, RA, RBA, CB, WB, WBB, EB, DB, RB, RBB, CA, WA, WBA, EA>(
+    a_read: &mut FramedRead<'_, DA, RA, RBA>,
+    b_write: &mut FramedWrite<'_, CB, WB, WBB>,
+    b_read: &mut FramedRead<'_, DB, RB, RBB>,
+    a_write: &mut FramedWrite<'_, CA, WA, WBA>,
+) -> Result<
+    (),
+    BridgeError<
+        ForwardError<RA::Error, DA::Error, WB::Error, EB>,
+        ForwardError<RB::Error, DB::Error, WA::Error, EA>,
+    >,
+>
+where
+    DA: Decoder,
+    RA: Read,
+    RBA: Buffer,
+    CB: for<'a> Encoder<DA::Item<'a>, Error = EB>,
+    WB: Write,
+    WBB: Buffer,
+    DB: Decoder,
+    RB: Read,
+    RBB: Buffer,
+    CA: for<'a> Encoder<DB::Item<'a>, Error = EA>,
+    WA: Write,
+    WBA: Buffer,
+{
+    match select(
+        pin!(forward(a_read, b_write)),
+        pin!(forward(b_read, a_write)),
+    )
+    .await
+    {
+        Either::Left(result) => result.map(|_| ()).map_err(BridgeError::AToB),
+        Either::Right(result) => result.map(|_| ()).map_err(BridgeError::BToA),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use embedded_io_async::Write as _;
+
+    use crate::{
+        ReadError,
+        codec::{bytes::Bytes, delimiter::Delimiter, lines::Lines},
+        mock::{Duplex, Noop, PendingReader},
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn forward_reencodes_frames() {
+        init_tracing();
+
+        let read_buf = &mut [0_u8; 16];
+        let mut src = FramedRead::new(Lines::new(), b"Hello\r\nworld!\n".as_slice(), read_buf);
+
+        let write_buf = &mut [0_u8; 16];
+        let mut dst = FramedWrite::new(Delimiter::new(b"#"), Vec::new(), write_buf);
+
+        let count = forward(&mut src, &mut dst).await.expect("Must forward");
+
+        assert_eq!(count, 2);
+        assert_eq!(dst.inner(), b"Hello#world!#");
+    }
+
+    #[tokio::test]
+    async fn forward_flushes_coalesced_frames_at_eof() {
+        init_tracing();
+
+        let read_buf = &mut [0_u8; 16];
+        let mut src = FramedRead::new(Lines::new(), b"Hello\nworld!\n".as_slice(), read_buf);
+
+        let write_buf = &mut [0_u8; 32];
+        let mut dst = FramedWrite::new(Lines::new(), Vec::new(), write_buf);
+
+        dst.set_flush_threshold(32);
+
+        forward(&mut src, &mut dst).await.expect("Must forward");
+
+        assert_eq!(dst.inner(), b"Hello\r\nworld!\r\n");
+    }
+
+    #[tokio::test]
+    async fn forward_read_error() {
+        init_tracing();
+
+        let read_buf = &mut [0_u8; 4];
+        let mut src = FramedRead::new(Lines::new(), b"Hello\n".as_slice(), read_buf);
+
+        let write_buf = &mut [0_u8; 16];
+        let mut dst = FramedWrite::new(Bytes::new(), Noop, write_buf);
+
+        assert!(matches!(
+            forward(&mut src, &mut dst).await,
+            Err(ForwardError::Read(ReadError::BufferTooSmall))
+        ));
+    }
+
+    #[tokio::test]
+    async fn bridge_forwards_both_directions() {
+        init_tracing();
+
+        // `b` never reaches eof, so the bridge ends once the slower `a` does.
+        let duplex = Duplex::<16>::new();
+        let (mut peer, b_end) = duplex.split();
+
+        peer.write_all(b"Hey\n").await.expect("Must write");
+
+        let a_read_buf = &mut [0_u8; 16];
+        let mut a_read = FramedRead::new(
+            Lines::new(),
+            PendingReader::new(b"Hello\n".as_slice(), 2),
+            a_read_buf,
+        );
+
+        let a_write_buf = &mut [0_u8; 16];
+        let mut a_write = FramedWrite::new(Lines::new(), Vec::new(), a_write_buf);
+
+        let b_read_buf = &mut [0_u8; 16];
+        let mut b_read = FramedRead::new(Lines::new(), b_end, b_read_buf);
+
+        let b_write_buf = &mut [0_u8; 16];
+        let mut b_write = FramedWrite::new(Lines::new(), Vec::new(), b_write_buf);
+
+        bridge(&mut a_read, &mut b_write, &mut b_read, &mut a_write)
+            .await
+            .expect("Must bridge");
+
+        assert_eq!(b_write.inner(), b"Hello\r\n");
+        assert_eq!(a_write.inner(), b"Hey\r\n");
+    }
+}
@@ -0,0 +1,363 @@
+//! Frames described by their types.
+//!
+//! [`FrameEncode`] and [`FrameDecode`] describe how a type is laid out on the wire, so the [`FrameCodec`] can encode and decode it.
+//! Payloads that depend on a type tag of their frame, e.g. an enum with one variant per message, implement [`Tagged`] and [`TaggedDecode`] instead.
+//!
+//! With the `derive` feature, the traits are derived for packet structs and tagged payload enums using [`derive@FrameEncode`] and [`derive@FrameDecode`].
+//!
+//! # Borrowed frames
+//!
+//! The [`FrameCodec`] decodes owned items only. Frames that borrow from the read buffer are decoded by a codec of their own:
+//!
+//! ```rust
+//! use framez::{
+//!     decode::Decoder,
+//!     frame::{FrameDecode, FrameError},
+//! };
+//!
+//! # struct Packet<'a>(&'a [u8]);
+//! # impl<'a> FrameDecode<'a> for Packet<'a> {
+//! #     fn decode_frame(src: &'a [u8]) -> Result<Option<(Self, usize)>, FrameError> {
+//! #         Ok(Some((Packet(src), src.len())))
+//! #     }
+//! # }
+//! struct PacketCodec;
+//!
+//! impl Decoder for PacketCodec {
+//!     type Item<'buf> = Packet<'buf>;
+//!     type Error = FrameError;
+//!
+//!     fn decode<'buf>(
+//!         &mut self,
+//!         src: &'buf mut [u8],
+//!     ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+//!         Packet::decode_frame(src)
+//!     }
+//!
+//!     fn decode_header(&mut self, src: &[u8]) -> Option<usize> {
+//!         Packet::frame_len(src)
+//!     }
+//! }
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::{decode::Decoder, encode::Encoder};
+
+#[cfg(feature = "derive")]
+pub use framez_derive::{FrameDecode, FrameEncode};
+
+/// An error that can occur while encoding or decoding a frame with [`FrameEncode`] or [`FrameDecode`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameError {
+    /// The destination buffer is too small to encode the frame.
+    BufferTooSmall,
+    /// The checksum of the frame does not match its bytes.
+    Checksum,
+    /// The type tag of the frame does not match any payload.
+    UnknownTag(u64),
+    /// The length of the frame is shorter than its header or does not match its payload.
+    InvalidLength,
+    /// A value does not fit into its field, e.g. the length of the frame into a `u8` length field.
+    FieldOverflow,
+}
+
+impl core::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+            Self::Checksum => write!(f, "Checksum mismatch"),
+            Self::UnknownTag(tag) => write!(f, "Unknown tag: {tag}"),
+            Self::InvalidLength => write!(f, "Invalid length"),
+            Self::FieldOverflow => write!(f, "Field overflow"),
+        }
+    }
+}
+
+impl core::error::Error for FrameError {}
+
+/// A type that is encoded as a frame or as a part of one.
+pub trait FrameEncode {
+    /// Encodes `self` into the start of `dst` and returns the number of written bytes.
+    fn encode_frame(&self, dst: &mut [u8]) -> Result<usize, FrameError>;
+}
+
+/// A type that is decoded from a frame or from a part of one.
+pub trait FrameDecode<'buf>: Sized {
+    /// Decodes `Self` from the start of `src`.
+    ///
+    /// # Return value
+    ///
+    /// - `Ok(Some((item, size)))` if `item` was decoded from the first `size` bytes of `src`.
+    /// - `Ok(None)` if more bytes are needed.
+    /// - `Err(error)` if the bytes do not form a valid frame.
+    fn decode_frame(src: &'buf [u8]) -> Result<Option<(Self, usize)>, FrameError>;
+
+    /// Returns the total length of the frame starting at `src`, if it can be determined from its header.
+    ///
+    /// See [`Decoder::decode_header`].
+    fn frame_len(_src: &[u8]) -> Option<usize> {
+        None
+    }
+}
+
+/// A payload that is identified by the type tag of its frame.
+pub trait Tagged {
+    /// Returns the type tag of the payload.
+    fn tag(&self) -> u64;
+}
+
+/// A payload that is decoded according to the type tag of its frame.
+pub trait TaggedDecode<'buf>: Sized {
+    /// Decodes the payload identified by `tag` from the start of `src`. See [`FrameDecode::decode_frame`].
+    fn decode_tagged(tag: u64, src: &'buf [u8]) -> Result<Option<(Self, usize)>, FrameError>;
+}
+
+/// An integer field of a frame, e.g. its length, type tag or checksum.
+pub trait IntField: Sized {
+    /// Returns the value of the field.
+    fn to_u64(&self) -> u64;
+
+    /// Creates a field holding `value`, or returns `None` if it does not fit.
+    fn from_u64(value: u64) -> Option<Self>;
+}
+
+macro_rules! int_field {
+    ($($ty:ty),*) => {
+        $(
+            impl IntField for $ty {
+                #[inline]
+                fn to_u64(&self) -> u64 {
+                    u64::from(*self)
+                }
+
+                #[inline]
+                fn from_u64(value: u64) -> Option<Self> {
+                    <$ty>::try_from(value).ok()
+                }
+            }
+        )*
+    };
+}
+
+int_field!(u8, u16, u32, u64);
+
+#[cfg(feature = "zerocopy")]
+macro_rules! byteorder_int_field {
+    ($($ty:ident => $int:ty),*) => {
+        $(
+            impl<O> IntField for zerocopy::byteorder::$ty<O>
+            where
+                O: zerocopy::byteorder::ByteOrder,
+            {
+                #[inline]
+                fn to_u64(&self) -> u64 {
+                    u64::from(self.get())
+                }
+
+                #[inline]
+                fn from_u64(value: u64) -> Option<Self> {
+                    <$int>::try_from(value).ok().map(Self::new)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "zerocopy")]
+byteorder_int_field!(U16 => u16, U32 => u32, U64 => u64);
+
+impl<T> FrameEncode for &T
+where
+    T: FrameEncode + ?Sized,
+{
+    #[inline]
+    fn encode_frame(&self, dst: &mut [u8]) -> Result<usize, FrameError> {
+        T::encode_frame(self, dst)
+    }
+}
+
+/// Raw bytes, e.g. an opaque payload. Encoded as they are.
+impl FrameEncode for [u8] {
+    fn encode_frame(&self, dst: &mut [u8]) -> Result<usize, FrameError> {
+        dst.get_mut(..self.len())
+            .ok_or(FrameError::BufferTooSmall)?
+            .copy_from_slice(self);
+
+        Ok(self.len())
+    }
+}
+
+/// Raw bytes, e.g. an opaque payload. Decoded from all of `src`, so the frame must have a length field.
+impl<'buf> FrameDecode<'buf> for &'buf [u8] {
+    #[inline]
+    fn decode_frame(src: &'buf [u8]) -> Result<Option<(Self, usize)>, FrameError> {
+        Ok(Some((src, src.len())))
+    }
+}
+
+/// A codec that encodes [`FrameEncode`] items and decodes owned [`FrameDecode`] items.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{
+///     FramedRead,
+///     frame::{FrameCodec, FrameDecode, FrameError},
+///     next,
+/// };
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Byte(u8);
+///
+/// impl FrameDecode<'_> for Byte {
+///     fn decode_frame(src: &[u8]) -> Result<Option<(Self, usize)>, FrameError> {
+///         Ok(src.first().map(|byte| (Byte(*byte), 1)))
+///     }
+/// }
+///
+/// # async fn run() {
+/// let buffer = &mut [0_u8; 8];
+/// let mut framer = FramedRead::new(FrameCodec::<Byte>::new(), b"\x01\x02".as_slice(), buffer);
+///
+/// assert_eq!(next!(framer).unwrap().unwrap(), Byte(1));
+/// # }
+/// ```
+pub struct FrameCodec<T> {
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T> FrameCodec<T> {
+    /// Creates a new [`FrameCodec`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self { _item: PhantomData }
+    }
+}
+
+impl<T> Default for FrameCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for FrameCodec<T> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<T> core::fmt::Debug for FrameCodec<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FrameCodec").finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<T> defmt::Format for FrameCodec<T> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "FrameCodec")
+    }
+}
+
+impl<T> Decoder for FrameCodec<T>
+where
+    T: for<'a> FrameDecode<'a>,
+{
+    type Item<'buf> = T;
+    type Error = FrameError;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        T::decode_frame(src)
+    }
+
+    fn decode_header(&mut self, src: &[u8]) -> Option<usize> {
+        T::frame_len(src)
+    }
+}
+
+impl<T, I> Encoder<I> for FrameCodec<T>
+where
+    I: FrameEncode,
+{
+    type Error = FrameError;
+
+    fn encode(&mut self, item: I, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        item.encode_frame(dst)
+    }
+}
+
+/// Helpers used by the code generated by [`derive@FrameEncode`] and [`derive@FrameDecode`].
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+    use crate::checksum::Checksum;
+
+    use super::{FrameError, IntField};
+
+    pub use zerocopy;
+
+    /// Reads the field starting at `at`, or returns `None` if `src` is too short.
+    #[inline]
+    pub fn read<T>(src: &[u8], at: usize) -> Option<T>
+    where
+        T: FromBytes,
+    {
+        T::read_from_prefix(src.get(at..)?)
+            .ok()
+            .map(|(value, _)| value)
+    }
+
+    /// Writes `value` to `dst` starting at `at`.
+    #[inline]
+    pub fn write<T>(dst: &mut [u8], at: usize, value: &T) -> Result<usize, FrameError>
+    where
+        T: IntoBytes + Immutable,
+    {
+        let bytes = value.as_bytes();
+
+        dst.get_mut(at..at.saturating_add(bytes.len()))
+            .ok_or(FrameError::BufferTooSmall)?
+            .copy_from_slice(bytes);
+
+        Ok(bytes.len())
+    }
+
+    /// Writes the integer field `T` holding `value` to `dst` starting at `at`.
+    #[inline]
+    pub fn write_int<T>(dst: &mut [u8], at: usize, value: u64) -> Result<usize, FrameError>
+    where
+        T: IntField + IntoBytes + Immutable,
+    {
+        write(
+            dst,
+            at,
+            &T::from_u64(value).ok_or(FrameError::FieldOverflow)?,
+        )
+    }
+
+    /// Returns the checksum of `frame`, computed as if the `len` bytes of the checksum field starting at `at` were zeroed.
+    pub fn checksum<C>(mut checksum: C, frame: &[u8], at: usize, len: usize) -> u64
+    where
+        C: Checksum,
+        C::Output: Into<u64>,
+    {
+        checksum.reset();
+        checksum.update(frame.get(..at).unwrap_or_default());
+
+        for _ in 0..len {
+            checksum.update(&[0]);
+        }
+
+        checksum.update(frame.get(at.saturating_add(len)..).unwrap_or_default());
+
+        checksum.finalize().into()
+    }
+}
@@ -1,23 +1,57 @@
-use embedded_io_async::{Read, Write};
+use core::convert::Infallible;
+
+use embedded_io_async::{Read, ReadReady, Write, WriteReady};
+#[cfg(feature = "futures")]
 use futures::{Sink, Stream};
 
 use crate::{
-    FramedCore, ReadError, WriteError,
-    decode::Decoder,
-    encode::Encoder,
-    state::{ReadState, ReadWriteState, WriteState},
+    CloseError, FramedCore, Frames, HandshakeError, ReadError, ReadPayloadError, RouteError,
+    SendStreamedError, WriteError,
+    buffer::Buffer,
+    clock::Clock,
+    decode::{Decoder, RingDecoder},
+    encode::{EncodeParts, Encoder},
+    functions,
+    ops::{self, MaybeNext},
+    quota::Quota,
+    router::{Handler, Router, Routes},
+    state::{
+        CommitError, FrameMeta, ReadSnapshot, ReadState, ReadWriteState, RingReadState,
+        RollbackError, ShiftPolicy, WriteState,
+    },
+};
+
+#[cfg(any(test, feature = "mock"))]
+use crate::mock::{Duplex, DuplexEnd};
+
+#[cfg(feature = "embedded-hal-async")]
+use {
+    crate::{pacing::Pacer, timing::Timing},
+    embedded_hal_async::delay::DelayNs,
 };
 
+#[cfg(feature = "embedded-storage-async")]
+use {crate::recorder::Recorder, embedded_storage_async::nor_flash::NorFlash};
+
+#[cfg(feature = "tokio")]
+use crate::adapters::FromTokio;
+
+#[cfg(feature = "futures-io")]
+use crate::adapters::FromFutures;
+
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+
 /// A framer that reads bytes from a [`Read`] source and decodes them into frames using a [`Decoder`].
 /// And a sink that writes encoded frames into an underlying [`Write`] sink using an [`Encoder`].
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct Framed<'buf, C, RW> {
+pub struct Framed<'buf, C, RW, RB = &'buf mut [u8], WB = &'buf mut [u8]> {
     /// The core framed implementation.
     ///
     /// This field is made public to be used in the [`functions`](crate::functions) module for library authors.
     /// If you are using this crate as a user, you should probably not care about this field.
-    pub core: FramedCore<'buf, C, RW>,
+    pub core: FramedCore<'buf, C, RW, RB, WB>,
 }
 
 impl<'buf, C, RW> Framed<'buf, C, RW> {
@@ -29,15 +63,238 @@ impl<'buf, C, RW> Framed<'buf, C, RW> {
         read_buffer: &'buf mut [u8],
         write_buffer: &'buf mut [u8],
     ) -> Self {
+        Self::with_buffers(codec, inner, read_buffer, write_buffer)
+    }
+
+    /// Creates a new [`Framed`] with the given `name`, `codec` and `reader/writer`.
+    ///
+    /// See [`Framed::with_name`].
+    #[inline]
+    pub const fn named(
+        name: &'static str,
+        codec: C,
+        inner: RW,
+        read_buffer: &'buf mut [u8],
+        write_buffer: &'buf mut [u8],
+    ) -> Self {
+        Self::new(codec, inner, read_buffer, write_buffer).with_name(name)
+    }
+}
+
+#[cfg(any(test, feature = "mock"))]
+impl<'buf, C, const N: usize> Framed<'buf, C, DuplexEnd<'buf, N>> {
+    /// Creates two [`Framed`]s connected by the in-memory `duplex`, so codec round trips can be tested without real IO, e.g. on target or in miri.
+    ///
+    /// The first framer uses `codec_a` and the `a` buffers, the second one uses `codec_b` and the `b` buffers.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use framez::{Framed, codec::lines::StrLines, mock::Duplex, next};
+    ///
+    /// # async fn run() {
+    /// let duplex = Duplex::<64>::new();
+    /// let (read_a, write_a) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+    /// let (read_b, write_b) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+    ///
+    /// let (mut a, mut b) = Framed::pair(
+    ///     &duplex,
+    ///     StrLines::new(),
+    ///     StrLines::new(),
+    ///     read_a,
+    ///     write_a,
+    ///     read_b,
+    ///     write_b,
+    /// );
+    ///
+    /// a.send("Hello").await.unwrap();
+    ///
+    /// assert_eq!(next!(b).unwrap().unwrap(), "Hello");
+    /// # }
+    /// ```
+    #[inline]
+    pub fn pair<D>(
+        duplex: &'buf Duplex<N>,
+        codec_a: C,
+        codec_b: D,
+        read_buffer_a: &'buf mut [u8],
+        write_buffer_a: &'buf mut [u8],
+        read_buffer_b: &'buf mut [u8],
+        write_buffer_b: &'buf mut [u8],
+    ) -> (Self, Framed<'buf, D, DuplexEnd<'buf, N>>) {
+        let (a, b) = duplex.split();
+
+        (
+            Framed::new(codec_a, a, read_buffer_a, write_buffer_a),
+            Framed::new(codec_b, b, read_buffer_b, write_buffer_b),
+        )
+    }
+}
+
+/// A [`Framed`] that owns its read and write buffers as arrays of `R` and `W` bytes.
+///
+/// The type does not borrow its buffers, so it can be stored in a struct or a `static` without self-referential workarounds.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{FramedOwnedBuf, codec::lines::StrLines, mock::Noop};
+///
+/// struct Connection {
+///     framed: FramedOwnedBuf<StrLines, Noop, 1024, 1024>,
+/// }
+///
+/// let connection = Connection {
+///     framed: FramedOwnedBuf::new_owned(StrLines::new(), Noop),
+/// };
+/// ```
+pub type FramedOwnedBuf<C, RW, const R: usize, const W: usize> =
+    Framed<'static, C, RW, [u8; R], [u8; W]>;
+
+impl<C, RW, const R: usize, const W: usize> FramedOwnedBuf<C, RW, R, W> {
+    /// Creates a new [`FramedOwnedBuf`] with zeroed read and write buffers.
+    #[inline]
+    pub const fn new_owned(codec: C, inner: RW) -> Self {
+        Self::with_buffers(codec, inner, [0; R], [0; W])
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<'buf, C, RW> Framed<'buf, C, FromTokio<RW>> {
+    /// Creates a new [`Framed`] with the given `codec` and [`tokio`](https://docs.rs/tokio/latest/tokio/index.html) `reader/writer`.
+    ///
+    /// The `reader/writer` is wrapped using [`FromTokio`].
+    #[inline]
+    pub fn new_tokio(
+        codec: C,
+        inner: RW,
+        read_buffer: &'buf mut [u8],
+        write_buffer: &'buf mut [u8],
+    ) -> Self {
+        Self::new(codec, FromTokio::new(inner), read_buffer, write_buffer)
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<'buf, C, RW> Framed<'buf, C, FromFutures<RW>> {
+    /// Creates a new [`Framed`] with the given `codec` and [`futures`](https://docs.rs/futures/latest/futures/index.html) `reader/writer`.
+    ///
+    /// The `reader/writer` is wrapped using [`FromFutures`].
+    #[inline]
+    pub fn new_futures(
+        codec: C,
+        inner: RW,
+        read_buffer: &'buf mut [u8],
+        write_buffer: &'buf mut [u8],
+    ) -> Self {
+        Self::new(codec, FromFutures::new(inner), read_buffer, write_buffer)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C, RW> Framed<'static, C, RW, alloc::boxed::Box<[u8]>, alloc::boxed::Box<[u8]>> {
+    /// Creates a new [`Framed`] with heap-allocated read and write buffers of the given capacities.
+    pub fn new_alloc(codec: C, inner: RW, read_capacity: usize, write_capacity: usize) -> Self {
+        Self::with_buffers(
+            codec,
+            inner,
+            alloc::vec![0; read_capacity].into_boxed_slice(),
+            alloc::vec![0; write_capacity].into_boxed_slice(),
+        )
+    }
+}
+
+impl<'buf, C, RW, RB, WB> Framed<'buf, C, RW, RB, WB> {
+    /// Creates a new [`Framed`] with the given `codec`, `reader/writer` and [`Buffer`]s.
+    ///
+    /// Unlike [`Framed::new`], the buffers can be owned by the [`Framed`], e.g. arrays.
+    #[inline]
+    pub const fn with_buffers(codec: C, inner: RW, read_buffer: RB, write_buffer: WB) -> Self {
         Self {
             core: FramedCore::new(
                 codec,
                 inner,
-                ReadWriteState::new(ReadState::new(read_buffer), WriteState::new(write_buffer)),
+                ReadWriteState::new(
+                    ReadState::with_buffer(read_buffer),
+                    WriteState::with_buffer(write_buffer),
+                ),
             ),
         }
     }
 
+    /// Sets the `name` included in every log line of the [`Framed`], e.g. `gps`.
+    ///
+    /// Tells the log lines of multiple framers apart, e.g. on a device with multiple serial links.
+    #[inline]
+    pub const fn with_name(mut self, name: &'static str) -> Self {
+        self.core.state.read.name = Some(name);
+        self.core.state.write.name = Some(name);
+        self
+    }
+
+    /// Returns the name included in every log line of the [`Framed`].
+    #[inline]
+    pub const fn name(&self) -> Option<&'static str> {
+        self.core.state.read.name
+    }
+
+    /// Sets the `on_frame` hook called on every decoded or encoded frame.
+    ///
+    /// Reports liveness without touching the application loops, e.g. by petting a hardware watchdog.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use core::sync::atomic::{AtomicU32, Ordering};
+    ///
+    /// use framez::{Framed, codec::lines::StrLines, next};
+    ///
+    /// static FRAMES: AtomicU32 = AtomicU32::new(0);
+    ///
+    /// fn pet() {
+    ///     FRAMES.fetch_add(1, Ordering::Relaxed);
+    /// }
+    ///
+    /// # async fn run() {
+    /// let (r_buf, w_buf) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+    /// let mut framed = Framed::new(StrLines::new(), b"Hello\n".as_slice(), r_buf, w_buf).with_on_frame(pet);
+    ///
+    /// assert_eq!(next!(framed).unwrap().unwrap(), "Hello");
+    /// assert_eq!(FRAMES.load(Ordering::Relaxed), 1);
+    /// # }
+    /// ```
+    #[inline]
+    pub const fn with_on_frame(mut self, on_frame: fn()) -> Self {
+        self.core.state.read.on_frame = Some(on_frame);
+        self.core.state.write.on_frame = Some(on_frame);
+        self
+    }
+
+    /// Sets the [`Quota`] that limits the bytes read from the underlying reader.
+    #[inline]
+    pub const fn with_quota(mut self, quota: Quota) -> Self {
+        self.core.state.read.quota = quota;
+        self
+    }
+
+    /// Returns the [`Quota`] of the read path.
+    #[inline]
+    pub const fn quota(&self) -> &Quota {
+        &self.core.state.read.quota
+    }
+
+    /// Returns a snapshot of the counters of the [`Framed`].
+    ///
+    /// See the [`metrics`](crate::metrics) module.
+    #[cfg(feature = "metrics")]
+    #[inline]
+    pub fn metrics(&self) -> Metrics {
+        Metrics::snapshot(
+            Some(&self.core.state.read.counters),
+            Some(&self.core.state.write.counters),
+        )
+    }
+
     /// Returns reference to the codec.
     #[inline]
     pub const fn codec(&self) -> &C {
@@ -64,13 +321,13 @@ impl<'buf, C, RW> Framed<'buf, C, RW> {
 
     /// Consumes the [`Framed`] and returns the `codec` and `reader/writer` and state.
     #[inline]
-    pub fn into_parts(self) -> (C, RW, ReadWriteState<'buf>) {
+    pub fn into_parts(self) -> (C, RW, ReadWriteState<'buf, RB, WB>) {
         self.core.into_parts()
     }
 
     #[inline]
     /// Creates a new [`Framed`] from its parts.
-    pub const fn from_parts(codec: C, read_write: RW, state: ReadWriteState<'buf>) -> Self {
+    pub const fn from_parts(codec: C, read_write: RW, state: ReadWriteState<'buf, RB, WB>) -> Self {
         Self {
             core: FramedCore::from_parts(codec, read_write, state),
         }
@@ -82,6 +339,139 @@ impl<'buf, C, RW> Framed<'buf, C, RW> {
         self.core.framable()
     }
 
+    /// Returns the [`ShiftPolicy`] of the read buffer.
+    #[inline]
+    pub const fn shift_policy(&self) -> ShiftPolicy {
+        self.core.shift_policy()
+    }
+
+    /// Sets the [`ShiftPolicy`] of the read buffer.
+    ///
+    /// Defaults to [`ShiftPolicy::WhenFull`].
+    #[inline]
+    pub const fn set_shift_policy(&mut self, shift_policy: ShiftPolicy) {
+        self.core.set_shift_policy(shift_policy);
+    }
+
+    /// Returns the [`flush_threshold`](WriteState::flush_threshold) of the write buffer.
+    #[inline]
+    pub const fn flush_threshold(&self) -> usize {
+        self.core.flush_threshold()
+    }
+
+    /// Sets the [`flush_threshold`](WriteState::flush_threshold) of the write buffer.
+    ///
+    /// Small frames are coalesced in the write buffer and written together once the threshold is reached.
+    /// Call [`Framed::flush`] to write them earlier. Defaults to `0`, which writes and flushes every frame as it is sent.
+    #[inline]
+    pub const fn set_flush_threshold(&mut self, flush_threshold: usize) {
+        self.core.set_flush_threshold(flush_threshold);
+    }
+
+    /// Returns the free region of the read buffer, to be filled directly, e.g. by a DMA driver.
+    ///
+    /// Call [`Framed::commit`] with the number of bytes written into it. See [`ReadState::writable_part`].
+    #[inline]
+    pub fn writable_part(&mut self) -> &mut [u8]
+    where
+        RB: Buffer,
+    {
+        self.core.writable_part()
+    }
+
+    /// Marks `size` bytes written into [`Framed::writable_part`] as read.
+    ///
+    /// See [`ReadState::commit`].
+    #[inline]
+    pub fn commit(&mut self, size: usize) -> Result<(), CommitError>
+    where
+        RB: Buffer,
+    {
+        self.core.commit(size)
+    }
+
+    /// Discards the buffered bytes that were not decoded yet and returns their number.
+    ///
+    /// See [`ReadState::discard_buffered`].
+    #[inline]
+    pub const fn discard_buffered(&mut self) -> usize {
+        self.core.discard_buffered()
+    }
+
+    /// Captures the decoding progress, so the frames decoded afterwards can be rolled back with [`Framed::restore`].
+    ///
+    /// See [`ReadState::snapshot`].
+    ///
+    /// # Example
+    ///
+    /// Try a codec and fall back to another one without losing bytes.
+    ///
+    /// ```rust
+    /// use framez::{Framed, codec::{delimiter::Delimiter, lines::StrLines}, mock::Noop};
+    ///
+    /// # async fn run() {
+    /// let r_buf = &mut [0u8; 64];
+    /// let w_buf = &mut [0u8; 64];
+    ///
+    /// let mut framed = Framed::new(StrLines::new(), b"\xFFbinary\0".as_slice(), r_buf, w_buf);
+    ///
+    /// // Reads all the bytes at once.
+    /// assert!(matches!(framed.maybe_next().await, Some(Ok(None))));
+    ///
+    /// let snapshot = framed.snapshot();
+    ///
+    /// // Not a line of text.
+    /// assert!(framed.frames().next().is_none());
+    ///
+    /// framed.restore(snapshot).unwrap();
+    ///
+    /// let (_, read, state) = framed.into_parts();
+    /// let mut framed = Framed::from_parts(Delimiter::new(&[0]), read, state);
+    ///
+    /// assert_eq!(framed.frames().next().unwrap().unwrap(), b"\xFFbinary");
+    /// # }
+    /// ```
+    #[inline]
+    pub const fn snapshot(&self) -> ReadSnapshot {
+        self.core.snapshot()
+    }
+
+    /// Rolls back the bytes consumed since the `snapshot` was taken with [`Framed::snapshot`], so they are decoded again.
+    ///
+    /// See [`ReadState::restore`].
+    #[inline]
+    pub const fn restore(&mut self, snapshot: ReadSnapshot) -> Result<(), RollbackError> {
+        self.core.restore(snapshot)
+    }
+
+    /// Discards the buffered bytes and the pending bytes of a partially written frame, and wipes the read and write buffers,
+    /// so credentials and key material passing through frames do not linger in memory, e.g. at the end of a session.
+    ///
+    /// This is the only wipe of the buffers that a framer performs. [`Framed::into_parts`] hands the buffered bytes over as they are,
+    /// e.g. to switch codecs with [`Framed::from_parts`], and dropping a framer leaves its borrowed buffers untouched.
+    /// Wrap the buffers in a [`Sensitive`](crate::buffer::Sensitive) to wipe them when they are finally dropped.
+    /// See [`ReadState::zeroize`] and [`WriteState::zeroize`].
+    #[cfg(feature = "zeroize")]
+    #[inline]
+    pub fn zeroize_buffers(&mut self)
+    where
+        RB: Buffer,
+        WB: Buffer,
+    {
+        self.core.zeroize_buffers()
+    }
+
+    /// Skips the next `n` raw bytes, starting with the buffered ones.
+    ///
+    /// See [`functions::drain`](crate::functions::drain).
+    pub async fn drain(&mut self, n: usize) -> Result<(), ReadPayloadError<RW::Error>>
+    where
+        RW: Read,
+        RB: Buffer,
+    {
+        self.core.drain(n).await
+    }
+
     /// Tries to read a frame from the underlying reader.
     ///
     /// # Return value
@@ -91,6 +481,11 @@ impl<'buf, C, RW> Framed<'buf, C, RW> {
     /// - `Some(Err(error))` if an error occurred. The caller should stop reading.
     /// - `None` if eof was reached. The caller should stop reading.
     ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe as long as the underlying reader is cancel safe.
+    /// See [`functions::maybe_next`](crate::functions::maybe_next#cancel-safety).
+    ///
     /// # Usage
     ///
     /// See [`next!`](crate::next!).
@@ -119,14 +514,60 @@ impl<'buf, C, RW> Framed<'buf, C, RW> {
     /// ```
     pub async fn maybe_next<'this>(
         &'this mut self,
-    ) -> Option<Result<Option<C::Item>, ReadError<RW::Error, C::Error>>>
+    ) -> Option<Result<Option<C::Item<'this>>, ReadError<RW::Error, C::Error>>>
     where
-        C: Decoder<'this>,
+        C: Decoder,
         RW: Read,
+        RB: Buffer,
     {
         self.core.maybe_next().await
     }
 
+    /// Like [`Framed::maybe_next`], but returns the nameable [`MaybeNext`] future,
+    /// e.g. to store it in a statically-allocated task structure.
+    ///
+    /// See [`ops`](crate::ops).
+    #[inline]
+    pub const fn maybe_next_future(&mut self) -> MaybeNext<'_, 'buf, C, RW, RB> {
+        self.core.maybe_next_future()
+    }
+
+    /// Returns a [`Frames`] that decodes the frames that are already buffered, without reading from the underlying reader.
+    ///
+    /// Draining the buffer after [`Framed::maybe_next`] skips the async state machine between frames,
+    /// which pays off when dozens of small frames arrive in a single read.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use framez::{Framed, codec::lines::StrLines};
+    ///
+    /// # async fn run() {
+    /// let r_buf = &mut [0u8; 64];
+    /// let w_buf = &mut [0u8; 64];
+    ///
+    /// let mut framed = Framed::new(StrLines::new(), b"a\nb\nc\n".as_slice(), r_buf, w_buf);
+    ///
+    /// // Reads all the bytes at once.
+    /// assert!(matches!(framed.maybe_next().await, Some(Ok(None))));
+    ///
+    /// let mut frames = framed.frames();
+    ///
+    /// assert_eq!(frames.next().unwrap().unwrap(), "a");
+    /// assert_eq!(frames.next().unwrap().unwrap(), "b");
+    /// assert_eq!(frames.next().unwrap().unwrap(), "c");
+    /// assert!(frames.next().is_none());
+    /// # }
+    /// ```
+    #[inline]
+    pub const fn frames(&mut self) -> Frames<'_, 'buf, C, RW::Error, RB>
+    where
+        RW: Read,
+    {
+        self.core.frames()
+    }
+
+    #[cfg(feature = "futures")]
     /// Converts the [`Framed`] into a stream of frames using the given `map` function.
     ///
     /// # Example
@@ -157,16 +598,36 @@ impl<'buf, C, RW> Framed<'buf, C, RW> {
     /// ```
     pub fn stream<U>(
         &mut self,
-        map: fn(<C as Decoder<'_>>::Item) -> U,
+        map: fn(C::Item<'_>) -> U,
     ) -> impl Stream<Item = Result<U, ReadError<RW::Error, C::Error>>> + '_
     where
         U: 'static,
-        C: for<'a> Decoder<'a>,
+        C: Decoder,
         RW: Read,
+        RB: Buffer,
     {
         self.core.stream(map)
     }
 
+    #[cfg(feature = "futures")]
+    /// Like [`Framed::stream`], but completes after yielding the first frame for which `until` returns `true`.
+    ///
+    /// The [`Framed`] stays usable once the stream is dropped, e.g. to switch from a command phase to a data phase of a protocol.
+    /// The bytes buffered after the terminal frame are kept for the next phase.
+    pub fn frames_until<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+        until: fn(&U) -> bool,
+    ) -> impl Stream<Item = Result<U, ReadError<RW::Error, C::Error>>> + '_
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+    {
+        self.core.frames_until(map, until)
+    }
+
     /// Tries to read a frame from the underlying reader and converts it using the given `map` function.
     ///
     /// # Return value
@@ -174,68 +635,1115 @@ impl<'buf, C, RW> Framed<'buf, C, RW> {
     /// - `Some(Ok(U))` if a frame was successfully decoded and mapped. Call `next` again to read more frames.
     /// - `Some(Err(error))` if an error occurred. The caller should stop reading.
     /// - `None` if eof was reached. The caller should stop reading.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe as long as the underlying reader is cancel safe.
+    /// See [`functions::maybe_next`](crate::functions::maybe_next#cancel-safety).
     pub async fn next<U>(
         &mut self,
-        map: fn(<C as Decoder<'_>>::Item) -> U,
+        map: fn(C::Item<'_>) -> U,
     ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
     where
         U: 'static,
-        C: for<'a> Decoder<'a>,
+        C: Decoder,
         RW: Read,
+        RB: Buffer,
     {
         self.core.next(map).await
     }
 
-    /// Writes a frame to the underlying `writer` and flushes it.
-    pub async fn send<I>(&mut self, item: I) -> Result<(), WriteError<RW::Error, C::Error>>
+    /// Like [`Framed::next`], but also returns the [`FrameMeta`] of the frame: its offset in the stream, its length and its index.
+    ///
+    /// Useful to reference frames by their position, e.g. in protocol debugging or black-box logs.
+    /// See [`functions::next_with_meta`](crate::functions::next_with_meta).
+    ///
+    /// # Cancel safety
+    ///
+    /// Same as [`Framed::next`].
+    pub async fn next_with_meta<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<(FrameMeta, U), ReadError<RW::Error, C::Error>>>
     where
-        C: Encoder<I>,
-        RW: Write,
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
     {
-        self.core.send(item).await
+        self.core.next_with_meta(map).await
     }
 
-    /// Converts the [`Framed`] into a sink.
-    pub fn sink<'this, I>(
-        &'this mut self,
-    ) -> impl Sink<I, Error = WriteError<RW::Error, C::Error>> + 'this
+    /// Like [`Framed::next`], but also returns the tick count of the `clock` at which the frame was received.
+    ///
+    /// See [`Clock`] and [`functions::next_timestamped`](crate::functions::next_timestamped).
+    ///
+    /// # Cancel safety
+    ///
+    /// Same as [`Framed::next`].
+    pub async fn next_timestamped<U, K>(
+        &mut self,
+        clock: &K,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<(u64, U), ReadError<RW::Error, C::Error>>>
     where
-        I: 'this,
-        C: Encoder<I>,
-        RW: Write,
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        K: Clock,
     {
-        self.core.sink()
+        self.core.next_timestamped(clock, map).await
     }
-}
 
-/// A framer that reads bytes from a [`Read`] source and decodes them into frames using a [`Decoder`].
-#[derive(Debug)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct FramedRead<'buf, C, R> {
-    /// The core framed implementation.
+    /// Tries to read a frame from the underlying reader and copies its bytes into `dst`.
     ///
-    /// This field is made public to be used in the [`functions`](crate::functions) module for library authors.
-    /// If you are using this crate as a user, you should probably not care about this field.
-    pub core: FramedCore<'buf, C, R>,
-}
-
-impl<'buf, C, R> FramedRead<'buf, C, R> {
-    /// Creates a new [`FramedRead`] with the given `decoder` and `reader`.
-    #[inline]
-    pub const fn new(codec: C, reader: R, buffer: &'buf mut [u8]) -> Self {
-        Self {
-            core: FramedCore::new(
-                codec,
-                reader,
-                ReadWriteState::new(ReadState::new(buffer), WriteState::empty()),
-            ),
-        }
+    /// Gives owned frames without `alloc` or `heapless`, e.g. to forward frames into DMA transmit buffers.
+    ///
+    /// # Return value
+    ///
+    /// - `Some(Ok(len))` if a frame was successfully decoded and its `len` bytes were copied to the start of `dst`. Call `next_into` again to read more frames.
+    /// - `Some(Err(error))` if an error occurred. [`ReadError::FrameTooLarge`] if the frame does not fit into `dst`. The frame is dropped and reading can continue.
+    /// - `None` if eof was reached. The caller should stop reading.
+    ///
+    /// # Cancel safety
+    ///
+    /// Same as [`Framed::next`].
+    pub async fn next_into(
+        &mut self,
+        dst: &mut [u8],
+    ) -> Option<Result<usize, ReadError<RW::Error, C::Error>>>
+    where
+        C: Decoder,
+        for<'a> C::Item<'a>: AsRef<[u8]>,
+        RW: Read,
+        RB: Buffer,
+    {
+        self.core.next_into(dst).await
     }
 
-    /// Returns reference to the codec.
-    #[inline]
-    pub const fn codec(&self) -> &C {
-        self.core.codec()
+    /// Reads frames until eof and dispatches each one to the handler registered for its key in the `router`.
+    ///
+    /// See [`Router`] and [`functions::route`](crate::functions::route).
+    pub async fn route<F, K, T, D, E>(
+        &mut self,
+        router: &mut Router<F, T, D>,
+    ) -> Result<(), RouteError<RW::Error, C::Error, E>>
+    where
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        F: for<'a> FnMut(&C::Item<'a>) -> K,
+        T: for<'a> Routes<K, C::Item<'a>, Error = E>,
+        D: for<'a> Handler<C::Item<'a>, Error = E>,
+    {
+        self.core.route(router).await
+    }
+
+    /// Like [`Framed::next`], but gives up once the `timeout` future completes.
+    ///
+    /// See [`functions::next_timeout`](crate::functions::next_timeout) and [`next_timeout!`](crate::next_timeout!) for borrowed frames.
+    pub async fn next_timeout<U, T>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+        timeout: T,
+    ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        T: Future<Output = ()>,
+    {
+        self.core.next_timeout(map, timeout).await
+    }
+
+    /// Like [`Framed::next`], but a frame also ends once the bus is quiet for the [`Timing::quiet_us`].
+    ///
+    /// See [`functions::next_quiet`](crate::functions::next_quiet).
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn next_quiet<U, D>(
+        &mut self,
+        timing: &mut Timing<D>,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        D: DelayNs,
+    {
+        self.core.next_quiet(timing, map).await
+    }
+
+    /// Like [`Framed::next`], but also appends the raw bytes of the frame to the log of the `recorder`.
+    ///
+    /// See [`functions::next_recorded`](crate::functions::next_recorded).
+    #[cfg(feature = "embedded-storage-async")]
+    pub async fn next_recorded<U, S, K, const N: usize>(
+        &mut self,
+        recorder: &mut Recorder<S, K, N>,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        S: NorFlash,
+        K: Clock,
+    {
+        self.core.next_recorded(recorder, map).await
+    }
+
+    /// Like [`Framed::next`], but gives up once no bytes arrived for the [`Timing::idle_us`].
+    ///
+    /// See [`functions::next_idle`](crate::functions::next_idle).
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn next_idle<U, D>(
+        &mut self,
+        timing: &mut Timing<D>,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        D: DelayNs,
+    {
+        self.core.next_idle(timing, map).await
+    }
+
+    /// Like [`Framed::next`], but a frame must complete within the [`Timing::frame_deadline_us`], measured from its first byte.
+    ///
+    /// See [`functions::next_deadline`](crate::functions::next_deadline).
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn next_deadline<U, D>(
+        &mut self,
+        timing: &mut Timing<D>,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        D: DelayNs,
+    {
+        self.core.next_deadline(timing, map).await
+    }
+
+    /// Like [`Framed::next`], but returns instead of waiting for the underlying reader to become ready.
+    ///
+    /// See [`functions::try_next`](crate::functions::try_next) and [`try_next!`](crate::try_next!) for borrowed frames.
+    ///
+    /// # Return value
+    ///
+    /// - `Some(Ok(Some(U)))` if a frame was successfully decoded and mapped. Call `try_next` again to read more frames.
+    /// - `Some(Ok(None))` if no frame is buffered and the reader is not ready. Call `try_next` again later.
+    /// - `Some(Err(error))` if an error occurred. The caller should stop reading.
+    /// - `None` if eof was reached. The caller should stop reading.
+    pub async fn try_next<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<Option<U>, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read + ReadReady,
+        RB: Buffer,
+    {
+        self.core.try_next(map).await
+    }
+
+    /// Reads the next chunk of a payload that follows a decoded frame.
+    ///
+    /// Used to read frames that are larger than the read buffer.
+    /// See [`functions::next_payload_chunk`](crate::functions::next_payload_chunk).
+    ///
+    /// # Return value
+    ///
+    /// - `Some(Ok(chunk))` if a chunk of at most `remaining` bytes was read. Call `next_payload_chunk` again to read the rest of the payload.
+    /// - `Some(Err(error))` if an error occurred. The caller should stop reading.
+    /// - `None` if `remaining` is `0`.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe as long as the underlying reader is cancel safe.
+    /// `remaining` is only decreased when a chunk is returned.
+    /// See [`functions::maybe_next`](crate::functions::maybe_next#cancel-safety).
+    pub async fn next_payload_chunk(
+        &mut self,
+        remaining: &mut usize,
+    ) -> Option<Result<&[u8], ReadPayloadError<RW::Error>>>
+    where
+        RW: Read,
+        RB: Buffer,
+    {
+        self.core.next_payload_chunk(remaining).await
+    }
+
+    /// Writes a frame to the underlying `writer` and flushes it.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe as long as the underlying writer is cancel safe.
+    /// A partially written frame is completed by the next call to `send`.
+    /// See [`functions::send`](crate::functions::send#cancel-safety).
+    pub async fn send<I>(&mut self, item: I) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+    {
+        self.core.send(item).await
+    }
+
+    /// Like [`Framed::send`], but returns the nameable [`Send`](ops::Send) future,
+    /// e.g. to store it in a statically-allocated task structure.
+    ///
+    /// See [`ops`](crate::ops).
+    #[inline]
+    pub const fn send_future<I>(&mut self, item: I) -> ops::Send<'_, 'buf, C, RW, I, WB> {
+        self.core.send_future(item)
+    }
+
+    /// Like [`Framed::send`], but also appends the encoded frame to the log of the `recorder` once it was sent.
+    ///
+    /// See [`functions::send_recorded`](crate::functions::send_recorded).
+    #[cfg(feature = "embedded-storage-async")]
+    pub async fn send_recorded<I, S, K, const N: usize>(
+        &mut self,
+        recorder: &mut Recorder<S, K, N>,
+        item: I,
+    ) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+        S: NorFlash,
+        K: Clock,
+    {
+        self.core.send_recorded(recorder, item).await
+    }
+
+    /// Like [`Framed::send`], but waits for the `pacer` first, so frames are not sent faster than the `pacer` allows.
+    ///
+    /// See [`functions::send_paced`](crate::functions::send_paced).
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn send_paced<I, D>(
+        &mut self,
+        pacer: &mut Pacer<D>,
+        item: I,
+    ) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+        D: DelayNs,
+    {
+        self.core.send_paced(pacer, item).await
+    }
+
+    /// Like [`Framed::send`], but keeps the bus silent for the [`Timing::gap_us`] after the frame was sent.
+    ///
+    /// See [`functions::send_gapped`](crate::functions::send_gapped).
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn send_gapped<I, D>(
+        &mut self,
+        timing: &mut Timing<D>,
+        item: I,
+    ) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+        D: DelayNs,
+    {
+        self.core.send_gapped(timing, item).await
+    }
+
+    /// Sends multiple frames and flushes once at the end.
+    ///
+    /// See [`functions::send_all`](crate::functions::send_all).
+    pub async fn send_all<I, T>(&mut self, items: T) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+        T: IntoIterator<Item = I>,
+    {
+        self.core.send_all(items).await
+    }
+
+    /// Writes the frames that were coalesced because of the [`flush_threshold`](WriteState::flush_threshold) and flushes the underlying writer.
+    ///
+    /// See [`functions::flush`](crate::functions::flush).
+    pub async fn flush(&mut self) -> Result<(), WriteError<RW::Error, Infallible>>
+    where
+        RW: Write,
+        WB: Buffer,
+    {
+        self.core.flush().await
+    }
+
+    /// Like [`Framed::send`], but writes the payload of the frame directly from the `item` instead of copying it into the write buffer.
+    ///
+    /// See [`functions::send_parts`](crate::functions::send_parts).
+    pub async fn send_parts<'a, I>(
+        &mut self,
+        item: I,
+    ) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: EncodeParts<'a, I>,
+        RW: Write,
+        WB: Buffer,
+    {
+        self.core.send_parts(item).await
+    }
+
+    /// Like [`Framed::send`], but lets the codec write the frame directly to the underlying writer using [`Encoder::encode_to_writer`].
+    ///
+    /// See [`functions::send_direct`](crate::functions::send_direct).
+    pub async fn send_direct<I>(&mut self, item: I) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+    {
+        self.core.send_direct(item).await
+    }
+
+    /// Sends a frame that is already encoded, e.g. received from another link, without copying it into the write buffer.
+    ///
+    /// See [`functions::send_raw`](crate::functions::send_raw).
+    pub async fn send_raw(&mut self, frame: &[u8]) -> Result<(), WriteError<RW::Error, Infallible>>
+    where
+        RW: Write,
+        WB: Buffer,
+    {
+        self.core.send_raw(frame).await
+    }
+
+    /// Like [`Framed::send`], but returns [`WriteError::NotReady`] instead of waiting for the underlying writer to become ready.
+    ///
+    /// See [`functions::try_send`](crate::functions::try_send).
+    pub async fn try_send<I>(&mut self, item: I) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write + WriteReady,
+        WB: Buffer,
+    {
+        self.core.try_send(item).await
+    }
+
+    /// Writes a frame with the given `header` followed by `payload_len` bytes streamed from `payload` and flushes it.
+    ///
+    /// The payload is copied through the write buffer in chunks, so the frame can be larger than the write buffer.
+    ///
+    /// See [`functions::send_streamed`](crate::functions::send_streamed).
+    pub async fn send_streamed<I, P>(
+        &mut self,
+        header: I,
+        payload: &mut P,
+        payload_len: usize,
+    ) -> Result<(), SendStreamedError<RW::Error, C::Error, P::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+        P: Read,
+    {
+        self.core.send_streamed(header, payload, payload_len).await
+    }
+
+    /// Establishes a session.
+    ///
+    /// Sends and flushes the `hello` frame, then reads the incoming frames until `accept` returns the negotiated session value for one of them.
+    /// Frames for which `accept` returns `None` are dropped, e.g. stale acks of a previous attempt.
+    /// Frames that follow the ack stay buffered and are returned by the next call to [`Framed::next`].
+    ///
+    /// Every attempt waits for the future returned by `timeout`, e.g. `async || delay.delay_ms(100).await`.
+    /// The `hello` frame is sent up to `attempts` times, at least once.
+    ///
+    /// # Errors
+    ///
+    /// - [`HandshakeError::Write`] if sending the `hello` frame failed.
+    /// - [`HandshakeError::Read`] if reading failed, or [`ReadError::Timeout`] if no ack was accepted after the last attempt.
+    /// - [`HandshakeError::Closed`] if the peer closed the connection before an ack was accepted.
+    pub async fn handshake<I, S, F, T>(
+        &mut self,
+        hello: I,
+        accept: F,
+        attempts: usize,
+        timeout: T,
+    ) -> Result<S, HandshakeError<RW::Error, <C as Encoder<I>>::Error, <C as Decoder>::Error>>
+    where
+        I: Clone,
+        C: Encoder<I> + Decoder,
+        RW: Read + Write,
+        RB: Buffer,
+        WB: Buffer,
+        F: for<'a> FnMut(C::Item<'a>) -> Option<S>,
+        T: AsyncFnMut(),
+    {
+        self.core.handshake(hello, accept, attempts, timeout).await
+    }
+
+    /// Closes the session gracefully.
+    ///
+    /// Sends and flushes the final `frame`, then reads the incoming frames until the peer closes the connection.
+    /// Every frame seen meanwhile is converted using the given `map` function and passed to `on_frame`.
+    ///
+    /// The draining is bounded by the `timeout` future, see [`Framed::next_timeout`].
+    /// Pass [`core::future::pending`] to wait for the peer without a timeout.
+    ///
+    /// # Errors
+    ///
+    /// - [`CloseError::Write`] if sending the `frame` failed. Nothing is read.
+    /// - [`CloseError::Read`] if reading failed, or [`ReadError::Timeout`] if the `timeout` completed before the peer closed the connection.
+    pub async fn close_with<I, U, T>(
+        &mut self,
+        frame: I,
+        map: fn(C::Item<'_>) -> U,
+        on_frame: impl FnMut(U),
+        timeout: T,
+    ) -> Result<(), CloseError<RW::Error, <C as Encoder<I>>::Error, <C as Decoder>::Error>>
+    where
+        U: 'static,
+        C: Encoder<I> + Decoder,
+        RW: Read + Write,
+        RB: Buffer,
+        WB: Buffer,
+        T: Future<Output = ()>,
+    {
+        self.core.close_with(frame, map, on_frame, timeout).await
+    }
+
+    #[cfg(feature = "futures")]
+    /// Converts the [`Framed`] into a sink.
+    ///
+    /// Items are encoded directly into the write buffer, which holds one frame at a time.
+    /// [`Sink::poll_ready`] waits until the previous frame is completely written,
+    /// so [`Sink::start_send`] never blocks and producers are backpressured by the writer.
+    /// Frames are flushed by [`Sink::poll_flush`], e.g. once per [`SinkExt::send`](futures::SinkExt::send)
+    /// or at the end of [`SinkExt::send_all`](futures::SinkExt::send_all).
+    ///
+    /// See [`Framed::ready_sink`] to also wait for the underlying writer to become ready.
+    pub fn sink<'this, I>(
+        &'this mut self,
+    ) -> impl Sink<I, Error = WriteError<RW::Error, C::Error>> + 'this
+    where
+        I: 'this,
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+    {
+        self.core.sink()
+    }
+
+    #[cfg(feature = "futures")]
+    /// Like [`Framed::sink`], but [`Sink::poll_ready`] also waits until the underlying writer is [`WriteReady`].
+    ///
+    /// [`WriteReady`] has no way to register a waker, so the task is woken immediately to check again while the writer is not ready.
+    pub fn ready_sink<'this, I>(
+        &'this mut self,
+    ) -> impl Sink<I, Error = WriteError<RW::Error, C::Error>> + 'this
+    where
+        I: 'this,
+        C: Encoder<I>,
+        RW: Write + WriteReady,
+        WB: Buffer,
+    {
+        self.core.ready_sink()
+    }
+}
+
+/// A framer that reads bytes from a [`Read`] source and decodes them into frames using a [`Decoder`].
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FramedRead<'buf, C, R, B = &'buf mut [u8]> {
+    /// The core framed implementation.
+    ///
+    /// This field is made public to be used in the [`functions`](crate::functions) module for library authors.
+    /// If you are using this crate as a user, you should probably not care about this field.
+    pub core: FramedCore<'buf, C, R, B>,
+}
+
+impl<'buf, C, R> FramedRead<'buf, C, R> {
+    /// Creates a new [`FramedRead`] with the given `decoder` and `reader`.
+    #[inline]
+    pub const fn new(codec: C, reader: R, buffer: &'buf mut [u8]) -> Self {
+        Self::with_buffer(codec, reader, buffer)
+    }
+
+    /// Creates a new [`FramedRead`] with the given `name`, `decoder` and `reader`.
+    ///
+    /// See [`Framed::with_name`].
+    #[inline]
+    pub const fn named(name: &'static str, codec: C, reader: R, buffer: &'buf mut [u8]) -> Self {
+        Self::new(codec, reader, buffer).with_name(name)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<'buf, C, R> FramedRead<'buf, C, FromTokio<R>> {
+    /// Creates a new [`FramedRead`] with the given `decoder` and [`tokio`](https://docs.rs/tokio/latest/tokio/index.html) `reader`.
+    ///
+    /// The `reader` is wrapped using [`FromTokio`].
+    #[inline]
+    pub fn new_tokio(codec: C, reader: R, buffer: &'buf mut [u8]) -> Self {
+        Self::new(codec, FromTokio::new(reader), buffer)
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<'buf, C, R> FramedRead<'buf, C, FromFutures<R>> {
+    /// Creates a new [`FramedRead`] with the given `decoder` and [`futures`](https://docs.rs/futures/latest/futures/index.html) `reader`.
+    ///
+    /// The `reader` is wrapped using [`FromFutures`].
+    #[inline]
+    pub fn new_futures(codec: C, reader: R, buffer: &'buf mut [u8]) -> Self {
+        Self::new(codec, FromFutures::new(reader), buffer)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C, R> FramedRead<'static, C, R, alloc::boxed::Box<[u8]>> {
+    /// Creates a new [`FramedRead`] with a heap-allocated buffer of the given capacity.
+    pub fn new_alloc(codec: C, reader: R, capacity: usize) -> Self {
+        Self::with_buffer(codec, reader, alloc::vec![0; capacity].into_boxed_slice())
+    }
+}
+
+impl<'buf, C, R, B> FramedRead<'buf, C, R, B> {
+    /// Creates a new [`FramedRead`] with the given `decoder`, `reader` and [`Buffer`].
+    ///
+    /// Unlike [`FramedRead::new`], the buffer can be owned by the [`FramedRead`], e.g. an array.
+    #[inline]
+    pub const fn with_buffer(codec: C, reader: R, buffer: B) -> Self {
+        Self {
+            core: FramedCore::new(
+                codec,
+                reader,
+                ReadWriteState::new(ReadState::with_buffer(buffer), WriteState::empty()),
+            ),
+        }
+    }
+
+    /// See [`Framed::with_name`].
+    #[inline]
+    pub const fn with_name(mut self, name: &'static str) -> Self {
+        self.core.state.read.name = Some(name);
+        self
+    }
+
+    /// See [`Framed::name`].
+    #[inline]
+    pub const fn name(&self) -> Option<&'static str> {
+        self.core.state.read.name
+    }
+
+    /// See [`Framed::with_on_frame`].
+    #[inline]
+    pub const fn with_on_frame(mut self, on_frame: fn()) -> Self {
+        self.core.state.read.on_frame = Some(on_frame);
+        self
+    }
+
+    /// See [`Framed::with_quota`].
+    #[inline]
+    pub const fn with_quota(mut self, quota: Quota) -> Self {
+        self.core.state.read.quota = quota;
+        self
+    }
+
+    /// See [`Framed::quota`].
+    #[inline]
+    pub const fn quota(&self) -> &Quota {
+        &self.core.state.read.quota
+    }
+
+    /// See [`Framed::metrics`].
+    #[cfg(feature = "metrics")]
+    #[inline]
+    pub fn metrics(&self) -> Metrics {
+        Metrics::snapshot(Some(&self.core.state.read.counters), None)
+    }
+
+    /// Returns reference to the codec.
+    #[inline]
+    pub const fn codec(&self) -> &C {
+        self.core.codec()
+    }
+
+    /// Returns mutable reference to the codec.
+    #[inline]
+    pub const fn codec_mut(&mut self) -> &mut C {
+        self.core.codec_mut()
+    }
+
+    /// Returns reference to the reader.
+    #[inline]
+    pub const fn inner(&self) -> &R {
+        self.core.inner()
+    }
+
+    /// Returns mutable reference to the reader.
+    #[inline]
+    pub const fn inner_mut(&mut self) -> &mut R {
+        self.core.inner_mut()
+    }
+
+    /// Consumes the [`FramedRead`] and returns the `codec` and `reader` and state.
+    #[inline]
+    pub fn into_parts(self) -> (C, R, ReadState<'buf, B>) {
+        let (codec, reader, state) = self.core.into_parts();
+
+        (codec, reader, state.read)
+    }
+
+    #[inline]
+    /// Creates a new [`FramedRead`] from its parts.
+    pub const fn from_parts(codec: C, read: R, state: ReadState<'buf, B>) -> Self {
+        Self {
+            core: FramedCore::from_parts(
+                codec,
+                read,
+                ReadWriteState::new(state, WriteState::empty()),
+            ),
+        }
+    }
+
+    /// Returns the number of bytes that can be framed.
+    #[inline]
+    pub const fn framable(&self) -> usize {
+        self.core.framable()
+    }
+
+    /// Returns the [`ShiftPolicy`] of the read buffer.
+    #[inline]
+    pub const fn shift_policy(&self) -> ShiftPolicy {
+        self.core.shift_policy()
+    }
+
+    /// Sets the [`ShiftPolicy`] of the read buffer.
+    ///
+    /// Defaults to [`ShiftPolicy::WhenFull`].
+    #[inline]
+    pub const fn set_shift_policy(&mut self, shift_policy: ShiftPolicy) {
+        self.core.set_shift_policy(shift_policy);
+    }
+
+    /// See [`Framed::writable_part`].
+    #[inline]
+    pub fn writable_part(&mut self) -> &mut [u8]
+    where
+        B: Buffer,
+    {
+        self.core.writable_part()
+    }
+
+    /// See [`Framed::commit`].
+    #[inline]
+    pub fn commit(&mut self, size: usize) -> Result<(), CommitError>
+    where
+        B: Buffer,
+    {
+        self.core.commit(size)
+    }
+
+    /// See [`Framed::discard_buffered`].
+    #[inline]
+    pub const fn discard_buffered(&mut self) -> usize {
+        self.core.discard_buffered()
+    }
+
+    /// See [`Framed::snapshot`].
+    #[inline]
+    pub const fn snapshot(&self) -> ReadSnapshot {
+        self.core.snapshot()
+    }
+
+    /// See [`Framed::restore`].
+    #[inline]
+    pub const fn restore(&mut self, snapshot: ReadSnapshot) -> Result<(), RollbackError> {
+        self.core.restore(snapshot)
+    }
+
+    /// See [`Framed::zeroize_buffers`].
+    #[cfg(feature = "zeroize")]
+    #[inline]
+    pub fn zeroize_buffers(&mut self)
+    where
+        B: Buffer,
+    {
+        self.core.zeroize_buffers()
+    }
+
+    /// See [`Framed::drain`].
+    pub async fn drain(&mut self, n: usize) -> Result<(), ReadPayloadError<R::Error>>
+    where
+        R: Read,
+        B: Buffer,
+    {
+        self.core.drain(n).await
+    }
+
+    /// See [`Framed::maybe_next`].
+    pub async fn maybe_next<'this>(
+        &'this mut self,
+    ) -> Option<Result<Option<C::Item<'this>>, ReadError<R::Error, C::Error>>>
+    where
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+    {
+        self.core.maybe_next().await
+    }
+
+    /// See [`Framed::maybe_next_future`].
+    #[inline]
+    pub const fn maybe_next_future(&mut self) -> MaybeNext<'_, 'buf, C, R, B> {
+        self.core.maybe_next_future()
+    }
+
+    /// See [`Framed::frames`].
+    #[inline]
+    pub const fn frames(&mut self) -> Frames<'_, 'buf, C, R::Error, B>
+    where
+        R: Read,
+    {
+        self.core.frames()
+    }
+
+    #[cfg(feature = "futures")]
+    /// See [`Framed::stream`].
+    pub fn stream<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+    ) -> impl Stream<Item = Result<U, ReadError<R::Error, C::Error>>> + '_
+    where
+        U: 'static,
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+    {
+        self.core.stream(map)
+    }
+
+    #[cfg(feature = "futures")]
+    /// See [`Framed::frames_until`].
+    pub fn frames_until<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+        until: fn(&U) -> bool,
+    ) -> impl Stream<Item = Result<U, ReadError<R::Error, C::Error>>> + '_
+    where
+        U: 'static,
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+    {
+        self.core.frames_until(map, until)
+    }
+
+    /// See [`Framed::next`].
+    pub async fn next<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+    {
+        self.core.next(map).await
+    }
+
+    /// See [`Framed::next_with_meta`].
+    pub async fn next_with_meta<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<(FrameMeta, U), ReadError<R::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+    {
+        self.core.next_with_meta(map).await
+    }
+
+    /// See [`Framed::next_timestamped`].
+    pub async fn next_timestamped<U, K>(
+        &mut self,
+        clock: &K,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<(u64, U), ReadError<R::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+        K: Clock,
+    {
+        self.core.next_timestamped(clock, map).await
+    }
+
+    /// See [`Framed::next_into`].
+    pub async fn next_into(
+        &mut self,
+        dst: &mut [u8],
+    ) -> Option<Result<usize, ReadError<R::Error, C::Error>>>
+    where
+        C: Decoder,
+        for<'a> C::Item<'a>: AsRef<[u8]>,
+        R: Read,
+        B: Buffer,
+    {
+        self.core.next_into(dst).await
+    }
+
+    /// See [`Framed::route`].
+    pub async fn route<F, K, T, D, E>(
+        &mut self,
+        router: &mut Router<F, T, D>,
+    ) -> Result<(), RouteError<R::Error, C::Error, E>>
+    where
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+        F: for<'a> FnMut(&C::Item<'a>) -> K,
+        T: for<'a> Routes<K, C::Item<'a>, Error = E>,
+        D: for<'a> Handler<C::Item<'a>, Error = E>,
+    {
+        self.core.route(router).await
+    }
+
+    /// See [`Framed::next_timeout`].
+    pub async fn next_timeout<U, T>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+        timeout: T,
+    ) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+        T: Future<Output = ()>,
+    {
+        self.core.next_timeout(map, timeout).await
+    }
+
+    /// See [`Framed::next_quiet`].
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn next_quiet<U, D>(
+        &mut self,
+        timing: &mut Timing<D>,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+        D: DelayNs,
+    {
+        self.core.next_quiet(timing, map).await
+    }
+
+    /// See [`Framed::next_recorded`].
+    #[cfg(feature = "embedded-storage-async")]
+    pub async fn next_recorded<U, S, K, const N: usize>(
+        &mut self,
+        recorder: &mut Recorder<S, K, N>,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+        S: NorFlash,
+        K: Clock,
+    {
+        self.core.next_recorded(recorder, map).await
+    }
+
+    /// See [`Framed::next_idle`].
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn next_idle<U, D>(
+        &mut self,
+        timing: &mut Timing<D>,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+        D: DelayNs,
+    {
+        self.core.next_idle(timing, map).await
+    }
+
+    /// See [`Framed::next_deadline`].
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn next_deadline<U, D>(
+        &mut self,
+        timing: &mut Timing<D>,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+        D: DelayNs,
+    {
+        self.core.next_deadline(timing, map).await
+    }
+
+    /// See [`Framed::try_next`].
+    pub async fn try_next<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<Option<U>, ReadError<R::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        R: Read + ReadReady,
+        B: Buffer,
+    {
+        self.core.try_next(map).await
+    }
+
+    /// See [`Framed::next_payload_chunk`].
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe as long as the underlying reader is cancel safe.
+    /// See [`Framed::next_payload_chunk`](Framed::next_payload_chunk#cancel-safety).
+    pub async fn next_payload_chunk(
+        &mut self,
+        remaining: &mut usize,
+    ) -> Option<Result<&[u8], ReadPayloadError<R::Error>>>
+    where
+        R: Read,
+        B: Buffer,
+    {
+        self.core.next_payload_chunk(remaining).await
+    }
+}
+
+/// A sink that writes encoded frames into an underlying [`Write`] sink using an [`Encoder`].
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FramedWrite<'buf, C, W, B = &'buf mut [u8]> {
+    /// The core framed implementation.
+    ///
+    /// This field is made public to be used in the [`functions`](crate::functions) module for library authors.
+    /// If you are using this crate as a user, you should probably not care about this field.
+    pub core: FramedCore<'buf, C, W, &'buf mut [u8], B>,
+}
+
+impl<'buf, C, W> FramedWrite<'buf, C, W> {
+    /// Creates a new [`FramedWrite`] with the given `encoder` and `writer`.
+    #[inline]
+    pub const fn new(codec: C, writer: W, buffer: &'buf mut [u8]) -> Self {
+        Self::with_buffer(codec, writer, buffer)
+    }
+
+    /// Creates a new [`FramedWrite`] with the given `name`, `encoder` and `writer`.
+    ///
+    /// See [`Framed::with_name`].
+    #[inline]
+    pub const fn named(name: &'static str, codec: C, writer: W, buffer: &'buf mut [u8]) -> Self {
+        Self::new(codec, writer, buffer).with_name(name)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<'buf, C, W> FramedWrite<'buf, C, FromTokio<W>> {
+    /// Creates a new [`FramedWrite`] with the given `encoder` and [`tokio`](https://docs.rs/tokio/latest/tokio/index.html) `writer`.
+    ///
+    /// The `writer` is wrapped using [`FromTokio`].
+    #[inline]
+    pub fn new_tokio(codec: C, writer: W, buffer: &'buf mut [u8]) -> Self {
+        Self::new(codec, FromTokio::new(writer), buffer)
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<'buf, C, W> FramedWrite<'buf, C, FromFutures<W>> {
+    /// Creates a new [`FramedWrite`] with the given `encoder` and [`futures`](https://docs.rs/futures/latest/futures/index.html) `writer`.
+    ///
+    /// The `writer` is wrapped using [`FromFutures`].
+    #[inline]
+    pub fn new_futures(codec: C, writer: W, buffer: &'buf mut [u8]) -> Self {
+        Self::new(codec, FromFutures::new(writer), buffer)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C, W> FramedWrite<'static, C, W, alloc::boxed::Box<[u8]>> {
+    /// Creates a new [`FramedWrite`] with a heap-allocated buffer of the given capacity.
+    pub fn new_alloc(codec: C, writer: W, capacity: usize) -> Self {
+        Self::with_buffer(codec, writer, alloc::vec![0; capacity].into_boxed_slice())
+    }
+}
+
+impl<'buf, C, W, B> FramedWrite<'buf, C, W, B> {
+    /// Creates a new [`FramedWrite`] with the given `encoder`, `writer` and [`Buffer`].
+    ///
+    /// Unlike [`FramedWrite::new`], the buffer can be owned by the [`FramedWrite`], e.g. an array.
+    #[inline]
+    pub const fn with_buffer(codec: C, writer: W, buffer: B) -> Self {
+        Self {
+            core: FramedCore::new(
+                codec,
+                writer,
+                ReadWriteState::new(ReadState::empty(), WriteState::with_buffer(buffer)),
+            ),
+        }
+    }
+
+    /// See [`Framed::with_name`].
+    #[inline]
+    pub const fn with_name(mut self, name: &'static str) -> Self {
+        self.core.state.write.name = Some(name);
+        self
+    }
+
+    /// See [`Framed::name`].
+    #[inline]
+    pub const fn name(&self) -> Option<&'static str> {
+        self.core.state.write.name
+    }
+
+    /// See [`Framed::with_on_frame`].
+    #[inline]
+    pub const fn with_on_frame(mut self, on_frame: fn()) -> Self {
+        self.core.state.write.on_frame = Some(on_frame);
+        self
+    }
+
+    /// See [`Framed::metrics`].
+    #[cfg(feature = "metrics")]
+    #[inline]
+    pub fn metrics(&self) -> Metrics {
+        Metrics::snapshot(None, Some(&self.core.state.write.counters))
+    }
+
+    /// Returns reference to the codec.
+    #[inline]
+    pub const fn codec(&self) -> &C {
+        self.core.codec()
     }
 
     /// Returns mutable reference to the codec.
@@ -244,169 +1752,379 @@ impl<'buf, C, R> FramedRead<'buf, C, R> {
         self.core.codec_mut()
     }
 
-    /// Returns reference to the reader.
+    /// Returns reference to the writer.
     #[inline]
-    pub const fn inner(&self) -> &R {
+    pub const fn inner(&self) -> &W {
         self.core.inner()
     }
 
-    /// Returns mutable reference to the reader.
+    /// Returns mutable reference to the writer.
     #[inline]
-    pub const fn inner_mut(&mut self) -> &mut R {
+    pub const fn inner_mut(&mut self) -> &mut W {
         self.core.inner_mut()
     }
 
-    /// Consumes the [`FramedRead`] and returns the `codec` and `reader` and state.
+    /// See [`Framed::flush_threshold`].
     #[inline]
-    pub fn into_parts(self) -> (C, R, ReadState<'buf>) {
-        let (codec, reader, state) = self.core.into_parts();
+    pub const fn flush_threshold(&self) -> usize {
+        self.core.flush_threshold()
+    }
 
-        (codec, reader, state.read)
+    /// See [`Framed::set_flush_threshold`].
+    #[inline]
+    pub const fn set_flush_threshold(&mut self, flush_threshold: usize) {
+        self.core.set_flush_threshold(flush_threshold);
     }
 
+    /// Consumes the [`FramedWrite`] and returns the `codec` and `writer` and state.
     #[inline]
-    /// Creates a new [`FramedRead`] from its parts.
-    pub const fn from_parts(codec: C, read: R, state: ReadState<'buf>) -> Self {
+    pub fn into_parts(self) -> (C, W, WriteState<'buf, B>) {
+        let (codec, writer, state) = self.core.into_parts();
+
+        (codec, writer, state.write)
+    }
+
+    #[inline]
+    /// Creates a new [`FramedWrite`] from its parts.
+    pub const fn from_parts(codec: C, write: W, state: WriteState<'buf, B>) -> Self {
         Self {
             core: FramedCore::from_parts(
                 codec,
-                read,
-                ReadWriteState::new(state, WriteState::empty()),
+                write,
+                ReadWriteState::new(ReadState::empty(), state),
             ),
         }
     }
 
-    /// Returns the number of bytes that can be framed.
+    /// See [`Framed::zeroize_buffers`].
+    #[cfg(feature = "zeroize")]
     #[inline]
-    pub const fn framable(&self) -> usize {
-        self.core.framable()
+    pub fn zeroize_buffers(&mut self)
+    where
+        B: Buffer,
+    {
+        self.core.zeroize_buffers()
     }
 
-    /// See [`Framed::maybe_next`].
-    pub async fn maybe_next<'this>(
-        &'this mut self,
-    ) -> Option<Result<Option<C::Item>, ReadError<R::Error, C::Error>>>
+    /// See [`Framed::send`].
+    pub async fn send<I>(&mut self, item: I) -> Result<(), WriteError<W::Error, C::Error>>
     where
-        C: Decoder<'this>,
-        R: Read,
+        C: Encoder<I>,
+        W: Write,
+        B: Buffer,
     {
-        self.core.maybe_next().await
+        self.core.send(item).await
     }
 
-    /// See [`Framed::stream`].
-    pub fn stream<U>(
+    /// See [`Framed::send_future`].
+    #[inline]
+    pub const fn send_future<I>(&mut self, item: I) -> ops::Send<'_, 'buf, C, W, I, B> {
+        self.core.send_future(item)
+    }
+
+    /// See [`Framed::send_recorded`].
+    #[cfg(feature = "embedded-storage-async")]
+    pub async fn send_recorded<I, S, K, const N: usize>(
         &mut self,
-        map: fn(<C as Decoder<'_>>::Item) -> U,
-    ) -> impl Stream<Item = Result<U, ReadError<R::Error, C::Error>>> + '_
+        recorder: &mut Recorder<S, K, N>,
+        item: I,
+    ) -> Result<(), WriteError<W::Error, C::Error>>
     where
-        U: 'static,
-        C: for<'a> Decoder<'a>,
-        R: Read,
+        C: Encoder<I>,
+        W: Write,
+        B: Buffer,
+        S: NorFlash,
+        K: Clock,
     {
-        self.core.stream(map)
+        self.core.send_recorded(recorder, item).await
     }
 
-    /// See [`Framed::next`].
-    pub async fn next<U>(
+    /// See [`Framed::send_paced`].
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn send_paced<I, D>(
         &mut self,
-        map: fn(<C as Decoder<'_>>::Item) -> U,
-    ) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+        pacer: &mut Pacer<D>,
+        item: I,
+    ) -> Result<(), WriteError<W::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        W: Write,
+        B: Buffer,
+        D: DelayNs,
+    {
+        self.core.send_paced(pacer, item).await
+    }
+
+    /// See [`Framed::send_gapped`].
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn send_gapped<I, D>(
+        &mut self,
+        timing: &mut Timing<D>,
+        item: I,
+    ) -> Result<(), WriteError<W::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        W: Write,
+        B: Buffer,
+        D: DelayNs,
+    {
+        self.core.send_gapped(timing, item).await
+    }
+
+    /// See [`Framed::send_all`].
+    pub async fn send_all<I, T>(&mut self, items: T) -> Result<(), WriteError<W::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        W: Write,
+        B: Buffer,
+        T: IntoIterator<Item = I>,
+    {
+        self.core.send_all(items).await
+    }
+
+    /// See [`Framed::flush`].
+    pub async fn flush(&mut self) -> Result<(), WriteError<W::Error, Infallible>>
+    where
+        W: Write,
+        B: Buffer,
+    {
+        self.core.flush().await
+    }
+
+    /// See [`Framed::send_direct`].
+    pub async fn send_direct<I>(&mut self, item: I) -> Result<(), WriteError<W::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        W: Write,
+        B: Buffer,
+    {
+        self.core.send_direct(item).await
+    }
+
+    /// See [`Framed::send_raw`].
+    pub async fn send_raw(&mut self, frame: &[u8]) -> Result<(), WriteError<W::Error, Infallible>>
+    where
+        W: Write,
+        B: Buffer,
+    {
+        self.core.send_raw(frame).await
+    }
+
+    /// See [`Framed::send_parts`].
+    pub async fn send_parts<'a, I>(&mut self, item: I) -> Result<(), WriteError<W::Error, C::Error>>
+    where
+        C: EncodeParts<'a, I>,
+        W: Write,
+        B: Buffer,
+    {
+        self.core.send_parts(item).await
+    }
+
+    /// See [`Framed::try_send`].
+    pub async fn try_send<I>(&mut self, item: I) -> Result<(), WriteError<W::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        W: Write + WriteReady,
+        B: Buffer,
+    {
+        self.core.try_send(item).await
+    }
+
+    /// See [`Framed::send_streamed`].
+    pub async fn send_streamed<I, P>(
+        &mut self,
+        header: I,
+        payload: &mut P,
+        payload_len: usize,
+    ) -> Result<(), SendStreamedError<W::Error, C::Error, P::Error>>
+    where
+        C: Encoder<I>,
+        W: Write,
+        B: Buffer,
+        P: Read,
+    {
+        self.core.send_streamed(header, payload, payload_len).await
+    }
+
+    #[cfg(feature = "futures")]
+    /// See [`Framed::sink`].
+    pub fn sink<'this, I>(
+        &'this mut self,
+    ) -> impl Sink<I, Error = WriteError<W::Error, C::Error>> + 'this
+    where
+        I: 'this,
+        C: Encoder<I>,
+        W: Write,
+        B: Buffer,
+    {
+        self.core.sink()
+    }
+
+    #[cfg(feature = "futures")]
+    /// See [`Framed::ready_sink`].
+    pub fn ready_sink<'this, I>(
+        &'this mut self,
+    ) -> impl Sink<I, Error = WriteError<W::Error, C::Error>> + 'this
     where
-        U: 'static,
-        C: for<'a> Decoder<'a>,
-        R: Read,
+        I: 'this,
+        C: Encoder<I>,
+        W: Write + WriteReady,
+        B: Buffer,
     {
-        self.core.next(map).await
+        self.core.ready_sink()
     }
 }
 
-/// A sink that writes encoded frames into an underlying [`Write`] sink using an [`Encoder`].
+/// A framer that reads bytes from a [`Read`] source into a ring buffer and decodes them into frames using a [`RingDecoder`].
+///
+/// Unlike [`FramedRead`], the buffered bytes are never shifted to the start of the buffer.
+/// See [`RingReadState`] and [`maybe_next_ring`](crate::functions::maybe_next_ring).
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct FramedWrite<'buf, C, W> {
-    /// The core framed implementation.
+pub struct RingFramedRead<'buf, C, R, B = &'buf mut [u8]> {
+    /// The codec used to decode frames.
+    pub codec: C,
+    /// The underlying reader.
+    pub inner: R,
+    /// The ring buffer read state.
     ///
     /// This field is made public to be used in the [`functions`](crate::functions) module for library authors.
     /// If you are using this crate as a user, you should probably not care about this field.
-    pub core: FramedCore<'buf, C, W>,
+    pub state: RingReadState<'buf, B>,
 }
 
-impl<'buf, C, W> FramedWrite<'buf, C, W> {
-    /// Creates a new [`FramedWrite`] with the given `encoder` and `writer`.
+impl<'buf, C, R> RingFramedRead<'buf, C, R> {
+    /// Creates a new [`RingFramedRead`] with the given `decoder` and `reader`.
     #[inline]
-    pub const fn new(codec: C, writer: W, buffer: &'buf mut [u8]) -> Self {
+    pub const fn new(codec: C, reader: R, buffer: &'buf mut [u8]) -> Self {
+        Self::with_buffer(codec, reader, buffer)
+    }
+
+    /// Creates a new [`RingFramedRead`] with the given `name`, `decoder` and `reader`.
+    ///
+    /// See [`Framed::with_name`].
+    #[inline]
+    pub const fn named(name: &'static str, codec: C, reader: R, buffer: &'buf mut [u8]) -> Self {
+        Self::new(codec, reader, buffer).with_name(name)
+    }
+}
+
+impl<'buf, C, R, B> RingFramedRead<'buf, C, R, B> {
+    /// Creates a new [`RingFramedRead`] with the given `decoder`, `reader` and [`Buffer`].
+    ///
+    /// Unlike [`RingFramedRead::new`], the buffer can be owned by the [`RingFramedRead`], e.g. an array.
+    #[inline]
+    pub const fn with_buffer(codec: C, reader: R, buffer: B) -> Self {
         Self {
-            core: FramedCore::new(
-                codec,
-                writer,
-                ReadWriteState::new(ReadState::empty(), WriteState::new(buffer)),
-            ),
+            codec,
+            inner: reader,
+            state: RingReadState::with_buffer(buffer),
         }
     }
 
+    /// See [`Framed::with_name`].
+    #[inline]
+    pub const fn with_name(mut self, name: &'static str) -> Self {
+        self.state.name = Some(name);
+        self
+    }
+
+    /// See [`Framed::name`].
+    #[inline]
+    pub const fn name(&self) -> Option<&'static str> {
+        self.state.name
+    }
+
+    /// See [`Framed::with_on_frame`].
+    #[inline]
+    pub const fn with_on_frame(mut self, on_frame: fn()) -> Self {
+        self.state.on_frame = Some(on_frame);
+        self
+    }
+
+    /// See [`Framed::metrics`].
+    #[cfg(feature = "metrics")]
+    #[inline]
+    pub fn metrics(&self) -> Metrics {
+        Metrics::snapshot(Some(&self.state.counters), None)
+    }
+
     /// Returns reference to the codec.
     #[inline]
     pub const fn codec(&self) -> &C {
-        self.core.codec()
+        &self.codec
     }
 
     /// Returns mutable reference to the codec.
     #[inline]
     pub const fn codec_mut(&mut self) -> &mut C {
-        self.core.codec_mut()
+        &mut self.codec
     }
 
-    /// Returns reference to the writer.
+    /// Returns reference to the reader.
     #[inline]
-    pub const fn inner(&self) -> &W {
-        self.core.inner()
+    pub const fn inner(&self) -> &R {
+        &self.inner
     }
 
-    /// Returns mutable reference to the writer.
+    /// Returns mutable reference to the reader.
     #[inline]
-    pub const fn inner_mut(&mut self) -> &mut W {
-        self.core.inner_mut()
+    pub const fn inner_mut(&mut self) -> &mut R {
+        &mut self.inner
     }
 
-    /// Consumes the [`FramedWrite`] and returns the `codec` and `writer` and state.
+    /// Consumes the [`RingFramedRead`] and returns the `codec` and `reader` and state.
     #[inline]
-    pub fn into_parts(self) -> (C, W, WriteState<'buf>) {
-        let (codec, writer, state) = self.core.into_parts();
-
-        (codec, writer, state.write)
+    pub fn into_parts(self) -> (C, R, RingReadState<'buf, B>) {
+        (self.codec, self.inner, self.state)
     }
 
     #[inline]
-    /// Creates a new [`FramedWrite`] from its parts.
-    pub const fn from_parts(codec: C, write: W, state: WriteState<'buf>) -> Self {
+    /// Creates a new [`RingFramedRead`] from its parts.
+    pub const fn from_parts(codec: C, read: R, state: RingReadState<'buf, B>) -> Self {
         Self {
-            core: FramedCore::from_parts(
-                codec,
-                write,
-                ReadWriteState::new(ReadState::empty(), state),
-            ),
+            codec,
+            inner: read,
+            state,
         }
     }
 
-    /// See [`Framed::send`].
-    pub async fn send<I>(&mut self, item: I) -> Result<(), WriteError<W::Error, C::Error>>
+    /// Returns the number of bytes that can be framed.
+    #[inline]
+    pub const fn framable(&self) -> usize {
+        self.state.framable()
+    }
+
+    /// Tries to read a frame from the underlying reader.
+    ///
+    /// See [`Framed::maybe_next`] and [`maybe_next_ring`](crate::functions::maybe_next_ring).
+    /// Use the [`next_ring!`](crate::next_ring!) macro to read frames in a loop.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe as long as the underlying reader is cancel safe.
+    /// See [`functions::maybe_next`](crate::functions::maybe_next#cancel-safety).
+    pub async fn maybe_next<'this>(
+        &'this mut self,
+    ) -> Option<Result<Option<C::Item<'this>>, ReadError<R::Error, C::Error>>>
     where
-        C: Encoder<I>,
-        W: Write,
+        C: RingDecoder,
+        R: Read,
+        B: Buffer,
     {
-        self.core.send(item).await
+        functions::maybe_next_ring(&mut self.state, &mut self.codec, &mut self.inner).await
     }
 
-    /// See [`Framed::sink`].
-    pub fn sink<'this, I>(
-        &'this mut self,
-    ) -> impl Sink<I, Error = WriteError<W::Error, C::Error>> + 'this
+    /// Discards the buffered bytes and wipes the read buffer.
+    ///
+    /// See [`Framed::zeroize_buffers`] and [`RingReadState::zeroize`].
+    #[cfg(feature = "zeroize")]
+    #[inline]
+    pub fn zeroize_buffers(&mut self)
     where
-        I: 'this,
-        C: Encoder<I>,
-        W: Write,
+        B: Buffer,
     {
-        self.core.sink()
+        self.state.zeroize()
     }
 }
 
@@ -416,12 +2134,19 @@ mod tests {
     #![allow(clippy::let_underscore_future)]
 
     use core::{pin::pin, str::FromStr};
-    use std::string::String;
+    use std::{string::String, vec::Vec};
 
     use embedded_io_adapters::tokio_1::FromTokio;
     use futures::{SinkExt, StreamExt};
 
-    use crate::{Framed, FramedRead, FramedWrite, codec::lines::StrLines, next};
+    use crate::{
+        Framed, FramedRead, FramedWrite,
+        codec::lines::StrLines,
+        mock::{PendingReader, ScriptedReader},
+        next,
+        state::FrameMeta,
+        tests::init_tracing,
+    };
 
     #[tokio::test]
     #[ignore = "assert that next! macro works on Framed"]
@@ -595,4 +2320,546 @@ mod tests {
             };
         }
     }
+
+    #[tokio::test]
+    async fn writable_part_and_commit() {
+        let (read, _write) = tokio::io::duplex(1024);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framed = FramedRead::new(StrLines::new(), FromTokio::new(read), buffer);
+
+        // Filled directly, e.g. by a DMA driver.
+        let writable = framed.writable_part();
+        assert_eq!(writable.len(), 16);
+
+        writable[..9].copy_from_slice(b"Hello\nwor");
+        framed.commit(9).expect("Must commit");
+
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "Hello");
+
+        // The remaining bytes are shifted to the start of the buffer before it is lent again.
+        framed.set_shift_policy(crate::state::ShiftPolicy::Eager);
+
+        assert!(matches!(framed.maybe_next().await, Some(Ok(None))));
+
+        let writable = framed.writable_part();
+        assert_eq!(writable.len(), 13);
+
+        writable[..4].copy_from_slice(b"ld!\n");
+        framed.commit(4).expect("Must commit");
+
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "world!");
+    }
+
+    #[tokio::test]
+    async fn frames_until_keeps_framed_usable() {
+        use tokio::io::AsyncWriteExt;
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        write
+            .write_all(b"MODE\nDATA\nEND\nHello\n")
+            .await
+            .expect("Must write");
+        drop(write);
+
+        let buffer = &mut [0_u8; 32];
+        let mut framed = FramedRead::new(StrLines::new(), FromTokio::new(read), buffer);
+
+        let commands = framed
+            .frames_until(|item| String::from(item), |item| item == "END")
+            .collect::<std::vec::Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<std::vec::Vec<_>, _>>()
+            .expect("Must decode");
+
+        assert_eq!(commands, ["MODE", "DATA", "END"]);
+
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "Hello");
+
+        assert!(next!(framed).is_none());
+    }
+
+    #[tokio::test]
+    async fn frames_drains_buffered_frames() {
+        use crate::mock::ScriptedReader;
+
+        let chunks: &[&[u8]] = &[b"a\nb\nc", b"\nd\n"];
+
+        let buffer = &mut [0_u8; 32];
+        let mut framed = FramedRead::new(StrLines::new(), ScriptedReader::new(chunks), buffer);
+
+        assert!(matches!(framed.maybe_next().await, Some(Ok(None))));
+
+        let mut frames = framed.frames();
+
+        assert_eq!(
+            frames.next().expect("Must be some").expect("Must decode"),
+            "a"
+        );
+        assert_eq!(
+            frames.next().expect("Must be some").expect("Must decode"),
+            "b"
+        );
+        assert!(frames.next().is_none());
+        assert_eq!(frames.framable(), 1);
+
+        // The partial frame is completed by the next read.
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "c");
+
+        let mut frames = framed.frames();
+
+        assert_eq!(
+            frames.next().expect("Must be some").expect("Must decode"),
+            "d"
+        );
+        assert!(frames.next().is_none());
+
+        assert!(next!(framed).is_none());
+    }
+
+    #[tokio::test]
+    async fn close_with_drains_until_eof() {
+        let (client, server) = tokio::io::duplex(1024);
+
+        let server = tokio::spawn(async move {
+            let read_buf = &mut [0_u8; 32];
+            let write_buf = &mut [0_u8; 32];
+            let mut framed =
+                Framed::new(StrLines::new(), FromTokio::new(server), read_buf, write_buf);
+
+            let item = next!(framed).expect("Must be some").expect("Must decode");
+            assert_eq!(item, "BYE");
+
+            framed.send("pending").await.expect("Must send");
+            framed.send("BYE").await.expect("Must send");
+        });
+
+        let read_buf = &mut [0_u8; 32];
+        let write_buf = &mut [0_u8; 32];
+        let mut framed = Framed::new(StrLines::new(), FromTokio::new(client), read_buf, write_buf);
+
+        let mut seen = std::vec::Vec::new();
+
+        framed
+            .close_with(
+                "BYE",
+                |item| String::from(item),
+                |item| seen.push(item),
+                core::future::pending(),
+            )
+            .await
+            .expect("Must close");
+
+        assert_eq!(seen, ["pending", "BYE"]);
+
+        server.await.expect("Must join");
+    }
+
+    #[tokio::test]
+    async fn close_with_timeout() {
+        let (client, _server) = tokio::io::duplex(1024);
+
+        let read_buf = &mut [0_u8; 32];
+        let write_buf = &mut [0_u8; 32];
+        let mut framed = Framed::new(StrLines::new(), FromTokio::new(client), read_buf, write_buf);
+
+        let result = framed
+            .close_with(
+                "BYE",
+                |item| String::from(item),
+                |_| {},
+                tokio::time::sleep(core::time::Duration::from_millis(10)),
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(crate::CloseError::Read(crate::ReadError::Timeout))
+        ));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn new_tokio_wraps_the_stream() {
+        let (client, server) = tokio::io::duplex(1024);
+
+        let write_buf = &mut [0_u8; 32];
+        let mut writer = FramedWrite::new_tokio(StrLines::new(), client, write_buf);
+
+        writer.send("Hello").await.expect("Must send");
+        drop(writer);
+
+        let read_buf = &mut [0_u8; 32];
+        let mut framed = Framed::new_tokio(StrLines::new(), server, read_buf, &mut []);
+
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "Hello");
+
+        assert!(next!(framed).is_none());
+    }
+
+    #[cfg(feature = "futures-io")]
+    #[tokio::test]
+    async fn new_futures_wraps_the_stream() {
+        let write_buf = &mut [0_u8; 32];
+        let mut writer = FramedWrite::new_futures(StrLines::new(), std::vec::Vec::new(), write_buf);
+
+        writer.send("Hello").await.expect("Must send");
+
+        let written = writer.inner().inner().clone();
+
+        let read_buf = &mut [0_u8; 32];
+        let mut framed = Framed::new_futures(
+            StrLines::new(),
+            futures::io::Cursor::new(written),
+            read_buf,
+            &mut [],
+        );
+
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "Hello");
+
+        assert!(next!(framed).is_none());
+    }
+
+    #[tokio::test]
+    async fn named_framers() {
+        crate::tests::init_tracing();
+
+        let read_buf = &mut [0_u8; 32];
+        let mut framed =
+            FramedRead::named("gps", StrLines::new(), b"$GPGGA\n".as_slice(), read_buf);
+
+        assert_eq!(framed.name(), Some("gps"));
+
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "$GPGGA");
+
+        let write_buf = &mut [0_u8; 32];
+        let framed = FramedWrite::new(StrLines::new(), std::vec::Vec::<u8>::new(), write_buf);
+
+        assert_eq!(framed.name(), None);
+        assert_eq!(framed.with_name("modem").name(), Some("modem"));
+
+        let (_, _, state) = Framed::named(
+            "radio",
+            StrLines::new(),
+            crate::mock::Noop,
+            &mut [],
+            &mut [],
+        )
+        .into_parts();
+
+        assert_eq!(state.reset().write.name, Some("radio"));
+    }
+
+    #[tokio::test]
+    async fn handshake_retries_until_ack() {
+        use tokio::io::AsyncWriteExt;
+
+        let (client, server) = tokio::io::duplex(1024);
+        let (server_read, mut server_write) = tokio::io::split(server);
+
+        let server = tokio::spawn(async move {
+            let read_buf = &mut [0_u8; 32];
+            let mut framed =
+                FramedRead::new(StrLines::new(), FromTokio::new(server_read), read_buf);
+
+            // The first hello is lost.
+            let item = next!(framed).expect("Must be some").expect("Must decode");
+            assert_eq!(item, "HELLO");
+
+            let item = next!(framed).expect("Must be some").expect("Must decode");
+            assert_eq!(item, "HELLO");
+
+            server_write
+                .write_all(b"noise\nACK 7\ndata\n")
+                .await
+                .expect("Must write");
+        });
+
+        let read_buf = &mut [0_u8; 32];
+        let write_buf = &mut [0_u8; 32];
+        let mut framed = Framed::new(StrLines::new(), FromTokio::new(client), read_buf, write_buf);
+
+        let session = framed
+            .handshake(
+                "HELLO",
+                |item| item.strip_prefix("ACK ")?.parse::<u8>().ok(),
+                3,
+                async || tokio::time::sleep(core::time::Duration::from_millis(50)).await,
+            )
+            .await
+            .expect("Must handshake");
+
+        assert_eq!(session, 7);
+
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "data");
+
+        server.await.expect("Must join");
+    }
+
+    #[tokio::test]
+    async fn handshake_timeout() {
+        let (client, server) = tokio::io::duplex(1024);
+
+        let read_buf = &mut [0_u8; 32];
+        let write_buf = &mut [0_u8; 32];
+        let mut framed = Framed::new(StrLines::new(), FromTokio::new(client), read_buf, write_buf);
+
+        let mut timeouts = 0;
+
+        let result = framed
+            .handshake(
+                "HELLO",
+                |_| Some(()),
+                2,
+                async || {
+                    timeouts += 1;
+
+                    tokio::time::sleep(core::time::Duration::from_millis(10)).await
+                },
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(crate::HandshakeError::Read(crate::ReadError::Timeout))
+        ));
+        assert_eq!(timeouts, 2);
+
+        drop(framed);
+
+        let read_buf = &mut [0_u8; 32];
+        let mut framed = FramedRead::new(StrLines::new(), FromTokio::new(server), read_buf);
+
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "HELLO");
+
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "HELLO");
+    }
+
+    #[tokio::test]
+    async fn frames_come_with_their_meta() {
+        init_tracing();
+
+        let read = PendingReader::new(b"Hello\r\nworld!\nHey\n".as_slice(), 3);
+
+        let buffer = &mut [0_u8; 8];
+        let mut framed = FramedRead::new(StrLines::new(), read, buffer);
+
+        let mut collected = Vec::new();
+
+        while let Some(item) = framed.next_with_meta(|line| line.len()).await {
+            collected.push(item.expect("Must decode"));
+        }
+
+        assert_eq!(
+            collected,
+            [
+                (
+                    FrameMeta {
+                        offset: 0,
+                        len: 7,
+                        index: 0
+                    },
+                    5
+                ),
+                (
+                    FrameMeta {
+                        offset: 7,
+                        len: 7,
+                        index: 1
+                    },
+                    6
+                ),
+                (
+                    FrameMeta {
+                        offset: 14,
+                        len: 4,
+                        index: 2
+                    },
+                    3
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn frames_are_stamped_when_received() {
+        init_tracing();
+
+        let ticks = core::cell::Cell::new(0_u64);
+        let clock = || {
+            ticks.set(ticks.get() + 1);
+            ticks.get()
+        };
+
+        let read = ScriptedReader::new(&[b"Hello\nHey\n", b"world!\n"]);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framed = FramedRead::new(StrLines::new(), read, buffer);
+
+        let mut collected = Vec::new();
+
+        while let Some(item) = framed
+            .next_timestamped(&clock, |line| String::from(line))
+            .await
+        {
+            collected.push(item.expect("Must decode"));
+        }
+
+        assert_eq!(
+            collected,
+            [(1, "Hello".into()), (2, "Hey".into()), (3, "world!".into())]
+        );
+    }
+
+    #[tokio::test]
+    async fn discarded_bytes_advance_the_offset() {
+        init_tracing();
+
+        let read = ScriptedReader::new(&[b"noise", b"Hello\n"]);
+
+        let buffer = &mut [0_u8; 8];
+        let mut framed = FramedRead::new(StrLines::new(), read, buffer);
+
+        assert!(matches!(framed.maybe_next().await, Some(Ok(None))));
+        assert_eq!(framed.discard_buffered(), 5);
+
+        let (meta, _) = framed
+            .next_with_meta(|_| ())
+            .await
+            .expect("Must be some")
+            .expect("Must decode");
+
+        assert_eq!(
+            meta,
+            FrameMeta {
+                offset: 5,
+                len: 6,
+                index: 0
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn discard_and_drain() {
+        use tokio::io::AsyncWriteExt;
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        write.write_all(b"boot noise").await.expect("Must write");
+
+        let buffer = &mut [0_u8; 8];
+        let mut framed = FramedRead::new(StrLines::new(), FromTokio::new(read), buffer);
+
+        assert!(matches!(framed.maybe_next().await, Some(Ok(None))));
+        assert_eq!(framed.discard_buffered(), 8);
+        assert_eq!(framed.framable(), 0);
+
+        // Skips the rest of the noise and the length of a garbage frame.
+        write
+            .write_all(b"\x00\x01\x02\x03Hello\n")
+            .await
+            .expect("Must write");
+        drop(write);
+
+        framed.drain(2 + 4).await.expect("Must drain");
+
+        let item = next!(framed).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "Hello");
+
+        assert!(matches!(
+            framed.drain(1).await,
+            Err(crate::ReadPayloadError::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn owned_buf_in_static() {
+        use crate::{FramedOwnedBuf, mock::Noop};
+
+        static FRAMED: std::sync::Mutex<FramedOwnedBuf<StrLines, Noop, 16, 16>> =
+            std::sync::Mutex::new(FramedOwnedBuf::new_owned(StrLines::new(), Noop));
+
+        let framed = FRAMED.lock().expect("Must lock");
+
+        assert_eq!(framed.core.state.read.buffer.len(), 16);
+        assert_eq!(framed.core.state.write.buffer.len(), 16);
+    }
+
+    #[tokio::test]
+    async fn owned_buffers() {
+        use tokio::io::DuplexStream;
+
+        /// Stores the framer without borrowing its buffers.
+        struct Connection {
+            framed: Framed<'static, StrLines, FromTokio<DuplexStream>, [u8; 32], [u8; 32]>,
+        }
+
+        let (client, server) = tokio::io::duplex(1024);
+
+        let mut client = Connection {
+            framed: Framed::with_buffers(StrLines::new(), FromTokio::new(client), [0; 32], [0; 32]),
+        };
+
+        let mut server = FramedRead::with_buffer(StrLines::new(), FromTokio::new(server), [0; 32]);
+
+        client.framed.send("Hello").await.expect("Must send");
+        client.framed.send("world!").await.expect("Must send");
+
+        drop(client);
+
+        let mut collected = std::vec::Vec::new();
+
+        while let Some(item) = next!(server) {
+            collected.push(String::from(item.expect("Must decode")));
+        }
+
+        assert_eq!(collected, ["Hello", "world!"]);
+    }
+
+    #[tokio::test]
+    async fn on_frame_is_called_on_every_frame() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        init_tracing();
+
+        static FRAMES: AtomicUsize = AtomicUsize::new(0);
+
+        fn pet() {
+            FRAMES.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let (client, server) = tokio::io::duplex(1024);
+
+        let (r_buf, w_buf) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+        let mut client =
+            Framed::new(StrLines::new(), FromTokio::new(client), r_buf, w_buf).with_on_frame(pet);
+
+        client.send("Hello").await.expect("Must send");
+        client.send("world!").await.expect("Must send");
+
+        assert_eq!(FRAMES.load(Ordering::Relaxed), 2);
+
+        let buffer = &mut [0_u8; 32];
+        let mut server = FramedWrite::new(StrLines::new(), FromTokio::new(server), buffer);
+
+        server.send("Hey").await.expect("Must send");
+
+        let item = next!(client).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "Hey");
+
+        // Only the frames of the client are counted.
+        assert_eq!(FRAMES.load(Ordering::Relaxed), 3);
+    }
 }
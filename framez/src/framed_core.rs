@@ -1,20 +1,45 @@
-use embedded_io_async::{Read, Write};
+use core::{convert::Infallible, pin::pin};
+
+use embedded_io_async::{Read, ReadReady, Write, WriteReady};
+#[cfg(feature = "futures")]
 use futures::{Sink, Stream};
 
 use crate::{
-    ReadError, WriteError, decode::Decoder, encode::Encoder, functions, state::ReadWriteState,
+    CloseError, Frames, HandshakeError, ReadError, ReadPayloadError, RouteError, SendStreamedError,
+    WriteError,
+    buffer::Buffer,
+    clock::Clock,
+    decode::Decoder,
+    encode::{EncodeParts, Encoder},
+    functions,
+    future::{Either, select},
+    ops::{self, MaybeNext},
+    router::{Handler, Router, Routes},
+    state::{CommitError, FrameMeta, ReadSnapshot, ReadWriteState, RollbackError, ShiftPolicy},
+};
+
+#[cfg(feature = "embedded-hal-async")]
+use {
+    crate::{pacing::Pacer, timing::Timing},
+    embedded_hal_async::delay::DelayNs,
 };
 
+#[cfg(feature = "embedded-storage-async")]
+use {crate::recorder::Recorder, embedded_storage_async::nor_flash::NorFlash};
+
+#[cfg(feature = "futures")]
+use crate::sink::FramedSink;
+
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct FramedCore<'buf, C, RW> {
+pub struct FramedCore<'buf, C, RW, RB = &'buf mut [u8], WB = &'buf mut [u8]> {
     pub codec: C,
     pub inner: RW,
-    pub state: ReadWriteState<'buf>,
+    pub state: ReadWriteState<'buf, RB, WB>,
 }
 
-impl<'buf, C, RW> FramedCore<'buf, C, RW> {
-    pub const fn new(codec: C, inner: RW, state: ReadWriteState<'buf>) -> Self {
+impl<'buf, C, RW, RB, WB> FramedCore<'buf, C, RW, RB, WB> {
+    pub const fn new(codec: C, inner: RW, state: ReadWriteState<'buf, RB, WB>) -> Self {
         Self {
             codec,
             inner,
@@ -48,13 +73,13 @@ impl<'buf, C, RW> FramedCore<'buf, C, RW> {
 
     /// Consumes the [`FramedCore`] and returns the `codec` and `reader/writer` and state.
     #[inline]
-    pub fn into_parts(self) -> (C, RW, ReadWriteState<'buf>) {
+    pub fn into_parts(self) -> (C, RW, ReadWriteState<'buf, RB, WB>) {
         (self.codec, self.inner, self.state)
     }
 
     #[inline]
     /// Creates a new [`FramedCore`] from its parts.
-    pub const fn from_parts(codec: C, inner: RW, state: ReadWriteState<'buf>) -> Self {
+    pub const fn from_parts(codec: C, inner: RW, state: ReadWriteState<'buf, RB, WB>) -> Self {
         Self {
             codec,
             inner,
@@ -68,39 +93,360 @@ impl<'buf, C, RW> FramedCore<'buf, C, RW> {
         self.state.read.framable()
     }
 
+    /// Returns the [`ShiftPolicy`] of the read buffer.
+    #[inline]
+    pub const fn shift_policy(&self) -> ShiftPolicy {
+        self.state.read.shift_policy
+    }
+
+    /// Sets the [`ShiftPolicy`] of the read buffer.
+    #[inline]
+    pub const fn set_shift_policy(&mut self, shift_policy: ShiftPolicy) {
+        self.state.read.shift_policy = shift_policy;
+    }
+
+    /// Returns the [`flush_threshold`](crate::state::WriteState::flush_threshold) of the write buffer.
+    #[inline]
+    pub const fn flush_threshold(&self) -> usize {
+        self.state.write.flush_threshold
+    }
+
+    /// Sets the [`flush_threshold`](crate::state::WriteState::flush_threshold) of the write buffer.
+    #[inline]
+    pub const fn set_flush_threshold(&mut self, flush_threshold: usize) {
+        self.state.write.flush_threshold = flush_threshold;
+    }
+
+    /// See [`Framed::writable_part`](crate::Framed::writable_part) for docs.
+    #[inline]
+    pub fn writable_part(&mut self) -> &mut [u8]
+    where
+        RB: Buffer,
+    {
+        self.state.read.writable_part()
+    }
+
+    /// See [`Framed::discard_buffered`](crate::Framed::discard_buffered) for docs.
+    #[inline]
+    pub const fn discard_buffered(&mut self) -> usize {
+        self.state.read.discard_buffered()
+    }
+
+    /// See [`Framed::snapshot`](crate::Framed::snapshot) for docs.
+    #[inline]
+    pub const fn snapshot(&self) -> ReadSnapshot {
+        self.state.read.snapshot()
+    }
+
+    /// See [`Framed::restore`](crate::Framed::restore) for docs.
+    #[inline]
+    pub const fn restore(&mut self, snapshot: ReadSnapshot) -> Result<(), RollbackError> {
+        self.state.read.restore(snapshot)
+    }
+
+    /// See [`Framed::zeroize_buffers`](crate::Framed::zeroize_buffers) for docs.
+    #[cfg(feature = "zeroize")]
+    #[inline]
+    pub fn zeroize_buffers(&mut self)
+    where
+        RB: Buffer,
+        WB: Buffer,
+    {
+        self.state.read.zeroize();
+        self.state.write.zeroize();
+    }
+
+    /// See [`Framed::drain`](crate::Framed::drain) for docs.
+    pub async fn drain(&mut self, n: usize) -> Result<(), ReadPayloadError<RW::Error>>
+    where
+        RW: Read,
+        RB: Buffer,
+    {
+        let mut remaining = n;
+
+        functions::drain(&mut self.state.read, &mut self.inner, &mut remaining).await
+    }
+
+    /// See [`Framed::commit`](crate::Framed::commit) for docs.
+    #[inline]
+    pub fn commit(&mut self, size: usize) -> Result<(), CommitError>
+    where
+        RB: Buffer,
+    {
+        self.state.read.commit(size)
+    }
+
     /// See [`Framed::maybe_next`](crate::Framed::maybe_next) for docs.
     pub async fn maybe_next<'this>(
         &'this mut self,
-    ) -> Option<Result<Option<C::Item>, ReadError<RW::Error, C::Error>>>
+    ) -> Option<Result<Option<C::Item<'this>>, ReadError<RW::Error, C::Error>>>
     where
-        C: Decoder<'this>,
+        C: Decoder,
         RW: Read,
+        RB: Buffer,
     {
         functions::maybe_next(&mut self.state.read, &mut self.codec, &mut self.inner).await
     }
 
+    /// See [`Framed::maybe_next_future`](crate::Framed::maybe_next_future) for docs.
+    #[inline]
+    pub const fn maybe_next_future(&mut self) -> MaybeNext<'_, 'buf, C, RW, RB> {
+        MaybeNext::new(&mut self.state.read, &mut self.codec, &mut self.inner)
+    }
+
+    /// See [`Framed::frames`](crate::Framed::frames) for docs.
+    #[inline]
+    pub const fn frames(&mut self) -> Frames<'_, 'buf, C, RW::Error, RB>
+    where
+        RW: Read,
+    {
+        Frames::new(&mut self.state.read, &mut self.codec)
+    }
+
     /// See [`Framed::next`](crate::Framed::next) for docs.
-    pub async fn next<'this, U>(
-        &'this mut self,
-        map: fn(<C as Decoder<'_>>::Item) -> U,
+    pub async fn next<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
     ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
     where
         U: 'static,
-        C: for<'a> Decoder<'a>,
+        C: Decoder,
         RW: Read,
+        RB: Buffer,
     {
         functions::next(&mut self.state.read, &mut self.codec, &mut self.inner, map).await
     }
 
+    /// See [`Framed::next_with_meta`](crate::Framed::next_with_meta) for docs.
+    pub async fn next_with_meta<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<(FrameMeta, U), ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+    {
+        functions::next_with_meta(&mut self.state.read, &mut self.codec, &mut self.inner, map).await
+    }
+
+    /// See [`Framed::next_timestamped`](crate::Framed::next_timestamped) for docs.
+    pub async fn next_timestamped<U, K>(
+        &mut self,
+        clock: &K,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<(u64, U), ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        K: Clock,
+    {
+        functions::next_timestamped(
+            &mut self.state.read,
+            &mut self.codec,
+            &mut self.inner,
+            clock,
+            map,
+        )
+        .await
+    }
+
+    /// See [`Framed::next_into`](crate::Framed::next_into) for docs.
+    pub async fn next_into(
+        &mut self,
+        dst: &mut [u8],
+    ) -> Option<Result<usize, ReadError<RW::Error, C::Error>>>
+    where
+        C: Decoder,
+        for<'a> C::Item<'a>: AsRef<[u8]>,
+        RW: Read,
+        RB: Buffer,
+    {
+        functions::next_into(&mut self.state.read, &mut self.codec, &mut self.inner, dst).await
+    }
+
+    /// See [`Framed::route`](crate::Framed::route) for docs.
+    pub async fn route<F, K, T, D, E>(
+        &mut self,
+        router: &mut Router<F, T, D>,
+    ) -> Result<(), RouteError<RW::Error, C::Error, E>>
+    where
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        F: for<'a> FnMut(&C::Item<'a>) -> K,
+        T: for<'a> Routes<K, C::Item<'a>, Error = E>,
+        D: for<'a> Handler<C::Item<'a>, Error = E>,
+    {
+        functions::route(
+            &mut self.state.read,
+            &mut self.codec,
+            &mut self.inner,
+            router,
+        )
+        .await
+    }
+
+    /// See [`Framed::next_timeout`](crate::Framed::next_timeout) for docs.
+    pub async fn next_timeout<U, T>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+        timeout: T,
+    ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        T: Future<Output = ()>,
+    {
+        functions::next_timeout(
+            &mut self.state.read,
+            &mut self.codec,
+            &mut self.inner,
+            map,
+            timeout,
+        )
+        .await
+    }
+
+    /// See [`Framed::next_quiet`](crate::Framed::next_quiet) for docs.
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn next_quiet<U, D>(
+        &mut self,
+        timing: &mut Timing<D>,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        D: DelayNs,
+    {
+        functions::next_quiet(
+            &mut self.state.read,
+            &mut self.codec,
+            &mut self.inner,
+            timing,
+            map,
+        )
+        .await
+    }
+
+    /// See [`Framed::next_recorded`](crate::Framed::next_recorded) for docs.
+    #[cfg(feature = "embedded-storage-async")]
+    pub async fn next_recorded<U, S, K, const N: usize>(
+        &mut self,
+        recorder: &mut Recorder<S, K, N>,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        S: NorFlash,
+        K: Clock,
+    {
+        functions::next_recorded(
+            &mut self.state.read,
+            &mut self.codec,
+            &mut self.inner,
+            recorder,
+            map,
+        )
+        .await
+    }
+
+    /// See [`Framed::next_idle`](crate::Framed::next_idle) for docs.
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn next_idle<U, D>(
+        &mut self,
+        timing: &mut Timing<D>,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        D: DelayNs,
+    {
+        functions::next_idle(
+            &mut self.state.read,
+            &mut self.codec,
+            &mut self.inner,
+            timing,
+            map,
+        )
+        .await
+    }
+
+    /// See [`Framed::next_deadline`](crate::Framed::next_deadline) for docs.
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn next_deadline<U, D>(
+        &mut self,
+        timing: &mut Timing<D>,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+        D: DelayNs,
+    {
+        functions::next_deadline(
+            &mut self.state.read,
+            &mut self.codec,
+            &mut self.inner,
+            timing,
+            map,
+        )
+        .await
+    }
+
+    /// See [`Framed::try_next`](crate::Framed::try_next) for docs.
+    pub async fn try_next<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<Option<U>, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read + ReadReady,
+        RB: Buffer,
+    {
+        functions::try_next(&mut self.state.read, &mut self.codec, &mut self.inner, map).await
+    }
+
+    /// See [`Framed::next_payload_chunk`](crate::Framed::next_payload_chunk) for docs.
+    pub async fn next_payload_chunk(
+        &mut self,
+        remaining: &mut usize,
+    ) -> Option<Result<&[u8], ReadPayloadError<RW::Error>>>
+    where
+        RW: Read,
+        RB: Buffer,
+    {
+        functions::next_payload_chunk(&mut self.state.read, &mut self.inner, remaining).await
+    }
+
+    #[cfg(feature = "futures")]
     /// See [`Framed::stream`](crate::Framed::stream) for docs.
     pub fn stream<U>(
         &mut self,
-        map: fn(<C as Decoder<'_>>::Item) -> U,
+        map: fn(C::Item<'_>) -> U,
     ) -> impl Stream<Item = Result<U, ReadError<RW::Error, C::Error>>> + '_
     where
         U: 'static,
-        C: for<'a> Decoder<'a>,
+        C: Decoder,
         RW: Read,
+        RB: Buffer,
     {
         futures::stream::unfold((self, false), move |(this, errored)| async move {
             if errored {
@@ -115,11 +461,53 @@ impl<'buf, C, RW> FramedCore<'buf, C, RW> {
         })
     }
 
+    #[cfg(feature = "futures")]
+    /// See [`Framed::frames_until`](crate::Framed::frames_until) for docs.
+    pub fn frames_until<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+        until: fn(&U) -> bool,
+    ) -> impl Stream<Item = Result<U, ReadError<RW::Error, C::Error>>> + '_
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+    {
+        futures::stream::unfold((self, false), move |(this, done)| async move {
+            if done {
+                return None;
+            }
+
+            match this.next(map).await {
+                Some(Ok(item)) => {
+                    let done = until(&item);
+
+                    Some((Ok(item), (this, done)))
+                }
+                Some(Err(err)) => Some((Err(err), (this, true))),
+                None => None,
+            }
+        })
+    }
+
+    /// See [`Framed::send_future`](crate::Framed::send_future) for docs.
+    #[inline]
+    pub const fn send_future<I>(&mut self, item: I) -> ops::Send<'_, 'buf, C, RW, I, WB> {
+        ops::Send::new(
+            &mut self.state.write,
+            &mut self.codec,
+            &mut self.inner,
+            item,
+        )
+    }
+
     /// See [`Framed::send`](crate::Framed::send) for docs.
     pub async fn send<I>(&mut self, item: I) -> Result<(), WriteError<RW::Error, C::Error>>
     where
         C: Encoder<I>,
         RW: Write,
+        WB: Buffer,
     {
         functions::send(
             &mut self.state.write,
@@ -130,6 +518,267 @@ impl<'buf, C, RW> FramedCore<'buf, C, RW> {
         .await
     }
 
+    /// See [`Framed::send_recorded`](crate::Framed::send_recorded) for docs.
+    #[cfg(feature = "embedded-storage-async")]
+    pub async fn send_recorded<I, S, K, const N: usize>(
+        &mut self,
+        recorder: &mut Recorder<S, K, N>,
+        item: I,
+    ) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+        S: NorFlash,
+        K: Clock,
+    {
+        functions::send_recorded(
+            &mut self.state.write,
+            &mut self.codec,
+            &mut self.inner,
+            recorder,
+            item,
+        )
+        .await
+    }
+
+    /// See [`Framed::send_paced`](crate::Framed::send_paced) for docs.
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn send_paced<I, D>(
+        &mut self,
+        pacer: &mut Pacer<D>,
+        item: I,
+    ) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+        D: DelayNs,
+    {
+        functions::send_paced(
+            &mut self.state.write,
+            &mut self.codec,
+            &mut self.inner,
+            pacer,
+            item,
+        )
+        .await
+    }
+
+    /// See [`Framed::send_gapped`](crate::Framed::send_gapped) for docs.
+    #[cfg(feature = "embedded-hal-async")]
+    pub async fn send_gapped<I, D>(
+        &mut self,
+        timing: &mut Timing<D>,
+        item: I,
+    ) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+        D: DelayNs,
+    {
+        functions::send_gapped(
+            &mut self.state.write,
+            &mut self.codec,
+            &mut self.inner,
+            timing,
+            item,
+        )
+        .await
+    }
+
+    /// See [`Framed::send_all`](crate::Framed::send_all) for docs.
+    pub async fn send_all<I, T>(&mut self, items: T) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+        T: IntoIterator<Item = I>,
+    {
+        functions::send_all(
+            &mut self.state.write,
+            &mut self.codec,
+            &mut self.inner,
+            items,
+        )
+        .await
+    }
+
+    /// See [`Framed::flush`](crate::Framed::flush) for docs.
+    pub async fn flush(&mut self) -> Result<(), WriteError<RW::Error, Infallible>>
+    where
+        RW: Write,
+        WB: Buffer,
+    {
+        functions::flush(&mut self.state.write, &mut self.inner).await
+    }
+
+    /// See [`Framed::send_direct`](crate::Framed::send_direct) for docs.
+    pub async fn send_direct<I>(&mut self, item: I) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+    {
+        functions::send_direct(
+            &mut self.state.write,
+            &mut self.codec,
+            &mut self.inner,
+            item,
+        )
+        .await
+    }
+
+    /// See [`Framed::send_raw`](crate::Framed::send_raw) for docs.
+    pub async fn send_raw(&mut self, frame: &[u8]) -> Result<(), WriteError<RW::Error, Infallible>>
+    where
+        RW: Write,
+        WB: Buffer,
+    {
+        functions::send_raw(&mut self.state.write, &mut self.inner, frame).await
+    }
+
+    /// See [`Framed::send_parts`](crate::Framed::send_parts) for docs.
+    pub async fn send_parts<'a, I>(
+        &mut self,
+        item: I,
+    ) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: EncodeParts<'a, I>,
+        RW: Write,
+        WB: Buffer,
+    {
+        functions::send_parts(
+            &mut self.state.write,
+            &mut self.codec,
+            &mut self.inner,
+            item,
+        )
+        .await
+    }
+
+    /// See [`Framed::try_send`](crate::Framed::try_send) for docs.
+    pub async fn try_send<I>(&mut self, item: I) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write + WriteReady,
+        WB: Buffer,
+    {
+        functions::try_send(
+            &mut self.state.write,
+            &mut self.codec,
+            &mut self.inner,
+            item,
+        )
+        .await
+    }
+
+    /// See [`Framed::send_streamed`](crate::Framed::send_streamed) for docs.
+    pub async fn send_streamed<I, P>(
+        &mut self,
+        header: I,
+        payload: &mut P,
+        payload_len: usize,
+    ) -> Result<(), SendStreamedError<RW::Error, C::Error, P::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+        P: Read,
+    {
+        functions::send_streamed(
+            &mut self.state.write,
+            &mut self.codec,
+            &mut self.inner,
+            header,
+            payload,
+            payload_len,
+        )
+        .await
+    }
+
+    /// See [`Framed::close_with`](crate::Framed::close_with) for docs.
+    pub async fn close_with<I, U, T>(
+        &mut self,
+        frame: I,
+        map: fn(C::Item<'_>) -> U,
+        mut on_frame: impl FnMut(U),
+        timeout: T,
+    ) -> Result<(), CloseError<RW::Error, <C as Encoder<I>>::Error, <C as Decoder>::Error>>
+    where
+        U: 'static,
+        C: Encoder<I> + Decoder,
+        RW: Read + Write,
+        RB: Buffer,
+        WB: Buffer,
+        T: Future<Output = ()>,
+    {
+        self.send(frame).await?;
+
+        let mut timeout = pin!(timeout);
+
+        loop {
+            match self.next_timeout(map, timeout.as_mut()).await {
+                Some(Ok(item)) => on_frame(item),
+                Some(Err(err)) => return Err(CloseError::Read(err)),
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// See [`Framed::handshake`](crate::Framed::handshake) for docs.
+    pub async fn handshake<I, S, F, T>(
+        &mut self,
+        hello: I,
+        mut accept: F,
+        attempts: usize,
+        mut timeout: T,
+    ) -> Result<S, HandshakeError<RW::Error, <C as Encoder<I>>::Error, <C as Decoder>::Error>>
+    where
+        I: Clone,
+        C: Encoder<I> + Decoder,
+        RW: Read + Write,
+        RB: Buffer,
+        WB: Buffer,
+        F: for<'a> FnMut(C::Item<'a>) -> Option<S>,
+        T: AsyncFnMut(),
+    {
+        for _ in 0..attempts.max(1) {
+            self.send(hello.clone()).await?;
+
+            let ack = async {
+                loop {
+                    match functions::maybe_next(
+                        &mut self.state.read,
+                        &mut self.codec,
+                        &mut self.inner,
+                    )
+                    .await
+                    {
+                        Some(Ok(None)) => continue,
+                        Some(Ok(Some(item))) => {
+                            if let Some(session) = accept(item) {
+                                return Ok(session);
+                            }
+                        }
+                        Some(Err(err)) => return Err(HandshakeError::Read(err)),
+                        None => return Err(HandshakeError::Closed),
+                    }
+                }
+            };
+
+            // Reading is cancel safe, so frames buffered when the timeout completes are kept for the next attempt.
+            match select(pin!(ack), pin!(timeout())).await {
+                Either::Left(result) => return result,
+                Either::Right(()) => continue,
+            }
+        }
+
+        Err(HandshakeError::Read(ReadError::Timeout))
+    }
+
+    #[cfg(feature = "futures")]
     /// See [`Framed::sink`](crate::Framed::sink) for docs.
     pub fn sink<'this, I>(
         &'this mut self,
@@ -138,11 +787,32 @@ impl<'buf, C, RW> FramedCore<'buf, C, RW> {
         I: 'this,
         C: Encoder<I>,
         RW: Write,
+        WB: Buffer,
     {
-        futures::sink::unfold(self, |this, item: I| async move {
-            this.send(item).await?;
+        FramedSink::new(
+            &mut self.codec,
+            &mut self.inner,
+            &mut self.state.write,
+            |_| Ok(true),
+        )
+    }
 
-            Ok::<_, WriteError<RW::Error, C::Error>>(this)
-        })
+    #[cfg(feature = "futures")]
+    /// See [`Framed::ready_sink`](crate::Framed::ready_sink) for docs.
+    pub fn ready_sink<'this, I>(
+        &'this mut self,
+    ) -> impl Sink<I, Error = WriteError<RW::Error, C::Error>> + 'this
+    where
+        I: 'this,
+        C: Encoder<I>,
+        RW: Write + WriteReady,
+        WB: Buffer,
+    {
+        FramedSink::new(
+            &mut self.codec,
+            &mut self.inner,
+            &mut self.state.write,
+            RW::write_ready,
+        )
     }
 }
@@ -0,0 +1,56 @@
+//! Synchronous decoding of the frames that are already buffered.
+
+use core::marker::PhantomData;
+
+use crate::{ReadError, buffer::Buffer, decode::Decoder, functions, state::ReadState};
+
+/// Decodes the frames that are already buffered, one after another, without reading from the underlying source.
+///
+/// Returned by [`Framed::frames`](crate::Framed::frames) and [`FramedRead::frames`](crate::FramedRead::frames).
+///
+/// When many small frames arrive in a single read, draining them with [`Frames::next`] skips the async state machine of
+/// [`maybe_next`](crate::functions::maybe_next) between frames.
+/// The frames borrow the buffer, so [`Frames`] is not an [`Iterator`], but it is used the same way with `while let`.
+#[derive(Debug)]
+pub struct Frames<'this, 'buf, C, I, B = &'buf mut [u8]> {
+    state: &'this mut ReadState<'buf, B>,
+    codec: &'this mut C,
+    _error: PhantomData<fn() -> I>,
+}
+
+impl<'this, 'buf, C, I, B> Frames<'this, 'buf, C, I, B> {
+    /// Creates a new [`Frames`] over the given `state` and `codec`.
+    #[inline]
+    pub const fn new(state: &'this mut ReadState<'buf, B>, codec: &'this mut C) -> Self {
+        Self {
+            state,
+            codec,
+            _error: PhantomData,
+        }
+    }
+
+    /// Returns the number of buffered bytes that were not decoded yet.
+    #[inline]
+    pub const fn framable(&self) -> usize {
+        self.state.framable()
+    }
+}
+
+impl<C, I, B> Frames<'_, '_, C, I, B>
+where
+    C: Decoder,
+    B: Buffer,
+{
+    /// Decodes the next buffered frame.
+    ///
+    /// # Return value
+    ///
+    /// - `Some(Ok(frame))` if a frame was successfully decoded.
+    /// - `Some(Err(error))` if an error occurred. The caller should stop reading.
+    /// - `None` if the buffered bytes do not contain a complete frame.
+    ///   Reading with [`maybe_next`](crate::functions::maybe_next) continues with the rest of the buffered bytes.
+    #[allow(clippy::type_complexity, clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Result<C::Item<'_>, ReadError<I, C::Error>>> {
+        functions::decode_pending(self.state, self.codec)?.transpose()
+    }
+}
@@ -6,18 +6,37 @@
 //!
 //! E.g. the websockets protocol requires to respond to the `ping` frame with a `pong` frame with the same payload.
 
-use embedded_io_async::{Read, Write};
+use core::{convert::Infallible, pin::pin};
+
+use embedded_io_async::{ErrorType, Read, ReadReady, Write, WriteReady};
 
 use crate::{
-    ReadError, WriteError,
-    decode::Decoder,
-    encode::Encoder,
-    logging::{debug, error, trace, warn},
-    state::{ReadState, WriteState},
+    ForwardError, ReadError, ReadPayloadError, RouteError, SendStreamedError, WriteError,
+    buffer::Buffer,
+    clock::Clock,
+    decode::{Decoder, RingDecoder},
+    encode::{EncodeBufferTooSmall, EncodeParts, Encoder},
+    future::{Either, select},
+    logging::{debug, error, span_record, trace, warn},
+    record::record,
+    router::{Handler, Router, Routes},
+    state::{FrameMeta, ReadPhase, ReadState, RingReadState, WriteState, ring_consume, ring_parts},
+};
+
+#[cfg(feature = "embedded-hal-async")]
+use {
+    crate::{pacing::Pacer, timing::Timing},
+    embedded_hal_async::delay::DelayNs,
+};
+
+#[cfg(feature = "embedded-storage-async")]
+use {
+    crate::recorder::{Direction, Recorder},
+    embedded_storage_async::nor_flash::NorFlash,
 };
 
 #[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
-use crate::logging::Formatter;
+use crate::{fmt::Bytes, logging::Name};
 
 #[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
 const READ: &str = "framez::read";
@@ -34,66 +53,480 @@ const WRITE: &str = "framez::write";
 /// - `Some(Err(error))` if an error occurred. The caller should stop reading.
 /// - `None` if eof was reached. The caller should stop reading.
 ///
+/// # Cancel safety
+///
+/// This function is cancel safe as long as `read`'s [`Read::read`] is cancel safe.
+///
+/// The only suspension point is the call to [`Read::read`] and the [`ReadState`] is only updated after it completes,
+/// except for a pending shift of the buffered bytes, which is synchronous and happens right before the read.
+/// Dropping the returned future (e.g. in a `select!` branch) never loses bytes that are already in the buffer
+/// and never leaves the [`ReadState`] in an inconsistent state. Calling `maybe_next` again resumes where the cancelled call left off.
+///
 /// # Usage
 ///
 /// See [`next!`](crate::next!).
-pub async fn maybe_next<'buf, C, R>(
-    state: &'buf mut ReadState<'_>,
+///
+/// # Tracing
+///
+/// With the `tracing` feature, every call runs inside a `maybe_next` span with the fields `name`, `fill` (the buffered bytes),
+/// `read` (the bytes read), `frame_len` (the length announced by [`Decoder::decode_header`]) and `consumed` (the size of the decoded frame).
+pub async fn maybe_next<'buf, C, R, B>(
+    state: &'buf mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+) -> Option<Result<Option<C::Item<'buf>>, ReadError<R::Error, C::Error>>>
+where
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+{
+    #[cfg(feature = "tracing")]
+    let span = tracing::debug_span!(
+        target: READ,
+        "maybe_next",
+        name = state.name,
+        fill = state.framable(),
+        read = tracing::field::Empty,
+        frame_len = tracing::field::Empty,
+        consumed = tracing::field::Empty,
+    );
+
+    let next = maybe_next_unspanned(state, codec, read);
+
+    #[cfg(feature = "tracing")]
+    let next = tracing::Instrument::instrument(next, span);
+
+    next.await
+}
+
+async fn maybe_next_unspanned<'buf, C, R, B>(
+    state: &'buf mut ReadState<'_, B>,
     codec: &mut C,
     read: &mut R,
-) -> Option<Result<Option<C::Item>, ReadError<R::Error, C::Error>>>
+) -> Option<Result<Option<C::Item<'buf>>, ReadError<R::Error, C::Error>>>
 where
-    C: Decoder<'buf>,
+    C: Decoder,
     R: Read,
+    B: Buffer,
 {
-    trace!(target: READ, "maybe_next called");
+    trace!(target: READ, "{}maybe_next called", Name(state.name));
 
     debug!(
         target: READ,
-        "total_consumed: {}, index: {}, buffer: {:?}",
+        "{}total_consumed: {}, index: {}, buffer: {:?}", Name(state.name),
         state.total_consumed,
         state.index,
-        Formatter(&state.buffer[state.total_consumed..state.index])
+        Bytes(
+            state
+                .buffer
+                .as_slice()
+                .get(state.total_consumed..state.index)
+                .unwrap_or_default()
+        )
     );
 
-    if state.shift {
-        state
-            .buffer
-            .copy_within(state.total_consumed..state.index, 0);
+    match state.phase {
+        ReadPhase::Decode | ReadPhase::DecodeEof => decode_pending(state, codec),
+        ReadPhase::Read | ReadPhase::Shift => match read_into(state, read).await {
+            Ok(()) => Some(Ok(None)),
+            Err(err) => Some(Err(err.cast_decode())),
+        },
+    }
+}
 
-        state.index -= state.total_consumed;
-        state.total_consumed = 0;
+/// Tries to decode a frame from the bytes that are already buffered, without reading from the underlying source.
+///
+/// Meant for protocol layers that read on their own, e.g. using [`ReadState::writable_part`] and [`ReadState::commit`],
+/// and need to drain the buffered frames synchronously between IO operations.
+///
+/// # Return value
+///
+/// - `Some(Ok(None))` if the buffered bytes do not contain a frame. More bytes must be read before calling `decode_pending` again.
+/// - `Some(Ok(Some(frame)))` if a frame was successfully decoded. Call `decode_pending` again to decode the next buffered frame.
+/// - `Some(Err(error))` if an error occurred. The caller should stop reading.
+/// - `None` if eof was reached and all buffered frames were decoded. The caller should stop reading.
+#[allow(clippy::type_complexity)]
+#[inline]
+pub fn decode_pending<'buf, C, I, B>(
+    state: &'buf mut ReadState<'_, B>,
+    codec: &mut C,
+) -> Option<Result<Option<C::Item<'buf>>, ReadError<I, C::Error>>>
+where
+    C: Decoder,
+    B: Buffer,
+{
+    decode_buffered(state, codec).map(|result| result.map_err(ReadError::cast_io))
+}
+
+/// Implements [`decode_pending`] independently of the reader, so it is instantiated once per codec instead of once per codec and reader.
+#[allow(clippy::type_complexity)]
+fn decode_buffered<'buf, C, B>(
+    state: &'buf mut ReadState<'_, B>,
+    codec: &mut C,
+) -> Option<Result<Option<C::Item<'buf>>, ReadError<Infallible, C::Error>>>
+where
+    C: Decoder,
+    B: Buffer,
+{
+    let eof = match state.phase {
+        ReadPhase::Read => return Some(Ok(None)),
+        ReadPhase::Shift => {
+            shift(state);
+
+            return Some(Ok(None));
+        }
+        ReadPhase::Decode => false,
+        ReadPhase::DecodeEof => true,
+    };
+
+    let buf_len = state.buffer.as_slice().len();
+
+    let Some(src) = state
+        .buffer
+        .as_mut_slice()
+        .get_mut(state.total_consumed..state.index)
+    else {
+        error!(target: READ, "{}Invalid state", Name(state.name));
+
+        record!(state, error);
+
+        return Some(Err(ReadError::InvalidState));
+    };
+
+    let framable = src.len();
+
+    if eof {
+        trace!(target: READ, "{}Framing on EOF", Name(state.name));
+
+        // The announced length only defers decoding. Whatever the decoder returns, the next frame is measured again.
+        state.frame_len = None;
+
+        match codec.decode_eof(src) {
+            Ok(Some((_, size))) if size == 0 || size > framable => {
+                error!(target: READ, "{}Bad decoder. consumed: {}, framable: {}", Name(state.name), size, framable);
+
+                record!(state, error);
+
+                return Some(Err(ReadError::BadDecoder(size)));
+            }
+            Ok(Some((item, size))) => {
+                state.total_consumed = state.total_consumed.saturating_add(size);
+                state.stream_offset = state.stream_offset.wrapping_add(size as u64);
+                state.frame_count = state.frame_count.wrapping_add(1);
+
+                record!(state, frame);
+                span_record!(consumed = size);
+
+                debug!(
+                    target: READ,
+                    "{}Frame decoded, consumed: {}, total_consumed: {}", Name(state.name),
+                    size, state.total_consumed,
+                );
+
+                return Some(Ok(Some(item)));
+            }
+            Ok(None) => {
+                debug!(target: READ, "{}No frame decoded", Name(state.name));
+
+                state.phase = ReadPhase::Read;
+
+                if state.index != state.total_consumed {
+                    error!(target: READ, "{}Bytes remaining on stream", Name(state.name));
+
+                    record!(state, error);
+
+                    return Some(Err(ReadError::BytesRemainingOnStream));
+                }
+
+                return None;
+            }
+            Err(err) => {
+                error!(target: READ, "{}Failed to decode frame", Name(state.name));
+
+                record!(state, error);
+
+                return Some(Err(ReadError::Decode(err)));
+            }
+        };
+    }
+
+    if state.frame_len.is_none() {
+        state.frame_len = codec.decode_header(src);
+    }
+
+    if let Some(frame_len) = state.frame_len {
+        span_record!(frame_len = frame_len);
+
+        if frame_len > buf_len {
+            error!(target: READ, "{}Buffer too small. frame_len: {}", Name(state.name), frame_len);
+
+            record!(state, error);
+
+            return Some(Err(ReadError::BufferTooSmall));
+        }
+
+        if src.len() < frame_len {
+            trace!(target: READ, "{}Partial frame. framable: {}, frame_len: {}", Name(state.name), src.len(), frame_len);
+
+            // Shift if the rest of the frame does not fit after the buffered bytes.
+            let shift = state.total_consumed.saturating_add(frame_len) > buf_len
+                || state
+                    .shift_policy
+                    .should_shift(state.total_consumed, state.index, buf_len);
+
+            state.phase = if shift {
+                ReadPhase::Shift
+            } else {
+                ReadPhase::Read
+            };
+
+            return Some(Ok(None));
+        }
+    }
+
+    trace!(target: READ, "{}Framing", Name(state.name));
+
+    // The announced length only defers decoding. Whatever the decoder returns, the next frame is measured again.
+    state.frame_len = None;
+
+    match codec.decode(src) {
+        Ok(Some((_, size))) if size == 0 || size > framable => {
+            error!(target: READ, "{}Bad decoder. consumed: {}, framable: {}", Name(state.name), size, framable);
+
+            record!(state, error);
+
+            Some(Err(ReadError::BadDecoder(size)))
+        }
+        Ok(Some((item, size))) => {
+            state.total_consumed = state.total_consumed.saturating_add(size);
+            state.stream_offset = state.stream_offset.wrapping_add(size as u64);
+            state.frame_count = state.frame_count.wrapping_add(1);
+
+            record!(state, frame);
+            span_record!(consumed = size);
+
+            debug!(
+                target: READ,
+                "{}Frame decoded, consumed: {}, total_consumed: {}", Name(state.name),
+                size, state.total_consumed,
+            );
+
+            Some(Ok(Some(item)))
+        }
+        Ok(None) => {
+            debug!(target: READ, "{}No frame decoded", Name(state.name));
+
+            let shift = state
+                .shift_policy
+                .should_shift(state.total_consumed, state.index, buf_len);
+
+            state.phase = if shift {
+                ReadPhase::Shift
+            } else {
+                ReadPhase::Read
+            };
+
+            Some(Ok(None))
+        }
+        Err(err) => {
+            error!(target: READ, "{}Failed to decode frame", Name(state.name));
+
+            record!(state, error);
+
+            Some(Err(ReadError::Decode(err)))
+        }
+    }
+}
+
+/// Performs exactly one read from `read` into the buffer, without decoding.
+///
+/// The framable bytes are shifted to the start of the buffer first, if the [`ShiftPolicy`](crate::state::ShiftPolicy) requested it.
+/// Combined with [`decode_pending`], this allows scheduling reads and decodes independently.
+///
+/// # Return value
+///
+/// The number of bytes read. `0` means eof was reached and [`decode_pending`] decodes the rest of the buffered bytes with [`Decoder::decode_eof`].
+///
+/// # Errors
+///
+/// - [`ReadError::BufferTooSmall`] if the buffer is full.
+/// - [`ReadError::IO`] if reading failed.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`].
+pub async fn fill<R, D, B>(
+    state: &mut ReadState<'_, B>,
+    read: &mut R,
+) -> Result<usize, ReadError<R::Error, D>>
+where
+    R: Read,
+    B: Buffer,
+{
+    // The framable bytes are kept by the shift, unlike the index.
+    let framable = state.framable();
+
+    read_into(state, read)
+        .await
+        .map_err(ReadError::cast_decode)?;
+
+    Ok(state.framable().saturating_sub(framable))
+}
+
+/// Shifts the framable bytes to the start of the buffer.
+///
+/// Synchronous, so it can not be interrupted by a cancellation.
+pub(crate) fn shift<B: Buffer>(state: &mut ReadState<'_, B>) {
+    let buffer = state.buffer.as_mut_slice();
+
+    if state.total_consumed > state.index || state.index > buffer.len() {
+        // Leave an inconsistent state as it is, decoding reports it.
+        return;
+    }
+
+    buffer.copy_within(state.total_consumed..state.index, 0);
+
+    state.index = state.index.saturating_sub(state.total_consumed);
+    state.total_consumed = 0;
+
+    trace!(target: READ, "{}Buffer shifted. copied: {}", Name(state.name), state.framable());
+
+    state.phase = ReadPhase::Read;
+}
+
+/// Reads more bytes into the free region of the buffer.
+///
+/// A pending shift is performed first, so a full buffer costs a single call to [`maybe_next`].
+///
+/// The read is the only suspension point of [`maybe_next`]. The shift is synchronous and the [`ReadState`] is otherwise only updated after the read completes,
+/// so dropping the future while it is pending leaves the buffered bytes intact.
+pub(crate) async fn read_into<R, B>(
+    state: &mut ReadState<'_, B>,
+    read: &mut R,
+) -> Result<(), ReadError<R::Error, Infallible>>
+where
+    R: Read,
+    B: Buffer,
+{
+    if state.phase == ReadPhase::Shift {
+        shift(state);
+    }
+
+    let Some(dst) = state
+        .buffer
+        .as_mut_slice()
+        .get_mut(state.index..)
+        .filter(|dst| !dst.is_empty())
+    else {
+        error!(target: READ, "{}Buffer too small", Name(state.name));
+
+        record!(state, error);
+
+        return Err(ReadError::BufferTooSmall);
+    };
+
+    trace!(target: READ, "{}Reading", Name(state.name));
+
+    let result = read.read(dst).await;
+
+    match result {
+        Err(err) => {
+            error!(target: READ, "{}Failed to read", Name(state.name));
+
+            record!(state, error);
+
+            Err(ReadError::IO(err))
+        }
+        Ok(0) => {
+            warn!(target: READ, "{}Got EOF", Name(state.name));
+
+            state.phase = ReadPhase::DecodeEof;
+
+            Ok(())
+        }
+        Ok(n) => {
+            record!(state, bytes, n);
+            span_record!(read = n);
+
+            debug!(target: READ, "{}Bytes read. bytes: {}", Name(state.name), n);
+
+            if !state.quota.try_consume(n) {
+                error!(target: READ, "{}Quota exceeded. read: {}", Name(state.name), state.quota.read());
+
+                record!(state, error);
 
-        trace!(target: READ, "Buffer shifted. copied: {}", state.framable());
+                return Err(ReadError::QuotaExceeded);
+            }
+
+            state.index = state.index.saturating_add(n);
 
-        state.shift = false;
+            state.phase = ReadPhase::Decode;
 
-        return Some(Ok(None));
+            Ok(())
+        }
     }
+}
+
+/// Like [`maybe_next`], but reads into a ring buffer.
+///
+/// The buffered bytes are never shifted to the start of the buffer. Instead, new bytes are read into the
+/// free region after the buffered bytes, wrapping around the end of the buffer.
+/// The [`RingDecoder`] receives the buffered bytes as two sequential slices.
+///
+/// # Return value
+///
+/// Same as [`maybe_next`].
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`].
+pub async fn maybe_next_ring<'buf, C, R, B>(
+    state: &'buf mut RingReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+) -> Option<Result<Option<C::Item<'buf>>, ReadError<R::Error, C::Error>>>
+where
+    C: RingDecoder,
+    R: Read,
+    B: Buffer,
+{
+    trace!(target: READ, "{}maybe_next_ring called", Name(state.name));
+
+    debug!(target: READ, "{}start: {}, len: {}", Name(state.name), state.start, state.len);
 
     if state.is_framable {
+        let capacity = state.buffer.as_slice().len();
+        let (head, tail) = ring_parts(state.buffer.as_mut_slice(), state.start, state.len);
+
         if state.eof {
-            trace!(target: READ, "Framing on EOF");
+            trace!(target: READ, "{}Framing on EOF", Name(state.name));
+
+            match codec.decode_ring_eof(head, tail) {
+                Ok(Some((_, size))) if size == 0 || size > state.len => {
+                    error!(target: READ, "{}Bad decoder. consumed: {}, framable: {}", Name(state.name), size, state.len);
+
+                    record!(state, error);
 
-            match codec.decode_eof(&mut state.buffer[state.total_consumed..state.index]) {
+                    return Some(Err(ReadError::BadDecoder(size)));
+                }
                 Ok(Some((item, size))) => {
-                    state.total_consumed += size;
+                    (state.start, state.len) = ring_consume(capacity, state.start, state.len, size);
+
+                    record!(state, frame);
 
-                    debug!(
-                        target: READ,
-                        "Frame decoded, consumed: {}, total_consumed: {}",
-                        size, state.total_consumed,
-                    );
+                    debug!(target: READ, "{}Frame decoded, consumed: {}, len: {}", Name(state.name), size, state.len);
 
                     return Some(Ok(Some(item)));
                 }
                 Ok(None) => {
-                    debug!(target: READ, "No frame decoded");
+                    debug!(target: READ, "{}No frame decoded", Name(state.name));
 
                     state.is_framable = false;
 
-                    if state.index != state.total_consumed {
-                        error!(target: READ, "Bytes remaining on stream");
+                    if state.len != 0 {
+                        error!(target: READ, "{}Bytes remaining on stream", Name(state.name));
+
+                        record!(state, error);
 
                         return Some(Err(ReadError::BytesRemainingOnStream));
                     }
@@ -101,62 +534,74 @@ where
                     return None;
                 }
                 Err(err) => {
-                    error!(target: READ, "Failed to decode frame");
+                    error!(target: READ, "{}Failed to decode frame", Name(state.name));
+
+                    record!(state, error);
 
                     return Some(Err(ReadError::Decode(err)));
                 }
-            };
+            }
         }
 
-        trace!(target: READ, "Framing");
+        trace!(target: READ, "{}Framing", Name(state.name));
 
-        let buf_len = state.buffer.len();
+        match codec.decode_ring(head, tail) {
+            Ok(Some((_, size))) if size == 0 || size > state.len => {
+                error!(target: READ, "{}Bad decoder. consumed: {}, framable: {}", Name(state.name), size, state.len);
 
-        match codec.decode(&mut state.buffer[state.total_consumed..state.index]) {
+                record!(state, error);
+
+                return Some(Err(ReadError::BadDecoder(size)));
+            }
             Ok(Some((item, size))) => {
-                state.total_consumed += size;
+                (state.start, state.len) = ring_consume(capacity, state.start, state.len, size);
 
-                debug!(
-                    target: READ,
-                    "Frame decoded, consumed: {}, total_consumed: {}",
-                    size, state.total_consumed,
-                );
+                record!(state, frame);
+
+                debug!(target: READ, "{}Frame decoded, consumed: {}, len: {}", Name(state.name), size, state.len);
 
                 return Some(Ok(Some(item)));
             }
             Ok(None) => {
-                debug!(target: READ, "No frame decoded");
-
-                state.shift = state.index >= buf_len;
+                debug!(target: READ, "{}No frame decoded", Name(state.name));
 
                 state.is_framable = false;
 
                 return Some(Ok(None));
             }
             Err(err) => {
-                error!(target: READ, "Failed to decode frame");
+                error!(target: READ, "{}Failed to decode frame", Name(state.name));
+
+                record!(state, error);
 
                 return Some(Err(ReadError::Decode(err)));
             }
         }
     }
 
-    if state.index >= state.buffer.len() {
-        error!(target: READ, "Buffer too small");
+    if state.free() == 0 {
+        error!(target: READ, "{}Buffer too small", Name(state.name));
+
+        record!(state, error);
 
         return Some(Err(ReadError::BufferTooSmall));
     }
 
-    trace!(target: READ, "Reading");
+    trace!(target: READ, "{}Reading", Name(state.name));
 
-    match read.read(&mut state.buffer[state.index..]).await {
+    // This is the only suspension point. See `maybe_next`.
+    let result = read.read(state.writable()).await;
+
+    match result {
         Err(err) => {
-            error!(target: READ, "Failed to read");
+            error!(target: READ, "{}Failed to read", Name(state.name));
+
+            record!(state, error);
 
             Some(Err(ReadError::IO(err)))
         }
         Ok(0) => {
-            warn!(target: READ, "Got EOF");
+            warn!(target: READ, "{}Got EOF", Name(state.name));
 
             state.eof = true;
 
@@ -165,9 +610,17 @@ where
             Some(Ok(None))
         }
         Ok(n) => {
-            debug!(target: READ, "Bytes read. bytes: {}", n);
+            record!(state, bytes, n);
+
+            debug!(target: READ, "{}Bytes read. bytes: {}", Name(state.name), n);
+
+            if state.commit(n).is_err() {
+                error!(target: READ, "{}Invalid state", Name(state.name));
 
-            state.index += n;
+                record!(state, error);
+
+                return Some(Err(ReadError::InvalidState));
+            }
 
             state.is_framable = true;
 
@@ -176,95 +629,3547 @@ where
     }
 }
 
-/// Like [`maybe_next`], but maps the decoded item to another type using the provided `map` function.
+/// Reads the next chunk of a payload that follows a decoded frame in the stream.
 ///
-/// The output type `U` is static. This means it is decoupled from the lifetime of the [`ReadState`].
-pub async fn maybe_next_mapped<'buf, C, R, U>(
-    state: &'buf mut ReadState<'_>,
-    codec: &mut C,
-    read: &mut R,
-    map: fn(<C as Decoder<'_>>::Item) -> U,
-) -> Option<Result<Option<U>, ReadError<R::Error, C::Error>>>
-where
-    U: 'static,
-    C: for<'a> Decoder<'a>,
-    R: Read,
-{
-    match maybe_next(state, codec, read).await {
-        Some(Ok(Some(item))) => Some(Ok(Some(map(item)))),
-        Some(Ok(None)) => Some(Ok(None)),
-        Some(Err(err)) => Some(Err(err)),
-        None => None,
-    }
-}
-
-/// Tries to read a frame and converts it using the given `map` function.
+/// This allows reading frames that are larger than the read buffer.
+/// The decoder decodes only the frame header and reports its size as consumed.
+/// The header tells the caller the length of the payload, e.g. through [`PayloadHeader`](crate::decode::PayloadHeader),
+/// which is then read in chunks using this function.
+/// The chunks are borrowed from the read buffer: first the already buffered bytes, then bytes read from `read`.
+///
+/// `remaining` is the number of payload bytes that are yet to be read and is decreased by the size of every returned chunk.
+/// After the payload is read, [`maybe_next`] continues decoding frames right after the payload.
 ///
 /// # Return value
 ///
-/// - `Some(Ok(U))` if a frame was successfully decoded and mapped. Call `next` again to read more frames.
+/// - `Some(Ok(chunk))` if a chunk of at most `remaining` bytes was read. Call `next_payload_chunk` again to read the rest of the payload.
 /// - `Some(Err(error))` if an error occurred. The caller should stop reading.
-/// - `None` if eof was reached. The caller should stop reading.
-pub async fn next<'buf, C, R, U>(
-    state: &'buf mut ReadState<'_>,
-    codec: &mut C,
+/// - `None` if `remaining` is `0`.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`].
+pub async fn next_payload_chunk<'buf, R, B>(
+    state: &'buf mut ReadState<'_, B>,
     read: &mut R,
-    map: fn(<C as Decoder<'_>>::Item) -> U,
-) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+    remaining: &mut usize,
+) -> Option<Result<&'buf [u8], ReadPayloadError<R::Error>>>
 where
-    U: 'static,
-    C: for<'a> Decoder<'a>,
     R: Read,
+    B: Buffer,
 {
-    loop {
-        match maybe_next_mapped(state, codec, read, map).await {
-            Some(Ok(None)) => continue,
-            Some(Ok(Some(item))) => return Some(Ok(item)),
-            Some(Err(err)) => return Some(Err(err)),
-            None => return None,
-        }
+    trace!(target: READ, "{}next_payload_chunk called. remaining: {}", Name(state.name), *remaining);
+
+    if *remaining == 0 {
+        return None;
     }
-}
 
-/// Sends a frame.
-pub async fn send<C, W, I>(
-    state: &mut WriteState<'_>,
-    codec: &mut C,
-    write: &mut W,
-    item: I,
-) -> Result<(), WriteError<W::Error, C::Error>>
-where
-    C: Encoder<I>,
-    W: Write,
-{
-    match codec.encode(item, state.buffer) {
-        Ok(size) => match write.write_all(&state.buffer[..size]).await {
-            Ok(_) => {
-                trace!(target: WRITE, "Wrote. buffer: {:?}", Formatter(&state.buffer[..size]));
+    if state.framable() == 0 {
+        if state.phase == ReadPhase::DecodeEof {
+            error!(target: READ, "{}Got EOF while reading payload", Name(state.name));
 
-                match write.flush().await {
-                    Ok(_) => {
-                        debug!(target: WRITE, "Flushed. bytes: {}", size);
+            record!(state, error);
 
-                        Ok(())
-                    }
-                    Err(err) => {
-                        error!(target: WRITE, "Failed to flush");
+            return Some(Err(ReadPayloadError::UnexpectedEof));
+        }
 
-                        Err(WriteError::IO(err))
-                    }
-                }
-            }
-            Err(err) => {
-                error!(target: WRITE, "Failed to write frame");
+        if state.buffer.as_slice().is_empty() {
+            error!(target: READ, "{}Buffer too small", Name(state.name));
 
-                Err(WriteError::IO(err))
+            record!(state, error);
+
+            return Some(Err(ReadPayloadError::BufferTooSmall));
+        }
+
+        // Nothing is buffered, so the whole buffer can be reused without shifting.
+        state.index = 0;
+        state.total_consumed = 0;
+        state.phase = ReadPhase::Read;
+
+        trace!(target: READ, "{}Reading payload", Name(state.name));
+
+        let result = read.read(state.buffer.as_mut_slice()).await;
+
+        match result {
+            Err(err) => {
+                error!(target: READ, "{}Failed to read", Name(state.name));
+
+                record!(state, error);
+
+                return Some(Err(ReadPayloadError::IO(err)));
+            }
+            Ok(0) => {
+                error!(target: READ, "{}Got EOF while reading payload", Name(state.name));
+
+                record!(state, error);
+
+                state.phase = ReadPhase::DecodeEof;
+
+                return Some(Err(ReadPayloadError::UnexpectedEof));
+            }
+            Ok(n) => {
+                record!(state, bytes, n);
+
+                debug!(target: READ, "{}Bytes read. bytes: {}", Name(state.name), n);
+
+                if !state.quota.try_consume(n) {
+                    error!(target: READ, "{}Quota exceeded. read: {}", Name(state.name), state.quota.read());
+
+                    record!(state, error);
+
+                    return Some(Err(ReadPayloadError::QuotaExceeded));
+                }
+
+                state.index = n;
+            }
+        }
+    }
+
+    let size = core::cmp::min(state.framable(), *remaining);
+    let start = state.total_consumed;
+
+    state.total_consumed = state.total_consumed.saturating_add(size);
+    state.stream_offset = state.stream_offset.wrapping_add(size as u64);
+
+    // The bytes after the payload are decoded next, on EOF if it was reached.
+    if state.phase != ReadPhase::DecodeEof {
+        state.phase = ReadPhase::Decode;
+    }
+
+    *remaining = remaining.saturating_sub(size);
+
+    debug!(
+        target: READ,
+        "{}Payload chunk read. size: {}, remaining: {}", Name(state.name),
+        size,
+        *remaining
+    );
+
+    match state
+        .buffer
+        .as_slice()
+        .get(start..start.saturating_add(size))
+    {
+        Some(chunk) => Some(Ok(chunk)),
+        None => {
+            error!(target: READ, "{}Invalid state", Name(state.name));
+
+            record!(state, error);
+
+            Some(Err(ReadPayloadError::InvalidState))
+        }
+    }
+}
+
+/// Like [`maybe_next`], but maps the decoded item to another type using the provided `map` function.
+///
+/// The output type `U` is static. This means it is decoupled from the lifetime of the [`ReadState`].
+pub async fn maybe_next_mapped<'buf, C, R, U, B>(
+    state: &'buf mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    map: fn(C::Item<'_>) -> U,
+) -> Option<Result<Option<U>, ReadError<R::Error, C::Error>>>
+where
+    U: 'static,
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+{
+    match maybe_next(state, codec, read).await {
+        Some(Ok(Some(item))) => Some(Ok(Some(map(item)))),
+        Some(Ok(None)) => Some(Ok(None)),
+        Some(Err(err)) => Some(Err(err)),
+        None => None,
+    }
+}
+
+/// Skips the next `remaining` raw bytes, starting with the buffered ones.
+///
+/// Used to resynchronize after garbage was received, e.g. boot noise of a rebooted device in the middle of a binary stream.
+/// `remaining` is decremented as bytes are skipped. The bytes read past the skipped ones stay buffered for decoding.
+///
+/// # Errors
+///
+/// Same as [`next_payload_chunk`].
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`next_payload_chunk`]. `remaining` holds the number of bytes that were not skipped yet.
+pub async fn drain<R, B>(
+    state: &mut ReadState<'_, B>,
+    read: &mut R,
+    remaining: &mut usize,
+) -> Result<(), ReadPayloadError<R::Error>>
+where
+    R: Read,
+    B: Buffer,
+{
+    while let Some(chunk) = next_payload_chunk(state, read, remaining).await {
+        let _ = chunk?;
+    }
+
+    debug!(target: READ, "{}Drained", Name(state.name));
+
+    Ok(())
+}
+
+/// Tries to read a frame and converts it using the given `map` function.
+///
+/// # Return value
+///
+/// - `Some(Ok(U))` if a frame was successfully decoded and mapped. Call `next` again to read more frames.
+/// - `Some(Err(error))` if an error occurred. The caller should stop reading.
+/// - `None` if eof was reached. The caller should stop reading.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`].
+pub async fn next<'buf, C, R, U, B>(
+    state: &'buf mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    map: fn(C::Item<'_>) -> U,
+) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+where
+    U: 'static,
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+{
+    loop {
+        match maybe_next_mapped(state, codec, read, map).await {
+            Some(Ok(None)) => continue,
+            Some(Ok(Some(item))) => return Some(Ok(item)),
+            Some(Err(err)) => return Some(Err(err)),
+            None => return None,
+        }
+    }
+}
+
+/// Like [`next`], but also returns the [`FrameMeta`] of the frame.
+///
+/// The offset and the index of the frame are taken from [`ReadState::stream_offset`] and [`ReadState::frame_count`],
+/// so they count from the creation of the state, or its last [`reset`](ReadState::reset).
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`].
+pub async fn next_with_meta<C, R, U, B>(
+    state: &mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    map: fn(C::Item<'_>) -> U,
+) -> Option<Result<(FrameMeta, U), ReadError<R::Error, C::Error>>>
+where
+    U: 'static,
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+{
+    loop {
+        let offset = state.stream_offset;
+
+        match maybe_next_mapped(state, codec, read, map).await {
+            Some(Ok(None)) => continue,
+            Some(Ok(Some(item))) => {
+                let meta = FrameMeta {
+                    offset,
+                    len: state.stream_offset.wrapping_sub(offset) as usize,
+                    index: state.frame_count.wrapping_sub(1),
+                };
+
+                return Some(Ok((meta, item)));
+            }
+            Some(Err(err)) => return Some(Err(err)),
+            None => return None,
+        }
+    }
+}
+
+/// Like [`next`], but also returns the tick count of the `clock` at which the frame was received.
+///
+/// The frame is stamped right after it was decoded, so a frame completed by a read is stamped once the read returned.
+/// Frames that were already buffered are stamped when they are decoded.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`].
+pub async fn next_timestamped<C, R, U, B, K>(
+    state: &mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    clock: &K,
+    map: fn(C::Item<'_>) -> U,
+) -> Option<Result<(u64, U), ReadError<R::Error, C::Error>>>
+where
+    U: 'static,
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+    K: Clock,
+{
+    loop {
+        match maybe_next_mapped(state, codec, read, map).await {
+            Some(Ok(None)) => continue,
+            Some(Ok(Some(item))) => return Some(Ok((clock.now(), item))),
+            Some(Err(err)) => return Some(Err(err)),
+            None => return None,
+        }
+    }
+}
+
+/// Like [`next`], but also appends the raw bytes of the frame to the log of the `recorder`.
+///
+/// The raw bytes are the bytes consumed by the decoder, as they are left in the read buffer after decoding,
+/// so decoders that transform frames in place, like [`Cobs`](crate::codec::cobs::Cobs), record the transformed bytes.
+///
+/// # Return value
+///
+/// Same as [`next`]. A frame that could not be recorded is still returned and counted in [`Recorder::failures`].
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`], as long as the storage is cancel safe.
+/// A frame is consumed once it is decoded, so it is lost if the future is dropped while the frame is recorded.
+#[cfg(feature = "embedded-storage-async")]
+pub async fn next_recorded<C, R, U, B, S, K, const N: usize>(
+    state: &mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    recorder: &mut Recorder<S, K, N>,
+    map: fn(C::Item<'_>) -> U,
+) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+where
+    U: 'static,
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+    S: NorFlash,
+    K: Clock,
+{
+    loop {
+        if state.phase != ReadPhase::Read {
+            let start = state.total_consumed;
+
+            let item = decode_pending(state, codec)?.map(|item| item.map(map));
+
+            match item {
+                Ok(Some(item)) => {
+                    let frame = state
+                        .buffer
+                        .as_slice()
+                        .get(start..state.total_consumed)
+                        .unwrap_or_default();
+
+                    if recorder.record(Direction::Read, frame).await.is_err() {
+                        warn!(target: READ, "{}Failed to record frame. failures: {}", Name(state.name), recorder.failures());
+                    }
+
+                    return Some(Ok(item));
+                }
+                Ok(None) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        if let Err(err) = read_into(state, read).await {
+            return Some(Err(err.cast_decode()));
+        }
+    }
+}
+
+/// Tries to read a frame and copies its bytes into `dst`.
+///
+/// Gives owned frames without `alloc` or `heapless`, e.g. to forward frames into DMA transmit buffers.
+///
+/// # Return value
+///
+/// - `Some(Ok(len))` if a frame was successfully decoded and its `len` bytes were copied to the start of `dst`. Call `next_into` again to read more frames.
+/// - `Some(Err(error))` if an error occurred. [`ReadError::FrameTooLarge`] if the frame does not fit into `dst`. The frame is dropped and reading can continue.
+/// - `None` if eof was reached. The caller should stop reading.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`].
+pub async fn next_into<C, R, B>(
+    state: &mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    dst: &mut [u8],
+) -> Option<Result<usize, ReadError<R::Error, C::Error>>>
+where
+    C: Decoder,
+    for<'a> C::Item<'a>: AsRef<[u8]>,
+    R: Read,
+    B: Buffer,
+{
+    #[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
+    let name = state.name;
+
+    loop {
+        match maybe_next(state, codec, read).await {
+            Some(Ok(None)) => continue,
+            Some(Ok(Some(item))) => {
+                let item = item.as_ref();
+
+                let Some(dst) = dst.get_mut(..item.len()) else {
+                    error!(target: READ, "{}Frame too large. len: {}", Name(name), item.len());
+
+                    return Some(Err(ReadError::FrameTooLarge(item.len())));
+                };
+
+                dst.copy_from_slice(item);
+
+                return Some(Ok(item.len()));
+            }
+            Some(Err(err)) => return Some(Err(err)),
+            None => return None,
+        }
+    }
+}
+
+/// Returns `true` if [`maybe_next`] can make progress without waiting for `read`.
+///
+/// That is the case if the buffered bytes can be framed, the buffer is full, or `read` is ready to be read from without blocking.
+///
+/// # Usage
+///
+/// See [`try_next!`](crate::try_next!).
+pub fn read_ready<R, B>(state: &ReadState<'_, B>, read: &mut R) -> Result<bool, R::Error>
+where
+    R: Read + ReadReady,
+    B: Buffer,
+{
+    if matches!(state.phase, ReadPhase::Decode | ReadPhase::DecodeEof)
+        || state.framable() >= state.buffer.as_slice().len()
+    {
+        return Ok(true);
+    }
+
+    read.read_ready()
+}
+
+/// Like [`next`], but gives up once the `timeout` future completes.
+///
+/// The `timeout` is any future that completes after the desired duration, e.g. `delay.delay_ms(100)` using an
+/// [`embedded_hal_async::delay::DelayNs`](https://docs.rs/embedded-hal-async/latest/embedded_hal_async/delay/trait.DelayNs.html),
+/// `embassy_time::Timer::after_millis(100)` or `tokio::time::sleep(Duration::from_millis(100))`.
+///
+/// # Return value
+///
+/// Same as [`next`]. [`ReadError::Timeout`] is returned if the `timeout` completes before a frame is decoded.
+/// The buffered bytes are kept, so calling `next_timeout` or [`next`] again resumes reading.
+///
+/// # Usage
+///
+/// See [`next_timeout!`](crate::next_timeout!) for borrowed frames.
+pub async fn next_timeout<'buf, C, R, U, B, T>(
+    state: &'buf mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    map: fn(C::Item<'_>) -> U,
+    timeout: T,
+) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+where
+    U: 'static,
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+    T: Future<Output = ()>,
+{
+    #[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
+    let name = state.name;
+
+    // `next` is cancel safe, so dropping it when the timeout completes keeps the buffered bytes.
+    match select(pin!(next(state, codec, read, map)), pin!(timeout)).await {
+        Either::Left(item) => item,
+        Either::Right(()) => {
+            warn!(target: READ, "{}Timeout", Name(name));
+
+            Some(Err(ReadError::Timeout))
+        }
+    }
+}
+
+/// Like [`next`], but a frame also ends once the bus is quiet for the [`Timing::quiet_us`], e.g. the T3.5 interval of Modbus RTU.
+///
+/// The quiet time is measured from the last received bytes and only while bytes are buffered.
+/// Once it elapses, the buffered bytes are decoded with [`Decoder::decode_eof`], without ending the stream.
+///
+/// # Return value
+///
+/// Same as [`next`]. [`ReadError::BytesRemainingOnStream`] is returned if the bus went quiet and the buffered bytes do not contain a frame.
+/// The bytes are discarded, so calling `next_quiet` again waits for the next frame.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`].
+#[cfg(feature = "embedded-hal-async")]
+pub async fn next_quiet<C, R, U, B, D>(
+    state: &mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    timing: &mut Timing<D>,
+    map: fn(C::Item<'_>) -> U,
+) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+where
+    U: 'static,
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+    D: DelayNs,
+{
+    loop {
+        if state.phase != ReadPhase::Read {
+            match decode_pending(state, codec)? {
+                Ok(Some(item)) => return Some(Ok(map(item))),
+                Ok(None) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        if state.framable() == 0 {
+            if let Err(err) = read_into(state, read).await {
+                return Some(Err(err.cast_decode()));
+            }
+
+            continue;
+        }
+
+        let quiet_us = timing.quiet_us();
+
+        // `read_into` is cancel safe, so dropping it when the bus is quiet keeps the buffered bytes.
+        match select(
+            pin!(read_into(state, read)),
+            pin!(timing.delay_mut().delay_us(quiet_us)),
+        )
+        .await
+        {
+            Either::Left(Ok(())) => continue,
+            Either::Left(Err(err)) => return Some(Err(err.cast_decode())),
+            Either::Right(()) => {}
+        }
+
+        trace!(target: READ, "{}Bus quiet. framable: {}", Name(state.name), state.framable());
+
+        state.phase = ReadPhase::DecodeEof;
+
+        let item = decode_pending(state, codec).map(|result| result.map(|item| item.map(map)));
+
+        // The stream did not end, so the rest of the buffered bytes are decoded as usual.
+        if state.phase == ReadPhase::DecodeEof {
+            state.phase = ReadPhase::Decode;
+        }
+
+        match item {
+            Some(Ok(Some(item))) => return Some(Ok(item)),
+            Some(Err(ReadError::BytesRemainingOnStream)) => {
+                warn!(target: READ, "{}Incomplete frame on quiet bus. discarded: {}", Name(state.name), state.framable());
+
+                state.discard_buffered();
+
+                return Some(Err(ReadError::BytesRemainingOnStream));
+            }
+            Some(Err(err)) => return Some(Err(err)),
+            Some(Ok(None)) | None => continue,
+        }
+    }
+}
+
+/// Like [`next`], but a frame must complete within the [`Timing::frame_deadline_us`], measured from its first byte.
+///
+/// The deadline starts once bytes of a partial frame are buffered, i.e. after the read that returned the first byte of the frame,
+/// or right away if bytes are already buffered. Complete frames that are already buffered are returned without waiting.
+///
+/// # Return value
+///
+/// Same as [`next`]. [`ReadError::FrameDeadline`] is returned if the deadline elapsed before the frame was decoded.
+/// The partial frame is discarded, so calling `next_deadline` again waits for the next frame.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`]. The deadline restarts with every call.
+#[cfg(feature = "embedded-hal-async")]
+pub async fn next_deadline<C, R, U, B, D>(
+    state: &mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    timing: &mut Timing<D>,
+    map: fn(C::Item<'_>) -> U,
+) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+where
+    U: 'static,
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+    D: DelayNs,
+{
+    loop {
+        if state.phase != ReadPhase::Read {
+            match decode_pending(state, codec)? {
+                Ok(Some(item)) => return Some(Ok(map(item))),
+                Ok(None) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        if state.framable() > 0 {
+            break;
+        }
+
+        if let Err(err) = read_into(state, read).await {
+            return Some(Err(err.cast_decode()));
+        }
+    }
+
+    let frame_deadline_us = timing.frame_deadline_us();
+
+    trace!(target: READ, "{}Partial frame, deadline started. framable: {}", Name(state.name), state.framable());
+
+    // `next` is cancel safe, so dropping it when the deadline elapses keeps the state consistent.
+    match select(
+        pin!(next(state, codec, read, map)),
+        pin!(timing.delay_mut().delay_us(frame_deadline_us)),
+    )
+    .await
+    {
+        Either::Left(item) => return item,
+        Either::Right(()) => {}
+    }
+
+    warn!(target: READ, "{}Frame deadline elapsed. discarded: {}", Name(state.name), state.framable());
+
+    state.discard_buffered();
+
+    Some(Err(ReadError::FrameDeadline))
+}
+
+/// Like [`next`], but gives up once no bytes arrived for the [`Timing::idle_us`].
+///
+/// The idle time restarts with every read that returned bytes, so slow frames are not affected as long as their bytes keep arriving.
+/// Complete frames that are already buffered are returned without waiting.
+///
+/// # Return value
+///
+/// Same as [`next`]. [`ReadError::Idle`] is returned if the idle time elapsed before a frame was decoded.
+/// The buffered bytes are kept, so calling `next_idle` or [`next`] again resumes reading.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`]. The idle time restarts with every call.
+#[cfg(feature = "embedded-hal-async")]
+pub async fn next_idle<C, R, U, B, D>(
+    state: &mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    timing: &mut Timing<D>,
+    map: fn(C::Item<'_>) -> U,
+) -> Option<Result<U, ReadError<R::Error, C::Error>>>
+where
+    U: 'static,
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+    D: DelayNs,
+{
+    loop {
+        if state.phase != ReadPhase::Read {
+            match decode_pending(state, codec)? {
+                Ok(Some(item)) => return Some(Ok(map(item))),
+                Ok(None) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        let idle_us = timing.idle_us();
+
+        // `read_into` is cancel safe, so dropping it when the link is idle keeps the buffered bytes.
+        match select(
+            pin!(read_into(state, read)),
+            pin!(timing.delay_mut().delay_us(idle_us)),
+        )
+        .await
+        {
+            Either::Left(Ok(())) => continue,
+            Either::Left(Err(err)) => return Some(Err(err.cast_decode())),
+            Either::Right(()) => {}
+        }
+
+        warn!(target: READ, "{}Idle timeout elapsed. framable: {}", Name(state.name), state.framable());
+
+        return Some(Err(ReadError::Idle));
+    }
+}
+
+/// Reads frames until eof and dispatches each one using the `router`.
+///
+/// # Return value
+///
+/// - `Ok(())` if eof was reached.
+/// - `Err(RouteError::Read(error))` if reading failed. The caller should stop reading.
+/// - `Err(RouteError::Handle(error))` if a handler failed. The frame is consumed, so calling `route` again continues with the next frame.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`], as long as the handlers are cancel safe.
+/// A frame is consumed once it is decoded, so it is lost if the future is dropped while the frame is handled.
+pub async fn route<C, R, B, F, K, T, D, E>(
+    state: &mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    router: &mut Router<F, T, D>,
+) -> Result<(), RouteError<R::Error, C::Error, E>>
+where
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+    F: for<'a> FnMut(&C::Item<'a>) -> K,
+    T: for<'a> Routes<K, C::Item<'a>, Error = E>,
+    D: for<'a> Handler<C::Item<'a>, Error = E>,
+{
+    #[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
+    let name = state.name;
+
+    loop {
+        match maybe_next(state, codec, read).await {
+            Some(Ok(None)) => continue,
+            Some(Ok(Some(item))) => {
+                if let Err(err) = router.dispatch(item).await {
+                    error!(target: READ, "{}Failed to handle frame", Name(name));
+
+                    return Err(RouteError::Handle(err));
+                }
+            }
+            Some(Err(err)) => return Err(RouteError::Read(err)),
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Reads frames until eof and sends each one using another framer's write state, codec and writer.
+///
+/// The frames are decoded with `decoder` and encoded again with `encoder`, so the destination may use a different codec,
+/// e.g. to forward the lines of a serial port as length-prefixed frames over TCP.
+/// Frames coalesced by the destination's [`WriteState::flush_threshold`] are flushed once eof is reached.
+///
+/// # Return value
+///
+/// - `Ok(count)` if eof was reached, with the number of forwarded frames.
+/// - `Err(ForwardError::Read(error))` if reading failed. The caller should stop forwarding.
+/// - `Err(ForwardError::Write(error))` if sending failed. The frame is consumed, see [`send`] for the unwritten bytes.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`] and [`send`].
+/// A frame is consumed once it is decoded, so it is lost if the future is dropped before it was encoded.
+pub async fn forward<D, R, RB, C, W, WB, E>(
+    read_state: &mut ReadState<'_, RB>,
+    decoder: &mut D,
+    read: &mut R,
+    write_state: &mut WriteState<'_, WB>,
+    encoder: &mut C,
+    write: &mut W,
+) -> Result<usize, ForwardError<R::Error, D::Error, W::Error, E>>
+where
+    D: Decoder,
+    R: Read,
+    RB: Buffer,
+    C: for<'a> Encoder<D::Item<'a>, Error = E>,
+    W: Write,
+    WB: Buffer,
+{
+    let mut count = 0_usize;
+
+    loop {
+        match maybe_next(read_state, decoder, read).await {
+            Some(Ok(None)) => continue,
+            Some(Ok(Some(item))) => {
+                send(write_state, encoder, write, item)
+                    .await
+                    .map_err(ForwardError::Write)?;
+
+                count = count.saturating_add(1);
+            }
+            Some(Err(err)) => return Err(ForwardError::Read(err)),
+            None => break,
+        }
+    }
+
+    flush(write_state, write)
+        .await
+        .map_err(ForwardError::Write)?;
+
+    debug!(target: WRITE, "{}Forwarded frames. count: {}", Name(write_state.name), count);
+
+    Ok(count)
+}
+
+/// Like [`next`], but returns instead of waiting for `read` to become ready.
+///
+/// # Return value
+///
+/// - `Some(Ok(Some(U)))` if a frame was successfully decoded and mapped. Call `try_next` again to read more frames.
+/// - `Some(Ok(None))` if no frame is buffered and `read` is not ready. Call `try_next` again later.
+/// - `Some(Err(error))` if an error occurred. The caller should stop reading.
+/// - `None` if eof was reached. The caller should stop reading.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`maybe_next`].
+pub async fn try_next<'buf, C, R, U, B>(
+    state: &'buf mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    map: fn(C::Item<'_>) -> U,
+) -> Option<Result<Option<U>, ReadError<R::Error, C::Error>>>
+where
+    U: 'static,
+    C: Decoder,
+    R: Read + ReadReady,
+    B: Buffer,
+{
+    loop {
+        match read_ready(state, read) {
+            Ok(true) => {}
+            Ok(false) => {
+                trace!(target: READ, "{}Not ready", Name(state.name));
+
+                return Some(Ok(None));
+            }
+            Err(err) => {
+                error!(target: READ, "{}Failed to check readiness", Name(state.name));
+
+                record!(state, error);
+
+                return Some(Err(ReadError::IO(err)));
+            }
+        }
+
+        match maybe_next_mapped(state, codec, read, map).await {
+            Some(Ok(None)) => continue,
+            Some(Ok(Some(item))) => return Some(Ok(Some(item))),
+            Some(Err(err)) => return Some(Err(err)),
+            None => return None,
+        }
+    }
+}
+
+/// Sends a frame.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe as long as `write`'s [`Write::write`] is cancel safe.
+///
+/// The [`WriteState`] tracks how many bytes of the encoded frame have been written.
+/// If the returned future is dropped while writing (e.g. in a `select!` branch),
+/// the next call to `send` first writes the rest of the partially written frame before encoding the new one.
+/// The peer never receives a torn frame followed by a fresh one.
+///
+/// The `item` itself is lost if the future is dropped before it was encoded.
+///
+/// # Errors
+///
+/// If writing fails with [`WriteError::IO`] or [`WriteError::WriteZero`], the bytes of the frame that were not written
+/// are kept in the [`WriteState`], just like on cancellation. The next call to `send` writes them before the new frame,
+/// so the peer still receives a complete frame, although the failed call returned an error.
+/// Call [`WriteState::discard_pending`] to drop them instead, e.g. when the connection is reset.
+///
+/// If the [`Encoder::encoded_len`] of the `item` exceeds the capacity of the write buffer, [`WriteError::BufferTooSmall`]
+/// is returned before anything is written.
+///
+/// A frame written by [`send_streamed`] that did not complete is never resumed, since the rest of its payload is lost.
+/// Instead, the next call returns [`WriteError::IncompleteStreamedFrame`] once and discards the `item`.
+///
+/// # Coalescing
+///
+/// If [`WriteState::flush_threshold`] is set, the frame is appended to the buffered ones instead of being written right away.
+/// The buffered frames are written and flushed once they reach the threshold, or when [`flush`] is called.
+/// If the [`Encoder::encoded_len`] of the `item` is unknown or does not fit after the buffered frames, they are written first.
+///
+/// # Tracing
+///
+/// With the `tracing` feature, every call runs inside a `send` span with the fields `name`, `pending` (the bytes buffered before the call)
+/// and `frame_len` (the size of the encoded frame).
+pub async fn send<C, W, I, B>(
+    state: &mut WriteState<'_, B>,
+    codec: &mut C,
+    write: &mut W,
+    item: I,
+) -> Result<(), WriteError<W::Error, C::Error>>
+where
+    C: Encoder<I>,
+    W: Write,
+    B: Buffer,
+{
+    #[cfg(feature = "tracing")]
+    let span = tracing::debug_span!(
+        target: WRITE,
+        "send",
+        name = state.name,
+        pending = state.pending(),
+        frame_len = tracing::field::Empty,
+    );
+
+    let send = send_unspanned(state, codec, write, item);
+
+    #[cfg(feature = "tracing")]
+    let send = tracing::Instrument::instrument(send, span);
+
+    send.await
+}
+
+async fn send_unspanned<C, W, I, B>(
+    state: &mut WriteState<'_, B>,
+    codec: &mut C,
+    write: &mut W,
+    item: I,
+) -> Result<(), WriteError<W::Error, C::Error>>
+where
+    C: Encoder<I>,
+    W: Write,
+    B: Buffer,
+{
+    if needs_room(state, codec, &item)? {
+        make_room(state, write)
+            .await
+            .map_err(WriteError::cast_encode)?;
+    }
+
+    if !encode_send(state, codec, item)? {
+        return Ok(());
+    }
+
+    write_sent(state, write)
+        .await
+        .map_err(WriteError::cast_encode)
+}
+
+/// Checks that the `item` fits the write buffer and returns whether [`send`] must call [`make_room`] before encoding it.
+///
+/// Coalesced frames are only written first if the [`Encoder::encoded_len`] of the `item` is unknown or does not fit after them.
+pub(crate) fn needs_room<C, I, W, B>(
+    state: &WriteState<'_, B>,
+    codec: &C,
+    item: &I,
+) -> Result<bool, WriteError<W, C::Error>>
+where
+    C: Encoder<I>,
+    B: Buffer,
+{
+    check_encoded_len(state, codec, item)?;
+
+    if state.flush_threshold == 0 || state.streaming {
+        return Ok(true);
+    }
+
+    let available = state.buffer.as_slice().len().saturating_sub(state.encoded);
+    let fits = codec.encoded_len(item).is_some_and(|len| len <= available);
+
+    Ok(!fits && state.encoded > 0)
+}
+
+/// Writes the pending bytes of the buffer, so [`encode_send`] encodes the next frame at its start.
+pub(crate) async fn make_room<W, B>(
+    state: &mut WriteState<'_, B>,
+    write: &mut W,
+) -> Result<(), WriteError<W::Error, Infallible>>
+where
+    W: Write,
+    B: Buffer,
+{
+    resume_pending(state, write).await?;
+
+    state.discard_pending();
+
+    Ok(())
+}
+
+/// Encodes the `item` after the pending bytes of the buffer.
+///
+/// Returns whether the frames must be written by [`write_sent`], or stay coalesced until the [`WriteState::flush_threshold`] is reached.
+pub(crate) fn encode_send<C, I, W, B>(
+    state: &mut WriteState<'_, B>,
+    codec: &mut C,
+    item: I,
+) -> Result<bool, WriteError<W, C::Error>>
+where
+    C: Encoder<I>,
+    B: Buffer,
+{
+    let Some(dst) = state.buffer.as_mut_slice().get_mut(state.encoded..) else {
+        error!(target: WRITE, "{}Invalid state", Name(state.name));
+
+        record!(state, error);
+
+        return Err(WriteError::InvalidState);
+    };
+
+    match codec.encode(item, dst) {
+        Ok(size) => {
+            state.encoded = state.encoded.saturating_add(size);
+
+            record!(state, frame);
+            span_record!(frame_len = size);
+        }
+        Err(err) => {
+            error!(target: WRITE, "{}Failed to encode frame", Name(state.name));
+
+            record!(state, error);
+
+            return Err(WriteError::Encode(err));
+        }
+    }
+
+    if state.encoded < state.flush_threshold {
+        trace!(target: WRITE, "{}Frame coalesced. pending: {}", Name(state.name), state.pending());
+
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+/// Writes the frames that [`encode_send`] encoded and flushes `write`.
+///
+/// Does not depend on the codec, so it is instantiated once per writer instead of once per codec and writer.
+/// Without a [`WriteState::flush_threshold`], the written frame is kept in the buffer until the next call to [`send`].
+pub(crate) async fn write_sent<W, B>(
+    state: &mut WriteState<'_, B>,
+    write: &mut W,
+) -> Result<(), WriteError<W::Error, Infallible>>
+where
+    W: Write,
+    B: Buffer,
+{
+    if state.flush_threshold > 0 {
+        return flush_pending(state, write).await;
+    }
+
+    write_pending(state, write).await?;
+
+    trace!(target: WRITE, "{}Wrote. buffer: {:?}", Name(state.name), Bytes(state.buffer.as_slice().get(..state.encoded).unwrap_or_default()));
+
+    match write.flush().await {
+        Ok(_) => {
+            debug!(target: WRITE, "{}Flushed. bytes: {}", Name(state.name), state.encoded);
+
+            Ok(())
+        }
+        Err(err) => {
+            error!(target: WRITE, "{}Failed to flush", Name(state.name));
+
+            record!(state, error);
+
+            Err(WriteError::IO(err))
+        }
+    }
+}
+
+/// Like [`send`], but waits for the `pacer` before sending the frame and records it afterwards.
+///
+/// The frame is recorded with its [`Encoder::encoded_len`], or with the number of buffered bytes if the length is unknown.
+/// Failed frames are not recorded.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`send`] and [`Pacer::wait`].
+#[cfg(feature = "embedded-hal-async")]
+pub async fn send_paced<C, W, I, B, D>(
+    state: &mut WriteState<'_, B>,
+    codec: &mut C,
+    write: &mut W,
+    pacer: &mut Pacer<D>,
+    item: I,
+) -> Result<(), WriteError<W::Error, C::Error>>
+where
+    C: Encoder<I>,
+    W: Write,
+    B: Buffer,
+    D: DelayNs,
+{
+    pacer.wait().await;
+
+    let len = codec.encoded_len(&item);
+
+    send(state, codec, write, item).await?;
+
+    pacer.record(len.unwrap_or(state.encoded));
+
+    trace!(target: WRITE, "{}Paced. debt: {}ns", Name(state.name), pacer.debt_ns());
+
+    Ok(())
+}
+
+/// Like [`send`], but also appends the encoded frame to the log of the `recorder` once it was sent.
+///
+/// With a [`WriteState::flush_threshold`], a frame is recorded once it is encoded. Coalesced frames of encoders without an
+/// [`Encoder::encoded_len`] are not recorded if they are written and flushed right away.
+///
+/// # Errors
+///
+/// Same as [`send`]. Nothing is recorded if sending failed. A frame that could not be recorded is counted in [`Recorder::failures`].
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`send`]. If the future is dropped while the frame is recorded,
+/// the frame was sent but may not be recorded.
+#[cfg(feature = "embedded-storage-async")]
+pub async fn send_recorded<C, W, I, B, S, K, const N: usize>(
+    state: &mut WriteState<'_, B>,
+    codec: &mut C,
+    write: &mut W,
+    recorder: &mut Recorder<S, K, N>,
+    item: I,
+) -> Result<(), WriteError<W::Error, C::Error>>
+where
+    C: Encoder<I>,
+    W: Write,
+    B: Buffer,
+    S: NorFlash,
+    K: Clock,
+{
+    let len = codec.encoded_len(&item);
+    let coalesce = state.flush_threshold > 0 && !state.streaming;
+    let available = state.buffer.as_slice().len().saturating_sub(state.encoded);
+
+    // Where `send` encodes the frame. See `needs_room`.
+    let start = match len {
+        Some(len) if coalesce && len <= available => state.encoded,
+        _ => 0,
+    };
+
+    send(state, codec, write, item).await?;
+
+    let end = match len {
+        Some(len) if coalesce => start.saturating_add(len),
+        _ => state.encoded,
+    };
+
+    let frame = state.buffer.as_slice().get(start..end).unwrap_or_default();
+
+    if frame.is_empty() {
+        trace!(target: WRITE, "{}Frame not recorded", Name(state.name));
+
+        return Ok(());
+    }
+
+    if recorder.record(Direction::Write, frame).await.is_err() {
+        warn!(target: WRITE, "{}Failed to record frame. failures: {}", Name(state.name), recorder.failures());
+    }
+
+    Ok(())
+}
+
+/// Like [`send`], but keeps the bus silent for the [`Timing::gap_us`] after the frame was sent.
+///
+/// Gives the inter-frame gap of protocols like Modbus RTU, or the turnaround time a half-duplex bus needs before the peer may respond.
+/// No gap is kept if sending failed.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`send`]. If the future is dropped while waiting for the gap,
+/// the frame was sent but the gap may be cut short.
+#[cfg(feature = "embedded-hal-async")]
+pub async fn send_gapped<C, W, I, B, D>(
+    state: &mut WriteState<'_, B>,
+    codec: &mut C,
+    write: &mut W,
+    timing: &mut Timing<D>,
+    item: I,
+) -> Result<(), WriteError<W::Error, C::Error>>
+where
+    C: Encoder<I>,
+    W: Write,
+    B: Buffer,
+    D: DelayNs,
+{
+    send(state, codec, write, item).await?;
+
+    let gap_us = timing.gap_us();
+
+    timing.delay_mut().delay_us(gap_us).await;
+
+    trace!(target: WRITE, "{}Gap kept. gap: {}us", Name(state.name), gap_us);
+
+    Ok(())
+}
+
+/// Writes the frames that [`send`] buffered because of the [`WriteState::flush_threshold`] and flushes `write`.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`send`].
+#[inline]
+pub async fn flush<W, E, B>(
+    state: &mut WriteState<'_, B>,
+    write: &mut W,
+) -> Result<(), WriteError<W::Error, E>>
+where
+    W: Write,
+    B: Buffer,
+{
+    flush_pending(state, write)
+        .await
+        .map_err(WriteError::cast_encode)
+}
+
+/// Implements [`flush`] independently of the codec.
+pub(crate) async fn flush_pending<W, B>(
+    state: &mut WriteState<'_, B>,
+    write: &mut W,
+) -> Result<(), WriteError<W::Error, Infallible>>
+where
+    W: Write,
+    B: Buffer,
+{
+    resume_pending(state, write).await?;
+
+    state.discard_pending();
+
+    match write.flush().await {
+        Ok(_) => {
+            debug!(target: WRITE, "{}Flushed", Name(state.name));
+
+            Ok(())
+        }
+        Err(err) => {
+            error!(target: WRITE, "{}Failed to flush", Name(state.name));
+
+            record!(state, error);
+
+            Err(WriteError::IO(err))
+        }
+    }
+}
+
+/// Sends multiple frames and flushes once at the end.
+///
+/// Frames whose [`Encoder::encoded_len`] fits the remaining space of the write buffer are encoded after the previous ones,
+/// so they are written together. Otherwise, the buffered frames are written before the next one is encoded.
+///
+/// # Cancel safety
+///
+/// This function is cancel safe under the same conditions as [`send`]. The items that were not encoded yet are lost if the future is dropped.
+///
+/// # Errors
+///
+/// Same as [`send`]. If encoding an item fails, the frames encoded before it are kept in the [`WriteState`] and written by the next call.
+pub async fn send_all<C, W, I, B, T>(
+    state: &mut WriteState<'_, B>,
+    codec: &mut C,
+    write: &mut W,
+    items: T,
+) -> Result<(), WriteError<W::Error, C::Error>>
+where
+    C: Encoder<I>,
+    W: Write,
+    B: Buffer,
+    T: IntoIterator<Item = I>,
+{
+    resume_pending(state, write)
+        .await
+        .map_err(WriteError::cast_encode)?;
+
+    state.encoded = 0;
+    state.written = 0;
+
+    for item in items {
+        check_encoded_len(state, codec, &item)?;
+
+        let available = state.buffer.as_slice().len().saturating_sub(state.encoded);
+        let fits = codec.encoded_len(&item).is_some_and(|len| len <= available);
+
+        if !fits && state.encoded > 0 {
+            write_pending(state, write)
+                .await
+                .map_err(WriteError::cast_encode)?;
+
+            state.encoded = 0;
+            state.written = 0;
+        }
+
+        let Some(dst) = state.buffer.as_mut_slice().get_mut(state.encoded..) else {
+            error!(target: WRITE, "{}Invalid state", Name(state.name));
+
+            record!(state, error);
+
+            return Err(WriteError::InvalidState);
+        };
+
+        match codec.encode(item, dst) {
+            Ok(size) => {
+                state.encoded = state.encoded.saturating_add(size);
+
+                record!(state, frame);
+            }
+            Err(err) => {
+                error!(target: WRITE, "{}Failed to encode frame", Name(state.name));
+
+                record!(state, error);
+
+                return Err(WriteError::Encode(err));
+            }
+        }
+    }
+
+    write_pending(state, write)
+        .await
+        .map_err(WriteError::cast_encode)?;
+
+    match write.flush().await {
+        Ok(_) => {
+            debug!(target: WRITE, "{}Flushed", Name(state.name));
+
+            Ok(())
+        }
+        Err(err) => {
+            error!(target: WRITE, "{}Failed to flush", Name(state.name));
+
+            record!(state, error);
+
+            Err(WriteError::IO(err))
+        }
+    }
+}
+
+/// Like [`send`], but returns instead of waiting for `write` to become ready.
+///
+/// Every write is preceded by a readiness check. Once the `item` is encoded, it is accepted:
+/// if `write` is not ready before the frame is completely written, the rest of the frame is kept in the [`WriteState`]
+/// and written first by the next call to `try_send` or [`send`].
+/// The frame is flushed once it is completely written.
+///
+/// # Errors
+///
+/// - [`WriteError::NotReady`] if `write` is not ready, or the rest of a previous frame could not be written yet.
+///   The `item` is not encoded and should be sent again later.
+///
+/// The other errors are the same as for [`send`].
+pub async fn try_send<C, W, I, B>(
+    state: &mut WriteState<'_, B>,
+    codec: &mut C,
+    write: &mut W,
+    item: I,
+) -> Result<(), WriteError<W::Error, C::Error>>
+where
+    C: Encoder<I>,
+    W: Write + WriteReady,
+    B: Buffer,
+{
+    if state.streaming {
+        return resume_pending(state, write)
+            .await
+            .map_err(WriteError::cast_encode);
+    }
+
+    check_encoded_len(state, codec, &item)?;
+
+    write_pending_ready(state, write)
+        .await
+        .map_err(WriteError::cast_encode)?;
+
+    if state.pending() > 0 || !write.write_ready().map_err(WriteError::IO)? {
+        trace!(target: WRITE, "{}Not ready", Name(state.name));
+
+        return Err(WriteError::NotReady);
+    }
+
+    match codec.encode(item, state.buffer.as_mut_slice()) {
+        Ok(size) => {
+            state.encoded = size;
+            state.written = 0;
+
+            record!(state, frame);
+        }
+        Err(err) => {
+            error!(target: WRITE, "{}Failed to encode frame", Name(state.name));
+
+            record!(state, error);
+
+            return Err(WriteError::Encode(err));
+        }
+    }
+
+    write_pending_ready(state, write)
+        .await
+        .map_err(WriteError::cast_encode)?;
+
+    if state.pending() > 0 {
+        debug!(target: WRITE, "{}Frame accepted. pending: {}", Name(state.name), state.pending());
+
+        return Ok(());
+    }
+
+    match write.flush().await {
+        Ok(_) => {
+            debug!(target: WRITE, "{}Flushed. bytes: {}", Name(state.name), state.encoded);
+
+            Ok(())
+        }
+        Err(err) => {
+            error!(target: WRITE, "{}Failed to flush", Name(state.name));
+
+            record!(state, error);
+
+            Err(WriteError::IO(err))
+        }
+    }
+}
+
+/// Sends a frame whose payload is streamed from a [`Read`] source.
+///
+/// The `header` is encoded into the write buffer using the `codec`.
+/// Exactly `payload_len` bytes are then read from `payload` and copied through the write buffer in chunks.
+/// This allows sending frames that are larger than the write buffer, e.g. firmware images or files.
+///
+/// The `header` is responsible for describing the payload to the peer, e.g. by encoding `payload_len`.
+/// The payload bytes are written as they are.
+///
+/// # Errors
+///
+/// - [`SendStreamedError::UnexpectedEof`] if `payload` reached EOF before `payload_len` bytes were read.
+/// - [`SendStreamedError::Payload`] if reading from `payload` failed.
+/// - [`SendStreamedError::BufferTooSmall`] if the write buffer has no room for the payload.
+/// - [`SendStreamedError::Write`] if encoding the `header` or writing the frame failed.
+///
+/// The payload is read into the write buffer before it is written, so an error may occur after some chunks of the frame
+/// were already written. In that case the peer received a torn frame and the next call to [`send`] or
+/// `send_streamed` returns [`WriteError::IncompleteStreamedFrame`] instead of resuming it.
+/// If no bytes of the frame were written, the next call sends its frame normally.
+///
+/// # Cancel safety
+///
+/// This function is not cancel safe. Bytes read from `payload` are lost if the returned future is dropped before it completes.
+/// A cancelled streamed frame is handled like a failed one. A frame that is cancelled while a chunk is written
+/// is reported as incomplete, even if the chunk was not written yet.
+pub async fn send_streamed<C, W, I, P, B>(
+    state: &mut WriteState<'_, B>,
+    codec: &mut C,
+    write: &mut W,
+    header: I,
+    payload: &mut P,
+    payload_len: usize,
+) -> Result<(), SendStreamedError<W::Error, C::Error, P::Error>>
+where
+    C: Encoder<I>,
+    W: Write,
+    P: Read,
+    B: Buffer,
+{
+    resume_pending(state, write)
+        .await
+        .map_err(WriteError::cast_encode)?;
+
+    // The buffered bytes of the frame are only pending once they are written,
+    // so a frame that fails or is cancelled before is dropped instead of being resumed.
+    state.discard_pending();
+
+    let mut encoded = match codec.encode(header, state.buffer.as_mut_slice()) {
+        Ok(size) => {
+            record!(state, frame);
+
+            size
+        }
+        Err(err) => {
+            error!(target: WRITE, "{}Failed to encode frame header", Name(state.name));
+
+            record!(state, error);
+
+            return Err(SendStreamedError::Write(WriteError::Encode(err)));
+        }
+    };
+
+    let mut total = 0;
+    let mut chunks_written = false;
+
+    while total < payload_len {
+        if encoded >= state.buffer.as_slice().len() {
+            write_chunk(state, write, encoded, chunks_written)
+                .await
+                .map_err(WriteError::cast_encode)?;
+
+            encoded = 0;
+            chunks_written = true;
+        }
+
+        let free = state.buffer.as_slice().len().saturating_sub(encoded);
+
+        if free == 0 {
+            error!(target: WRITE, "{}Buffer too small", Name(state.name));
+
+            record!(state, error);
+
+            abort_streamed(state, chunks_written);
+
+            return Err(SendStreamedError::BufferTooSmall);
+        }
+
+        let size = core::cmp::min(free, payload_len.saturating_sub(total));
+
+        let Some(dst) = state
+            .buffer
+            .as_mut_slice()
+            .get_mut(encoded..encoded.saturating_add(size))
+        else {
+            error!(target: WRITE, "{}Invalid state", Name(state.name));
+
+            record!(state, error);
+
+            abort_streamed(state, chunks_written);
+
+            return Err(WriteError::InvalidState.into());
+        };
+
+        match payload.read(dst).await {
+            Ok(0) => {
+                error!(target: WRITE, "{}Got EOF while reading payload. missing: {}", Name(state.name), payload_len.saturating_sub(total));
+
+                record!(state, error);
+
+                abort_streamed(state, chunks_written);
+
+                return Err(SendStreamedError::UnexpectedEof);
+            }
+            Ok(n) => {
+                trace!(target: WRITE, "{}Payload chunk read. bytes: {}", Name(state.name), n);
+
+                encoded = encoded.saturating_add(n);
+                total = total.saturating_add(n);
+            }
+            Err(err) => {
+                error!(target: WRITE, "{}Failed to read payload", Name(state.name));
+
+                record!(state, error);
+
+                abort_streamed(state, chunks_written);
+
+                return Err(SendStreamedError::Payload(err));
+            }
+        }
+    }
+
+    write_chunk(state, write, encoded, chunks_written)
+        .await
+        .map_err(WriteError::cast_encode)?;
+
+    state.streaming = false;
+
+    match write.flush().await {
+        Ok(_) => {
+            debug!(target: WRITE, "{}Flushed. payload bytes: {}", Name(state.name), total);
+
+            Ok(())
+        }
+        Err(err) => {
+            error!(target: WRITE, "{}Failed to flush", Name(state.name));
+
+            record!(state, error);
+
+            Err(SendStreamedError::Write(WriteError::IO(err)))
+        }
+    }
+}
+
+/// Sends a frame without copying its payload into the write buffer.
+///
+/// Only the header and trailer of the `item` are encoded into the write buffer using [`EncodeParts::encode_parts`].
+/// The header, the payload borrowed from the `item` and the trailer are then written one after another, and the writer is flushed once.
+/// This halves the memory traffic for large frames, and allows sending payloads that are larger than the write buffer.
+///
+/// # Errors
+///
+/// Same as [`send`].
+///
+/// The payload is not buffered, so a frame that failed after some of its bytes were written can not be resumed.
+/// Like with [`send_streamed`], the next call to [`send`] or `send_parts` returns [`WriteError::IncompleteStreamedFrame`].
+///
+/// # Cancel safety
+///
+/// This function is not cancel safe. A cancelled frame is handled like a failed one.
+pub async fn send_parts<'a, C, W, I, B>(
+    state: &mut WriteState<'_, B>,
+    codec: &mut C,
+    write: &mut W,
+    item: I,
+) -> Result<(), WriteError<W::Error, C::Error>>
+where
+    C: EncodeParts<'a, I>,
+    W: Write,
+    B: Buffer,
+{
+    resume_pending(state, write)
+        .await
+        .map_err(WriteError::cast_encode)?;
+
+    let parts = match codec.encode_parts(item, state.buffer.as_mut_slice()) {
+        Ok(parts) => {
+            record!(state, frame);
+
+            parts
+        }
+        Err(err) => {
+            error!(target: WRITE, "{}Failed to encode frame parts", Name(state.name));
+
+            record!(state, error);
+
+            return Err(WriteError::Encode(err));
+        }
+    };
+
+    // Stays set until the frame is completely written. See `resume_pending`.
+    state.streaming = true;
+
+    state.encoded = parts.header;
+    state.written = 0;
+
+    write_pending(state, write)
+        .await
+        .map_err(WriteError::cast_encode)?;
+
+    write_payload(state, write, parts.payload)
+        .await
+        .map_err(WriteError::cast_encode)?;
+
+    state.encoded = parts.header.saturating_add(parts.trailer);
+
+    write_pending(state, write)
+        .await
+        .map_err(WriteError::cast_encode)?;
+
+    state.streaming = false;
+
+    match write.flush().await {
+        Ok(_) => {
+            debug!(target: WRITE, "{}Flushed. payload bytes: {}", Name(state.name), parts.payload.len());
+
+            Ok(())
+        }
+        Err(err) => {
+            error!(target: WRITE, "{}Failed to flush", Name(state.name));
+
+            record!(state, error);
+
+            Err(WriteError::IO(err))
+        }
+    }
+}
+
+/// Sends a frame using [`Encoder::encode_to_writer`], so codecs that stream their output skip the write buffer.
+///
+/// The write buffer is lent to the codec, for codecs that encode into it before writing.
+/// Unlike [`send`], the [`Encoder::encoded_len`] of the `item` is not checked against the capacity of the write buffer,
+/// so codecs that stream their output can send items of any size.
+///
+/// # Errors
+///
+/// Same as [`send`].
+///
+/// A frame that failed after some of its bytes were written can not be resumed.
+/// Like with [`send_parts`], the next call to [`send`] or `send_direct` returns [`WriteError::IncompleteStreamedFrame`].
+///
+/// # Cancel safety
+///
+/// This function is not cancel safe. A cancelled frame is handled like a failed one.
+#[cfg_attr(
+    not(any(
+        feature = "log",
+        feature = "defmt",
+        feature = "tracing",
+        feature = "metrics"
+    )),
+    allow(unused_variables)
+)]
+pub async fn send_direct<C, W, I, B>(
+    state: &mut WriteState<'_, B>,
+    codec: &mut C,
+    write: &mut W,
+    item: I,
+) -> Result<(), WriteError<W::Error, C::Error>>
+where
+    C: Encoder<I>,
+    W: Write,
+    B: Buffer,
+{
+    resume_pending(state, write)
+        .await
+        .map_err(WriteError::cast_encode)?;
+
+    state.discard_pending();
+
+    let mut streamed = Streamed {
+        write,
+        streaming: &mut state.streaming,
+    };
+
+    let result = codec
+        .encode_to_writer(item, state.buffer.as_mut_slice(), &mut streamed)
+        .await;
+
+    match result {
+        Ok(size) => {
+            record!(state, frame);
+            record!(state, bytes, size);
+
+            trace!(target: WRITE, "{}Wrote directly. bytes: {}", Name(state.name), size);
+        }
+        Err(WriteError::Encode(err)) => {
+            error!(target: WRITE, "{}Failed to encode frame", Name(state.name));
+
+            record!(state, error);
+
+            return Err(WriteError::Encode(err));
+        }
+        Err(err) => {
+            error!(target: WRITE, "{}Failed to write frame", Name(state.name));
+
+            record!(state, error);
+
+            return Err(err);
+        }
+    }
+
+    state.streaming = false;
+
+    flush_pending(state, write)
+        .await
+        .map_err(WriteError::cast_encode)
+}
+
+/// The writer of [`send_direct`].
+///
+/// Sets `streaming` once the codec wrote the first byte of the frame, so a frame that failed or was cancelled
+/// before any of its bytes were written is not reported as incomplete. See `resume_pending`.
+struct Streamed<'a, W> {
+    write: &'a mut W,
+    streaming: &'a mut bool,
+}
+
+impl<W> ErrorType for Streamed<'_, W>
+where
+    W: ErrorType,
+{
+    type Error = W::Error;
+}
+
+impl<W> Write for Streamed<'_, W>
+where
+    W: Write,
+{
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let n = self.write.write(buf).await?;
+
+        if n > 0 {
+            // Stays set until the frame is completely written.
+            *self.streaming = true;
+        }
+
+        Ok(n)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.write.flush().await
+    }
+}
+
+/// Sends a frame that is already encoded, without copying it into the write buffer.
+///
+/// Meant for forwarding frames, e.g. from one link to another, where encoding them again would only copy their bytes.
+/// The `frame` is written as it is, and the writer is flushed once. For the [`Bytes`](crate::codec::bytes::Bytes) codec, it is the same as [`send_parts`].
+///
+/// The frames that [`send`] coalesced because of the [`WriteState::flush_threshold`] are written first, so the order of the frames is kept.
+///
+/// # Errors
+///
+/// Same as [`send_parts`], without the encoding errors.
+///
+/// # Cancel safety
+///
+/// This function is not cancel safe. A cancelled frame is handled like a failed one.
+#[inline]
+pub async fn send_raw<W, E, B>(
+    state: &mut WriteState<'_, B>,
+    write: &mut W,
+    frame: &[u8],
+) -> Result<(), WriteError<W::Error, E>>
+where
+    W: Write,
+    B: Buffer,
+{
+    write_raw(state, write, frame)
+        .await
+        .map_err(WriteError::cast_encode)
+}
+
+/// Implements [`send_raw`] independently of the codec.
+async fn write_raw<W, B>(
+    state: &mut WriteState<'_, B>,
+    write: &mut W,
+    frame: &[u8],
+) -> Result<(), WriteError<W::Error, Infallible>>
+where
+    W: Write,
+    B: Buffer,
+{
+    resume_pending(state, write).await?;
+
+    state.discard_pending();
+
+    record!(state, frame);
+
+    // Stays set until the frame is completely written. See `resume_pending`.
+    state.streaming = true;
+
+    write_payload(state, write, frame).await?;
+
+    state.streaming = false;
+
+    match write.flush().await {
+        Ok(_) => {
+            debug!(target: WRITE, "{}Flushed. raw bytes: {}", Name(state.name), frame.len());
+
+            Ok(())
+        }
+        Err(err) => {
+            error!(target: WRITE, "{}Failed to flush", Name(state.name));
+
+            record!(state, error);
+
+            Err(WriteError::IO(err))
+        }
+    }
+}
+
+/// Writes the `payload` directly to `write`, bypassing the write buffer.
+#[cfg_attr(
+    not(any(
+        feature = "log",
+        feature = "defmt",
+        feature = "tracing",
+        feature = "metrics"
+    )),
+    allow(unused_variables)
+)]
+async fn write_payload<W, B>(
+    state: &mut WriteState<'_, B>,
+    write: &mut W,
+    mut payload: &[u8],
+) -> Result<(), WriteError<W::Error, Infallible>>
+where
+    W: Write,
+    B: Buffer,
+{
+    while !payload.is_empty() {
+        match write.write(payload).await {
+            Ok(0) => {
+                error!(target: WRITE, "{}Failed to write payload. Wrote zero bytes", Name(state.name));
+
+                record!(state, error);
+
+                return Err(WriteError::WriteZero);
+            }
+            Ok(n) => {
+                payload = payload.get(n..).unwrap_or_default();
+
+                record!(state, bytes, n);
+            }
+            Err(err) => {
+                error!(target: WRITE, "{}Failed to write payload", Name(state.name));
+
+                record!(state, error);
+
+                return Err(WriteError::IO(err));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the first `encoded` bytes of the write buffer as a chunk of a frame of [`send_streamed`].
+///
+/// A failed chunk aborts the frame, which is only reported as incomplete if some of its bytes were written.
+async fn write_chunk<W, B>(
+    state: &mut WriteState<'_, B>,
+    write: &mut W,
+    encoded: usize,
+    chunks_written: bool,
+) -> Result<(), WriteError<W::Error, Infallible>>
+where
+    W: Write,
+    B: Buffer,
+{
+    state.encoded = encoded;
+    state.written = 0;
+
+    // Stays set until the frame is completely written. See `resume_pending`.
+    state.streaming = true;
+
+    if let Err(err) = write_pending(state, write).await {
+        abort_streamed(state, chunks_written || state.written > 0);
+
+        return Err(err);
+    }
+
+    state.discard_pending();
+
+    Ok(())
+}
+
+/// Drops the buffered bytes of a streamed frame that can not be completed.
+///
+/// The frame is only reported as incomplete if some of its bytes were already written.
+const fn abort_streamed<B>(state: &mut WriteState<'_, B>, chunks_written: bool) {
+    state.discard_pending();
+
+    state.streaming = chunks_written;
+}
+
+/// Returns [`WriteError::BufferTooSmall`] if the [`Encoder::encoded_len`] of the `item` exceeds the capacity of the write buffer.
+pub(crate) fn check_encoded_len<C, I, W, B>(
+    state: &WriteState<'_, B>,
+    codec: &C,
+    item: &I,
+) -> Result<(), WriteError<W, C::Error>>
+where
+    C: Encoder<I>,
+    B: Buffer,
+{
+    let available = state.buffer.as_slice().len();
+
+    match codec.encoded_len(item) {
+        Some(required) if required > available => {
+            error!(target: WRITE, "{}Frame does not fit the write buffer. required: {}, available: {}", Name(state.name), required, available);
+
+            record!(state, error);
+
+            Err(WriteError::BufferTooSmall(EncodeBufferTooSmall {
+                required,
+                available,
+            }))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Writes the rest of a frame that was not completely written by a previous call.
+///
+/// An incomplete streamed frame is not resumed, but reported once as [`WriteError::IncompleteStreamedFrame`].
+pub(crate) async fn resume_pending<W, B>(
+    state: &mut WriteState<'_, B>,
+    write: &mut W,
+) -> Result<(), WriteError<W::Error, Infallible>>
+where
+    W: Write,
+    B: Buffer,
+{
+    if state.streaming {
+        error!(target: WRITE, "{}Previous streamed frame is incomplete", Name(state.name));
+
+        record!(state, error);
+
+        state.discard_pending();
+        state.streaming = false;
+
+        return Err(WriteError::IncompleteStreamedFrame);
+    }
+
+    if state.pending() > 0 {
+        debug!(target: WRITE, "{}Resuming partial frame. pending: {}", Name(state.name), state.pending());
+
+        write_pending(state, write).await?;
+    }
+
+    Ok(())
+}
+
+/// Writes the pending bytes of the current encoded frame.
+///
+/// [`WriteState::written`] is updated after every completed write, so this function can be cancelled and resumed.
+pub(crate) async fn write_pending<W, B>(
+    state: &mut WriteState<'_, B>,
+    write: &mut W,
+) -> Result<(), WriteError<W::Error, Infallible>>
+where
+    W: Write,
+    B: Buffer,
+{
+    while state.written < state.encoded {
+        let Some(pending) = state.buffer.as_slice().get(state.written..state.encoded) else {
+            error!(target: WRITE, "{}Invalid state", Name(state.name));
+
+            record!(state, error);
+
+            return Err(WriteError::InvalidState);
+        };
+
+        match write.write(pending).await {
+            Ok(0) => {
+                error!(target: WRITE, "{}Failed to write frame. Wrote zero bytes", Name(state.name));
+
+                record!(state, error);
+
+                return Err(WriteError::WriteZero);
+            }
+            Ok(n) => {
+                state.written = state.written.saturating_add(n);
+
+                record!(state, bytes, n);
+            }
+            Err(err) => {
+                error!(target: WRITE, "{}Failed to write frame", Name(state.name));
+
+                record!(state, error);
+
+                return Err(WriteError::IO(err));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Like [`write_pending`], but stops as soon as `write` is not ready.
+async fn write_pending_ready<W, B>(
+    state: &mut WriteState<'_, B>,
+    write: &mut W,
+) -> Result<(), WriteError<W::Error, Infallible>>
+where
+    W: Write + WriteReady,
+    B: Buffer,
+{
+    while state.written < state.encoded {
+        if !write.write_ready().map_err(WriteError::IO)? {
+            return Ok(());
+        }
+
+        let Some(pending) = state.buffer.as_slice().get(state.written..state.encoded) else {
+            error!(target: WRITE, "{}Invalid state", Name(state.name));
+
+            record!(state, error);
+
+            return Err(WriteError::InvalidState);
+        };
+
+        match write.write(pending).await {
+            Ok(0) => {
+                error!(target: WRITE, "{}Failed to write frame. Wrote zero bytes", Name(state.name));
+
+                record!(state, error);
+
+                return Err(WriteError::WriteZero);
+            }
+            Ok(n) => {
+                state.written = state.written.saturating_add(n);
+
+                record!(state, bytes, n);
+            }
+            Err(err) => {
+                error!(target: WRITE, "{}Failed to write frame", Name(state.name));
+
+                record!(state, error);
+
+                return Err(WriteError::IO(err));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use embedded_io_async::{ErrorKind, ErrorType, Read, ReadReady, Write, WriteReady};
+
+    use embedded_io_adapters::tokio_1::FromTokio;
+    use futures::FutureExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use crate::{
+        FramedRead, FramedWrite, ReadError, ReadPayloadError, SendStreamedError, WriteError,
+        codec::{bytes::Bytes, cobs::Cobs, lines::Lines},
+        decode::{Decoder, PayloadHeader},
+        encode::{EncodeBufferTooSmall, Encoder, write_all},
+        functions::{decode_pending, fill},
+        mock::PendingReader,
+        next, next_timeout, send,
+        state::{ReadPhase, ShiftPolicy},
+        tests::init_tracing,
+        try_next,
+    };
+
+    #[tokio::test]
+    async fn maybe_next_is_cancel_safe() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Lines::new(), FromTokio::new(read), buffer);
+
+        let mut collected = Vec::<Vec<u8>>::new();
+
+        write.write_all(b"Hel").await.expect("Must write");
+
+        // The partial frame is read into the buffer, then the pending read for the rest is cancelled.
+        for _ in 0..3 {
+            let polled = async { next!(framer).is_some() }.now_or_never();
+
+            assert!(polled.is_none());
+        }
+
+        assert_eq!(framer.framable(), 3);
+
+        write.write_all(b"lo\nHey\n").await.expect("Must write");
+        drop(write);
+
+        while let Some(item) = next!(framer) {
+            collected.push(item.expect("Must decode").into());
+        }
+
+        assert_eq!(collected, [b"Hello".as_slice(), b"Hey"]);
+    }
+
+    #[tokio::test]
+    async fn maybe_next_is_cancel_safe_after_shift() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        let buffer = &mut [0_u8; 8];
+        let mut framer = FramedRead::new(Lines::new(), FromTokio::new(read), buffer);
+
+        // Fills the buffer, so the partial frame must be shifted before reading more bytes.
+        write.write_all(b"Hello\nWo").await.expect("Must write");
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b"Hello");
+
+        // Fails to decode, shifts the buffer, then the pending read is cancelled.
+        let polled = async { next!(framer).is_some() }.now_or_never();
+
+        assert!(polled.is_none());
+        assert_eq!(framer.core.state.read.phase, ReadPhase::Read);
+        assert_eq!(framer.core.state.read.total_consumed, 0);
+        assert_eq!(framer.core.state.read.index, 2);
+        assert_eq!(&framer.core.state.read.buffer[..2], b"Wo");
+
+        write.write_all(b"rld\n").await.expect("Must write");
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, b"World");
+    }
+
+    #[tokio::test]
+    async fn send_resumes_partial_frame_after_cancel() {
+        init_tracing();
+
+        // The duplex only accepts 4 bytes until the reader reads, so the first frame is only partially written.
+        let (read, write) = tokio::io::duplex(4);
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Lines::new(), FromTokio::new(write), buffer);
+
+        let polled = framer.send(b"Hello, world!".as_slice()).now_or_never();
+
+        assert!(polled.is_none());
+
+        assert_eq!(framer.core.state.write.written, 4);
+        assert_eq!(framer.core.state.write.pending(), 11);
+
+        let reader = tokio::spawn(async move {
+            let buffer = &mut [0_u8; 32];
+            let mut framer = FramedRead::new(Lines::new(), FromTokio::new(read), buffer);
+
+            let mut collected = Vec::<Vec<u8>>::new();
+
+            while let Some(item) = next!(framer) {
+                collected.push(item.expect("Must decode").into());
+            }
+
+            collected
+        });
+
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        drop(framer);
+
+        let collected = reader.await.expect("Must join");
+
+        assert_eq!(collected, [b"Hello, world!".as_slice(), b"Hey"]);
+    }
+
+    /// Accepts `accept` bytes, then fails once.
+    struct FailingWriter {
+        accept: usize,
+        failed: bool,
+        written: Vec<u8>,
+    }
+
+    impl ErrorType for FailingWriter {
+        type Error = ErrorKind;
+    }
+
+    impl Write for FailingWriter {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            if !self.failed && self.written.len() >= self.accept {
+                self.failed = true;
+
+                return Err(ErrorKind::Other);
+            }
+
+            let size = match self.failed {
+                true => buf.len(),
+                false => core::cmp::min(buf.len(), self.accept - self.written.len()),
+            };
+
+            self.written.extend_from_slice(&buf[..size]);
+
+            Ok(size)
+        }
+    }
+
+    #[tokio::test]
+    async fn send_resumes_partial_frame_after_error() {
+        init_tracing();
+
+        let writer = FailingWriter {
+            accept: 4,
+            failed: false,
+            written: Vec::new(),
+        };
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Lines::new(), writer, buffer);
+
+        let result = framer.send(b"Hello, world!".as_slice()).await;
+
+        assert!(matches!(result, Err(WriteError::IO(ErrorKind::Other))));
+        assert_eq!(framer.core.state.write.pending(), 11);
+
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        assert_eq!(framer.inner().written, b"Hello, world!\r\nHey\r\n");
+    }
+
+    #[tokio::test]
+    async fn send_discards_pending_frame() {
+        init_tracing();
+
+        let writer = FailingWriter {
+            accept: 4,
+            failed: false,
+            written: Vec::new(),
+        };
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Lines::new(), writer, buffer);
+
+        let result = framer.send(b"Hello, world!".as_slice()).await;
+
+        assert!(result.is_err());
+
+        framer.core.state.write.discard_pending();
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        assert_eq!(framer.inner().written, b"HellHey\r\n");
+    }
+
+    #[tokio::test]
+    async fn send_checks_encoded_len() {
+        init_tracing();
+
+        let writer = FailingWriter {
+            accept: usize::MAX,
+            failed: false,
+            written: Vec::new(),
+        };
+
+        let buffer = &mut [0_u8; 6];
+        let mut framer = FramedWrite::new(Lines::new(), writer, buffer);
+
+        let result = framer.send(b"Hello".as_slice()).await;
+
+        assert!(matches!(
+            result,
+            Err(WriteError::BufferTooSmall(EncodeBufferTooSmall {
+                required: 7,
+                available: 6
+            }))
+        ));
+        assert_eq!(framer.core.state.write.encoded, 0);
+
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        assert_eq!(framer.inner().written, b"Hey\r\n");
+    }
+
+    #[test]
+    fn decode_pending_does_not_read() {
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(Lines::new(), FailingReader, buffer);
+
+        let writable = framer.writable_part();
+        writable[..13].copy_from_slice(b"Hello\nHey\nPar");
+        framer.commit(13).expect("Must commit");
+
+        let state = &mut framer.core.state.read;
+        let codec = &mut framer.core.codec;
+
+        let item = decode_pending::<_, ErrorKind, _>(state, codec)
+            .expect("Must be some")
+            .expect("Must decode");
+        assert_eq!(item, Some(b"Hello".as_slice()));
+
+        let item = decode_pending::<_, ErrorKind, _>(state, codec)
+            .expect("Must be some")
+            .expect("Must decode");
+        assert_eq!(item, Some(b"Hey".as_slice()));
+
+        // The partial frame stays buffered.
+        let item = decode_pending::<_, ErrorKind, _>(state, codec)
+            .expect("Must be some")
+            .expect("Must decode");
+        assert_eq!(item, None);
+
+        framer.writable_part()[..5].copy_from_slice(b"tial\n");
+        framer.commit(5).expect("Must commit");
+
+        let item =
+            decode_pending::<_, ErrorKind, _>(&mut framer.core.state.read, &mut framer.core.codec)
+                .expect("Must be some")
+                .expect("Must decode");
+        assert_eq!(item, Some(b"Partial".as_slice()));
+    }
+
+    #[tokio::test]
+    async fn fill_then_decode_pending() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        write.write_all(b"Hello\nHe").await.expect("Must write");
+
+        let buffer = &mut [0_u8; 8];
+        let mut framer = FramedRead::new(Lines::new(), FromTokio::new(read), buffer);
+
+        let mut collected = Vec::<Vec<u8>>::new();
+
+        let size = fill::<_, (), _>(&mut framer.core.state.read, &mut framer.core.inner)
+            .await
+            .expect("Must fill");
+        assert_eq!(size, 8);
+
+        write.write_all(b"y\n").await.expect("Must write");
+        drop(write);
+
+        loop {
+            loop {
+                match decode_pending::<_, ErrorKind, _>(
+                    &mut framer.core.state.read,
+                    &mut framer.core.codec,
+                ) {
+                    Some(Ok(Some(item))) => collected.push(item.into()),
+                    Some(Ok(None)) => break,
+                    Some(Err(_)) => panic!("Must decode"),
+                    None => break,
+                }
+            }
+
+            let size = fill::<_, (), _>(&mut framer.core.state.read, &mut framer.core.inner)
+                .await
+                .expect("Must fill");
+
+            if size == 0 {
+                assert!(
+                    decode_pending::<_, ErrorKind, _>(
+                        &mut framer.core.state.read,
+                        &mut framer.core.codec
+                    )
+                    .is_none()
+                );
+
+                break;
+            }
+        }
+
+        assert_eq!(collected, [b"Hello".as_slice(), b"Hey"]);
+    }
+
+    #[tokio::test]
+    async fn next_into_copies_frames() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(
+            Lines::new(),
+            b"Hello\nHello, world!\nHey\n".as_slice(),
+            buffer,
+        );
+
+        let dst = &mut [0_u8; 8];
+
+        let len = framer
+            .next_into(dst)
+            .await
+            .expect("Must be some")
+            .expect("Must copy");
+        assert_eq!(&dst[..len], b"Hello");
+
+        assert!(matches!(
+            framer.next_into(dst).await,
+            Some(Err(ReadError::FrameTooLarge(13)))
+        ));
+
+        // The frame that was too large is dropped.
+        let len = framer
+            .next_into(dst)
+            .await
+            .expect("Must be some")
+            .expect("Must copy");
+        assert_eq!(&dst[..len], b"Hey");
+
+        assert!(framer.next_into(dst).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn next_timeout_keeps_buffered_bytes() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Lines::new(), FromTokio::new(read), buffer);
+
+        write.write_all(b"Hel").await.expect("Must write");
+
+        let timeout = tokio::time::sleep(core::time::Duration::from_millis(10));
+        let item = next_timeout!(framer, timeout);
+
+        assert!(matches!(item, Some(Err(ReadError::Timeout))));
+        assert_eq!(framer.framable(), 3);
+
+        write.write_all(b"lo\nHey\n").await.expect("Must write");
+
+        let timeout = tokio::time::sleep(core::time::Duration::from_secs(10));
+        let item = next_timeout!(framer, timeout)
+            .expect("Must be some")
+            .expect("Must decode");
+        assert_eq!(item, b"Hello");
+
+        let timeout = tokio::time::sleep(core::time::Duration::from_secs(10));
+        let item = framer
+            .next_timeout(|item| item.len(), timeout)
+            .await
+            .expect("Must be some")
+            .expect("Must decode");
+        assert_eq!(item, 3);
+
+        let timeout = tokio::time::sleep(core::time::Duration::from_millis(10));
+        let item = framer.next_timeout(|item| item.len(), timeout).await;
+        assert!(matches!(item, Some(Err(ReadError::Timeout))));
+    }
+
+    /// Records every write and counts the flushes.
+    #[derive(Default)]
+    struct CountingWriter {
+        writes: Vec<Vec<u8>>,
+        flushes: usize,
+    }
+
+    impl ErrorType for CountingWriter {
+        type Error = ErrorKind;
+    }
+
+    impl Write for CountingWriter {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.writes.push(buf.to_vec());
+
+            Ok(buf.len())
+        }
+
+        async fn flush(&mut self) -> Result<(), Self::Error> {
+            self.flushes += 1;
+
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn send_all_flushes_once() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedWrite::new(Lines::new(), CountingWriter::default(), buffer);
+
+        send!(
+            framer,
+            b"Hello".as_slice(),
+            b"Hey".as_slice(),
+            b"sup".as_slice()
+        )
+        .expect("Must send");
+
+        // The third frame does not fit the rest of the buffer.
+        assert_eq!(
+            framer.inner().writes,
+            [b"Hello\r\nHey\r\n".as_slice(), b"sup\r\n"]
+        );
+        assert_eq!(framer.inner().flushes, 1);
+
+        framer
+            .send_all([b"Hi".as_slice(); 3])
+            .await
+            .expect("Must send");
+
+        assert_eq!(framer.inner().writes[2], b"Hi\r\nHi\r\nHi\r\n");
+        assert_eq!(framer.inner().flushes, 2);
+    }
+
+    /// Fails on every read.
+    struct FailingReader;
+
+    impl ErrorType for FailingReader {
+        type Error = ErrorKind;
+    }
+
+    impl Read for FailingReader {
+        async fn read(&mut self, _: &mut [u8]) -> Result<usize, Self::Error> {
+            Err(ErrorKind::Other)
+        }
+    }
+
+    #[tokio::test]
+    async fn send_streamed_larger_than_buffer() {
+        init_tracing();
+
+        let (mut read, write) = tokio::io::duplex(1024);
+
+        let buffer = &mut [0_u8; 4];
+        let mut framer = FramedWrite::new(Bytes::new(), FromTokio::new(write), buffer);
+
+        let mut payload: &[u8] = b"Hello, world!";
+
+        framer
+            .send_streamed(b"13:".as_slice(), &mut payload, 13)
+            .await
+            .expect("Must send");
+
+        drop(framer);
+
+        let mut collected = Vec::new();
+        read.read_to_end(&mut collected).await.expect("Must read");
+
+        assert_eq!(collected, b"13:Hello, world!");
+    }
+
+    #[tokio::test]
+    async fn next_payload_chunk_larger_than_buffer() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        write
+            .write_all(b"13\nHello, world!end\n")
+            .await
+            .expect("Must write");
+        drop(write);
+
+        let buffer = &mut [0_u8; 8];
+        let mut framer = FramedRead::new(Lines::new(), FromTokio::new(read), buffer);
+
+        let header = next!(framer).expect("Must be some").expect("Must decode");
+        let mut remaining: usize = core::str::from_utf8(header)
+            .expect("Must be utf8")
+            .parse()
+            .expect("Must be a number");
+
+        let mut payload = Vec::new();
+
+        while let Some(chunk) = framer.next_payload_chunk(&mut remaining).await {
+            let chunk = chunk.expect("Must read");
+
+            assert!(chunk.len() <= 8);
+
+            payload.extend_from_slice(chunk);
+        }
+
+        assert_eq!(payload, b"Hello, world!");
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+
+        assert_eq!(item, b"end");
+        assert!(next!(framer).is_none());
+    }
+
+    /// Decodes a big endian `u16` payload length header.
+    struct LengthHeader;
+
+    struct Header(usize);
+
+    impl PayloadHeader for Header {
+        fn payload_len(&self) -> usize {
+            self.0
+        }
+    }
+
+    impl Decoder for LengthHeader {
+        type Item<'buf> = Header;
+        type Error = core::convert::Infallible;
+
+        fn decode<'buf>(
+            &mut self,
+            src: &'buf mut [u8],
+        ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+            match src {
+                [high, low, ..] => Ok(Some((
+                    Header(u16::from_be_bytes([*high, *low]) as usize),
+                    2,
+                ))),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn next_payload_chunk_with_payload_header() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        write
+            .write_all(b"\x00\x0dHello, world!\x00\x03end")
+            .await
+            .expect("Must write");
+        drop(write);
+
+        let buffer = &mut [0_u8; 8];
+        let mut framer = FramedRead::new(LengthHeader, FromTokio::new(read), buffer);
+
+        let mut payloads = Vec::new();
+
+        while let Some(header) = next!(framer) {
+            let mut remaining = header.expect("Must decode").payload_len();
+            let mut payload = Vec::new();
+
+            while let Some(chunk) = framer.next_payload_chunk(&mut remaining).await {
+                payload.extend_from_slice(chunk.expect("Must read"));
+            }
+
+            payloads.push(payload);
+        }
+
+        assert_eq!(payloads, [b"Hello, world!".as_slice(), b"end"]);
+    }
+
+    #[tokio::test]
+    async fn next_payload_chunk_short_payload() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        write.write_all(b"\x00\x0dHello").await.expect("Must write");
+        drop(write);
+
+        let buffer = &mut [0_u8; 8];
+        let mut framer = FramedRead::new(LengthHeader, FromTokio::new(read), buffer);
+
+        let mut remaining = next!(framer)
+            .expect("Must be some")
+            .expect("Must decode")
+            .payload_len();
+
+        let chunk = framer
+            .next_payload_chunk(&mut remaining)
+            .await
+            .expect("Must be some")
+            .expect("Must read");
+
+        assert_eq!(chunk, b"Hello");
+        assert_eq!(remaining, 8);
+
+        let result = framer.next_payload_chunk(&mut remaining).await;
+
+        assert!(matches!(result, Some(Err(ReadPayloadError::UnexpectedEof))));
+        assert_eq!(remaining, 8);
+    }
+
+    #[tokio::test]
+    async fn shift_policy() {
+        init_tracing();
+
+        for (shift_policy, shift) in [
+            (ShiftPolicy::WhenFull, false),
+            (ShiftPolicy::AboveThreshold(7), false),
+            (ShiftPolicy::AboveThreshold(6), true),
+            (ShiftPolicy::Eager, true),
+        ] {
+            let (read, mut write) = tokio::io::duplex(1024);
+
+            write.write_all(b"Hello\nWor").await.expect("Must write");
+
+            let buffer = &mut [0_u8; 16];
+            let mut framer = FramedRead::new(Lines::new(), FromTokio::new(read), buffer);
+            framer.set_shift_policy(shift_policy);
+
+            let item = next!(framer).expect("Must be some").expect("Must decode");
+            assert_eq!(item, b"Hello");
+
+            // Fails to decode the partial frame.
+            let item = framer.maybe_next().await.expect("Must be some");
+            assert!(matches!(item, Ok(None)));
+
+            assert_eq!(framer.core.state.read.phase == ReadPhase::Shift, shift);
+            assert_eq!(framer.framable(), 3);
+
+            write.write_all(b"ld\n").await.expect("Must write");
+
+            // Shifts, if requested, and reads in the same call.
+            let item = framer.maybe_next().await.expect("Must be some");
+            assert!(matches!(item, Ok(None)));
+
+            let index = if shift { 6 } else { 12 };
+            assert_eq!(framer.core.state.read.index, index);
+
+            let item = next!(framer).expect("Must be some").expect("Must decode");
+            assert_eq!(item, b"World");
+        }
+    }
+
+    #[tokio::test]
+    async fn send_streamed_payload_error() {
+        init_tracing();
+
+        let writer = FailingWriter {
+            accept: usize::MAX,
+            failed: false,
+            written: Vec::new(),
+        };
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Bytes::new(), writer, buffer);
+
+        let result = framer
+            .send_streamed(b"13:".as_slice(), &mut FailingReader, 13)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(SendStreamedError::Payload(ErrorKind::Other))
+        ));
+
+        // Nothing of the failed frame was written, so it does not leak into the next frame.
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        assert_eq!(framer.inner().written, b"Hey");
+    }
+
+    #[tokio::test]
+    async fn send_streamed_first_write_error() {
+        init_tracing();
+
+        // Fails on the first write.
+        let writer = FailingWriter {
+            accept: 0,
+            failed: false,
+            written: Vec::new(),
+        };
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Bytes::new(), writer, buffer);
+
+        let mut payload: &[u8] = b"Hello";
+
+        let result = framer
+            .send_streamed(b"5:".as_slice(), &mut payload, 5)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(SendStreamedError::Write(WriteError::IO(ErrorKind::Other)))
+        ));
+
+        // Nothing of the failed frame was written, so the next frame is sent normally.
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        assert_eq!(framer.inner().written, b"Hey");
+    }
+
+    #[tokio::test]
+    async fn send_streamed_cancelled_while_reading_payload() {
+        init_tracing();
+
+        let writer = FailingWriter {
+            accept: usize::MAX,
+            failed: false,
+            written: Vec::new(),
+        };
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Bytes::new(), writer, buffer);
+
+        let mut payload = PendingReader::new(b"Hello".as_slice(), 1);
+
+        let polled = framer
+            .send_streamed(b"5:".as_slice(), &mut payload, 5)
+            .now_or_never();
+
+        assert!(polled.is_none());
+
+        // The cancelled frame was only buffered, so it is dropped.
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        assert_eq!(framer.inner().written, b"Hey");
+    }
+
+    #[tokio::test]
+    async fn send_streamed_short_payload() {
+        init_tracing();
+
+        let writer = FailingWriter {
+            accept: usize::MAX,
+            failed: false,
+            written: Vec::new(),
+        };
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Bytes::new(), writer, buffer);
+
+        let mut payload: &[u8] = b"Hello";
+
+        let result = framer
+            .send_streamed(b"13:".as_slice(), &mut payload, 13)
+            .await;
+
+        assert!(matches!(result, Err(SendStreamedError::UnexpectedEof)));
+
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        assert_eq!(framer.inner().written, b"Hey");
+    }
+
+    #[tokio::test]
+    async fn send_streamed_stops_at_payload_len() {
+        init_tracing();
+
+        let writer = FailingWriter {
+            accept: usize::MAX,
+            failed: false,
+            written: Vec::new(),
+        };
+
+        let buffer = &mut [0_u8; 4];
+        let mut framer = FramedWrite::new(Bytes::new(), writer, buffer);
+
+        let mut payload: &[u8] = b"Hello, world!";
+
+        framer
+            .send_streamed(b"5:".as_slice(), &mut payload, 5)
+            .await
+            .expect("Must send");
+
+        assert_eq!(framer.inner().written, b"5:Hello");
+        assert_eq!(payload, b", world!");
+    }
+
+    #[tokio::test]
+    async fn send_streamed_cancelled_is_not_resumed() {
+        init_tracing();
+
+        // The duplex only accepts 4 bytes until the reader reads, so the streamed frame is only partially written.
+        let (mut read, write) = tokio::io::duplex(4);
+
+        let buffer = &mut [0_u8; 8];
+        let mut framer = FramedWrite::new(Bytes::new(), FromTokio::new(write), buffer);
+
+        let mut payload: &[u8] = b"Hello, world!";
+
+        let polled = framer
+            .send_streamed(b"13:".as_slice(), &mut payload, 13)
+            .now_or_never();
+
+        assert!(polled.is_none());
+
+        let reader = tokio::spawn(async move {
+            let mut collected = Vec::new();
+            read.read_to_end(&mut collected).await.expect("Must read");
+
+            collected
+        });
+
+        let result = framer.send(b"Hey".as_slice()).await;
+
+        assert!(matches!(result, Err(WriteError::IncompleteStreamedFrame)));
+
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        drop(framer);
+
+        let collected = reader.await.expect("Must join");
+
+        // The partially written bytes of the streamed frame are not resumed.
+        assert_eq!(collected, b"13:HHey");
+    }
+
+    /// Reads from and writes to memory, but is only ready while `ready` is set.
+    struct ReadyMock {
+        ready: bool,
+        read: Vec<u8>,
+        written: Vec<u8>,
+        max_write: usize,
+    }
+
+    impl ReadyMock {
+        fn new(read: &[u8]) -> Self {
+            Self {
+                ready: false,
+                read: read.to_vec(),
+                written: Vec::new(),
+                max_write: usize::MAX,
             }
-        },
-        Err(err) => {
-            error!(target: WRITE, "Failed to encode frame");
+        }
+    }
+
+    impl ErrorType for ReadyMock {
+        type Error = ErrorKind;
+    }
+
+    impl Read for ReadyMock {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let size = core::cmp::min(buf.len(), self.read.len());
+
+            buf[..size].copy_from_slice(&self.read[..size]);
+            self.read.drain(..size);
+
+            Ok(size)
+        }
+    }
+
+    impl ReadReady for ReadyMock {
+        fn read_ready(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.ready)
+        }
+    }
+
+    impl Write for ReadyMock {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            let size = core::cmp::min(buf.len(), self.max_write);
+
+            self.written.extend_from_slice(&buf[..size]);
+
+            Ok(size)
+        }
+    }
+
+    impl WriteReady for ReadyMock {
+        fn write_ready(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.ready)
+        }
+    }
+
+    #[tokio::test]
+    async fn try_next_returns_when_not_ready() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Lines::new(), ReadyMock::new(b"Hello\nHey\n"), buffer);
+
+        let item = framer.try_next(|item| item.len()).await;
+        assert!(matches!(item, Some(Ok(None))));
+        assert_eq!(framer.framable(), 0);
+
+        framer.inner_mut().ready = true;
+
+        let item = framer.try_next(|item| item.len()).await;
+        assert!(matches!(item, Some(Ok(Some(5)))));
+
+        // The second frame is already buffered, so readiness does not matter.
+        framer.inner_mut().ready = false;
+
+        let item = try_next!(framer)
+            .expect("Must be some")
+            .expect("Must decode");
+        assert_eq!(item, Some(b"Hey".as_slice()));
+
+        assert!(matches!(try_next!(framer), Some(Ok(None))));
+
+        framer.inner_mut().ready = true;
+
+        assert!(try_next!(framer).is_none());
+    }
+
+    #[tokio::test]
+    async fn try_send_returns_not_ready() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedWrite::new(Lines::new(), ReadyMock::new(b""), buffer);
+
+        let result = framer.try_send(b"Hello".as_slice()).await;
+        assert!(matches!(result, Err(WriteError::NotReady)));
+        assert_eq!(framer.core.state.write.encoded, 0);
+
+        framer.inner_mut().ready = true;
+        framer.inner_mut().max_write = 4;
+
+        framer
+            .try_send(b"Hello".as_slice())
+            .await
+            .expect("Must send");
+        assert_eq!(framer.inner().written, b"Hello\r\n");
+    }
+
+    /// Readiness drops after the first write, so the frame is accepted but only partially written.
+    struct ReadyOnce(ReadyMock);
+
+    impl ErrorType for ReadyOnce {
+        type Error = ErrorKind;
+    }
+
+    impl Write for ReadyOnce {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.0.ready = false;
+
+            self.0.write(buf).await
+        }
+    }
+
+    impl WriteReady for ReadyOnce {
+        fn write_ready(&mut self) -> Result<bool, Self::Error> {
+            self.0.write_ready()
+        }
+    }
+
+    #[tokio::test]
+    async fn try_send_keeps_pending_frame() {
+        init_tracing();
+
+        let mut mock = ReadyMock::new(b"");
+        mock.ready = true;
+        mock.max_write = 4;
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedWrite::new(Lines::new(), ReadyOnce(mock), buffer);
+
+        framer
+            .try_send(b"Hello".as_slice())
+            .await
+            .expect("Must accept");
+        assert_eq!(framer.core.state.write.pending(), 3);
+
+        // The rest of the first frame is not written yet, so the new item is rejected.
+        let result = framer.try_send(b"Hey".as_slice()).await;
+        assert!(matches!(result, Err(WriteError::NotReady)));
+
+        framer.inner_mut().0.ready = true;
+
+        // Writes the rest of the first frame, then readiness drops again.
+        let result = framer.try_send(b"Hey".as_slice()).await;
+        assert!(matches!(result, Err(WriteError::NotReady)));
+        assert_eq!(framer.core.state.write.pending(), 0);
+
+        framer.inner_mut().0.ready = true;
+        framer.inner_mut().0.max_write = usize::MAX;
+
+        framer
+            .try_send(b"Hey".as_slice())
+            .await
+            .expect("Must accept");
+        assert_eq!(framer.inner().0.written, b"Hello\r\nHey\r\n");
+    }
+
+    /// Claims to consume one byte more than it was given.
+    struct Overconsuming;
+
+    impl Decoder for Overconsuming {
+        type Item<'buf> = &'buf [u8];
+        type Error = core::convert::Infallible;
+
+        fn decode<'buf>(
+            &mut self,
+            src: &'buf mut [u8],
+        ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+            if src.is_empty() {
+                return Ok(None);
+            }
+
+            let size = src.len() + 1;
+
+            Ok(Some((src, size)))
+        }
+    }
+
+    /// Claims to consume nothing.
+    struct Nonconsuming;
+
+    impl Decoder for Nonconsuming {
+        type Item<'buf> = &'buf [u8];
+        type Error = core::convert::Infallible;
+
+        fn decode<'buf>(
+            &mut self,
+            src: &'buf mut [u8],
+        ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+            Ok(Some((src, 0)))
+        }
+    }
+
+    #[tokio::test]
+    async fn misbehaving_decoder_returns_bad_decoder() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Overconsuming, b"Hello".as_slice(), buffer);
+
+        assert!(matches!(next!(framer), Some(Err(ReadError::BadDecoder(6)))));
+        assert_eq!(framer.core.state.read.framable(), 5);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Nonconsuming, b"Hello".as_slice(), buffer);
+
+        assert!(matches!(next!(framer), Some(Err(ReadError::BadDecoder(0)))));
+        assert_eq!(framer.core.state.read.framable(), 5);
+    }
+
+    #[tokio::test]
+    async fn inconsistent_write_state_returns_invalid_state() {
+        init_tracing();
+
+        let writer = FailingWriter {
+            accept: usize::MAX,
+            failed: false,
+            written: Vec::new(),
+        };
+
+        let buffer = &mut [0_u8; 8];
+        let mut framer = FramedWrite::new(Lines::new(), writer, buffer);
+
+        framer.core.state.write.encoded = 16;
+
+        assert!(matches!(
+            send!(framer, b"Hey".as_slice()),
+            Err(WriteError::InvalidState)
+        ));
+
+        framer.core.state.write.discard_pending();
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        assert_eq!(framer.inner().written, b"Hey\r\n");
+    }
+
+    /// Decodes frames with a one byte length prefix and counts the decode attempts on partial frames.
+    #[derive(Default)]
+    struct LengthPrefixed {
+        partial: usize,
+    }
+
+    impl Decoder for LengthPrefixed {
+        type Item<'buf> = &'buf [u8];
+        type Error = core::convert::Infallible;
+
+        fn decode<'buf>(
+            &mut self,
+            src: &'buf mut [u8],
+        ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+            let Some((len, rest)) = src.split_first() else {
+                return Ok(None);
+            };
+
+            let Some(payload) = rest.get(..*len as usize) else {
+                self.partial += 1;
+
+                return Ok(None);
+            };
+
+            Ok(Some((payload, *len as usize + 1)))
+        }
+
+        fn decode_header(&mut self, src: &[u8]) -> Option<usize> {
+            src.first().map(|len| *len as usize + 1)
+        }
+    }
+
+    /// Returns one byte per read.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl ErrorType for Trickle<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl Read for Trickle<'_> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let Some((byte, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+
+            buf[0] = *byte;
+            self.0 = rest;
+
+            Ok(1)
+        }
+    }
+
+    #[tokio::test]
+    async fn decode_header_skips_partial_frames() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 8];
+        let mut framer = FramedRead::new(
+            LengthPrefixed::default(),
+            Trickle(b"\x05Hello\x06world!"),
+            buffer,
+        );
+
+        let mut collected = Vec::<Vec<u8>>::new();
+
+        while let Some(item) = next!(framer) {
+            collected.push(item.expect("Must decode").into());
+        }
+
+        assert_eq!(collected, [b"Hello".as_slice(), b"world!"]);
+
+        assert_eq!(framer.codec().partial, 0);
+    }
+
+    #[tokio::test]
+    async fn decode_header_frame_larger_than_buffer() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 8];
+        let mut framer = FramedRead::new(
+            LengthPrefixed::default(),
+            b"\x08Hello, world!".as_slice(),
+            buffer,
+        );
+
+        assert!(matches!(
+            next!(framer),
+            Some(Err(ReadError::BufferTooSmall))
+        ));
+        assert_eq!(framer.codec().partial, 0);
+    }
+
+    /// Records every write call.
+    #[derive(Default)]
+    struct RecordingWriter {
+        writes: Vec<Vec<u8>>,
+    }
+
+    impl ErrorType for RecordingWriter {
+        type Error = ErrorKind;
+    }
+
+    impl Write for RecordingWriter {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.writes.push(buf.to_vec());
+
+            Ok(buf.len())
+        }
+    }
+
+    #[tokio::test]
+    async fn send_coalesces_until_flush_threshold() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedWrite::new(Lines::new(), RecordingWriter::default(), buffer);
+
+        framer.set_flush_threshold(12);
+
+        framer.send(b"one".as_slice()).await.expect("Must send");
+        framer.send(b"two".as_slice()).await.expect("Must send");
+
+        assert!(framer.inner().writes.is_empty());
+
+        // Reaches the threshold.
+        framer.send(b"six".as_slice()).await.expect("Must send");
+
+        assert_eq!(framer.inner().writes, [b"one\r\ntwo\r\nsix\r\n".as_slice()]);
+
+        framer
+            .send(b"Hello, 12".as_slice())
+            .await
+            .expect("Must send");
+
+        // Does not fit after the buffered frame.
+        framer.send(b"four".as_slice()).await.expect("Must send");
+
+        assert_eq!(framer.inner().writes.len(), 2);
+        assert_eq!(framer.inner().writes[1], b"Hello, 12\r\n");
+
+        framer.flush().await.expect("Must flush");
+
+        assert_eq!(framer.inner().writes[2], b"four\r\n");
+        assert_eq!(framer.core.state.write.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn send_parts_does_not_copy_the_payload() {
+        init_tracing();
+
+        // Only the trailer fits the write buffer.
+        let buffer = &mut [0_u8; 2];
+        let mut framer = FramedWrite::new(Lines::new(), RecordingWriter::default(), buffer);
+
+        framer
+            .send_parts(b"Hello, world!".as_slice())
+            .await
+            .expect("Must send");
+
+        assert_eq!(
+            framer.inner().writes,
+            [b"Hello, world!".as_slice(), b"\r\n"]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_parts_failed_frame_is_not_resumed() {
+        init_tracing();
+
+        let writer = FailingWriter {
+            accept: 4,
+            failed: false,
+            written: Vec::new(),
+        };
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Lines::new(), writer, buffer);
+
+        assert!(
+            framer
+                .send_parts(b"Hello, world!".as_slice())
+                .await
+                .is_err()
+        );
+
+        assert!(matches!(
+            framer.send(b"Hey".as_slice()).await,
+            Err(WriteError::IncompleteStreamedFrame)
+        ));
 
-            Err(WriteError::Encode(err))
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        assert_eq!(framer.inner().written, b"HellHey\r\n");
+    }
+
+    #[tokio::test]
+    async fn send_direct_skips_the_write_buffer() {
+        init_tracing();
+
+        // Too small for the frame.
+        let buffer = &mut [0_u8; 4];
+        let mut framer = FramedWrite::new(Lines::new(), RecordingWriter::default(), buffer);
+
+        framer
+            .send_direct(b"Hello, world!".as_slice())
+            .await
+            .expect("Must send");
+
+        assert_eq!(
+            framer.inner().writes,
+            [b"Hello, world!".as_slice(), b"\r\n"]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_direct_encodes_into_the_write_buffer_by_default() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Cobs::new(), RecordingWriter::default(), buffer);
+
+        framer
+            .send_direct(b"\x01\x00\x02".as_slice())
+            .await
+            .expect("Must send");
+
+        assert_eq!(framer.inner().writes, [b"\x02\x01\x02\x02\x00".as_slice()]);
+
+        let buffer = &mut [0_u8; 2];
+        let mut framer = FramedWrite::new(Cobs::new(), RecordingWriter::default(), buffer);
+
+        assert!(matches!(
+            framer.send_direct(b"\x01\x00\x02".as_slice()).await,
+            Err(WriteError::Encode(_))
+        ));
+
+        // Nothing was written, so the next frame is sent normally.
+        framer.send(b"".as_slice()).await.expect("Must send");
+    }
+
+    /// Streams the length of the item, then fails to encode it.
+    struct Torn;
+
+    impl Encoder<&[u8]> for Torn {
+        type Error = EncodeBufferTooSmall;
+
+        fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
+            Err(EncodeBufferTooSmall {
+                required: item.len(),
+                available: dst.len(),
+            })
+        }
+
+        async fn encode_to_writer<W>(
+            &mut self,
+            item: &[u8],
+            buf: &mut [u8],
+            write: &mut W,
+        ) -> Result<usize, WriteError<W::Error, Self::Error>>
+        where
+            W: Write,
+        {
+            write_all(write, &[item.len() as u8]).await?;
+
+            self.encode(item, buf).map_err(WriteError::Encode)
+        }
+    }
+
+    #[tokio::test]
+    async fn send_direct_reports_frames_torn_by_the_codec() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Torn, RecordingWriter::default(), buffer);
+
+        assert!(matches!(
+            framer.send_direct(b"Hello".as_slice()).await,
+            Err(WriteError::Encode(_))
+        ));
+
+        // The length was written.
+        assert!(matches!(
+            framer.send_raw(b"Hey").await,
+            Err(WriteError::IncompleteStreamedFrame)
+        ));
+
+        framer.send_raw(b"Hey").await.expect("Must send");
+
+        assert_eq!(
+            framer.inner().writes,
+            [b"\x05".as_slice(), b"Hey".as_slice()]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_direct_first_write_error() {
+        init_tracing();
+
+        let writer = FailingWriter {
+            accept: 0,
+            failed: false,
+            written: Vec::new(),
+        };
+
+        let buffer = &mut [0_u8; 8];
+        let mut framer = FramedWrite::new(Lines::new(), writer, buffer);
+
+        assert!(matches!(
+            framer.send_direct(b"Hello".as_slice()).await,
+            Err(WriteError::IO(ErrorKind::Other))
+        ));
+
+        // Nothing was written, so the next frame is sent normally.
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        assert_eq!(framer.inner().written, b"Hey\r\n");
+    }
+
+    #[tokio::test]
+    async fn send_raw_keeps_coalesced_frames_in_order() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Lines::new(), RecordingWriter::default(), buffer);
+        framer.set_flush_threshold(16);
+
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+        framer
+            .send_raw(b"Hello, world!\r\n")
+            .await
+            .expect("Must send");
+
+        assert_eq!(
+            framer.inner().writes,
+            [b"Hey\r\n".as_slice(), b"Hello, world!\r\n"]
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn spans_record_fields() {
+        use std::sync::{Arc, Mutex};
+
+        use tracing::{
+            Subscriber,
+            field::{Field, Visit},
+            span::{Attributes, Id, Record},
+        };
+        use tracing_subscriber::{
+            Layer,
+            layer::{Context, SubscriberExt},
+            util::SubscriberInitExt,
+        };
+
+        type Fields = Arc<Mutex<Vec<(&'static str, u64)>>>;
+
+        struct Collect(Fields);
+
+        impl Visit for &Collect {
+            fn record_u64(&mut self, field: &Field, value: u64) {
+                self.0
+                    .lock()
+                    .expect("Must lock")
+                    .push((field.name(), value));
+            }
+
+            fn record_debug(&mut self, _: &Field, _: &dyn core::fmt::Debug) {}
+        }
+
+        impl<S: Subscriber> Layer<S> for Collect {
+            fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+                attrs.record(&mut &*self);
+            }
+
+            fn on_record(&self, _: &Id, values: &Record<'_>, _: Context<'_, S>) {
+                values.record(&mut &*self);
+            }
+        }
+
+        let fields = Fields::default();
+        let subscriber = tracing_subscriber::registry().with(Collect(fields.clone()));
+
+        let guard = subscriber.set_default();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(Lines::new(), b"Hello\r\n".as_slice(), buffer);
+
+        async {
+            next!(framer).expect("Must be some").expect("Must decode");
         }
+        .now_or_never()
+        .expect("Must be ready");
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Lines::new(), Vec::new(), buffer);
+
+        framer
+            .send(b"Hey".as_slice())
+            .now_or_never()
+            .expect("Must be ready")
+            .expect("Must send");
+
+        drop(guard);
+
+        let fields = fields.lock().expect("Must lock");
+
+        assert!(fields.contains(&("fill", 0)));
+        assert!(fields.contains(&("read", 7)));
+        assert!(fields.contains(&("fill", 7)));
+        assert!(fields.contains(&("consumed", 7)));
+        assert!(fields.contains(&("pending", 0)));
+        assert!(fields.contains(&("frame_len", 5)));
     }
 }
@@ -0,0 +1,57 @@
+//! Minimal future combinators, so the core API does not depend on `futures`.
+
+use core::{
+    future::{Future, poll_fn},
+    pin::Pin,
+    task::Poll,
+};
+
+/// The output of [`select`].
+#[derive(Debug)]
+pub enum Either<A, B> {
+    /// The first future completed first.
+    Left(A),
+    /// The second future completed first.
+    Right(B),
+}
+
+/// Waits for the first of two futures to complete.
+///
+/// The first future is polled first, so it wins if both are ready.
+pub async fn select<A, B>(mut a: Pin<&mut A>, mut b: Pin<&mut B>) -> Either<A::Output, B::Output>
+where
+    A: Future,
+    B: Future,
+{
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = a.as_mut().poll(cx) {
+            return Poll::Ready(Either::Left(output));
+        }
+
+        if let Poll::Ready(output) = b.as_mut().poll(cx) {
+            return Poll::Ready(Either::Right(output));
+        }
+
+        Poll::Pending
+    })
+    .await
+}
+
+/// Yields to the executor once, after waking the current task, so other tasks can make progress.
+#[cfg(any(test, feature = "heapless", feature = "mock"))]
+pub async fn yield_now() {
+    let mut yielded = false;
+
+    poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+
+        yielded = true;
+
+        cx.waker().wake_by_ref();
+
+        Poll::Pending
+    })
+    .await
+}
@@ -0,0 +1,285 @@
+//! Request-response exchanges on half-duplex buses.
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_io_async::{Read, Write};
+
+use crate::{
+    Framed, ReadError, TransferError, WriteError,
+    buffer::Buffer,
+    decode::Decoder,
+    encode::Encoder,
+    logging::{debug, trace},
+    timing::Timing,
+};
+
+#[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
+use crate::logging::Name;
+
+#[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
+const TARGET: &str = "framez::half_duplex";
+
+/// Wraps a [`Framed`] on a half-duplex bus, e.g. RS-485, IR or a single-wire UART, where only one side may transmit at a time.
+///
+/// Sending and receiving strictly alternate: [`HalfDuplex::send`] returns only once the frame was flushed and the bus was
+/// silent for the [`Timing::gap_us`], so the transmitter is turned around before the next call to [`HalfDuplex::next`] can start reading.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_hal_async::delay::DelayNs;
+/// use framez::{Framed, codec::lines::StrLines, half_duplex::HalfDuplex, mock::Noop, timing::Timing};
+///
+/// # async fn run<D: DelayNs>(delay: D) {
+/// let (read_buf, write_buf) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+/// let framed = Framed::new(StrLines::new(), Noop, read_buf, write_buf);
+///
+/// let mut bus = HalfDuplex::new(framed, Timing::new(delay).with_gap_us(100)).with_response_timeout_us(50_000);
+///
+/// let response = bus.transfer("Ping", |line| line.len()).await;
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct HalfDuplex<'buf, C, RW, D, RB = &'buf mut [u8], WB = &'buf mut [u8]> {
+    framed: Framed<'buf, C, RW, RB, WB>,
+    timing: Timing<D>,
+    /// The time to wait for a response in microseconds.
+    response_timeout_us: u32,
+}
+
+impl<'buf, C, RW, D, RB, WB> HalfDuplex<'buf, C, RW, D, RB, WB> {
+    /// Creates a new [`HalfDuplex`] that turns the bus around using the [`Timing::gap_us`] of `timing`.
+    ///
+    /// The response timeout defaults to `1` second.
+    #[inline]
+    pub const fn new(framed: Framed<'buf, C, RW, RB, WB>, timing: Timing<D>) -> Self {
+        Self {
+            framed,
+            timing,
+            response_timeout_us: 1_000_000,
+        }
+    }
+
+    /// Sets the time [`HalfDuplex::transfer`] waits for a response in microseconds.
+    #[inline]
+    pub const fn with_response_timeout_us(mut self, response_timeout_us: u32) -> Self {
+        self.response_timeout_us = response_timeout_us;
+        self
+    }
+
+    /// Returns the time [`HalfDuplex::transfer`] waits for a response in microseconds.
+    #[inline]
+    pub const fn response_timeout_us(&self) -> u32 {
+        self.response_timeout_us
+    }
+
+    /// Returns reference to the framer.
+    #[inline]
+    pub const fn framed(&self) -> &Framed<'buf, C, RW, RB, WB> {
+        &self.framed
+    }
+
+    /// Returns mutable reference to the framer.
+    #[inline]
+    pub const fn framed_mut(&mut self) -> &mut Framed<'buf, C, RW, RB, WB> {
+        &mut self.framed
+    }
+
+    /// Returns reference to the timing.
+    #[inline]
+    pub const fn timing(&self) -> &Timing<D> {
+        &self.timing
+    }
+
+    /// Returns mutable reference to the timing.
+    #[inline]
+    pub const fn timing_mut(&mut self) -> &mut Timing<D> {
+        &mut self.timing
+    }
+
+    /// Consumes the [`HalfDuplex`] and returns the framer and the timing.
+    #[inline]
+    pub fn into_parts(self) -> (Framed<'buf, C, RW, RB, WB>, Timing<D>) {
+        (self.framed, self.timing)
+    }
+
+    /// Sends a frame and waits until the bus is turned around.
+    ///
+    /// The frame is flushed, even if it was coalesced, then the bus is kept silent for the [`Timing::gap_us`].
+    /// No gap is kept if sending failed.
+    pub async fn send<I>(&mut self, item: I) -> Result<(), WriteError<RW::Error, C::Error>>
+    where
+        C: Encoder<I>,
+        RW: Write,
+        WB: Buffer,
+        D: DelayNs,
+    {
+        self.framed.send(item).await?;
+        self.framed.flush().await.map_err(WriteError::cast_encode)?;
+
+        let gap_us = self.timing.gap_us();
+
+        self.timing.delay_mut().delay_us(gap_us).await;
+
+        trace!(target: TARGET, "{}Turned around. gap: {}us", Name(self.framed.core.state.write.name), gap_us);
+
+        Ok(())
+    }
+
+    /// Reads a frame and converts it using the given `map` function. See [`Framed::next`].
+    pub async fn next<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<RW::Error, C::Error>>>
+    where
+        U: 'static,
+        C: Decoder,
+        RW: Read,
+        RB: Buffer,
+    {
+        self.framed.next(map).await
+    }
+
+    /// Sends the `request`, then reads the response and converts it using the given `map` function.
+    ///
+    /// Bytes that are still buffered from before the request, e.g. a partial response to a previous request that timed out,
+    /// are discarded first, so they are not mistaken for the response.
+    ///
+    /// # Errors
+    ///
+    /// - [`TransferError::Write`] if sending the `request` failed. Nothing is read.
+    /// - [`TransferError::Read`] if reading failed, or [`ReadError::Timeout`] if no response was received within the [`HalfDuplex::response_timeout_us`].
+    /// - [`TransferError::Closed`] if the peer closed the connection before sending a response.
+    pub async fn transfer<I, U>(
+        &mut self,
+        request: I,
+        map: fn(<C as Decoder>::Item<'_>) -> U,
+    ) -> Result<U, TransferError<RW::Error, <C as Encoder<I>>::Error, <C as Decoder>::Error>>
+    where
+        U: 'static,
+        C: Encoder<I> + Decoder,
+        RW: Read + Write,
+        RB: Buffer,
+        WB: Buffer,
+        D: DelayNs,
+    {
+        let discarded = self.framed.discard_buffered();
+
+        if discarded > 0 {
+            debug!(target: TARGET, "{}Discarded stale bytes. discarded: {}", Name(self.framed.core.state.read.name), discarded);
+        }
+
+        self.send(request).await?;
+
+        let timeout = self.timing.delay_mut().delay_us(self.response_timeout_us);
+
+        match self.framed.next_timeout(map, timeout).await {
+            Some(Ok(response)) => Ok(response),
+            Some(Err(err)) => Err(TransferError::Read(err)),
+            None => Err(TransferError::Closed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_io_async::Write as _;
+
+    use crate::{
+        codec::lines::StrLines,
+        mock::{Duplex, RecordingDelay},
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn transfer_sends_turns_around_and_reads_the_response() {
+        init_tracing();
+
+        let duplex = Duplex::<32>::new();
+        let (end, mut peer) = duplex.split();
+
+        peer.write_all(b"Pong\n").await.expect("Must write");
+
+        let (read_buf, write_buf) = (&mut [0_u8; 16], &mut [0_u8; 16]);
+        let framed = Framed::new(StrLines::new(), end, read_buf, write_buf);
+
+        let mut bus = HalfDuplex::new(
+            framed,
+            Timing::new(RecordingDelay::<4>::new()).with_gap_us(100),
+        )
+        .with_response_timeout_us(5_000);
+
+        let response = bus
+            .transfer("Ping", |line| std::string::String::from(line))
+            .await
+            .expect("Must transfer");
+
+        assert_eq!(response, "Pong");
+
+        // The gap. The response was read before the timeout was polled.
+        assert_eq!(bus.timing().delay().delays(), [100_000]);
+
+        let buf = &mut [0_u8; 16];
+        let n = embedded_io_async::Read::read(&mut peer, buf)
+            .await
+            .expect("Must read");
+
+        assert_eq!(&buf[..n], b"Ping\r\n");
+    }
+
+    #[tokio::test]
+    async fn stale_bytes_are_discarded_before_the_request() {
+        init_tracing();
+
+        let duplex = Duplex::<32>::new();
+        let (end, mut peer) = duplex.split();
+
+        // A partial response that arrives too late.
+        peer.write_all(b"Lat").await.expect("Must write");
+
+        let (read_buf, write_buf) = (&mut [0_u8; 16], &mut [0_u8; 16]);
+        let framed = Framed::new(StrLines::new(), end, read_buf, write_buf);
+
+        // The delay completes right away, so the timeout wins once nothing more can be read.
+        let mut bus = HalfDuplex::new(framed, Timing::new(RecordingDelay::<4>::new()));
+
+        assert!(matches!(
+            bus.transfer("Ping", |line| line.len()).await,
+            Err(TransferError::Read(ReadError::Timeout))
+        ));
+
+        peer.write_all(b"Pong\n").await.expect("Must write");
+
+        let len = bus
+            .transfer("Ping", |line| line.len())
+            .await
+            .expect("Must transfer");
+
+        assert_eq!(len, 4);
+    }
+
+    #[tokio::test]
+    async fn send_flushes_coalesced_frames() {
+        init_tracing();
+
+        let duplex = Duplex::<32>::new();
+        let (end, mut peer) = duplex.split();
+
+        let (read_buf, write_buf) = (&mut [0_u8; 16], &mut [0_u8; 16]);
+        let mut framed = Framed::new(StrLines::new(), end, read_buf, write_buf);
+
+        framed.set_flush_threshold(16);
+
+        let mut bus = HalfDuplex::new(framed, Timing::new(RecordingDelay::<4>::new()));
+
+        bus.send("Hello").await.expect("Must send");
+
+        let buf = &mut [0_u8; 16];
+        let n = embedded_io_async::Read::read(&mut peer, buf)
+            .await
+            .expect("Must read");
+
+        assert_eq!(&buf[..n], b"Hello\r\n");
+    }
+}
@@ -0,0 +1,326 @@
+//! Lending streams of borrowed frames.
+//!
+//! A [`LendingStream`] yields items that borrow the stream itself, e.g. frames borrowed from the read buffer of a framer.
+//! Unlike a [`Stream`](https://docs.rs/futures/latest/futures/stream/trait.Stream.html), an item must be dropped before the next one is requested.
+//!
+//! [`LendingStream`] is implemented for [`FramedRead`] and [`Framed`], so frames can be consumed by generic code without mapping them to owned items like [`Framed::stream`](crate::Framed::stream).
+//! The framers have an inherent `next` method that maps the frames, so [`LendingStream::next`] is called by its path on them.
+//!
+//! # Example
+//!
+//! ```rust
+//! use framez::{FramedRead, codec::lines::StrLines, lending::LendingStream};
+//!
+//! # async fn run() {
+//! let buffer = &mut [0_u8; 32];
+//! let mut framer = FramedRead::new(StrLines::new(), b"Hello\nHey\n".as_slice(), buffer);
+//!
+//! while let Some(item) = LendingStream::next(&mut framer).await {
+//!     let item: &str = item.unwrap();
+//! }
+//! # }
+//! ```
+
+use core::{
+    future::Future,
+    pin::{Pin, pin},
+    task::{Context, Poll},
+};
+
+use embedded_io_async::Read;
+use polonius_the_crab::{polonius, polonius_return};
+
+use crate::{
+    Framed, FramedRead, ReadError,
+    buffer::Buffer,
+    decode::Decoder,
+    functions,
+    state::{ReadPhase, ReadState},
+};
+
+/// A stream of items that borrow the stream.
+pub trait LendingStream {
+    /// The items of the stream, borrowing it for `'a`.
+    type Item<'a>
+    where
+        Self: 'a;
+
+    /// Polls the next item of the stream.
+    ///
+    /// # Return value
+    ///
+    /// - `Poll::Ready(Some(item))` if an item is available.
+    /// - `Poll::Ready(None)` if the stream ended.
+    /// - `Poll::Pending` if no item is available yet. The waker of `cx` is woken once the stream should be polled again.
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Self::Item<'_>>>;
+
+    /// Returns a future of the next item of the stream.
+    #[inline]
+    fn next(&mut self) -> Next<'_, Self>
+    where
+        Self: Sized,
+    {
+        Next::new(self)
+    }
+}
+
+impl<S> LendingStream for &mut S
+where
+    S: LendingStream,
+{
+    type Item<'a>
+        = S::Item<'a>
+    where
+        Self: 'a;
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Self::Item<'_>>> {
+        (*self).poll_next(cx)
+    }
+}
+
+/// The future of [`LendingStream::next`].
+///
+/// The future is fused. Polling it again after it completed returns `Poll::Ready(None)` instead of panicking.
+#[derive(Debug)]
+pub struct Next<'a, S> {
+    /// Taken when the future completes, so the item can borrow the stream for `'a`.
+    stream: Option<&'a mut S>,
+}
+
+impl<'a, S> Next<'a, S> {
+    /// Creates a new [`Next`] over the given `stream`.
+    #[inline]
+    pub const fn new(stream: &'a mut S) -> Self {
+        Self {
+            stream: Some(stream),
+        }
+    }
+}
+
+impl<'a, S> Future for Next<'a, S>
+where
+    S: LendingStream,
+{
+    type Output = Option<S::Item<'a>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Some(mut stream) = self.stream.take() else {
+            return Poll::Ready(None);
+        };
+
+        // A ready item borrows the stream for `'a`, while a pending poll gives it back.
+        // The borrow checker can not express this yet, see https://github.com/rust-lang/rust/issues/54663.
+        polonius!(|stream| -> Poll<Option<S::Item<'polonius>>> {
+            if let Poll::Ready(item) = stream.poll_next(cx) {
+                polonius_return!(Poll::Ready(item));
+            }
+        });
+
+        self.stream = Some(stream);
+
+        Poll::Pending
+    }
+}
+
+/// Polls the next frame borrowed from the buffer of the `state`.
+///
+/// Like [`MaybeNext`](crate::ops::MaybeNext), the read is polled and dropped if it is pending.
+/// Bytes are read until a frame is decoded, the stream ends or the read is pending.
+#[allow(clippy::type_complexity)]
+fn poll_next<'a, C, R, B>(
+    mut state: &'a mut ReadState<'_, B>,
+    codec: &mut C,
+    read: &mut R,
+    cx: &mut Context<'_>,
+) -> Poll<Option<Result<C::Item<'a>, ReadError<R::Error, C::Error>>>>
+where
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+{
+    loop {
+        while !matches!(state.phase, ReadPhase::Decode | ReadPhase::DecodeEof) {
+            match pin!(functions::read_into(state, read)).poll(cx) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(err)) => return Poll::Ready(Some(Err(err.cast_decode()))),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        // A decoded frame borrows the state for `'a`, while an incomplete one gives it back to read more bytes.
+        polonius!(
+            |state| -> Poll<Option<Result<C::Item<'polonius>, ReadError<R::Error, C::Error>>>> {
+                match functions::decode_pending(state, codec) {
+                    Some(Ok(Some(item))) => polonius_return!(Poll::Ready(Some(Ok(item)))),
+                    Some(Ok(None)) => {}
+                    Some(Err(err)) => polonius_return!(Poll::Ready(Some(Err(err)))),
+                    None => polonius_return!(Poll::Ready(None)),
+                }
+            }
+        );
+    }
+}
+
+/// Yields the frames borrowed from the read buffer.
+///
+/// The stream is pending whenever more bytes are needed. See [`MaybeNext`](crate::ops::MaybeNext) for how the read is polled.
+impl<C, R, B> LendingStream for FramedRead<'_, C, R, B>
+where
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+{
+    type Item<'a>
+        = Result<C::Item<'a>, ReadError<R::Error, C::Error>>
+    where
+        Self: 'a;
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Self::Item<'_>>> {
+        poll_next(
+            &mut self.core.state.read,
+            &mut self.core.codec,
+            &mut self.core.inner,
+            cx,
+        )
+    }
+}
+
+/// See the [`LendingStream`] of [`FramedRead`].
+impl<C, RW, RB, WB> LendingStream for Framed<'_, C, RW, RB, WB>
+where
+    C: Decoder,
+    RW: Read,
+    RB: Buffer,
+{
+    type Item<'a>
+        = Result<C::Item<'a>, ReadError<RW::Error, C::Error>>
+    where
+        Self: 'a;
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Self::Item<'_>>> {
+        poll_next(
+            &mut self.core.state.read,
+            &mut self.core.codec,
+            &mut self.core.inner,
+            cx,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::future::poll_fn;
+    use std::{string::String, vec::Vec};
+
+    use crate::{
+        codec::lines::StrLines,
+        mock::{PendingReader, ScriptedReader},
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    /// Consumes any lending stream.
+    async fn count<S>(stream: &mut S) -> usize
+    where
+        S: LendingStream,
+    {
+        let mut count = 0;
+
+        while stream.next().await.is_some() {
+            count += 1;
+        }
+
+        count
+    }
+
+    #[tokio::test]
+    async fn yields_borrowed_frames() {
+        init_tracing();
+
+        let read = PendingReader::new(ScriptedReader::new(&[b"Hel", b"lo\nHey\n", b"!\n"]), 2);
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(StrLines::new(), read, buffer);
+
+        let mut items = Vec::new();
+
+        while let Some(item) = LendingStream::next(&mut framer).await {
+            let item: &str = item.expect("Must decode");
+
+            items.push(String::from(item));
+        }
+
+        assert_eq!(items, ["Hello", "Hey", "!"]);
+        assert!(framer.core.inner.pendings() > 0);
+    }
+
+    #[tokio::test]
+    async fn poll_next_yields_borrowed_frames() {
+        init_tracing();
+
+        let read = PendingReader::new(ScriptedReader::new(&[b"Hello\n", b"Hey\n"]), 1);
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(StrLines::new(), read, buffer);
+
+        let mut lens = Vec::new();
+
+        while let Some(len) = poll_fn(|cx| {
+            framer
+                .poll_next(cx)
+                .map(|item| item.map(|item| item.map(|item: &str| item.len())))
+        })
+        .await
+        {
+            lens.push(len.expect("Must decode"));
+        }
+
+        assert_eq!(lens, [5, 3]);
+    }
+
+    #[test]
+    fn poll_next_reads_until_a_frame_is_decoded() {
+        init_tracing();
+
+        // Every chunk is ready, but the frame is incomplete after the first one.
+        let read = ScriptedReader::new(&[b"Hel", b"lo\n"]);
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(StrLines::new(), read, buffer);
+
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+
+        match framer.poll_next(&mut cx) {
+            Poll::Ready(Some(Ok(item))) => assert_eq!(item, "Hello"),
+            _ => panic!("Must decode in a single poll"),
+        }
+    }
+
+    #[tokio::test]
+    async fn next_is_fused() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(StrLines::new(), b"Hello\n".as_slice(), buffer);
+
+        let mut next = LendingStream::next(&mut framer);
+
+        assert!(matches!((&mut next).await, Some(Ok("Hello"))));
+        assert!(next.await.is_none());
+    }
+
+    #[tokio::test]
+    async fn framed_is_a_lending_stream() {
+        init_tracing();
+
+        let read = PendingReader::new(ScriptedReader::new(&[b"Hello\n", b"Hey\n"]), 1);
+
+        let r_buf = &mut [0_u8; 32];
+        let w_buf = &mut [0_u8; 32];
+        let mut framed = Framed::new(StrLines::new(), read, r_buf, w_buf);
+
+        assert_eq!(count(&mut framed).await, 2);
+        assert_eq!(count(&mut &mut framed).await, 0);
+    }
+}
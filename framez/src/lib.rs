@@ -6,48 +6,181 @@
 //! [`Read`](https://docs.rs/embedded-io-async/latest/embedded_io_async/trait.Read.html) and [`Write`](https://docs.rs/embedded-io-async/latest/embedded_io_async/trait.Write.html) traits.
 //!
 //! It's recommended to use [`embedded_io_adapters`](https://docs.rs/embedded-io-adapters/0.6.1/embedded_io_adapters/) if you are using other async `Read` and `Write` traits like [`tokio`](https://docs.rs/tokio/latest/tokio/index.html)'s [`AsyncRead`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncRead.html) and [`AsyncWrite`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncWrite.html).
+//! With the `tokio` feature, the `new_tokio` constructors, e.g. [`Framed::new_tokio`], wrap them for you.
+//! With the `futures-io` feature, the `new_futures` constructors, e.g. [`Framed::new_futures`], do the same for [`futures`](https://docs.rs/futures/latest/futures/index.html)' [`AsyncRead`](https://docs.rs/futures/latest/futures/io/trait.AsyncRead.html) and [`AsyncWrite`](https://docs.rs/futures/latest/futures/io/trait.AsyncWrite.html).
 //!
 //! See the examples for more information.
 //!
 //! ## Features
 //!
 //! - `log`: Enables logging using [`log`](https://docs.rs/log/latest/log/).
-//! - `tracing`: Enables logging using [`tracing`](https://docs.rs/tracing/latest/tracing/).
+//! - `tracing`: Enables logging using [`tracing`](https://docs.rs/tracing/latest/tracing/) and wraps [`maybe_next`](crate::functions::maybe_next) and [`send`](crate::functions::send) in spans with fields, e.g. the frame length and the bytes read.
 //! - `defmt`: Enables logging using [`defmt`](https://docs.rs/defmt/latest/defmt/index.html)
 //!   and implements [`defmt::Format`](https://docs.rs/defmt/latest/defmt/trait.Format.html) for structs and enums.
+//!   Buffers are logged as hex dumps of at most 32 bytes, or of as many as the `FRAMEZ_DEFMT_MAX_BYTES` environment variable sets at build time.
+//! - `redact`: Logs only the lengths of buffers formatted by [`Bytes`](crate::fmt::Bytes), e.g. `<4 bytes>`, never their contents, so logging can be enabled on devices that handle credentials or personal data.
+//! - `alloc`: Enables heap-allocated buffers using [`Framed::new_alloc`] and the [`Owned`](crate::codec::owned::Owned) codec adapter that decodes into owned items.
+//! - `futures`: Enables the [`Stream`](https://docs.rs/futures/latest/futures/stream/trait.Stream.html) and [`Sink`](https://docs.rs/futures/latest/futures/sink/trait.Sink.html) APIs, e.g. [`Framed::stream`] and [`Framed::sink`]. Enabled by default.
+//! - `heapless`: Implements [`Buffer`](crate::buffer::Buffer) for [`heapless::Vec`](https://docs.rs/heapless/latest/heapless/struct.Vec.html) and [`FrameBuffer`](crate::buffer::FrameBuffer) for `heapless::Vec` and `heapless::String`, so they can be used with the [`Copied`](crate::codec::copied::Copied) codec adapter, and the [`SpscRead`](crate::channel::SpscRead) and [`SpscWrite`](crate::channel::SpscWrite) transports over a [`heapless::spsc::Queue`](https://docs.rs/heapless/latest/heapless/spsc/struct.Queue.html).
+//! - `std`: Enables the [`blocking`] framers over [`std::io::Read`](https://doc.rust-lang.org/std/io/trait.Read.html) and [`std::io::Write`](https://doc.rust-lang.org/std/io/trait.Write.html), e.g. for host-side tools. Implies `alloc`.
+//! - `tokio`: Enables constructors that accept [`tokio`](https://docs.rs/tokio/latest/tokio/index.html)'s `AsyncRead` and `AsyncWrite` directly, e.g. [`Framed::new_tokio`]. Implies `std`.
+//! - `futures-io`: Enables constructors that accept [`futures`](https://docs.rs/futures/latest/futures/index.html)' `AsyncRead` and `AsyncWrite` directly, e.g. [`Framed::new_futures`]. Implies `std`.
+//! - `tokio-util`: Enables [`FromTokioUtil`](crate::codec::tokio_util::FromTokioUtil), which drives [`tokio_util`](https://docs.rs/tokio-util/latest/tokio_util/codec/index.html)'s codecs as a [`Decoder`](crate::decode::Decoder) and [`Encoder`](crate::encode::Encoder),
+//!   and [`ToTokioUtil`](crate::codec::tokio_util::ToTokioUtil), which does the reverse. Implies `std`.
+//! - `embedded-hal-async`: Enables the [`Pacer`](crate::pacing::Pacer) that rate limits sent frames using an [`embedded_hal_async::delay::DelayNs`](https://docs.rs/embedded-hal-async/latest/embedded_hal_async/delay/trait.DelayNs.html), e.g. [`Framed::send_paced`],
+//!   and the [`Timing`](crate::timing::Timing) that keeps silent intervals around frames, e.g. [`Framed::send_gapped`] and [`Framed::next_quiet`],
+//!   and the [`HalfDuplex`](crate::half_duplex::HalfDuplex) wrapper for request-response exchanges on half-duplex buses.
+//! - `embassy-sync`: Enables [`SharedFramed`](crate::shared::SharedFramed), which shares a read half and a write half between tasks using [`embassy_sync`](https://docs.rs/embassy-sync/latest/embassy_sync/)'s mutexes.
+//! - `metrics`: Counts the frames, bytes and errors of every framer using [`portable_atomic`](https://docs.rs/portable-atomic/latest/portable_atomic/) counters, e.g. [`Framed::metrics`].
+//! - `metrics-facade`: Also records the counters using the [`metrics`](https://docs.rs/metrics/latest/metrics/) crate facade. Implies `metrics` and `std`.
+//! - `postcard-rpc`: Enables the [`postcard_rpc`](crate::postcard_rpc) transport, which implements [`postcard_rpc`](https://docs.rs/postcard-rpc/latest/postcard_rpc/)'s `WireRx` and `WireTx`
+//!   using the [`PostcardRpc`](crate::postcard_rpc::PostcardRpc) codec, COBS encoded frames. Implies `embassy-sync`.
+//! - `serde`: Enables the [`SerdeCodec`](crate::codec::serde::SerdeCodec) that encodes and decodes typed items using a pluggable [`WireFormat`](crate::codec::serde::WireFormat).
+//! - `postcard`: Enables the [`postcard`](https://docs.rs/postcard/latest/postcard/) [`WireFormat`](crate::codec::serde::WireFormat). Implies `serde`.
+//! - `serde-json-core`: Enables the [`serde-json-core`](https://docs.rs/serde-json-core/latest/serde_json_core/) [`WireFormat`](crate::codec::serde::WireFormat). Implies `serde`.
+//! - `cbor`: Enables the CBOR [`WireFormat`](crate::codec::serde::WireFormat) using [`minicbor-serde`](https://docs.rs/minicbor-serde/latest/minicbor_serde/). Implies `serde`.
+//! - `nom`: Enables the [`Nom`](crate::codec::nom::Nom) codec adapter that decodes frames using a [`nom`](https://docs.rs/nom/latest/nom/) streaming parser.
+//! - `winnow`: Enables the [`Winnow`](crate::codec::winnow::Winnow) codec adapter that decodes frames using a [`winnow`](https://docs.rs/winnow/latest/winnow/) parser of partial input.
+//! - `zeroize`: Enables the [`Sensitive`](crate::buffer::Sensitive) buffer that is wiped when it is dropped, and [`Framed::zeroize_buffers`], so credentials and key material passing through frames do not linger in memory.
+//!   Buffers are not wiped by `into_parts`, by switching codecs with `from_parts` or by dropping a framer, unless they are wrapped in a `Sensitive`.
+//! - `embedded-storage-async`: Enables the [`Recorder`](crate::recorder::Recorder) that appends the raw bytes of read and sent frames to a ring log in NOR flash using [`embedded_storage_async`](https://docs.rs/embedded-storage-async/latest/embedded_storage_async/), e.g. [`Framed::next_recorded`] and [`Framed::send_recorded`], for post-mortem analysis of field failures.
+//! - `lending-stream`: Enables the [`LendingStream`](crate::lending::LendingStream) trait, implemented for [`FramedRead`] and [`Framed`], so frames borrowed from the read buffer can be consumed by generic code without mapping them to owned items.
+//! - `embassy-usb`: Enables the [`CdcAcm`](crate::cdc_acm::CdcAcm) adapter that frames an [`embassy_usb`](https://docs.rs/embassy-usb/latest/embassy_usb/) CDC-ACM class, e.g. a USB serial port.
+//! - `futures-channel`: Enables the [`pipe`](crate::channel::pipe) transport over a [`futures::channel::mpsc`](https://docs.rs/futures/latest/futures/channel/mpsc/index.html) channel, e.g. an in-process pipe between tasks or a test double. Implies `futures` and `std`.
+//! - `mock`: Enables the [`mock`](crate::mock) module with [`Read`](embedded_io_async::Read) and [`Write`](embedded_io_async::Write) implementations for testing, e.g. an in-memory duplex, a scripted reader and writers that fail or write partially.
+//! - `test-util`: Enables the [`test_util`](crate::test_util) module with a [`Conformance`](crate::test_util::Conformance) check for codecs, that checks round-tripping, chunking, EOF handling and reported frame sizes. Implies `mock` and `std`.
+//! - `arbitrary`: Implements [`arbitrary::Arbitrary`](https://docs.rs/arbitrary/latest/arbitrary/trait.Arbitrary.html) for [`ChunkPlan`](crate::test_util::ChunkPlan), so fuzzers can generate how a stream is split into reads. Implies `test-util`.
+//! - `zerocopy`: Implements [`IntField`](crate::frame::IntField) for [`zerocopy`](https://docs.rs/zerocopy/latest/zerocopy/)'s byte order aware integers, e.g. `zerocopy::big_endian::U16`.
+//! - `derive`: Enables the [`FrameEncode`](derive@crate::frame::FrameEncode) and [`FrameDecode`](derive@crate::frame::FrameDecode) derive macros for packet structs and tagged payload enums. Implies `zerocopy`.
 
 #![no_std]
 #![deny(unsafe_code)]
 #![deny(missing_debug_implementations)]
 #![deny(missing_docs)]
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::indexing_slicing,
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::unreachable,
+        clippy::arithmetic_side_effects
+    )
+)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+pub mod adapters;
+
+#[cfg(feature = "std")]
+pub mod blocking;
+
+pub mod broadcast;
+
+pub mod buffer;
+
+#[cfg(any(feature = "heapless", feature = "futures-channel"))]
+pub mod channel;
+
+pub mod checksum;
+
+pub mod clock;
+
 pub mod codec;
 pub mod decode;
+pub mod detect;
 pub mod encode;
+pub mod fmt;
+pub mod frame;
 
 mod framed;
-pub use framed::{Framed, FramedRead, FramedWrite};
+pub use framed::{Framed, FramedOwnedBuf, FramedRead, FramedWrite, RingFramedRead};
 
 mod framed_core;
 use framed_core::FramedCore;
 
+mod frames;
+pub use frames::Frames;
+
 pub mod functions;
 
 mod error;
-pub use error::{ReadError, WriteError};
+pub use error::{
+    BridgeError, CloseError, DetectError, ForwardError, HandshakeError, ReadError,
+    ReadPayloadError, RouteError, SendStreamedError, TransferError, WriteError,
+};
+
+pub mod forward;
+
+pub mod merge;
+
+pub mod ops;
+
+pub mod packet;
+
+pub mod quota;
+
+pub mod router;
 
 pub mod state;
 
+#[cfg(feature = "embedded-hal-async")]
+pub mod pacing;
+
+#[cfg(feature = "embedded-hal-async")]
+pub mod timing;
+
+#[cfg(feature = "embedded-hal-async")]
+pub mod half_duplex;
+
+#[cfg(feature = "embedded-storage-async")]
+pub mod recorder;
+
+#[cfg(feature = "embassy-sync")]
+pub mod shared;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+#[cfg(feature = "postcard-rpc")]
+pub mod postcard_rpc;
+
+#[cfg(feature = "embassy-usb")]
+pub mod cdc_acm;
+
+#[cfg(feature = "lending-stream")]
+pub mod lending;
+
+mod record;
+
+mod future;
+
 pub(crate) mod logging;
 
+#[cfg(feature = "futures")]
+mod sink;
+
 mod macros;
 
 #[doc(hidden)]
+pub mod __private {
+    //! Re-exports used by the macros.
+
+    pub use crate::future::{Either, select};
+}
+
+#[cfg(any(test, feature = "mock"))]
 pub mod mock;
 
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
 #[cfg(test)]
 mod tests;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "std"))]
 extern crate std;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
@@ -4,7 +4,7 @@
 mod formatter;
 
 #[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
-pub(crate) use formatter::Formatter;
+pub(crate) use formatter::Name;
 
 macro_rules! trace {
     (target: $target:expr, $($arg:tt)+) => {
@@ -71,7 +71,16 @@ macro_rules! warn_ {
     };
 }
 
+/// Records a field of the current span, if the `tracing` feature is enabled.
+macro_rules! span_record {
+    ($field:ident = $value:expr) => {
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record(stringify!($field), $value);
+    };
+}
+
 pub(crate) use debug;
 pub(crate) use error;
+pub(crate) use span_record;
 pub(crate) use trace;
 pub(crate) use warn_ as warn;
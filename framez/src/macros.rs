@@ -18,6 +18,11 @@ macro_rules! maybe_next {
 /// - `Some(Ok(frame))` if a frame was successfully decoded. Call `next` again to read more frames.
 /// - `Some(Err(error))` if an error occurred. The caller should stop reading.
 /// - `None` if eof was reached. The caller should stop reading.
+///
+/// # Cancel safety
+///
+/// Using `next!` inside a `select!` branch is cancel safe as long as the underlying reader is cancel safe.
+/// See [`maybe_next`](crate::functions::maybe_next#cancel-safety).
 #[macro_export]
 macro_rules! next {
     ($framed:expr) => {{
@@ -32,10 +37,109 @@ macro_rules! next {
     }};
 }
 
+/// Convenience macro to call [`maybe_next_ring`](crate::functions::maybe_next_ring) on a [`RingFramedRead`](crate::RingFramedRead).
+#[macro_export]
+macro_rules! maybe_next_ring {
+    ($framed:expr) => {{
+        $crate::functions::maybe_next_ring(
+            &mut $framed.state,
+            &mut $framed.codec,
+            &mut $framed.inner,
+        )
+        .await
+    }};
+}
+
+/// Calls [`maybe_next_ring!`](crate::maybe_next_ring!) on a [`RingFramedRead`](crate::RingFramedRead) in a loop until a frame is returned or an error occurs.
+///
+/// # Return value
+///
+/// Same as [`next!`](crate::next!).
+///
+/// # Cancel safety
+///
+/// Same as [`next!`](crate::next!#cancel-safety).
+#[macro_export]
+macro_rules! next_ring {
+    ($framed:expr) => {{
+        'next: loop {
+            match $crate::maybe_next_ring!($framed) {
+                Some(Ok(None)) => continue 'next,
+                Some(Ok(Some(item))) => break 'next Some(Ok(item)),
+                Some(Err(err)) => break 'next Some(Err(err)),
+                None => break 'next None,
+            }
+        }
+    }};
+}
+
+/// Like [`next!`](crate::next!), but gives up once the `timeout` future completes.
+///
+/// See [`next_timeout`](crate::functions::next_timeout).
+///
+/// # Return value
+///
+/// Same as [`next!`](crate::next!). [`ReadError::Timeout`](crate::ReadError::Timeout) is returned if the `timeout` completes before a frame is decoded.
+/// The buffered bytes are kept, so reading can be resumed.
+#[macro_export]
+macro_rules! next_timeout {
+    ($framed:expr, $timeout:expr) => {{
+        let mut timeout = ::core::pin::pin!($timeout);
+
+        'next: loop {
+            let item = ::core::pin::pin!(async { $crate::maybe_next!($framed) });
+
+            match $crate::__private::select(item, timeout.as_mut()).await {
+                $crate::__private::Either::Left(Some(Ok(None))) => continue 'next,
+                $crate::__private::Either::Left(Some(Ok(Some(item)))) => {
+                    break 'next Some(Ok(item));
+                }
+                $crate::__private::Either::Left(Some(Err(err))) => break 'next Some(Err(err)),
+                $crate::__private::Either::Left(None) => break 'next None,
+                $crate::__private::Either::Right(()) => {
+                    break 'next Some(Err($crate::ReadError::Timeout));
+                }
+            }
+        }
+    }};
+}
+
+/// Like [`next!`](crate::next!), but returns instead of waiting for the underlying reader of a [`Framed`](crate::Framed) or [`FramedRead`](`crate::FramedRead`) to become ready.
+///
+/// The reader must implement [`ReadReady`](embedded_io_async::ReadReady). See [`read_ready`](crate::functions::read_ready).
+///
+/// # Return value
+///
+/// - `Some(Ok(Some(frame)))` if a frame was successfully decoded. Call `try_next` again to read more frames.
+/// - `Some(Ok(None))` if no frame is buffered and the reader is not ready. Call `try_next` again later.
+/// - `Some(Err(error))` if an error occurred. The caller should stop reading.
+/// - `None` if eof was reached. The caller should stop reading.
+#[macro_export]
+macro_rules! try_next {
+    ($framed:expr) => {{
+        'next: loop {
+            match $crate::functions::read_ready(&$framed.core.state.read, &mut $framed.core.inner) {
+                Ok(true) => {}
+                Ok(false) => break 'next Some(Ok(None)),
+                Err(err) => break 'next Some(Err($crate::ReadError::IO(err))),
+            }
+
+            match $crate::maybe_next!($framed) {
+                Some(Ok(None)) => continue 'next,
+                Some(Ok(Some(item))) => break 'next Some(Ok(Some(item))),
+                Some(Err(err)) => break 'next Some(Err(err)),
+                None => break 'next None,
+            }
+        }
+    }};
+}
+
 /// Convenience macro to call [`send`](crate::functions::send) on a [`Framed`](crate::Framed) or [`FramedWrite`](`crate::FramedWrite`).
+///
+/// Multiple items are sent using [`send_all`](crate::functions::send_all), which flushes once at the end.
 #[macro_export]
 macro_rules! send {
-    ($framed:expr, $item:expr) => {{
+    ($framed:expr, $item:expr $(,)?) => {{
         $crate::functions::send(
             &mut $framed.core.state.write,
             &mut $framed.core.codec,
@@ -44,4 +148,30 @@ macro_rules! send {
         )
         .await
     }};
+    ($framed:expr, $($item:expr),+ $(,)?) => {{
+        $crate::functions::send_all(
+            &mut $framed.core.state.write,
+            &mut $framed.core.codec,
+            &mut $framed.core.inner,
+            [$($item),+],
+        )
+        .await
+    }};
+}
+
+/// Formats the arguments directly into the write buffer of a [`Framed`](crate::Framed) or [`FramedWrite`](`crate::FramedWrite`) and sends the frame.
+///
+/// Takes the same arguments as [`core::format_args!`]. The codec must implement [`EncodeFmt`](crate::encode::EncodeFmt),
+/// e.g. [`Lines`](crate::codec::lines::Lines) or [`StrLines`](crate::codec::lines::StrLines).
+#[macro_export]
+macro_rules! send_fmt {
+    ($framed:expr, $($arg:tt)+) => {{
+        $crate::functions::send(
+            &mut $framed.core.state.write,
+            &mut $crate::encode::FmtEncoder::new(&mut $framed.core.codec),
+            &mut $framed.core.inner,
+            ::core::format_args!($($arg)+),
+        )
+        .await
+    }};
 }
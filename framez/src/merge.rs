@@ -0,0 +1,280 @@
+//! Reading of frames from multiple framers at once.
+//!
+//! A [`Merge`] drives several [`FramedRead`]s concurrently and yields the frame of whichever reader decodes one first,
+//! together with the index of that reader, e.g. for a gateway reading from multiple UARTs.
+
+use core::{
+    future::{Future, poll_fn},
+    pin::pin,
+    task::Poll,
+};
+
+use embedded_io_async::Read;
+
+use crate::{FramedRead, ReadError, buffer::Buffer, decode::Decoder, functions};
+
+/// Reads frames from several [`FramedRead`]s at once.
+///
+/// Every call to [`Merge::next`] polls the readers in turn, starting after the reader that yielded the last frame,
+/// so a busy reader does not starve the others.
+///
+/// # Cancel safety
+///
+/// [`Merge::next`] is cancel safe as long as the readers are, see [`maybe_next`](crate::functions::maybe_next#cancel-safety).
+/// The read of each reader is only polled within a single poll of [`Merge::next`] and is dropped right after,
+/// so dropping the returned future never interrupts the state machine of a reader.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{FramedRead, codec::lines::StrLines, merge::Merge};
+///
+/// # async fn run() {
+/// let (a_buf, b_buf) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+///
+/// let readers = &mut [
+///     FramedRead::new(StrLines::new(), b"Hello\n".as_slice(), a_buf),
+///     FramedRead::new(StrLines::new(), b"Hey\n".as_slice(), b_buf),
+/// ];
+///
+/// let mut merge = Merge::new(readers);
+///
+/// while let Some((index, item)) = merge.next(|item| item.len()).await {
+///     let len = item.unwrap();
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Merge<'a, 'buf, C, R, const N: usize, B = &'buf mut [u8]> {
+    readers: &'a mut [FramedRead<'buf, C, R, B>; N],
+    /// Whether each reader reached eof or failed.
+    done: [bool; N],
+    /// The index of the reader that is polled first.
+    start: usize,
+}
+
+impl<'a, 'buf, C, R, const N: usize, B> Merge<'a, 'buf, C, R, N, B> {
+    /// Creates a new [`Merge`] over the given `readers`.
+    #[inline]
+    pub const fn new(readers: &'a mut [FramedRead<'buf, C, R, B>; N]) -> Self {
+        Self {
+            readers,
+            done: [false; N],
+            start: 0,
+        }
+    }
+
+    /// Returns reference to the readers.
+    #[inline]
+    pub const fn readers(&self) -> &[FramedRead<'buf, C, R, B>; N] {
+        self.readers
+    }
+
+    /// Returns mutable reference to the readers.
+    #[inline]
+    pub const fn readers_mut(&mut self) -> &mut [FramedRead<'buf, C, R, B>; N] {
+        self.readers
+    }
+
+    /// Returns `true` if the reader at `index` reached eof or failed, so it is not read anymore.
+    #[inline]
+    pub fn is_done(&self, index: usize) -> bool {
+        self.done.get(index).copied().unwrap_or(true)
+    }
+
+    /// Consumes the [`Merge`] and returns the readers.
+    #[inline]
+    pub fn into_inner(self) -> &'a mut [FramedRead<'buf, C, R, B>; N] {
+        self.readers
+    }
+
+    /// Reads the next frame of any reader and converts it using the given `map` function.
+    ///
+    /// # Return value
+    ///
+    /// - `Some((index, Ok(U)))` if the reader at `index` decoded a frame.
+    /// - `Some((index, Err(error)))` if an error occurred while reading from the reader at `index`.
+    ///   The reader is not read anymore, while the other readers continue.
+    /// - `None` if all readers reached eof or failed.
+    #[allow(clippy::type_complexity)]
+    pub async fn next<U>(
+        &mut self,
+        map: fn(C::Item<'_>) -> U,
+    ) -> Option<(usize, Result<U, ReadError<R::Error, C::Error>>)>
+    where
+        U: 'static,
+        C: Decoder,
+        R: Read,
+        B: Buffer,
+    {
+        poll_fn(|cx| {
+            for offset in 0..N {
+                let index = self
+                    .start
+                    .saturating_add(offset)
+                    .checked_rem(N)
+                    .unwrap_or_default();
+
+                let (Some(reader), Some(done)) =
+                    (self.readers.get_mut(index), self.done.get_mut(index))
+                else {
+                    continue;
+                };
+
+                if *done {
+                    continue;
+                }
+
+                loop {
+                    let next = pin!(functions::maybe_next_mapped(
+                        &mut reader.core.state.read,
+                        &mut reader.core.codec,
+                        &mut reader.core.inner,
+                        map,
+                    ));
+
+                    let item = match next.poll(cx) {
+                        Poll::Ready(Some(Ok(None))) => continue,
+                        Poll::Ready(Some(Ok(Some(item)))) => Ok(item),
+                        Poll::Ready(Some(Err(err))) => {
+                            *done = true;
+
+                            Err(err)
+                        }
+                        Poll::Ready(None) => {
+                            *done = true;
+
+                            break;
+                        }
+                        Poll::Pending => break,
+                    };
+
+                    self.start = index.saturating_add(1).checked_rem(N).unwrap_or_default();
+
+                    return Poll::Ready(Some((index, item)));
+                }
+            }
+
+            if self.done.iter().all(|done| *done) {
+                return Poll::Ready(None);
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use embedded_io_async::ErrorKind;
+
+    use crate::{
+        codec::lines::StrLines,
+        mock::{FailAfter, FaultyReader, PendingReader},
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn frames_of_all_readers_are_yielded() {
+        init_tracing();
+
+        let (a_buf, b_buf) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+
+        let readers = &mut [
+            FramedRead::new(
+                StrLines::new(),
+                PendingReader::new(b"a1\na2\n".as_slice(), 1),
+                a_buf,
+            ),
+            FramedRead::new(
+                StrLines::new(),
+                PendingReader::new(b"b1\n".as_slice(), 3),
+                b_buf,
+            ),
+        ];
+
+        let mut merge = Merge::new(readers);
+
+        let mut collected = Vec::new();
+
+        while let Some((index, item)) = merge.next(|item| std::string::String::from(item)).await {
+            collected.push((index, item.expect("Must decode")));
+        }
+
+        collected.sort();
+
+        assert_eq!(
+            collected,
+            [(0, "a1".into()), (0, "a2".into()), (1, "b1".into())]
+        );
+    }
+
+    #[tokio::test]
+    async fn readers_take_turns() {
+        init_tracing();
+
+        let (a_buf, b_buf) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+
+        let readers = &mut [
+            FramedRead::new(StrLines::new(), b"a1\na2\na3\n".as_slice(), a_buf),
+            FramedRead::new(StrLines::new(), b"b1\nb2\n".as_slice(), b_buf),
+        ];
+
+        let mut merge = Merge::new(readers);
+
+        let mut indices = Vec::new();
+
+        while let Some((index, item)) = merge.next(|_| ()).await {
+            item.expect("Must decode");
+
+            indices.push(index);
+        }
+
+        assert_eq!(indices, [0, 1, 0, 1, 0]);
+    }
+
+    #[tokio::test]
+    async fn errors_are_reported_with_their_index() {
+        init_tracing();
+
+        let (a_buf, b_buf) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+
+        let readers = &mut [
+            FramedRead::new(
+                StrLines::new(),
+                FaultyReader::new(
+                    b"a1\n".as_slice(),
+                    FailAfter::Operations(0),
+                    ErrorKind::BrokenPipe,
+                ),
+                a_buf,
+            ),
+            FramedRead::new(
+                StrLines::new(),
+                FaultyReader::new(
+                    b"b1\n".as_slice(),
+                    FailAfter::Operations(1),
+                    ErrorKind::BrokenPipe,
+                ),
+                b_buf,
+            ),
+        ];
+
+        let mut merge = Merge::new(readers);
+
+        assert!(matches!(
+            merge.next(|_| ()).await,
+            Some((0, Err(ReadError::IO(ErrorKind::BrokenPipe))))
+        ));
+
+        assert!(matches!(merge.next(|_| ()).await, Some((1, Ok(())))));
+        assert!(merge.is_done(0));
+        assert!(matches!(merge.next(|_| ()).await, Some((1, Err(_)))));
+        assert!(merge.next(|_| ()).await.is_none());
+    }
+}
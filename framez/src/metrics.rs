@@ -0,0 +1,241 @@
+//! Counters of the frames and bytes that pass through a framer.
+//!
+//! Every [`ReadState`](crate::state::ReadState), [`RingReadState`](crate::state::RingReadState) and [`WriteState`](crate::state::WriteState)
+//! owns a set of [`Counters`]. The counters of a framer are read using e.g. [`Framed::metrics`](crate::Framed::metrics).
+//!
+//! The counters are [`portable_atomic`](https://docs.rs/portable-atomic/latest/portable_atomic/) atomics,
+//! so they are also available on targets without native 64-bit atomics.
+//!
+//! With the `metrics-facade` feature, every update is also recorded using the [`metrics`](https://docs.rs/metrics/latest/metrics/) crate facade:
+//!
+//! - `framez_frames_total`: The number of frames.
+//! - `framez_bytes_total`: The number of bytes.
+//! - `framez_errors_total`: The number of errors.
+//!
+//! Each of them is labeled with the `direction`, `read` or `write`, and the `name` of the state, if any.
+
+use portable_atomic::{AtomicU64, Ordering};
+
+/// The direction of the frames counted by [`Counters`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    /// Frames read from the source.
+    Read,
+    /// Frames written to the sink.
+    Write,
+}
+
+impl Direction {
+    /// Returns the label of the direction.
+    #[inline]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+        }
+    }
+}
+
+/// Atomic counters of the frames, bytes and errors of one direction.
+#[derive(Debug)]
+pub struct Counters {
+    direction: Direction,
+    frames: AtomicU64,
+    bytes: AtomicU64,
+    errors: AtomicU64,
+}
+
+impl Counters {
+    /// Creates new zeroed [`Counters`] for the given `direction`.
+    #[inline]
+    pub const fn new(direction: Direction) -> Self {
+        Self {
+            direction,
+            frames: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the direction of the counted frames.
+    #[inline]
+    pub const fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Returns the number of frames decoded or written.
+    #[inline]
+    pub fn frames(&self) -> u64 {
+        self.frames.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes read or written.
+    #[inline]
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of errors.
+    #[inline]
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    /// Resets all counters to zero.
+    #[inline]
+    pub fn clear(&self) {
+        self.frames.store(0, Ordering::Relaxed);
+        self.bytes.store(0, Ordering::Relaxed);
+        self.errors.store(0, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn add_frame(&self, _name: Option<&'static str>) {
+        self.frames.fetch_add(1, Ordering::Relaxed);
+
+        #[cfg(feature = "metrics-facade")]
+        facade::increment("framez_frames_total", self.direction, _name, 1);
+    }
+
+    #[inline]
+    pub(crate) fn add_bytes(&self, _name: Option<&'static str>, bytes: usize) {
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+
+        #[cfg(feature = "metrics-facade")]
+        facade::increment("framez_bytes_total", self.direction, _name, bytes as u64);
+    }
+
+    #[inline]
+    pub(crate) fn add_error(&self, _name: Option<&'static str>) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+
+        #[cfg(feature = "metrics-facade")]
+        facade::increment("framez_errors_total", self.direction, _name, 1);
+    }
+}
+
+/// A snapshot of the [`Counters`] of a framer.
+///
+/// The counters of a direction the framer does not support are zero, e.g. [`Metrics::frames_out`] of a [`FramedRead`](crate::FramedRead).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Metrics {
+    /// The number of frames decoded.
+    pub frames_in: u64,
+    /// The number of frames written.
+    pub frames_out: u64,
+    /// The number of bytes read.
+    pub bytes_in: u64,
+    /// The number of bytes written.
+    pub bytes_out: u64,
+    /// The number of errors while reading.
+    pub read_errors: u64,
+    /// The number of errors while writing.
+    pub write_errors: u64,
+}
+
+impl Metrics {
+    /// Creates a snapshot of the given read and write [`Counters`].
+    pub fn snapshot(read: Option<&Counters>, write: Option<&Counters>) -> Self {
+        let mut metrics = Self::default();
+
+        if let Some(read) = read {
+            metrics.frames_in = read.frames();
+            metrics.bytes_in = read.bytes();
+            metrics.read_errors = read.errors();
+        }
+
+        if let Some(write) = write {
+            metrics.frames_out = write.frames();
+            metrics.bytes_out = write.bytes();
+            metrics.write_errors = write.errors();
+        }
+
+        metrics
+    }
+
+    /// Returns the total number of errors.
+    #[inline]
+    pub const fn errors(&self) -> u64 {
+        self.read_errors.saturating_add(self.write_errors)
+    }
+}
+
+#[cfg(feature = "metrics-facade")]
+mod facade {
+    use super::Direction;
+
+    pub(super) fn increment(
+        key: &'static str,
+        direction: Direction,
+        name: Option<&'static str>,
+        value: u64,
+    ) {
+        match name {
+            Some(name) => {
+                metrics::counter!(key, "direction" => direction.as_str(), "name" => name)
+                    .increment(value);
+            }
+            None => metrics::counter!(key, "direction" => direction.as_str()).increment(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use crate::{
+        FramedRead, FramedWrite, ReadError, codec::lines::Lines, next, tests::init_tracing,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn counts_read_frames_bytes_and_errors() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(Lines::new(), b"Hello\nHey\nrest".as_slice(), buffer);
+
+        while let Some(item) = next!(framer) {
+            if let Err(err) = item {
+                assert!(matches!(err, ReadError::BytesRemainingOnStream));
+
+                break;
+            }
+        }
+
+        assert_eq!(
+            framer.metrics(),
+            Metrics {
+                frames_in: 2,
+                bytes_in: 14,
+                read_errors: 1,
+                ..Metrics::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn counts_written_frames_and_bytes() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Lines::new(), Vec::<u8>::new(), buffer);
+
+        framer.send(b"Hello".as_slice()).await.expect("Must send");
+        framer.send(b"Hey".as_slice()).await.expect("Must send");
+
+        let metrics = framer.metrics();
+
+        assert_eq!(metrics.frames_out, 2);
+        assert_eq!(metrics.bytes_out, 12);
+        assert_eq!(metrics.errors(), 0);
+
+        framer.core.state.write.counters.clear();
+
+        assert_eq!(framer.metrics(), Metrics::default());
+    }
+}
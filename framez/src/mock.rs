@@ -1,10 +1,31 @@
-//! Noop implementation of embedded-io-async traits for testing purposes.
+//! Mock implementations of [`Read`] and [`Write`] for testing.
+//!
+//! - [`Noop`] reads and writes nothing, e.g. for framers in examples that never do IO.
+//! - [`Duplex`] connects two framers in memory, see [`Framed::pair`](crate::Framed::pair).
+//! - [`ScriptedReader`] returns predefined chunks, to test how frames split across reads are decoded.
+//! - [`FaultyReader`] and [`FaultyWriter`] inject IO errors after a number of bytes or operations.
+//! - [`PartialWriter`] accepts only a few bytes per write, to test short writes.
+//! - [`RecordingWriter`] records the written bytes per flush, without allocating.
+//! - [`PendingReader`] returns [`Poll::Pending`] before every read, to test cancellation.
+//! - [`RecordingDelay`] records the requested delays without waiting, to test pacing and timing. Requires the `embedded-hal-async` feature.
+//!
+//! Nothing is allocated, so every mock can also be used on target.
 
-use core::convert::Infallible;
+use core::{
+    cell::RefCell,
+    convert::Infallible,
+    future::poll_fn,
+    task::{Poll, Waker},
+};
 
-use embedded_io_async::{ErrorType, Read, Write};
+use embedded_io_async::{ErrorKind, ErrorType, Read, Write};
 
-#[derive(Debug)]
+use crate::future::yield_now;
+
+/// A [`Read`] and [`Write`] implementation that does nothing.
+///
+/// Reads fill nothing but report the whole buffer as read, and writes report the whole buffer as written.
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Noop;
 
 impl ErrorType for Noop {
@@ -22,3 +43,1206 @@ impl Write for Noop {
         Ok(buf.len())
     }
 }
+
+/// One direction of a [`Duplex`]: a ring buffer of `N` bytes.
+#[derive(Debug)]
+struct Pipe<const N: usize> {
+    buf: [u8; N],
+    /// The index of the first buffered byte.
+    start: usize,
+    /// The number of buffered bytes.
+    len: usize,
+    /// Whether one of the ends was dropped.
+    closed: bool,
+    read_waker: Option<Waker>,
+    write_waker: Option<Waker>,
+}
+
+impl<const N: usize> Pipe<N> {
+    const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            start: 0,
+            len: 0,
+            closed: false,
+            read_waker: None,
+            write_waker: None,
+        }
+    }
+
+    fn read(&mut self, dst: &mut [u8]) -> usize {
+        let n = dst.len().min(self.len);
+
+        for slot in dst.iter_mut().take(n) {
+            *slot = self.buf.get(self.start).copied().unwrap_or_default();
+
+            self.start = self
+                .start
+                .saturating_add(1)
+                .checked_rem(N)
+                .unwrap_or_default();
+        }
+
+        self.len = self.len.saturating_sub(n);
+
+        n
+    }
+
+    fn write(&mut self, src: &[u8]) -> usize {
+        let n = src.len().min(N.saturating_sub(self.len));
+
+        for (offset, byte) in src.iter().take(n).enumerate() {
+            if let Some(slot) = self.buf.get_mut(
+                self.start
+                    .saturating_add(self.len)
+                    .saturating_add(offset)
+                    .checked_rem(N)
+                    .unwrap_or_default(),
+            ) {
+                *slot = *byte;
+            }
+        }
+
+        self.len = self.len.saturating_add(n);
+
+        n
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+
+        if let Some(waker) = self.read_waker.take() {
+            waker.wake();
+        }
+
+        if let Some(waker) = self.write_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// An in-memory, `no_std` duplex stream with two connected ends, each buffering up to `N` bytes per direction.
+///
+/// Bytes written to one [`DuplexEnd`] are read from the other. Reading waits while nothing is buffered, writing waits while the buffer is full.
+/// Dropping an end lets the other end read the buffered bytes and then reach EOF, while its writes fail with [`ErrorKind::BrokenPipe`].
+///
+/// The ends are not `Send`, so they must be polled by the same task, e.g. using `join`.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_io_async::{Read, Write};
+/// use framez::mock::Duplex;
+///
+/// # async fn run() {
+/// let duplex = Duplex::<16>::new();
+/// let (mut a, mut b) = duplex.split();
+///
+/// a.write_all(b"Hello").await.unwrap();
+///
+/// let buf = &mut [0_u8; 16];
+/// let n = b.read(buf).await.unwrap();
+///
+/// assert_eq!(&buf[..n], b"Hello");
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Duplex<const N: usize> {
+    a_to_b: RefCell<Pipe<N>>,
+    b_to_a: RefCell<Pipe<N>>,
+}
+
+impl<const N: usize> Duplex<N> {
+    /// Creates a new [`Duplex`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            a_to_b: RefCell::new(Pipe::new()),
+            b_to_a: RefCell::new(Pipe::new()),
+        }
+    }
+
+    /// Returns the two connected ends of the [`Duplex`].
+    ///
+    /// Each end must be split off only once. Splitting a [`Duplex`] again after dropping its ends yields closed ends.
+    #[inline]
+    pub const fn split(&self) -> (DuplexEnd<'_, N>, DuplexEnd<'_, N>) {
+        (
+            DuplexEnd {
+                read: &self.b_to_a,
+                write: &self.a_to_b,
+            },
+            DuplexEnd {
+                read: &self.a_to_b,
+                write: &self.b_to_a,
+            },
+        )
+    }
+}
+
+impl<const N: usize> Default for Duplex<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One end of a [`Duplex`].
+#[derive(Debug)]
+pub struct DuplexEnd<'a, const N: usize> {
+    read: &'a RefCell<Pipe<N>>,
+    write: &'a RefCell<Pipe<N>>,
+}
+
+impl<const N: usize> Drop for DuplexEnd<'_, N> {
+    fn drop(&mut self) {
+        self.write.borrow_mut().close();
+        self.read.borrow_mut().close();
+    }
+}
+
+impl<const N: usize> ErrorType for DuplexEnd<'_, N> {
+    type Error = ErrorKind;
+}
+
+impl<const N: usize> Read for DuplexEnd<'_, N> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        poll_fn(|cx| {
+            let mut pipe = self.read.borrow_mut();
+
+            if pipe.len > 0 {
+                let n = pipe.read(buf);
+
+                if let Some(waker) = pipe.write_waker.take() {
+                    waker.wake();
+                }
+
+                return Poll::Ready(Ok(n));
+            }
+
+            if pipe.closed {
+                return Poll::Ready(Ok(0));
+            }
+
+            pipe.read_waker = Some(cx.waker().clone());
+
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl<const N: usize> Write for DuplexEnd<'_, N> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        poll_fn(|cx| {
+            let mut pipe = self.write.borrow_mut();
+
+            if pipe.closed {
+                return Poll::Ready(Err(ErrorKind::BrokenPipe));
+            }
+
+            if pipe.len < N {
+                let n = pipe.write(buf);
+
+                if let Some(waker) = pipe.read_waker.take() {
+                    waker.wake();
+                }
+
+                return Poll::Ready(Ok(n));
+            }
+
+            pipe.write_waker = Some(cx.waker().clone());
+
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+/// A reader that returns a predefined sequence of byte chunks, at most one chunk per [`Read::read`], then EOF.
+///
+/// Lets tests split frames across reads at exact boundaries. A chunk that does not fit into the read buffer,
+/// or exceeds [`ScriptedReader::with_max_read`], is returned over several reads. Empty chunks are skipped, since returning `0` bytes means EOF.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{FramedRead, codec::lines::StrLines, mock::ScriptedReader, next};
+///
+/// # async fn run() {
+/// let chunks: &[&[u8]] = &[b"Hel", b"lo\nH", b"ey\n"];
+///
+/// let buffer = &mut [0_u8; 32];
+/// let mut reader = FramedRead::new(StrLines::new(), ScriptedReader::new(chunks), buffer);
+///
+/// assert_eq!(next!(reader).unwrap().unwrap(), "Hello");
+/// assert_eq!(next!(reader).unwrap().unwrap(), "Hey");
+/// assert!(next!(reader).is_none());
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScriptedReader<'a> {
+    chunks: &'a [&'a [u8]],
+    /// The index of the current chunk.
+    index: usize,
+    /// The number of bytes of the current chunk that have been read.
+    offset: usize,
+    max_read: usize,
+}
+
+impl<'a> ScriptedReader<'a> {
+    /// Creates a new [`ScriptedReader`] that returns the `chunks` in order.
+    #[inline]
+    pub const fn new(chunks: &'a [&'a [u8]]) -> Self {
+        Self {
+            chunks,
+            index: 0,
+            offset: 0,
+            max_read: usize::MAX,
+        }
+    }
+
+    /// Limits every read to at most `max_read` bytes, e.g. `1` to return a single byte per read.
+    ///
+    /// `0` is treated as `1`.
+    #[inline]
+    pub const fn with_max_read(mut self, max_read: usize) -> Self {
+        self.max_read = if max_read == 0 { 1 } else { max_read };
+        self
+    }
+
+    /// Returns `true` if every chunk has been read.
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        let mut rest = self.chunks.get(self.index..).unwrap_or_default().iter();
+
+        rest.next().is_none_or(|chunk| chunk.len() <= self.offset)
+            && rest.all(|chunk| chunk.is_empty())
+    }
+}
+
+impl ErrorType for ScriptedReader<'_> {
+    type Error = Infallible;
+}
+
+impl Read for ScriptedReader<'_> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        while let Some(chunk) = self.chunks.get(self.index) {
+            let remaining = chunk.get(self.offset..).unwrap_or_default();
+
+            if remaining.is_empty() {
+                self.index = self.index.saturating_add(1);
+                self.offset = 0;
+
+                continue;
+            }
+
+            let n = remaining.len().min(buf.len()).min(self.max_read);
+
+            if let (Some(dst), Some(src)) = (buf.get_mut(..n), remaining.get(..n)) {
+                dst.copy_from_slice(src);
+            }
+
+            self.offset = self.offset.saturating_add(n);
+
+            return Ok(n);
+        }
+
+        Ok(0)
+    }
+}
+
+/// When a [`FaultyReader`] or a [`FaultyWriter`] injects its error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailAfter {
+    /// After this many bytes passed through. Operations are shortened, so exactly this many bytes pass.
+    Bytes(usize),
+    /// After this many successful operations.
+    Operations(usize),
+}
+
+/// Counts the bytes and operations of a [`FaultyReader`] or a [`FaultyWriter`] and decides when to fail.
+#[derive(Debug, Clone)]
+struct Fault {
+    after: FailAfter,
+    kind: ErrorKind,
+    persistent: bool,
+    bytes: usize,
+    operations: usize,
+    failed: bool,
+}
+
+impl Fault {
+    const fn new(after: FailAfter, kind: ErrorKind) -> Self {
+        Self {
+            after,
+            kind,
+            persistent: false,
+            bytes: 0,
+            operations: 0,
+            failed: false,
+        }
+    }
+
+    /// Returns the number of bytes the next operation may pass, or the error to inject.
+    fn check(&mut self, len: usize) -> Result<usize, ErrorKind> {
+        if self.failed && !self.persistent {
+            return Ok(len);
+        }
+
+        let allowed = match self.after {
+            FailAfter::Bytes(bytes) => bytes.saturating_sub(self.bytes).min(len),
+            FailAfter::Operations(operations) if self.operations < operations => len,
+            FailAfter::Operations(_) => 0,
+        };
+
+        if allowed == 0 && len > 0 {
+            self.failed = true;
+
+            return Err(self.kind);
+        }
+
+        Ok(allowed)
+    }
+
+    fn record(&mut self, bytes: usize) {
+        self.bytes = self.bytes.saturating_add(bytes);
+        self.operations = self.operations.saturating_add(1);
+    }
+}
+
+/// Wraps a [`Read`] source and fails with an [`ErrorKind`] after a number of bytes or operations, see [`FailAfter`].
+///
+/// The error is injected once, then reading continues from the inner source, e.g. to test recovering from [`ReadError::IO`](crate::ReadError::IO).
+/// Use [`FaultyReader::persistent`] to inject the error on every read after the threshold.
+/// Errors of the inner source are reported using their [`ErrorKind`].
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_io_async::ErrorKind;
+/// use framez::{
+///     FramedRead, ReadError,
+///     codec::lines::StrLines,
+///     mock::{FailAfter, FaultyReader},
+///     next,
+/// };
+///
+/// # async fn run() {
+/// let reader = FaultyReader::new(b"Hello\nHey\n".as_slice(), FailAfter::Bytes(6), ErrorKind::TimedOut);
+///
+/// let buffer = &mut [0_u8; 32];
+/// let mut reader = FramedRead::new(StrLines::new(), reader, buffer);
+///
+/// assert_eq!(next!(reader).unwrap().unwrap(), "Hello");
+/// assert!(matches!(next!(reader), Some(Err(ReadError::IO(ErrorKind::TimedOut)))));
+/// assert_eq!(next!(reader).unwrap().unwrap(), "Hey");
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FaultyReader<R> {
+    inner: R,
+    fault: Fault,
+}
+
+impl<R> FaultyReader<R> {
+    /// Creates a new [`FaultyReader`] that fails with `kind` after `after`.
+    #[inline]
+    pub const fn new(inner: R, after: FailAfter, kind: ErrorKind) -> Self {
+        Self {
+            inner,
+            fault: Fault::new(after, kind),
+        }
+    }
+
+    /// Injects the error on every read after the threshold, instead of once.
+    #[inline]
+    pub const fn persistent(mut self) -> Self {
+        self.fault.persistent = true;
+        self
+    }
+
+    /// Returns the number of bytes read so far.
+    #[inline]
+    pub const fn bytes(&self) -> usize {
+        self.fault.bytes
+    }
+
+    /// Returns reference to the inner source.
+    #[inline]
+    pub const fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Consumes the [`FaultyReader`] and returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> ErrorType for FaultyReader<R> {
+    type Error = ErrorKind;
+}
+
+impl<R> Read for FaultyReader<R>
+where
+    R: Read,
+{
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let allowed = self.fault.check(buf.len())?;
+
+        let n = self
+            .inner
+            .read(buf.get_mut(..allowed).unwrap_or_default())
+            .await
+            .map_err(|err| embedded_io_async::Error::kind(&err))?;
+
+        self.fault.record(n);
+
+        Ok(n)
+    }
+}
+
+/// Wraps a [`Write`] sink and fails with an [`ErrorKind`] after a number of bytes or operations, see [`FailAfter`].
+///
+/// Writes and flushes count as operations. The error is injected once, then writing continues to the inner sink,
+/// e.g. to test recovering from [`WriteError::IO`](crate::WriteError::IO). Use [`FaultyWriter::persistent`] to inject the error on every operation after the threshold.
+/// Errors of the inner sink are reported using their [`ErrorKind`].
+#[derive(Debug, Clone)]
+pub struct FaultyWriter<W> {
+    inner: W,
+    fault: Fault,
+}
+
+impl<W> FaultyWriter<W> {
+    /// Creates a new [`FaultyWriter`] that fails with `kind` after `after`.
+    #[inline]
+    pub const fn new(inner: W, after: FailAfter, kind: ErrorKind) -> Self {
+        Self {
+            inner,
+            fault: Fault::new(after, kind),
+        }
+    }
+
+    /// Injects the error on every operation after the threshold, instead of once.
+    #[inline]
+    pub const fn persistent(mut self) -> Self {
+        self.fault.persistent = true;
+        self
+    }
+
+    /// Returns the number of bytes written so far.
+    #[inline]
+    pub const fn bytes(&self) -> usize {
+        self.fault.bytes
+    }
+
+    /// Returns reference to the inner sink.
+    #[inline]
+    pub const fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Consumes the [`FaultyWriter`] and returns the inner sink.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W> ErrorType for FaultyWriter<W> {
+    type Error = ErrorKind;
+}
+
+impl<W> Write for FaultyWriter<W>
+where
+    W: Write,
+{
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let allowed = self.fault.check(buf.len())?;
+
+        let n = self
+            .inner
+            .write(buf.get(..allowed).unwrap_or_default())
+            .await
+            .map_err(|err| embedded_io_async::Error::kind(&err))?;
+
+        self.fault.record(n);
+
+        Ok(n)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        if let FailAfter::Operations(_) = self.fault.after {
+            self.fault.check(1)?;
+        }
+
+        self.inner
+            .flush()
+            .await
+            .map_err(|err| embedded_io_async::Error::kind(&err))?;
+
+        self.fault.record(0);
+
+        Ok(())
+    }
+}
+
+/// Wraps a [`Write`] sink and accepts at most a number of bytes per write, to test the handling of short writes.
+///
+/// Flushes can be delayed, so they yield to the executor a number of times before flushing the inner sink.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{FramedWrite, codec::lines::StrLines, mock::PartialWriter};
+///
+/// # async fn run() {
+/// let buffer = &mut [0_u8; 32];
+/// let mut writer = FramedWrite::new(StrLines::new(), PartialWriter::new(Vec::new(), 1), buffer);
+///
+/// writer.send("Hello").await.unwrap();
+///
+/// assert_eq!(writer.inner().writes(), 7);
+/// assert_eq!(writer.inner().inner(), b"Hello\r\n");
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PartialWriter<W> {
+    inner: W,
+    max_write: usize,
+    flush_delay: usize,
+    writes: usize,
+    flushes: usize,
+}
+
+impl<W> PartialWriter<W> {
+    /// Creates a new [`PartialWriter`] that writes at most `max_write` bytes per write.
+    ///
+    /// `0` is treated as `1`.
+    #[inline]
+    pub const fn new(inner: W, max_write: usize) -> Self {
+        Self {
+            inner,
+            max_write: if max_write == 0 { 1 } else { max_write },
+            flush_delay: 0,
+            writes: 0,
+            flushes: 0,
+        }
+    }
+
+    /// Yields to the executor `flush_delay` times in every flush, before flushing the inner sink.
+    #[inline]
+    pub const fn with_flush_delay(mut self, flush_delay: usize) -> Self {
+        self.flush_delay = flush_delay;
+        self
+    }
+
+    /// Returns the number of writes so far.
+    #[inline]
+    pub const fn writes(&self) -> usize {
+        self.writes
+    }
+
+    /// Returns the number of completed flushes so far.
+    #[inline]
+    pub const fn flushes(&self) -> usize {
+        self.flushes
+    }
+
+    /// Returns reference to the inner sink.
+    #[inline]
+    pub const fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Consumes the [`PartialWriter`] and returns the inner sink.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W> ErrorType for PartialWriter<W>
+where
+    W: ErrorType,
+{
+    type Error = W::Error;
+}
+
+impl<W> Write for PartialWriter<W>
+where
+    W: Write,
+{
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let len = buf.len().min(self.max_write);
+
+        let n = self.inner.write(buf.get(..len).unwrap_or_default()).await?;
+
+        self.writes = self.writes.saturating_add(1);
+
+        Ok(n)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        for _ in 0..self.flush_delay {
+            yield_now().await;
+        }
+
+        self.inner.flush().await?;
+
+        self.flushes = self.flushes.saturating_add(1);
+
+        Ok(())
+    }
+}
+
+/// A [`Write`] sink that records everything written into a buffer of `N` bytes, for assertions.
+///
+/// Every flush ends a segment, so the bytes can also be inspected per flush, e.g. per sent frame, see [`RecordingWriter::segments`].
+/// Up to `S` segments are recorded. Defaults to `16`.
+///
+/// Nothing is allocated, so encoders can be tested on `no_std` targets.
+/// Writing more than `N` bytes, or flushing more than `S` segments, fails with [`ErrorKind::OutOfMemory`].
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{FramedWrite, codec::lines::StrLines, mock::RecordingWriter};
+///
+/// # async fn run() {
+/// let buffer = &mut [0_u8; 32];
+/// let mut writer = FramedWrite::new(StrLines::new(), RecordingWriter::<64>::new(), buffer);
+///
+/// writer.send("Hello").await.unwrap();
+/// writer.send("Hey").await.unwrap();
+///
+/// assert_eq!(writer.inner().written(), b"Hello\r\nHey\r\n");
+/// assert!(writer.inner().segments().eq([b"Hello\r\n".as_slice(), b"Hey\r\n"]));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordingWriter<const N: usize, const S: usize = 16> {
+    buf: [u8; N],
+    len: usize,
+    /// The ends of the flushed segments.
+    ends: [usize; S],
+    segments: usize,
+}
+
+impl<const N: usize, const S: usize> RecordingWriter<N, S> {
+    /// Creates a new empty [`RecordingWriter`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+            ends: [0; S],
+            segments: 0,
+        }
+    }
+
+    /// Returns every byte written so far.
+    #[inline]
+    pub fn written(&self) -> &[u8] {
+        self.buf.get(..self.len).unwrap_or_default()
+    }
+
+    /// Returns the bytes written between consecutive flushes, skipping flushes that followed no write.
+    pub fn segments(&self) -> impl Iterator<Item = &[u8]> {
+        let ends = self.ends.get(..self.segments).unwrap_or_default();
+
+        ends.iter().scan(0, |start, &end| {
+            let segment = self.buf.get(*start..end).unwrap_or_default();
+
+            *start = end;
+
+            Some(segment)
+        })
+    }
+
+    /// Returns the bytes written after the last flush.
+    #[inline]
+    pub fn unflushed(&self) -> &[u8] {
+        self.buf
+            .get(self.flushed_len()..self.len)
+            .unwrap_or_default()
+    }
+
+    /// Clears the recorded bytes and segments.
+    #[inline]
+    pub const fn clear(&mut self) {
+        self.len = 0;
+        self.segments = 0;
+    }
+
+    fn flushed_len(&self) -> usize {
+        match self.segments.checked_sub(1) {
+            Some(last) => self.ends.get(last).copied().unwrap_or_default(),
+            None => 0,
+        }
+    }
+}
+
+impl<const N: usize, const S: usize> Default for RecordingWriter<N, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const S: usize> ErrorType for RecordingWriter<N, S> {
+    type Error = ErrorKind;
+}
+
+impl<const N: usize, const S: usize> Write for RecordingWriter<N, S> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let dst = self
+            .buf
+            .get_mut(self.len..self.len.saturating_add(buf.len()))
+            .ok_or(ErrorKind::OutOfMemory)?;
+
+        dst.copy_from_slice(buf);
+
+        self.len = self.len.saturating_add(buf.len());
+
+        Ok(buf.len())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        if self.len == self.flushed_len() {
+            return Ok(());
+        }
+
+        let end = self
+            .ends
+            .get_mut(self.segments)
+            .ok_or(ErrorKind::OutOfMemory)?;
+
+        *end = self.len;
+
+        self.segments = self.segments.saturating_add(1);
+
+        Ok(())
+    }
+}
+
+/// Wraps a [`Read`] source and returns [`Poll::Pending`] a number of times before every read of the inner source.
+///
+/// The task is woken before returning [`Poll::Pending`], so it is polled again. A read future that is dropped while pending
+/// keeps the progress of its countdown, so polling a framer once and dropping its future, over and over, eventually reads the whole source.
+/// Used to test that cancelling a read mid-way leaves the framer in a consistent state.
+///
+/// # Example
+///
+/// ```rust
+/// use futures::FutureExt;
+/// use framez::{FramedRead, codec::lines::StrLines, mock::PendingReader};
+///
+/// let buffer = &mut [0_u8; 32];
+/// let mut reader = FramedRead::new(StrLines::new(), PendingReader::new(b"Hello\n".as_slice(), 1), buffer);
+///
+/// // Cancelled while the read is pending.
+/// assert!(reader.maybe_next().now_or_never().is_none());
+///
+/// // Resumes the read. The frame is decoded by the next call.
+/// assert!(reader.maybe_next().now_or_never().unwrap().unwrap().unwrap().is_none());
+///
+/// let item = reader.maybe_next().now_or_never().unwrap().unwrap().unwrap();
+///
+/// assert_eq!(item, Some("Hello"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PendingReader<R> {
+    inner: R,
+    pending: usize,
+    remaining: usize,
+    pendings: usize,
+}
+
+impl<R> PendingReader<R> {
+    /// Creates a new [`PendingReader`] that returns [`Poll::Pending`] `pending` times before every read.
+    #[inline]
+    pub const fn new(inner: R, pending: usize) -> Self {
+        Self {
+            inner,
+            pending,
+            remaining: pending,
+            pendings: 0,
+        }
+    }
+
+    /// Returns the number of times [`Poll::Pending`] was returned so far.
+    #[inline]
+    pub const fn pendings(&self) -> usize {
+        self.pendings
+    }
+
+    /// Returns reference to the inner source.
+    #[inline]
+    pub const fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Consumes the [`PendingReader`] and returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> ErrorType for PendingReader<R>
+where
+    R: ErrorType,
+{
+    type Error = R::Error;
+}
+
+impl<R> Read for PendingReader<R>
+where
+    R: Read,
+{
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        poll_fn(|cx| {
+            if self.remaining == 0 {
+                return Poll::Ready(());
+            }
+
+            self.remaining = self.remaining.saturating_sub(1);
+            self.pendings = self.pendings.saturating_add(1);
+
+            cx.waker().wake_by_ref();
+
+            Poll::Pending
+        })
+        .await;
+
+        let n = self.inner.read(buf).await?;
+
+        self.remaining = self.pending;
+
+        Ok(n)
+    }
+}
+
+/// A [`DelayNs`](embedded_hal_async::delay::DelayNs) implementation that records the requested delays without waiting.
+///
+/// Keeps the first `N` delays. Every delay is added to [`RecordingDelay::total_ns`], even if it was not kept.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_hal_async::delay::DelayNs;
+/// use framez::mock::RecordingDelay;
+///
+/// # async fn run() {
+/// let mut delay = RecordingDelay::<4>::new();
+///
+/// delay.delay_us(2).await;
+/// delay.delay_ns(500).await;
+///
+/// assert_eq!(delay.delays(), [2_000, 500]);
+/// assert_eq!(delay.total_ns(), 2_500);
+/// # }
+/// ```
+#[cfg(feature = "embedded-hal-async")]
+#[derive(Debug, Clone)]
+pub struct RecordingDelay<const N: usize = 16> {
+    delays: [u32; N],
+    len: usize,
+    total_ns: u64,
+}
+
+#[cfg(feature = "embedded-hal-async")]
+impl<const N: usize> RecordingDelay<N> {
+    /// Creates a new [`RecordingDelay`] without recorded delays.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            delays: [0; N],
+            len: 0,
+            total_ns: 0,
+        }
+    }
+
+    /// Returns the kept delays in nanoseconds.
+    #[inline]
+    pub fn delays(&self) -> &[u32] {
+        self.delays.get(..self.len).unwrap_or_default()
+    }
+
+    /// Returns the sum of all requested delays in nanoseconds.
+    #[inline]
+    pub const fn total_ns(&self) -> u64 {
+        self.total_ns
+    }
+}
+
+#[cfg(feature = "embedded-hal-async")]
+impl<const N: usize> Default for RecordingDelay<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "embedded-hal-async")]
+impl<const N: usize> embedded_hal_async::delay::DelayNs for RecordingDelay<N> {
+    async fn delay_ns(&mut self, ns: u32) {
+        if let Some(delay) = self.delays.get_mut(self.len) {
+            *delay = ns;
+
+            self.len = self.len.saturating_add(1);
+        }
+
+        self.total_ns = self.total_ns.saturating_add(u64::from(ns));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use crate::{
+        Framed, FramedRead, ReadError,
+        codec::lines::{Lines, StrLines},
+        next, send,
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn pair_round_trip() {
+        init_tracing();
+
+        // Smaller than a frame, so both ends wait for each other.
+        let duplex = Duplex::<4>::new();
+        let (read_a, write_a) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+        let (read_b, write_b) = (&mut [0_u8; 32], &mut [0_u8; 32]);
+
+        let (mut a, mut b) = Framed::pair(
+            &duplex,
+            StrLines::new(),
+            StrLines::new(),
+            read_a,
+            write_a,
+            read_b,
+            write_b,
+        );
+
+        let items = ["Hello", "Hey", "How are you?"];
+
+        let client = async {
+            for item in items {
+                a.send(item).await.expect("Must send");
+
+                assert_eq!(next!(a).expect("Must be some").expect("Must decode"), item);
+            }
+        };
+
+        let server = async {
+            for _ in items {
+                let item = next!(b).expect("Must be some").expect("Must decode");
+
+                send!(b, item).expect("Must send");
+            }
+        };
+
+        tokio::join!(client, server);
+    }
+
+    #[tokio::test]
+    async fn dropped_end() {
+        init_tracing();
+
+        let duplex = Duplex::<16>::new();
+        let (mut a, mut b) = duplex.split();
+
+        a.write_all(b"Hello").await.expect("Must write");
+
+        drop(a);
+
+        let buf = &mut [0_u8; 16];
+
+        assert_eq!(b.read(buf).await, Ok(5));
+        assert_eq!(b.read(buf).await, Ok(0));
+        assert_eq!(b.write(b"Hey").await, Err(ErrorKind::BrokenPipe));
+    }
+
+    #[tokio::test]
+    async fn scripted_reader_splits_frames() {
+        init_tracing();
+
+        let chunks: &[&[u8]] = &[b"", b"Hel", b"lo\r", b"\nHey\n", b"", b"Hi"];
+
+        for max_read in [1, 2, 3, usize::MAX] {
+            let buffer = &mut [0_u8; 8];
+            let mut reader = FramedRead::new(
+                Lines::new(),
+                ScriptedReader::new(chunks).with_max_read(max_read),
+                buffer,
+            );
+
+            let mut collected = Vec::new();
+
+            while let Some(item) = next!(reader) {
+                match item {
+                    Ok(item) => collected.push(item.to_vec()),
+                    Err(err) => {
+                        assert!(matches!(err, ReadError::BytesRemainingOnStream));
+
+                        break;
+                    }
+                }
+            }
+
+            assert_eq!(collected, [b"Hello".to_vec(), b"Hey".to_vec()]);
+            assert!(reader.inner().is_done());
+        }
+    }
+
+    #[tokio::test]
+    async fn faulty_writer_fails_once() {
+        init_tracing();
+
+        let writer = FaultyWriter::new(Vec::new(), FailAfter::Bytes(4), ErrorKind::Other);
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = crate::FramedWrite::new(StrLines::new(), writer, buffer);
+
+        assert!(matches!(
+            framer.send("Hello").await,
+            Err(crate::WriteError::IO(ErrorKind::Other))
+        ));
+
+        // The rest of the failed frame is written before the next one.
+        framer.send("Hey").await.expect("Must send");
+
+        assert_eq!(framer.inner().inner(), b"Hello\r\nHey\r\n");
+    }
+
+    #[tokio::test]
+    async fn faulty_reader_persistent() {
+        init_tracing();
+
+        let reader = FaultyReader::new(
+            b"Hello\nHey\n".as_slice(),
+            FailAfter::Operations(1),
+            ErrorKind::Interrupted,
+        )
+        .persistent();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(StrLines::new(), reader, buffer);
+
+        assert_eq!(
+            next!(framer).expect("Must be some").expect("Must decode"),
+            "Hello"
+        );
+        assert_eq!(
+            next!(framer).expect("Must be some").expect("Must decode"),
+            "Hey"
+        );
+
+        for _ in 0..2 {
+            assert!(matches!(
+                next!(framer),
+                Some(Err(ReadError::IO(ErrorKind::Interrupted)))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn partial_writer_completes_frames() {
+        init_tracing();
+
+        let writer = PartialWriter::new(Vec::new(), 3).with_flush_delay(2);
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = crate::FramedWrite::new(StrLines::new(), writer, buffer);
+
+        framer.send("Hello").await.expect("Must send");
+        framer.send("Hey").await.expect("Must send");
+
+        let writer = framer.inner();
+
+        assert_eq!(writer.inner(), b"Hello\r\nHey\r\n");
+        assert_eq!(writer.writes(), 5);
+        assert_eq!(writer.flushes(), 2);
+    }
+
+    #[tokio::test]
+    async fn recording_writer_segments() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer =
+            crate::FramedWrite::new(StrLines::new(), RecordingWriter::<16, 2>::new(), buffer);
+
+        framer.send("Hello").await.expect("Must send");
+        framer.send("Hey").await.expect("Must send");
+
+        let segments: &[&[u8]] = &[b"Hello\r\n", b"Hey\r\n"];
+
+        assert_eq!(framer.inner().segments().collect::<Vec<_>>(), segments);
+        assert!(framer.inner().unflushed().is_empty());
+
+        // The third segment does not fit.
+        assert!(matches!(
+            framer.send("!").await,
+            Err(crate::WriteError::IO(ErrorKind::OutOfMemory))
+        ));
+        assert_eq!(framer.inner().unflushed(), b"!\r\n");
+
+        // Neither does the frame.
+        framer.inner_mut().clear();
+
+        assert!(matches!(
+            framer.send("How are you doing?").await,
+            Err(crate::WriteError::IO(ErrorKind::OutOfMemory))
+        ));
+    }
+
+    #[test]
+    fn pending_reader_cancelled_reads() {
+        use futures::FutureExt;
+
+        init_tracing();
+
+        let chunks: &[&[u8]] = &[b"Hel", b"lo\nH", b"ey", b"\n"];
+        let reader = PendingReader::new(ScriptedReader::new(chunks), 2);
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(StrLines::new(), reader, buffer);
+
+        let mut items = Vec::new();
+        let mut cancelled = 0;
+
+        // Polls every future once, then drops it.
+        loop {
+            match framer.maybe_next().now_or_never() {
+                None => cancelled += 1,
+                Some(Some(Ok(Some(item)))) => items.push(std::string::String::from(item)),
+                Some(Some(Ok(None))) => {}
+                Some(Some(Err(err))) => panic!("Must not fail: {err:?}"),
+                Some(None) => break,
+            }
+        }
+
+        // Including the read that reaches EOF.
+        assert_eq!(cancelled, 10);
+        assert_eq!(framer.inner().pendings(), 10);
+        assert_eq!(items, ["Hello", "Hey"]);
+    }
+}
@@ -0,0 +1,305 @@
+//! Nameable futures of the core operations.
+//!
+//! The `async fn`s of the [`functions`] module return opaque futures that can not be named,
+//! so they can not be stored in statically-allocated task structures or composed by hand without boxing them.
+//! [`MaybeNext`] and [`Send`] are concrete [`Future`]s of [`functions::maybe_next`] and [`functions::send`].
+//!
+//! The reads and writes of the underlying source are polled once and dropped if they are pending.
+//! This relies on the cancel safety of the underlying reader and writer, since the [`ReadState`] and [`WriteState`]
+//! track the progress between polls. See [`functions::maybe_next`](crate::functions::maybe_next#cancel-safety)
+//! and [`functions::send`](crate::functions::send#cancel-safety).
+//!
+//! # Example
+//!
+//! ```rust
+//! use framez::{FramedRead, codec::lines::StrLines, ops::MaybeNext};
+//!
+//! /// A task that stores its read future by name.
+//! struct ReadTask<'a, 'buf> {
+//!     next: MaybeNext<'a, 'buf, StrLines, &'static [u8]>,
+//! }
+//!
+//! # async fn run() {
+//! let buffer = &mut [0_u8; 32];
+//! let mut framer = FramedRead::new(StrLines::new(), b"Hello\n".as_slice(), buffer);
+//!
+//! // Reads the bytes.
+//! let task = ReadTask { next: framer.maybe_next_future() };
+//! assert!(matches!(task.next.await, Some(Ok(None))));
+//!
+//! // Decodes the frame.
+//! let task = ReadTask { next: framer.maybe_next_future() };
+//! assert!(matches!(task.next.await, Some(Ok(Some("Hello")))));
+//! # }
+//! ```
+
+use core::{
+    future::Future,
+    pin::{Pin, pin},
+    task::{Context, Poll, ready},
+};
+
+use embedded_io_async::{Read, Write};
+
+use crate::{
+    ReadError, WriteError,
+    buffer::Buffer,
+    decode::Decoder,
+    encode::Encoder,
+    functions,
+    state::{ReadPhase, ReadState, WriteState},
+};
+
+/// The future of [`functions::maybe_next`].
+///
+/// Returned by [`Framed::maybe_next_future`](crate::Framed::maybe_next_future) and [`FramedRead::maybe_next_future`](crate::FramedRead::maybe_next_future).
+///
+/// Buffered frames are decoded on the first poll. Otherwise, the read is polled once per poll and dropped if it is pending.
+/// The future is fused. Polling it again after it completed returns `None` instead of panicking.
+///
+/// Unlike [`functions::maybe_next`], the future does not run inside a `tracing` span.
+#[derive(Debug)]
+pub struct MaybeNext<'a, 'buf, C, R, B = &'buf mut [u8]> {
+    /// Taken when the future completes, so a decoded frame can borrow the buffer for `'a`.
+    state: Option<&'a mut ReadState<'buf, B>>,
+    codec: &'a mut C,
+    read: &'a mut R,
+}
+
+impl<'a, 'buf, C, R, B> MaybeNext<'a, 'buf, C, R, B> {
+    /// Creates a new [`MaybeNext`] over the given `state`, `codec` and `read`.
+    #[inline]
+    pub const fn new(state: &'a mut ReadState<'buf, B>, codec: &'a mut C, read: &'a mut R) -> Self {
+        Self {
+            state: Some(state),
+            codec,
+            read,
+        }
+    }
+}
+
+impl<'a, C, R, B> Future for MaybeNext<'a, '_, C, R, B>
+where
+    C: Decoder,
+    R: Read,
+    B: Buffer,
+{
+    type Output = Option<Result<Option<C::Item<'a>>, ReadError<R::Error, C::Error>>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let Some(state) = this.state.take() else {
+            return Poll::Ready(None);
+        };
+
+        if matches!(state.phase, ReadPhase::Decode | ReadPhase::DecodeEof) {
+            return Poll::Ready(functions::decode_pending(state, this.codec));
+        }
+
+        let polled = pin!(functions::read_into(state, this.read)).poll(cx);
+
+        match polled {
+            Poll::Ready(Ok(())) => Poll::Ready(Some(Ok(None))),
+            Poll::Ready(Err(err)) => Poll::Ready(Some(Err(err.cast_decode()))),
+            Poll::Pending => {
+                this.state = Some(state);
+
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// The future of [`functions::send`].
+///
+/// Returned by [`Framed::send_future`](crate::Framed::send_future) and [`FramedWrite::send_future`](crate::FramedWrite::send_future).
+///
+/// The `item` is kept until the rest of a previously written frame is written, and encoded right after.
+/// The encoded frame is then written and flushed, polling the writer once per poll.
+/// Unlike [`functions::send`], the `item` is not lost if the future is dropped before it was encoded.
+///
+/// Unlike [`functions::send`], the future does not run inside a `tracing` span.
+#[derive(Debug)]
+pub struct Send<'a, 'buf, C, W, I, B = &'buf mut [u8]> {
+    state: &'a mut WriteState<'buf, B>,
+    codec: &'a mut C,
+    write: &'a mut W,
+    /// Taken when the item is encoded.
+    item: Option<I>,
+}
+
+impl<'a, 'buf, C, W, I, B> Send<'a, 'buf, C, W, I, B> {
+    /// Creates a new [`Send`] that sends `item` over the given `state`, `codec` and `write`.
+    #[inline]
+    pub const fn new(
+        state: &'a mut WriteState<'buf, B>,
+        codec: &'a mut C,
+        write: &'a mut W,
+        item: I,
+    ) -> Self {
+        Self {
+            state,
+            codec,
+            write,
+            item: Some(item),
+        }
+    }
+
+    /// Consumes the [`Send`] and returns the `item` if it was not encoded yet.
+    #[inline]
+    pub fn into_item(self) -> Option<I> {
+        self.item
+    }
+}
+
+// The item is never pinned.
+impl<C, W, I, B> Unpin for Send<'_, '_, C, W, I, B> {}
+
+impl<C, W, I, B> Future for Send<'_, '_, C, W, I, B>
+where
+    C: Encoder<I>,
+    W: Write,
+    B: Buffer,
+{
+    type Output = Result<(), WriteError<W::Error, C::Error>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Some(item) = this.item.as_ref() {
+            // Like `send`, a failed call discards the item.
+            let needs_room =
+                functions::needs_room(this.state, this.codec, item).inspect_err(|_| {
+                    this.item = None;
+                })?;
+
+            if needs_room {
+                let polled = pin!(functions::make_room(this.state, this.write)).poll(cx);
+
+                if let Err(err) = ready!(polled) {
+                    this.item = None;
+
+                    return Poll::Ready(Err(err.cast_encode()));
+                }
+            }
+
+            let Some(item) = this.item.take() else {
+                return Poll::Ready(Ok(()));
+            };
+
+            if !functions::encode_send(this.state, this.codec, item)? {
+                return Poll::Ready(Ok(()));
+            }
+        }
+
+        pin!(functions::write_sent(this.state, this.write))
+            .poll(cx)
+            .map_err(WriteError::cast_encode)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use crate::{
+        FramedRead, FramedWrite,
+        codec::lines::{Lines, StrLines},
+        mock::{Duplex, PendingReader},
+        next,
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    /// Stores the futures by name, like a statically-allocated task would.
+    struct Task<'a, 'buf> {
+        next: MaybeNext<'a, 'buf, StrLines, PendingReader<&'static [u8]>>,
+    }
+
+    #[tokio::test]
+    async fn maybe_next_survives_pending_reads() {
+        init_tracing();
+
+        let read = PendingReader::new(b"Hello\nHey\n".as_slice(), 2);
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(StrLines::new(), read, buffer);
+
+        let mut items = Vec::new();
+
+        loop {
+            let task = Task {
+                next: framer.maybe_next_future(),
+            };
+
+            match task.next.await {
+                Some(Ok(Some(item))) => items.push(std::string::String::from(item)),
+                Some(Ok(None)) => continue,
+                Some(Err(err)) => panic!("Must not fail: {err:?}"),
+                None => break,
+            }
+        }
+
+        assert_eq!(items, ["Hello", "Hey"]);
+        assert!(framer.core.inner.pendings() > 0);
+    }
+
+    #[tokio::test]
+    async fn maybe_next_is_fused() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(StrLines::new(), b"Hello\n".as_slice(), buffer);
+
+        let mut next = framer.maybe_next_future();
+
+        assert!(matches!((&mut next).await, Some(Ok(None))));
+        assert!(next.await.is_none());
+    }
+
+    #[tokio::test]
+    async fn send_keeps_item_until_encoded() {
+        init_tracing();
+
+        // Smaller than a frame, so the writer waits for the reader.
+        let duplex = Duplex::<4>::new();
+        let (read, mut write) = duplex.split();
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Lines::new(), &mut write, buffer);
+
+        // Writes 4 bytes of the first frame, then waits for the reader.
+        assert!(futures::FutureExt::now_or_never(framer.send(b"Hello".as_slice())).is_none());
+
+        // Waits for the rest of the first frame before encoding the item.
+        let mut send = framer.send_future(b"Hey".as_slice());
+        assert!(futures::FutureExt::now_or_never(&mut send).is_none());
+        assert_eq!(send.into_item(), Some(b"Hey".as_slice()));
+
+        let reader = async {
+            let buffer = &mut [0_u8; 32];
+            let mut framer = FramedRead::new(Lines::new(), read, buffer);
+
+            let mut collected = Vec::<Vec<u8>>::new();
+
+            for _ in 0..2 {
+                collected.push(
+                    next!(framer)
+                        .expect("Must be some")
+                        .expect("Must decode")
+                        .into(),
+                );
+            }
+
+            collected
+        };
+
+        let (sent, collected) = tokio::join!(framer.send_future(b"Hey".as_slice()), reader);
+
+        sent.expect("Must send");
+
+        assert_eq!(collected, [b"Hello".as_slice(), b"Hey"]);
+    }
+}
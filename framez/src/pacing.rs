@@ -0,0 +1,198 @@
+//! Rate limiting of sent frames.
+
+use embedded_hal_async::delay::DelayNs;
+
+/// Paces sent frames to a maximum frame rate and byte rate.
+///
+/// Used with [`send_paced`](crate::functions::send_paced), e.g. to respect the duty cycle of a LoRa radio
+/// or to leave room for other nodes on an RS-485 bus.
+///
+/// Every sent frame costs the [`frame_interval_us`](Pacer::frame_interval_us) plus the [`byte_interval_ns`](Pacer::byte_interval_ns)
+/// for each of its bytes. The cost is paid by waiting on the [`DelayNs`] before the next frame is sent.
+///
+/// # Note
+///
+/// The pacer has no clock, so the time that passes between two sends is not subtracted from the cost.
+/// Frames are therefore never sent faster than configured, but may be sent slower.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Pacer<D> {
+    /// The delay used to wait between frames.
+    delay: D,
+    /// The cost of every frame in microseconds.
+    frame_interval_us: u32,
+    /// The cost of every byte in nanoseconds.
+    byte_interval_ns: u32,
+    /// The cost of the sent frames that was not paid yet in nanoseconds.
+    debt_ns: u64,
+}
+
+impl<D> Pacer<D> {
+    /// Creates a new [`Pacer`] with the given `delay` that does not wait.
+    #[inline]
+    pub const fn new(delay: D) -> Self {
+        Self {
+            delay,
+            frame_interval_us: 0,
+            byte_interval_ns: 0,
+            debt_ns: 0,
+        }
+    }
+
+    /// Sets the minimum time between two frames in microseconds.
+    #[inline]
+    pub const fn with_frame_interval_us(mut self, frame_interval_us: u32) -> Self {
+        self.frame_interval_us = frame_interval_us;
+        self
+    }
+
+    /// Sets the time every sent byte occupies the bus in nanoseconds.
+    ///
+    /// E.g. `10 * 1_000_000_000 / 9600` for a 9600 baud UART with 10 bits per byte.
+    #[inline]
+    pub const fn with_byte_interval_ns(mut self, byte_interval_ns: u32) -> Self {
+        self.byte_interval_ns = byte_interval_ns;
+        self
+    }
+
+    /// Returns the minimum time between two frames in microseconds.
+    #[inline]
+    pub const fn frame_interval_us(&self) -> u32 {
+        self.frame_interval_us
+    }
+
+    /// Returns the time every sent byte occupies the bus in nanoseconds.
+    #[inline]
+    pub const fn byte_interval_ns(&self) -> u32 {
+        self.byte_interval_ns
+    }
+
+    /// Returns the time the next call to [`Pacer::wait`] waits in nanoseconds.
+    #[inline]
+    pub const fn debt_ns(&self) -> u64 {
+        self.debt_ns
+    }
+
+    /// Returns reference to the delay.
+    #[inline]
+    pub const fn delay(&self) -> &D {
+        &self.delay
+    }
+
+    /// Returns mutable reference to the delay.
+    #[inline]
+    pub const fn delay_mut(&mut self) -> &mut D {
+        &mut self.delay
+    }
+
+    /// Consumes the [`Pacer`] and returns the delay.
+    #[inline]
+    pub fn into_delay(self) -> D {
+        self.delay
+    }
+
+    /// Records a sent frame of `bytes` bytes.
+    #[inline]
+    pub const fn record(&mut self, bytes: usize) {
+        let frame_ns = (self.frame_interval_us as u64).saturating_mul(1_000);
+        let bytes_ns = (bytes as u64).saturating_mul(self.byte_interval_ns as u64);
+
+        self.debt_ns = self
+            .debt_ns
+            .saturating_add(frame_ns)
+            .saturating_add(bytes_ns);
+    }
+
+    /// Forgets the cost of the sent frames, e.g. after the bus was idle for a known time.
+    #[inline]
+    pub const fn reset(&mut self) {
+        self.debt_ns = 0;
+    }
+}
+
+impl<D> Pacer<D>
+where
+    D: DelayNs,
+{
+    /// Waits until the cost of the recorded frames is paid.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. The cost is reduced after every completed delay,
+    /// so the next call waits at least the rest of it.
+    pub async fn wait(&mut self) {
+        while self.debt_ns > 0 {
+            let ns = u32::try_from(self.debt_ns).unwrap_or(u32::MAX);
+
+            self.delay.delay_ns(ns).await;
+
+            self.debt_ns = self.debt_ns.saturating_sub(ns as u64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use crate::{FramedWrite, codec::lines::Lines, mock::RecordingDelay, tests::init_tracing};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn waits_for_frames_and_bytes() {
+        let mut pacer = Pacer::new(RecordingDelay::<4>::new())
+            .with_frame_interval_us(1_000)
+            .with_byte_interval_ns(500);
+
+        pacer.wait().await;
+        assert!(pacer.delay().delays().is_empty());
+
+        pacer.record(4);
+        assert_eq!(pacer.debt_ns(), 1_002_000);
+
+        pacer.wait().await;
+        assert_eq!(pacer.debt_ns(), 0);
+        assert_eq!(pacer.delay().delays(), [1_002_000]);
+    }
+
+    #[tokio::test]
+    async fn long_waits_are_split() {
+        let mut pacer = Pacer::new(RecordingDelay::<4>::new()).with_frame_interval_us(u32::MAX);
+
+        pacer.record(0);
+        pacer.wait().await;
+
+        assert!(pacer.delay().delays().len() > 1);
+        assert_eq!(pacer.delay().total_ns(), u32::MAX as u64 * 1_000);
+    }
+
+    #[tokio::test]
+    async fn send_paced_waits_before_the_next_frame() {
+        init_tracing();
+
+        let mut pacer = Pacer::new(RecordingDelay::<4>::new())
+            .with_frame_interval_us(10)
+            .with_byte_interval_ns(100);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedWrite::new(Lines::new(), Vec::new(), buffer);
+
+        framer
+            .send_paced(&mut pacer, b"Hello".as_slice())
+            .await
+            .expect("Must send");
+
+        assert!(pacer.delay().delays().is_empty());
+
+        framer
+            .send_paced(&mut pacer, b"world!".as_slice())
+            .await
+            .expect("Must send");
+
+        // "Hello\r\n" is 7 bytes.
+        assert_eq!(pacer.delay().delays(), [10_700]);
+        assert_eq!(pacer.debt_ns(), 10_800);
+        assert_eq!(framer.core.inner, b"Hello\r\nworld!\r\n");
+    }
+}
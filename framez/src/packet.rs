@@ -0,0 +1,624 @@
+//! Packets with a header carrying their length, payload type and checksum.
+//!
+//! A packet is laid out as:
+//!
+//! ```text
+//! +--------------+---------------+--------------+---------+
+//! | length (u16) | payload type  | checksum     | payload |
+//! |              | (u16)         | (u32)        |         |
+//! +--------------+---------------+--------------+---------+
+//! ```
+//!
+//! The fields of the [`Header`] are big endian. The length counts the whole packet, header included.
+//! The checksum is computed over the whole packet with the checksum field zeroed, using [`Crc32::iso_hdlc`] by default.
+//!
+//! The payloads and their serialization are supplied by [`PayloadEncode`] and [`PayloadDecode`], the framing and checksum by the [`PacketCodec`].
+
+use core::marker::PhantomData;
+
+use crate::{
+    checksum::{Checksum, Crc32},
+    decode::Decoder,
+    encode::{EncodeBufferTooSmall, Encoder},
+};
+
+/// The header of a packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Header {
+    /// The length of the packet, header included.
+    pub length: u16,
+    /// The type of the payload.
+    pub payload_type: u16,
+    /// The checksum of the packet.
+    pub checksum: u32,
+}
+
+impl Header {
+    /// The size of the header.
+    pub const SIZE: usize = 8;
+
+    /// Reads the header from the start of `src`, or returns `None` if `src` is too short.
+    pub fn read(src: &[u8]) -> Option<Self> {
+        let bytes: &[u8; Self::SIZE] = src.get(..Self::SIZE)?.try_into().ok()?;
+        let [l0, l1, t0, t1, c0, c1, c2, c3] = *bytes;
+
+        Some(Self {
+            length: u16::from_be_bytes([l0, l1]),
+            payload_type: u16::from_be_bytes([t0, t1]),
+            checksum: u32::from_be_bytes([c0, c1, c2, c3]),
+        })
+    }
+
+    /// Writes the header to the start of `dst`, or returns `None` if `dst` is too short.
+    pub fn write(&self, dst: &mut [u8]) -> Option<()> {
+        let [l0, l1] = self.length.to_be_bytes();
+        let [t0, t1] = self.payload_type.to_be_bytes();
+        let [c0, c1, c2, c3] = self.checksum.to_be_bytes();
+
+        dst.get_mut(..Self::SIZE)?
+            .copy_from_slice(&[l0, l1, t0, t1, c0, c1, c2, c3]);
+
+        Some(())
+    }
+
+    /// Returns the length of the payload, or `None` if the length of the packet is shorter than the header.
+    #[inline]
+    pub const fn payload_length(&self) -> Option<usize> {
+        (self.length as usize).checked_sub(Self::SIZE)
+    }
+}
+
+/// A payload that is encoded into a packet.
+pub trait PayloadEncode {
+    /// The type of error returned when serializing the payload fails, e.g. because the buffer is too small.
+    type Error;
+
+    /// Returns the type of the payload, written to the [`Header`].
+    fn payload_type(&self) -> u16;
+
+    /// Serializes the payload into the start of `dst` and returns the number of bytes written.
+    fn encode_payload(&self, dst: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+impl<T> PayloadEncode for &T
+where
+    T: PayloadEncode + ?Sized,
+{
+    type Error = T::Error;
+
+    #[inline]
+    fn payload_type(&self) -> u16 {
+        T::payload_type(self)
+    }
+
+    #[inline]
+    fn encode_payload(&self, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        T::encode_payload(self, dst)
+    }
+}
+
+/// The payloads that are decoded from packets, e.g. an enum with one variant per payload type.
+///
+/// The decoded payloads may borrow from the read buffer. A payload type with a lifetime implements it for its `'static` version,
+/// e.g. `impl PayloadDecode for Message<'static>` with `type Payload<'buf> = Message<'buf>`.
+pub trait PayloadDecode {
+    /// The decoded payload.
+    type Payload<'buf>;
+
+    /// The type of error returned when deserializing the payload fails.
+    type Error;
+
+    /// Deserializes the payload of type `payload_type` from the whole `src`.
+    ///
+    /// Returns `Ok(None)` if the `payload_type` is unknown.
+    fn decode_payload<'buf>(
+        payload_type: u16,
+        src: &'buf [u8],
+    ) -> Result<Option<Self::Payload<'buf>>, Self::Error>;
+}
+
+/// An error that can occur while decoding a packet.
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PacketDecodeError<E> {
+    /// The length of the packet is shorter than its header.
+    InvalidLength(u16),
+    /// The checksum of the packet does not match its bytes.
+    Checksum,
+    /// The payload type is unknown.
+    UnknownPayloadType(u16),
+    /// Deserializing the payload failed.
+    Payload(E),
+}
+
+impl<E> core::fmt::Display for PacketDecodeError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidLength(length) => write!(f, "Invalid length: {length}"),
+            Self::Checksum => write!(f, "Checksum mismatch"),
+            Self::UnknownPayloadType(payload_type) => {
+                write!(f, "Unknown payload type: {payload_type}")
+            }
+            Self::Payload(err) => write!(f, "Payload error: {err}"),
+        }
+    }
+}
+
+impl<E> core::error::Error for PacketDecodeError<E> where E: core::fmt::Display + core::fmt::Debug {}
+
+/// An error that can occur while encoding a packet.
+#[non_exhaustive]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PacketEncodeError<E> {
+    /// The buffer is too small to fit the header.
+    BufferTooSmall(EncodeBufferTooSmall),
+    /// The packet is longer than its length field can hold. Contains the length of the packet.
+    PacketTooLarge(usize),
+    /// Serializing the payload failed.
+    Payload(E),
+}
+
+impl<E> core::fmt::Display for PacketEncodeError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall(err) => write!(f, "Buffer too small: {err}"),
+            Self::PacketTooLarge(length) => write!(f, "Packet too large: {length} bytes"),
+            Self::Payload(err) => write!(f, "Payload error: {err}"),
+        }
+    }
+}
+
+impl<E> core::error::Error for PacketEncodeError<E> where E: core::fmt::Display + core::fmt::Debug {}
+
+/// A codec that decodes packets into payloads of [`PayloadDecode`] `P` and encodes [`PayloadEncode`] payloads into packets.
+///
+/// The checksum `C` is a [`Checksum`] with an output of at most 32 bits, [`Crc32::iso_hdlc`] by default.
+///
+/// A packet that fails to decode, e.g. because of a checksum mismatch, is reported once and skipped, so reading can continue with the next packet.
+/// A packet with an invalid length can not be skipped reliably, so only its header is skipped.
+/// Skipped packets are consumed together with the next valid packet, so they must fit into the read buffer with it.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{
+///     FramedRead, FramedWrite,
+///     next,
+///     packet::{PacketCodec, PayloadDecode, PayloadEncode},
+/// };
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Message<'a> {
+///     Ping,
+///     Echo(&'a [u8]),
+/// }
+///
+/// impl PayloadEncode for Message<'_> {
+///     type Error = ();
+///
+///     fn payload_type(&self) -> u16 {
+///         match self {
+///             Message::Ping => 1,
+///             Message::Echo(_) => 2,
+///         }
+///     }
+///
+///     fn encode_payload(&self, dst: &mut [u8]) -> Result<usize, Self::Error> {
+///         let bytes: &[u8] = match self {
+///             Message::Ping => &[],
+///             Message::Echo(bytes) => bytes,
+///         };
+///
+///         dst.get_mut(..bytes.len()).ok_or(())?.copy_from_slice(bytes);
+///
+///         Ok(bytes.len())
+///     }
+/// }
+///
+/// impl PayloadDecode for Message<'static> {
+///     type Payload<'buf> = Message<'buf>;
+///     type Error = ();
+///
+///     fn decode_payload<'buf>(payload_type: u16, src: &'buf [u8]) -> Result<Option<Message<'buf>>, ()> {
+///         match payload_type {
+///             1 => Ok(Some(Message::Ping)),
+///             2 => Ok(Some(Message::Echo(src))),
+///             _ => Ok(None),
+///         }
+///     }
+/// }
+///
+/// # async fn run() {
+/// let write_buf = &mut [0_u8; 32];
+/// let mut writer = FramedWrite::new(PacketCodec::<Message<'static>>::new(), Vec::new(), write_buf);
+///
+/// writer.send(Message::Echo(b"Hello")).await.unwrap();
+///
+/// let (_, bytes, _) = writer.into_parts();
+///
+/// let read_buf = &mut [0_u8; 32];
+/// let mut reader = FramedRead::new(PacketCodec::<Message<'static>>::new(), bytes.as_slice(), read_buf);
+///
+/// let message = next!(reader).unwrap().unwrap();
+///
+/// assert_eq!(message, Message::Echo(b"Hello"));
+/// # }
+/// ```
+///
+/// # Note
+///
+/// This codec tracks progress using an internal state of the underlying buffer, and it must not be used across multiple framing sessions.
+pub struct PacketCodec<P, C = Crc32> {
+    checksum: C,
+    /// The number of bytes of the skipped packets at the start of the slice.
+    skip: usize,
+    _payload: PhantomData<fn() -> P>,
+}
+
+impl<P> PacketCodec<P> {
+    /// Creates a new [`PacketCodec`] using [`Crc32::iso_hdlc`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self::with_checksum(Crc32::iso_hdlc())
+    }
+}
+
+impl<P, C> PacketCodec<P, C> {
+    /// Creates a new [`PacketCodec`] using the given `checksum`, e.g. a hardware CRC engine of the target.
+    #[inline]
+    pub const fn with_checksum(checksum: C) -> Self {
+        Self {
+            checksum,
+            skip: 0,
+            _payload: PhantomData,
+        }
+    }
+
+    /// Returns reference to the checksum.
+    #[inline]
+    pub const fn checksum(&self) -> &C {
+        &self.checksum
+    }
+
+    /// Returns the checksum of `packet`, computed with the checksum field of its header zeroed.
+    fn compute(&mut self, packet: &[u8]) -> u32
+    where
+        C: Checksum,
+        C::Output: Into<u32>,
+    {
+        self.checksum.reset();
+        self.checksum.update(packet.get(..4).unwrap_or_default());
+        self.checksum.update(&[0; 4]);
+        self.checksum
+            .update(packet.get(Header::SIZE..).unwrap_or_default());
+
+        self.checksum.finalize().into()
+    }
+}
+
+impl<P> Default for PacketCodec<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P, C> Clone for PacketCodec<P, C>
+where
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            checksum: self.checksum.clone(),
+            skip: self.skip,
+            _payload: PhantomData,
+        }
+    }
+}
+
+impl<P, C> core::fmt::Debug for PacketCodec<P, C>
+where
+    C: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PacketCodec")
+            .field("checksum", &self.checksum)
+            .field("skip", &self.skip)
+            .finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<P, C> defmt::Format for PacketCodec<P, C>
+where
+    C: defmt::Format,
+{
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "PacketCodec {{ checksum: {}, skip: {} }}",
+            self.checksum,
+            self.skip
+        )
+    }
+}
+
+impl<P, C> Decoder for PacketCodec<P, C>
+where
+    P: PayloadDecode,
+    C: Checksum,
+    C::Output: Into<u32>,
+{
+    type Item<'buf> = P::Payload<'buf>;
+    type Error = PacketDecodeError<P::Error>;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        let skip = self.skip;
+
+        let Some(header) = src.get(skip..).and_then(Header::read) else {
+            return Ok(None);
+        };
+
+        let Some(payload_length) = header.payload_length() else {
+            self.skip = self.skip.saturating_add(Header::SIZE);
+
+            return Err(PacketDecodeError::InvalidLength(header.length));
+        };
+
+        let length = Header::SIZE.saturating_add(payload_length);
+
+        let Some(packet) = src.get(skip..skip.saturating_add(length)) else {
+            return Ok(None);
+        };
+
+        // The packet is complete, so it is consumed even if it fails to decode.
+        self.skip = self.skip.saturating_add(length);
+
+        if self.compute(packet) != header.checksum {
+            return Err(PacketDecodeError::Checksum);
+        }
+
+        let payload = packet.get(Header::SIZE..).unwrap_or_default();
+
+        match P::decode_payload(header.payload_type, payload) {
+            Ok(Some(payload)) => {
+                let size = core::mem::take(&mut self.skip);
+
+                Ok(Some((payload, size)))
+            }
+            Ok(None) => Err(PacketDecodeError::UnknownPayloadType(header.payload_type)),
+            Err(err) => Err(PacketDecodeError::Payload(err)),
+        }
+    }
+
+    fn decode_header(&mut self, src: &[u8]) -> Option<usize> {
+        let header = Header::read(src.get(self.skip..)?)?;
+
+        Some(
+            self.skip
+                .saturating_add(usize::from(header.length).max(Header::SIZE)),
+        )
+    }
+}
+
+impl<P, C, I> Encoder<I> for PacketCodec<P, C>
+where
+    I: PayloadEncode,
+    C: Checksum,
+    C::Output: Into<u32>,
+{
+    type Error = PacketEncodeError<I::Error>;
+
+    fn encode(&mut self, item: I, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let Some(payload) = dst.get_mut(Header::SIZE..) else {
+            return Err(PacketEncodeError::BufferTooSmall(EncodeBufferTooSmall {
+                required: Header::SIZE,
+                available: dst.len(),
+            }));
+        };
+
+        let payload_length = item
+            .encode_payload(payload)
+            .map_err(PacketEncodeError::Payload)?;
+
+        let length = Header::SIZE.saturating_add(payload_length);
+
+        let mut header = Header {
+            length: u16::try_from(length).map_err(|_| PacketEncodeError::PacketTooLarge(length))?,
+            payload_type: item.payload_type(),
+            checksum: 0,
+        };
+
+        header.write(dst);
+        header.checksum = self.compute(dst.get(..length).unwrap_or_default());
+        header.write(dst);
+
+        Ok(length)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use crate::{FramedRead, FramedWrite, next, tests::init_tracing};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Message<'a> {
+        Ping,
+        Echo(&'a [u8]),
+    }
+
+    impl PayloadEncode for Message<'_> {
+        type Error = EncodeBufferTooSmall;
+
+        fn payload_type(&self) -> u16 {
+            match self {
+                Message::Ping => 1,
+                Message::Echo(_) => 2,
+            }
+        }
+
+        fn encode_payload(&self, dst: &mut [u8]) -> Result<usize, Self::Error> {
+            let bytes: &[u8] = match self {
+                Message::Ping => &[],
+                Message::Echo(bytes) => bytes,
+            };
+
+            let available = dst.len();
+
+            dst.get_mut(..bytes.len())
+                .ok_or(EncodeBufferTooSmall {
+                    required: bytes.len(),
+                    available,
+                })?
+                .copy_from_slice(bytes);
+
+            Ok(bytes.len())
+        }
+    }
+
+    impl PayloadDecode for Message<'static> {
+        type Payload<'buf> = Message<'buf>;
+        type Error = ();
+
+        fn decode_payload<'buf>(
+            payload_type: u16,
+            src: &'buf [u8],
+        ) -> Result<Option<Message<'buf>>, Self::Error> {
+            match payload_type {
+                1 if src.is_empty() => Ok(Some(Message::Ping)),
+                1 => Err(()),
+                2 => Ok(Some(Message::Echo(src))),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    type Codec = PacketCodec<Message<'static>>;
+
+    fn encode(messages: &[Message<'_>]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        for message in messages {
+            let buf = &mut [0_u8; 32];
+            let size = Codec::new().encode(message, buf).expect("Must encode");
+
+            bytes.extend_from_slice(&buf[..size]);
+        }
+
+        bytes
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        init_tracing();
+
+        let write_buf = &mut [0_u8; 32];
+        let mut writer = FramedWrite::new(Codec::new(), Vec::new(), write_buf);
+
+        writer.send(Message::Ping).await.expect("Must send");
+        writer
+            .send(Message::Echo(b"Hello"))
+            .await
+            .expect("Must send");
+
+        let (_, bytes, _) = writer.into_parts();
+
+        let checksum = Crc32::iso_hdlc().checksum(&[0, 8, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(&bytes[..4], &[0, 8, 0, 1]);
+        assert_eq!(&bytes[4..8], &checksum.to_be_bytes());
+
+        let read_buf = &mut [0_u8; 32];
+        let mut reader = FramedRead::new(Codec::new(), bytes.as_slice(), read_buf);
+
+        let message = next!(reader).expect("Must be some").expect("Must decode");
+        assert_eq!(message, Message::Ping);
+
+        let message = next!(reader).expect("Must be some").expect("Must decode");
+        assert_eq!(message, Message::Echo(b"Hello"));
+
+        assert!(next!(reader).is_none());
+    }
+
+    #[tokio::test]
+    async fn bad_packets_are_skipped() {
+        init_tracing();
+
+        let mut bytes = encode(&[
+            Message::Echo(b"corrupted"),
+            Message::Ping,
+            Message::Echo(b"Hello"),
+        ]);
+
+        // Corrupts the payload of the first packet.
+        bytes[8] ^= 0xFF;
+
+        let mut unknown = encode(&[Message::Ping]);
+        unknown[3] = 42;
+        let checksum = Codec::new().compute(&unknown);
+        unknown[4..8].copy_from_slice(&checksum.to_be_bytes());
+
+        bytes.splice(17..17, unknown);
+
+        // The skipped packets stay buffered until the next packet is decoded.
+        let read_buf = &mut [0_u8; 64];
+        let mut reader = FramedRead::new(Codec::new(), bytes.as_slice(), read_buf);
+
+        assert!(matches!(
+            next!(reader),
+            Some(Err(crate::ReadError::Decode(PacketDecodeError::Checksum)))
+        ));
+
+        assert!(matches!(
+            next!(reader),
+            Some(Err(crate::ReadError::Decode(
+                PacketDecodeError::UnknownPayloadType(42)
+            )))
+        ));
+
+        let message = next!(reader).expect("Must be some").expect("Must decode");
+        assert_eq!(message, Message::Ping);
+
+        let message = next!(reader).expect("Must be some").expect("Must decode");
+        assert_eq!(message, Message::Echo(b"Hello"));
+    }
+
+    #[test]
+    fn encode_errors() {
+        let mut codec = Codec::new();
+
+        assert!(matches!(
+            codec.encode(Message::Ping, &mut [0_u8; 4]),
+            Err(PacketEncodeError::BufferTooSmall(EncodeBufferTooSmall {
+                required: 8,
+                available: 4
+            }))
+        ));
+
+        assert!(matches!(
+            codec.encode(Message::Echo(b"Hello"), &mut [0_u8; 10]),
+            Err(PacketEncodeError::Payload(_))
+        ));
+
+        let payload = [0_u8; u16::MAX as usize];
+
+        assert!(matches!(
+            codec.encode(Message::Echo(&payload), &mut [0_u8; u16::MAX as usize + 8]),
+            Err(PacketEncodeError::PacketTooLarge(65543))
+        ));
+    }
+}
@@ -0,0 +1,477 @@
+//! [`postcard-rpc`](https://docs.rs/postcard-rpc/latest/postcard_rpc/) transport on top of framez framers.
+//!
+//! Frames are encoded using the [`PostcardRpc`] codec, which uses the [`Cobs`] codec, so the link is wire compatible with `postcard-rpc`'s own serial transports,
+//! e.g. the `cobs-serial` host client.
+//!
+//! - [`FramedRead`] with the [`PostcardRpc`] codec implements [`WireRx`].
+//! - A shared reference to a [`FramedWireTx`] implements [`WireTx`]. The [`FramedWireTx`] locks its [`FramedWrite`] for every frame,
+//!   so the server and the tasks publishing topics can send on the same link.
+//!
+//! The [`WireSpawn`](postcard_rpc::server::WireSpawn) depends on the executor, e.g. `postcard-rpc`'s embassy implementation.
+//!
+//! # Example
+//!
+//! ```rust
+//! use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+//! use framez::{
+//!     FramedRead, FramedWrite,
+//!     mock::Noop,
+//!     postcard_rpc::{FramedWireTx, PostcardRpc},
+//! };
+//! use postcard_rpc::{
+//!     header::{VarHeader, VarKey, VarSeq},
+//!     server::{WireRx, WireTx},
+//! };
+//!
+//! # async fn run() {
+//! let read_buf = &mut [0_u8; 64];
+//! let write_buf = &mut [0_u8; 64];
+//!
+//! let mut rx = FramedRead::new(PostcardRpc::new(), b"\x01\x00".as_slice(), read_buf);
+//! let tx = FramedWireTx::<NoopRawMutex, _, _>::new(FramedWrite::new(PostcardRpc::new(), Noop, write_buf));
+//!
+//! let buf = &mut [0_u8; 64];
+//! let frame = rx.receive(buf).await.unwrap();
+//!
+//! assert!(frame.is_empty());
+//!
+//! let header = VarHeader {
+//!     key: VarKey::Key1(postcard_rpc::Key1::from_key8(postcard_rpc::Key::for_path::<u8>("ping"))),
+//!     seq_no: VarSeq::Seq1(0),
+//! };
+//!
+//! (&tx).send(header, &42_u8).await.unwrap();
+//! # }
+//! ```
+
+use core::{cell::Cell, convert::Infallible, fmt::Arguments};
+
+use embassy_sync::{
+    blocking_mutex::{Mutex as BlockingMutex, raw::RawMutex},
+    mutex::Mutex,
+};
+use embedded_io_async::{Read, Write};
+use postcard_rpc::{
+    Topic,
+    header::{VarHeader, VarKey, VarKeyKind, VarSeq},
+    postcard::{
+        self, Serializer,
+        ser_flavors::{Cobs as CobsFlavor, Flavor, Slice},
+    },
+    server::{WireRx, WireRxErrorKind, WireTx, WireTxErrorKind},
+    standard_icd::LoggingTopic,
+};
+use serde::Serialize;
+
+use crate::{
+    FramedRead, FramedWrite, ReadError, WriteError,
+    buffer::Buffer,
+    codec::cobs::Cobs,
+    decode::Decoder,
+    encode::{EncodeBufferTooSmall, Encoder},
+};
+
+/// The largest encoded [`VarHeader`]: a discriminant byte, a 4 byte sequence number and an 8 byte key.
+const MAX_HEADER_LEN: usize = 1 + 4 + 8;
+
+/// A `postcard-rpc` frame: a [`VarHeader`] followed by the postcard serialized `body`.
+#[derive(Debug, Clone, Copy)]
+pub struct Message<'a, T: ?Sized> {
+    /// The header of the frame.
+    pub header: &'a VarHeader,
+    /// The body of the frame.
+    pub body: &'a T,
+}
+
+/// A codec that decodes and encodes `postcard-rpc` frames using the [`Cobs`] codec.
+///
+/// Decodes frames into bytes, which start with the [`VarHeader`]. Encodes raw bytes and [`Message`]s.
+/// A [`Message`] is serialized and COBS encoded in one pass, without an intermediate buffer.
+///
+/// # Note
+///
+/// This codec tracks progress using an internal state of the underlying buffer, and it must not be used across multiple framing sessions.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PostcardRpc {
+    cobs: Cobs,
+}
+
+impl PostcardRpc {
+    /// Creates a new [`PostcardRpc`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self { cobs: Cobs::new() }
+    }
+}
+
+impl Decoder for PostcardRpc {
+    type Item<'buf> = &'buf mut [u8];
+    type Error = Infallible;
+
+    fn decode<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+        self.cobs.decode(src)
+    }
+}
+
+impl Encoder<&[u8]> for PostcardRpc {
+    type Error = EncodeBufferTooSmall;
+
+    fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
+        self.cobs.encode(item, dst)
+    }
+
+    fn encoded_len(&self, item: &&[u8]) -> Option<usize> {
+        Some(Cobs::encoded_len(item))
+    }
+}
+
+impl<T> Encoder<Message<'_, T>> for PostcardRpc
+where
+    T: Serialize + ?Sized,
+{
+    type Error = postcard::Error;
+
+    fn encode(&mut self, item: Message<'_, T>, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let header_buf = &mut [0_u8; MAX_HEADER_LEN];
+
+        let (header, _) = item
+            .header
+            .write_to_slice(header_buf)
+            .ok_or(postcard::Error::SerializeBufferFull)?;
+
+        let mut flavor = CobsFlavor::try_new(Slice::new(dst))?;
+
+        flavor.try_extend(header)?;
+
+        let mut serializer = Serializer { output: flavor };
+
+        item.body.serialize(&mut serializer)?;
+
+        serializer.output.finalize().map(|used| used.len())
+    }
+}
+
+impl<R, B> WireRx for FramedRead<'_, PostcardRpc, R, B>
+where
+    R: Read,
+    B: Buffer,
+{
+    type Error = WireRxErrorKind;
+
+    /// Reads the next frame and copies it into `buf`.
+    ///
+    /// If a frame does not fit into the read buffer, the buffered bytes are discarded, so the server can keep receiving.
+    async fn receive<'a>(&mut self, buf: &'a mut [u8]) -> Result<&'a mut [u8], Self::Error> {
+        match self.next_into(buf).await {
+            Some(Ok(len)) => buf.get_mut(..len).ok_or(WireRxErrorKind::Other),
+            Some(Err(ReadError::FrameTooLarge(_))) => Err(WireRxErrorKind::ReceivedMessageTooLarge),
+            Some(Err(ReadError::BufferTooSmall)) => {
+                self.discard_buffered();
+                *self.codec_mut() = PostcardRpc::new();
+
+                Err(WireRxErrorKind::ReceivedMessageTooLarge)
+            }
+            Some(Err(ReadError::BytesRemainingOnStream)) | None => {
+                Err(WireRxErrorKind::ConnectionClosed)
+            }
+            Some(Err(_)) => Err(WireRxErrorKind::Other),
+        }
+    }
+}
+
+/// A [`FramedWrite`] with the [`PostcardRpc`] codec, protected by a [`Mutex`].
+///
+/// A shared reference to it implements [`WireTx`], so it can be cloned into every sender of a `postcard-rpc` server,
+/// e.g. from a `&'static` reference of a `static_cell::StaticCell`.
+#[derive(Debug)]
+pub struct FramedWireTx<'buf, M, W, B = &'buf mut [u8]>
+where
+    M: RawMutex,
+{
+    framed: Mutex<M, FramedWrite<'buf, PostcardRpc, W, B>>,
+    /// The sequence number of the next log message.
+    log_seq: BlockingMutex<M, Cell<u16>>,
+}
+
+impl<'buf, M, W, B> FramedWireTx<'buf, M, W, B>
+where
+    M: RawMutex,
+{
+    /// Creates a new [`FramedWireTx`] from the given `framed` writer.
+    #[inline]
+    pub const fn new(framed: FramedWrite<'buf, PostcardRpc, W, B>) -> Self {
+        Self {
+            framed: Mutex::new(framed),
+            log_seq: BlockingMutex::new(Cell::new(0)),
+        }
+    }
+
+    /// Returns mutable reference to the writer.
+    #[inline]
+    pub fn framed_mut(&mut self) -> &mut FramedWrite<'buf, PostcardRpc, W, B> {
+        self.framed.get_mut()
+    }
+
+    /// Consumes the [`FramedWireTx`] and returns the writer.
+    #[inline]
+    pub fn into_inner(self) -> FramedWrite<'buf, PostcardRpc, W, B> {
+        self.framed.into_inner()
+    }
+
+    /// Returns the next sequence number of the [`LoggingTopic`].
+    fn next_log_seq(&self) -> u16 {
+        self.log_seq.lock(|seq| {
+            let current = seq.get();
+
+            seq.set(current.wrapping_add(1));
+
+            current
+        })
+    }
+
+    /// Returns the header of the next message of the [`LoggingTopic`].
+    fn log_header(&self, kind: VarKeyKind) -> VarHeader {
+        let key = match kind {
+            VarKeyKind::Key1 => VarKey::Key1(LoggingTopic::TOPIC_KEY1),
+            VarKeyKind::Key2 => VarKey::Key2(LoggingTopic::TOPIC_KEY2),
+            VarKeyKind::Key4 => VarKey::Key4(LoggingTopic::TOPIC_KEY4),
+            VarKeyKind::Key8 => VarKey::Key8(LoggingTopic::TOPIC_KEY),
+        };
+
+        VarHeader {
+            key,
+            seq_no: VarSeq::Seq2(self.next_log_seq()),
+        }
+    }
+
+    /// Locks the writer and sends a frame.
+    async fn send_item<I>(&self, item: I) -> Result<(), WireTxErrorKind>
+    where
+        PostcardRpc: Encoder<I>,
+        W: Write,
+        B: Buffer,
+    {
+        self.framed
+            .lock()
+            .await
+            .send(item)
+            .await
+            .map_err(tx_error_kind)
+    }
+}
+
+impl<M, W, B> WireTx for &FramedWireTx<'_, M, W, B>
+where
+    M: RawMutex,
+    W: Write,
+    B: Buffer,
+{
+    type Error = WireTxErrorKind;
+
+    async fn send<T: Serialize + ?Sized>(
+        &self,
+        hdr: VarHeader,
+        msg: &T,
+    ) -> Result<(), Self::Error> {
+        self.send_item(Message {
+            header: &hdr,
+            body: msg,
+        })
+        .await
+    }
+
+    async fn send_raw(&self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.send_item(buf).await
+    }
+
+    async fn send_log_str(&self, kkind: VarKeyKind, s: &str) -> Result<(), Self::Error> {
+        let header = self.log_header(kkind);
+
+        self.send_item(Message {
+            header: &header,
+            body: s,
+        })
+        .await
+    }
+
+    async fn send_log_fmt<'a>(
+        &self,
+        kkind: VarKeyKind,
+        a: Arguments<'a>,
+    ) -> Result<(), Self::Error> {
+        let header = self.log_header(kkind);
+
+        self.send_item(Message {
+            header: &header,
+            body: &a,
+        })
+        .await
+    }
+}
+
+/// Maps a [`WriteError`] to the [`WireTxErrorKind`] that tells the server whether to keep running.
+fn tx_error_kind<I, E>(err: WriteError<I, E>) -> WireTxErrorKind {
+    match err {
+        WriteError::IO(_) | WriteError::WriteZero => WireTxErrorKind::ConnectionClosed,
+        _ => WireTxErrorKind::Other,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+    use postcard_rpc::{Key, Key1};
+
+    use crate::{mock::Noop, tests::init_tracing};
+
+    use super::*;
+
+    fn header(seq: u8) -> VarHeader {
+        VarHeader {
+            key: VarKey::Key1(Key1::from_key8(Key::for_path::<u32>("ping"))),
+            seq_no: VarSeq::Seq1(seq),
+        }
+    }
+
+    /// Decodes the COBS frames in `bytes` into headers and bodies.
+    async fn frames(bytes: &[u8]) -> Vec<(VarHeader, Vec<u8>)> {
+        let buffer = &mut [0_u8; 64];
+        let mut framer = FramedRead::new(PostcardRpc::new(), bytes, buffer);
+
+        let mut frames = Vec::new();
+
+        while let Some(frame) = crate::next!(framer) {
+            let frame = frame.expect("Must decode");
+            let (header, body) = VarHeader::take_from_slice(frame).expect("Must have a header");
+
+            frames.push((header, body.to_vec()));
+        }
+
+        frames
+    }
+
+    #[tokio::test]
+    async fn send_messages_and_logs() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 64];
+        let tx = FramedWireTx::<NoopRawMutex, _, _>::new(FramedWrite::new(
+            PostcardRpc::new(),
+            Vec::<u8>::new(),
+            buffer,
+        ));
+
+        (&tx).send(header(7), &0x1234_u32).await.expect("Must send");
+        (&tx)
+            .send_log_str(VarKeyKind::Key1, "Hello")
+            .await
+            .expect("Must send");
+        (&tx)
+            .send_log_fmt(VarKeyKind::Key1, format_args!("Hello {}", 42))
+            .await
+            .expect("Must send");
+
+        let written = tx.into_inner().into_parts().1;
+        let frames = frames(&written).await;
+
+        assert_eq!(frames.len(), 3);
+
+        assert_eq!(frames[0].0, header(7));
+        assert_eq!(
+            postcard::from_bytes::<u32>(&frames[0].1).expect("Must deserialize"),
+            0x1234
+        );
+
+        assert_eq!(frames[1].0.key, VarKey::Key1(LoggingTopic::TOPIC_KEY1));
+        assert_eq!(frames[1].0.seq_no, VarSeq::Seq2(0));
+        assert_eq!(
+            postcard::from_bytes::<&str>(&frames[1].1).expect("Must deserialize"),
+            "Hello"
+        );
+
+        assert_eq!(frames[2].0.seq_no, VarSeq::Seq2(1));
+        assert_eq!(
+            postcard::from_bytes::<&str>(&frames[2].1).expect("Must deserialize"),
+            "Hello 42"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_too_large() {
+        init_tracing();
+
+        let buffer = &mut [0_u8; 8];
+        let tx = FramedWireTx::<NoopRawMutex, _, _>::new(FramedWrite::new(
+            PostcardRpc::new(),
+            Noop,
+            buffer,
+        ));
+
+        assert!(matches!(
+            (&tx).send(header(0), "Hello, world!").await,
+            Err(WireTxErrorKind::Other)
+        ));
+    }
+
+    #[tokio::test]
+    async fn receive_frames() {
+        init_tracing();
+
+        let mut bytes = std::vec![0_u8; 64];
+        let size = PostcardRpc::new()
+            .encode(
+                Message {
+                    header: &header(3),
+                    body: &7_u32,
+                },
+                &mut bytes,
+            )
+            .expect("Must encode");
+
+        bytes.truncate(size);
+        bytes.extend_from_slice(&[0xFF; 20]);
+        bytes.push(0);
+        bytes.extend_from_slice(b"\x01\x00");
+
+        let buffer = &mut [0_u8; 16];
+        let mut rx = FramedRead::new(PostcardRpc::new(), bytes.as_slice(), buffer);
+
+        let buf = &mut [0_u8; 16];
+
+        let frame = rx.receive(buf).await.expect("Must receive");
+        let (received, body) = VarHeader::take_from_slice(frame).expect("Must have a header");
+
+        assert_eq!(received, header(3));
+        assert_eq!(
+            postcard::from_bytes::<u32>(body).expect("Must deserialize"),
+            7
+        );
+
+        assert!(matches!(
+            rx.receive(buf).await,
+            Err(WireRxErrorKind::ReceivedMessageTooLarge)
+        ));
+
+        loop {
+            match rx.receive(buf).await {
+                Ok(frame) => {
+                    assert!(frame.is_empty());
+
+                    break;
+                }
+                Err(err) => assert!(matches!(err, WireRxErrorKind::ReceivedMessageTooLarge)),
+            }
+        }
+
+        assert!(matches!(
+            rx.receive(buf).await,
+            Err(WireRxErrorKind::ConnectionClosed)
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+//! Limits of the bytes read from a source.
+
+/// Limits the bytes a [`ReadState`](crate::state::ReadState) reads from its source,
+/// e.g. to bound what a misbehaving device connected to a multi-tenant gateway can consume.
+///
+/// - A budget bounds the total number of read bytes.
+/// - A rate bounds the number of bytes read within an interval of a clock.
+///
+/// A read that exceeds a limit fails with [`ReadError::QuotaExceeded`](crate::ReadError::QuotaExceeded) and its bytes are dropped.
+/// Once the budget is exhausted, every further read fails. The rate allows reading again in the next interval.
+///
+/// The limits are checked after each read, so a single read may exceed them by up to the size of the read buffer.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{FramedRead, codec::lines::StrLines, quota::Quota};
+///
+/// fn now() -> u64 {
+///     // E.g. `embassy_time::Instant::now().as_millis()`
+///     0
+/// }
+///
+/// let buffer = &mut [0_u8; 32];
+///
+/// // At most 1 KiB per second and 1 MiB in total.
+/// let quota = Quota::unlimited()
+///     .with_budget(1024 * 1024)
+///     .with_rate(1024, 1000, now);
+///
+/// let framer = FramedRead::new(StrLines::new(), b"Hello\n".as_slice(), buffer).with_quota(quota);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Quota {
+    /// The maximum total number of bytes.
+    budget: Option<u64>,
+    rate: Option<Rate>,
+    /// The total number of read bytes.
+    read: u64,
+    /// The tick count at the start of the current interval.
+    window_start: u64,
+    /// The number of bytes read within the current interval.
+    window_read: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rate {
+    max_bytes: u64,
+    interval: u64,
+    clock: fn() -> u64,
+}
+
+impl Quota {
+    /// Creates a new [`Quota`] without limits.
+    #[inline]
+    pub const fn unlimited() -> Self {
+        Self {
+            budget: None,
+            rate: None,
+            read: 0,
+            window_start: 0,
+            window_read: 0,
+        }
+    }
+
+    /// Limits the total number of read bytes to `bytes`.
+    #[inline]
+    pub const fn with_budget(mut self, bytes: u64) -> Self {
+        self.budget = Some(bytes);
+        self
+    }
+
+    /// Limits the number of bytes read within every `interval` ticks of the `clock` to `max_bytes`.
+    ///
+    /// The `clock` returns a monotonic tick count, see [`Clock`](crate::clock::Clock).
+    #[inline]
+    pub const fn with_rate(mut self, max_bytes: u64, interval: u64, clock: fn() -> u64) -> Self {
+        self.rate = Some(Rate {
+            max_bytes,
+            interval,
+            clock,
+        });
+        self
+    }
+
+    /// Returns the total number of read bytes.
+    #[inline]
+    pub const fn read(&self) -> u64 {
+        self.read
+    }
+
+    /// Returns the number of bytes left in the budget, or `None` if there is no budget.
+    #[inline]
+    pub const fn remaining(&self) -> Option<u64> {
+        match self.budget {
+            Some(budget) => Some(budget.saturating_sub(self.read)),
+            None => None,
+        }
+    }
+
+    /// Accounts `bytes` read bytes and returns `true` if they are within the limits.
+    ///
+    /// Called for every read of a [`ReadState`](crate::state::ReadState).
+    /// Call it when filling the buffer directly, e.g. before [`ReadState::commit`](crate::state::ReadState::commit).
+    pub fn try_consume(&mut self, bytes: usize) -> bool {
+        let bytes = bytes as u64;
+
+        self.read = self.read.saturating_add(bytes);
+
+        let within_budget = self.budget.is_none_or(|budget| self.read <= budget);
+
+        let within_rate = match self.rate {
+            None => true,
+            Some(rate) => {
+                let now = (rate.clock)();
+
+                if now.wrapping_sub(self.window_start) >= rate.interval {
+                    self.window_start = now;
+                    self.window_read = 0;
+                }
+
+                self.window_read = self.window_read.saturating_add(bytes);
+
+                self.window_read <= rate.max_bytes
+            }
+        };
+
+        within_budget && within_rate
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    use crate::{
+        FramedRead, ReadError, ReadPayloadError, codec::lines::StrLines, mock::ScriptedReader,
+        next, tests::init_tracing,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn budget_is_enforced() {
+        init_tracing();
+
+        let read = ScriptedReader::new(&[b"Hello\n", b"world!\n"]);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(StrLines::new(), read, buffer)
+            .with_quota(Quota::unlimited().with_budget(10));
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "Hello");
+
+        assert!(matches!(next!(framer), Some(Err(ReadError::QuotaExceeded))));
+
+        assert_eq!(framer.quota().remaining(), Some(0));
+    }
+
+    static NOW: AtomicU64 = AtomicU64::new(0);
+
+    fn now() -> u64 {
+        NOW.load(Ordering::Relaxed)
+    }
+
+    #[tokio::test]
+    async fn rate_is_enforced_per_interval() {
+        init_tracing();
+
+        let read = ScriptedReader::new(&[b"Hello\n", b"world!\n", b"Hey\n"]);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(StrLines::new(), read, buffer)
+            .with_quota(Quota::unlimited().with_rate(8, 1_000, now));
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "Hello");
+
+        // The frame is dropped.
+        assert!(matches!(next!(framer), Some(Err(ReadError::QuotaExceeded))));
+
+        NOW.store(1_000, Ordering::Relaxed);
+
+        let item = next!(framer).expect("Must be some").expect("Must decode");
+        assert_eq!(item, "Hey");
+    }
+
+    #[tokio::test]
+    async fn budget_is_enforced_on_payloads() {
+        init_tracing();
+
+        let read = ScriptedReader::new(&[b"Hello", b"world!"]);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(StrLines::new(), read, buffer)
+            .with_quota(Quota::unlimited().with_budget(8));
+
+        let mut remaining = 11;
+
+        let chunk = framer
+            .next_payload_chunk(&mut remaining)
+            .await
+            .expect("Must be some")
+            .expect("Must read");
+        assert_eq!(chunk, b"Hello");
+
+        assert!(matches!(
+            framer.next_payload_chunk(&mut remaining).await,
+            Some(Err(ReadPayloadError::QuotaExceeded))
+        ));
+    }
+}
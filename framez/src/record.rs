@@ -0,0 +1,25 @@
+//! Recording of metrics events.
+
+/// Records an event in the counters of a state, if the `metrics` feature is enabled.
+///
+/// A frame also calls the `on_frame` hook of the state.
+macro_rules! record {
+    ($state:expr, frame) => {
+        if let Some(on_frame) = $state.on_frame {
+            on_frame();
+        }
+
+        #[cfg(feature = "metrics")]
+        $state.counters.add_frame($state.name);
+    };
+    ($state:expr, bytes, $bytes:expr) => {
+        #[cfg(feature = "metrics")]
+        $state.counters.add_bytes($state.name, $bytes);
+    };
+    ($state:expr, error) => {
+        #[cfg(feature = "metrics")]
+        $state.counters.add_error($state.name);
+    };
+}
+
+pub(crate) use record;
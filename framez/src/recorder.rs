@@ -0,0 +1,661 @@
+//! Black-box recording of frames to flash.
+//!
+//! A [`Recorder`] appends the raw bytes of the frames read with [`next_recorded`](crate::functions::next_recorded)
+//! and sent with [`send_recorded`](crate::functions::send_recorded) to a region of NOR flash,
+//! so the last frames before a field failure can be read out and analyzed after the device was reset.
+//!
+//! # Format
+//!
+//! The region is used as a ring log of records. Every record is a [`Header`] of [`HEADER_LEN`] bytes followed by the stored bytes of the frame,
+//! padded with `0xFF` to the [`WRITE_SIZE`](NorFlash::WRITE_SIZE) of the flash.
+//!
+//! Records never span erase sectors. A record that does not fit into the rest of a sector starts the next one, which is erased first.
+//! After the last sector, the log wraps around and erases the first one, dropping its oldest records.
+//! Erased flash reads as `0xFF`, so the end of the records in a sector is the first [`Header`] that does not decode.
+//! The newest records are in the sector whose first record has the highest [`Header::sequence`].
+//!
+//! The header is laid out as follows, with all numbers in little endian:
+//!
+//! | Offset | Size | Field                                                       |
+//! |--------|------|-------------------------------------------------------------|
+//! | 0      | 1    | Magic byte `0xA5`                                           |
+//! | 1      | 1    | Flags: bit 0 is the [`Direction`], bit 1 marks a truncated frame |
+//! | 2      | 2    | [`Header::len`]                                             |
+//! | 4      | 4    | [`Header::sequence`]                                        |
+//! | 8      | 8    | [`Header::timestamp`]                                       |
+
+use embedded_storage_async::nor_flash::NorFlash;
+
+use crate::clock::Clock;
+
+/// The length of a [`Header`] in bytes.
+pub const HEADER_LEN: usize = 16;
+
+const MAGIC: u8 = 0xA5;
+
+const WRITE_FLAG: u8 = 0b01;
+
+const TRUNCATED_FLAG: u8 = 0b10;
+
+/// The direction of a recorded frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    /// A frame read from the source.
+    Read,
+    /// A frame written to the sink.
+    Write,
+}
+
+/// The header of a record in the log of a [`Recorder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Header {
+    /// The direction of the frame.
+    pub direction: Direction,
+    /// The frame was longer than the stored bytes.
+    pub truncated: bool,
+    /// The number of stored bytes of the frame.
+    pub len: u16,
+    /// The number of the record, incremented with every record.
+    pub sequence: u32,
+    /// The tick count of the [`Clock`] of the [`Recorder`] when the frame was recorded.
+    pub timestamp: u64,
+}
+
+impl Header {
+    /// Encodes the header into its bytes.
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut flags = 0;
+
+        if self.direction == Direction::Write {
+            flags |= WRITE_FLAG;
+        }
+
+        if self.truncated {
+            flags |= TRUNCATED_FLAG;
+        }
+
+        let [l0, l1] = self.len.to_le_bytes();
+        let [s0, s1, s2, s3] = self.sequence.to_le_bytes();
+        let [t0, t1, t2, t3, t4, t5, t6, t7] = self.timestamp.to_le_bytes();
+
+        [
+            MAGIC, flags, l0, l1, s0, s1, s2, s3, t0, t1, t2, t3, t4, t5, t6, t7,
+        ]
+    }
+
+    /// Decodes a header from its bytes.
+    ///
+    /// Returns `None` if the bytes are not a header, e.g. erased flash.
+    pub fn from_bytes(bytes: &[u8; HEADER_LEN]) -> Option<Self> {
+        let [
+            MAGIC,
+            flags,
+            l0,
+            l1,
+            s0,
+            s1,
+            s2,
+            s3,
+            t0,
+            t1,
+            t2,
+            t3,
+            t4,
+            t5,
+            t6,
+            t7,
+        ] = *bytes
+        else {
+            return None;
+        };
+
+        if flags & !(WRITE_FLAG | TRUNCATED_FLAG) != 0 {
+            return None;
+        }
+
+        let direction = if flags & WRITE_FLAG != 0 {
+            Direction::Write
+        } else {
+            Direction::Read
+        };
+
+        Some(Self {
+            direction,
+            truncated: flags & TRUNCATED_FLAG != 0,
+            len: u16::from_le_bytes([l0, l1]),
+            sequence: u32::from_le_bytes([s0, s1, s2, s3]),
+            timestamp: u64::from_le_bytes([t0, t1, t2, t3, t4, t5, t6, t7]),
+        })
+    }
+}
+
+/// Appends frames to a ring log in a region of NOR flash. See the [module](self) docs for the format.
+///
+/// The region is the whole `storage`, e.g. a partition of the flash of the device.
+/// Every record is assembled in a scratch buffer of `N` bytes before it is written, so frames longer than `N` minus the [`HEADER_LEN`]
+/// or longer than an erase sector are truncated.
+///
+/// The [`READ_SIZE`](embedded_storage_async::nor_flash::ReadNorFlash::READ_SIZE) of the flash must divide the [`HEADER_LEN`] and its [`WRITE_SIZE`](NorFlash::WRITE_SIZE).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use framez::{Framed, codec::lines::StrLines, recorder::Recorder};
+///
+/// // Continues the log that survived the last reset.
+/// let mut recorder = Recorder::<_, _, 256>::resume(flash, || embassy_time::Instant::now().as_ticks()).await?;
+///
+/// let mut framed = Framed::new(StrLines::new(), uart, r_buf, w_buf);
+///
+/// while let Some(line) = framed.next_recorded(&mut recorder, |line| heapless::String::<64>::try_from(line)).await {
+///     framed.send_recorded(&mut recorder, "OK").await?;
+/// }
+/// ```
+pub struct Recorder<S, K, const N: usize = 256> {
+    /// The flash the log is written to.
+    storage: S,
+    /// The clock that timestamps the records.
+    clock: K,
+    /// The offset of the next record in the storage.
+    offset: u32,
+    /// The sequence number of the next record.
+    sequence: u32,
+    /// The number of records that could not be written.
+    failures: u32,
+    /// Assembles a record before it is written.
+    scratch: [u8; N],
+}
+
+impl<S, K, const N: usize> Recorder<S, K, N> {
+    /// Creates a new [`Recorder`] that starts a log at the start of the `storage`, timestamping the records with the `clock`.
+    ///
+    /// The storage is erased sector by sector as the log grows. Records left over from a previous log are not erased in advance,
+    /// so the storage should be erased, e.g. after it was read out. Use [`Recorder::resume`] to continue a previous log instead.
+    #[inline]
+    pub const fn new(storage: S, clock: K) -> Self {
+        Self {
+            storage,
+            clock,
+            offset: 0,
+            sequence: 0,
+            failures: 0,
+            scratch: [0; N],
+        }
+    }
+
+    /// Returns the offset of the next record in the storage.
+    #[inline]
+    pub const fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Returns the sequence number of the next record.
+    #[inline]
+    pub const fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Returns the number of records that could not be written to the storage.
+    #[inline]
+    pub const fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Returns reference to the storage.
+    #[inline]
+    pub const fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// Returns mutable reference to the storage.
+    ///
+    /// Writing to the region of the log corrupts it.
+    #[inline]
+    pub const fn storage_mut(&mut self) -> &mut S {
+        &mut self.storage
+    }
+
+    /// Returns reference to the clock.
+    #[inline]
+    pub const fn clock(&self) -> &K {
+        &self.clock
+    }
+
+    /// Consumes the [`Recorder`] and returns the storage.
+    #[inline]
+    pub fn into_storage(self) -> S {
+        self.storage
+    }
+}
+
+impl<S, K, const N: usize> core::fmt::Debug for Recorder<S, K, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Recorder")
+            .field("offset", &self.offset)
+            .field("sequence", &self.sequence)
+            .field("failures", &self.failures)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<S, K, const N: usize> defmt::Format for Recorder<S, K, N> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "Recorder {{ offset: {}, sequence: {}, failures: {}, .. }}",
+            self.offset,
+            self.sequence,
+            self.failures
+        )
+    }
+}
+
+impl<S, K, const N: usize> Recorder<S, K, N>
+where
+    S: NorFlash,
+    K: Clock,
+{
+    /// Creates a new [`Recorder`] that continues the log in the `storage` after its newest record.
+    ///
+    /// Starts a new log at the start of the storage if it contains no records.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the storage if reading it failed.
+    pub async fn resume(storage: S, clock: K) -> Result<Self, S::Error> {
+        let mut recorder = Self::new(storage, clock);
+
+        let sector = S::ERASE_SIZE as u32;
+        let capacity = recorder.capacity();
+
+        let mut newest: Option<(u32, u32)> = None;
+        let mut start = 0_u32;
+
+        while start < capacity {
+            if let Some(header) = recorder.read_header(start).await? {
+                let is_newer = newest.is_none_or(|(sequence, _)| {
+                    // Wrapping, so the log continues past `u32::MAX` records.
+                    (header.sequence.wrapping_sub(sequence) as i32) > 0
+                });
+
+                if is_newer {
+                    newest = Some((header.sequence, start));
+                }
+            }
+
+            start = start.saturating_add(sector);
+        }
+
+        let Some((_, start)) = newest else {
+            return Ok(recorder);
+        };
+
+        let end = start.saturating_add(sector);
+        let mut offset = start;
+
+        while offset.saturating_add(HEADER_LEN as u32) <= end {
+            let Some(header) = recorder.read_header(offset).await? else {
+                break;
+            };
+
+            recorder.sequence = header.sequence.wrapping_add(1);
+
+            offset = offset.saturating_add(record_len::<S>(header.len as usize) as u32);
+        }
+
+        recorder.offset = offset;
+
+        Ok(recorder)
+    }
+
+    /// Appends the `frame` to the log, timestamped with the [`Clock`] of the recorder.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the storage if erasing or writing it failed. The failure is counted in [`Recorder::failures`].
+    pub async fn record(&mut self, direction: Direction, frame: &[u8]) -> Result<(), S::Error> {
+        let result = self.append(direction, frame).await;
+
+        if result.is_err() {
+            self.failures = self.failures.saturating_add(1);
+        }
+
+        result
+    }
+
+    async fn append(&mut self, direction: Direction, frame: &[u8]) -> Result<(), S::Error> {
+        let sector = S::ERASE_SIZE as u32;
+        let capacity = self.capacity();
+
+        // The largest record that fits into the scratch buffer and a sector.
+        let max_record_len = N
+            .min(S::ERASE_SIZE)
+            .checked_div(S::WRITE_SIZE)
+            .unwrap_or_default()
+            .saturating_mul(S::WRITE_SIZE);
+        let max_len = max_record_len
+            .saturating_sub(HEADER_LEN)
+            .min(u16::MAX as usize);
+
+        if capacity == 0 || max_len == 0 {
+            return Ok(());
+        }
+
+        let len = frame.len().min(max_len);
+        let record_len = record_len::<S>(len);
+
+        let in_sector = self.offset.checked_rem(sector).unwrap_or_default();
+
+        if in_sector.saturating_add(record_len as u32) > sector {
+            self.offset = self.offset.saturating_sub(in_sector).saturating_add(sector);
+        }
+
+        if self.offset >= capacity {
+            self.offset = 0;
+        }
+
+        if self.offset.checked_rem(sector) == Some(0) {
+            self.storage
+                .erase(self.offset, self.offset.saturating_add(sector))
+                .await?;
+        }
+
+        let header = Header {
+            direction,
+            truncated: len < frame.len(),
+            len: len as u16,
+            sequence: self.sequence,
+            timestamp: self.clock.now(),
+        };
+
+        let Some(record) = self.scratch.get_mut(..record_len) else {
+            return Ok(());
+        };
+
+        let Some((head, rest)) = record.split_at_mut_checked(HEADER_LEN) else {
+            return Ok(());
+        };
+
+        let Some((payload, padding)) = rest.split_at_mut_checked(len) else {
+            return Ok(());
+        };
+
+        head.copy_from_slice(&header.to_bytes());
+        payload.copy_from_slice(frame.get(..len).unwrap_or_default());
+        padding.fill(0xFF);
+
+        self.storage.write(self.offset, record).await?;
+
+        self.offset = self.offset.saturating_add(record_len as u32);
+        self.sequence = self.sequence.wrapping_add(1);
+
+        Ok(())
+    }
+
+    /// Returns the size of the region in whole sectors.
+    fn capacity(&self) -> u32 {
+        let sector = S::ERASE_SIZE;
+
+        self.storage
+            .capacity()
+            .checked_div(sector)
+            .unwrap_or_default()
+            .saturating_mul(sector) as u32
+    }
+
+    /// Reads the [`Header`] at `offset`.
+    async fn read_header(&mut self, offset: u32) -> Result<Option<Header>, S::Error> {
+        let mut bytes = [0; HEADER_LEN];
+
+        self.storage.read(offset, &mut bytes).await?;
+
+        Ok(Header::from_bytes(&bytes))
+    }
+}
+
+/// Returns the length of a record of a frame with `len` stored bytes, padded to the write size of the flash.
+fn record_len<S: NorFlash>(len: usize) -> usize {
+    let len = HEADER_LEN.saturating_add(len);
+
+    len.checked_next_multiple_of(S::WRITE_SIZE).unwrap_or(len)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{string::String, vec::Vec};
+
+    use embedded_storage_async::nor_flash::{ErrorType, NorFlashErrorKind, ReadNorFlash};
+
+    use crate::{
+        FramedRead, FramedWrite, codec::lines::Lines, mock::ScriptedReader, tests::init_tracing,
+    };
+
+    use super::*;
+
+    /// NOR flash in memory with sectors of 64 bytes. Writes can only clear bits.
+    struct MemFlash {
+        bytes: Vec<u8>,
+    }
+
+    impl MemFlash {
+        fn new(sectors: usize) -> Self {
+            Self {
+                bytes: std::vec![0xFF; sectors * 64],
+            }
+        }
+
+        /// Returns the header and the stored bytes of the record at `offset`.
+        fn record(&self, offset: usize) -> Option<(Header, &[u8])> {
+            let header = self.bytes.get(offset..offset + HEADER_LEN)?;
+            let header = Header::from_bytes(header.try_into().ok()?)?;
+
+            let start = offset + HEADER_LEN;
+            let frame = self.bytes.get(start..start + header.len as usize)?;
+
+            Some((header, frame))
+        }
+
+        /// Returns the records of all sectors, ordered by their sequence number.
+        fn records(&self) -> Vec<(Header, &[u8])> {
+            let mut records = Vec::new();
+
+            for sector in (0..self.bytes.len()).step_by(64) {
+                let mut offset = sector;
+
+                while offset + HEADER_LEN <= sector + 64 {
+                    let Some((header, frame)) = self.record(offset) else {
+                        break;
+                    };
+
+                    records.push((header, frame));
+
+                    offset += record_len::<Self>(frame.len());
+                }
+            }
+
+            records.sort_by_key(|(header, _)| header.sequence);
+
+            records
+        }
+    }
+
+    impl ErrorType for MemFlash {
+        type Error = NorFlashErrorKind;
+    }
+
+    impl ReadNorFlash for MemFlash {
+        const READ_SIZE: usize = 1;
+
+        async fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+            let offset = offset as usize;
+
+            let src = self
+                .bytes
+                .get(offset..offset + bytes.len())
+                .ok_or(NorFlashErrorKind::OutOfBounds)?;
+
+            bytes.copy_from_slice(src);
+
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            self.bytes.len()
+        }
+    }
+
+    impl NorFlash for MemFlash {
+        const WRITE_SIZE: usize = 4;
+        const ERASE_SIZE: usize = 64;
+
+        async fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+            self.bytes
+                .get_mut(from as usize..to as usize)
+                .ok_or(NorFlashErrorKind::OutOfBounds)?
+                .fill(0xFF);
+
+            Ok(())
+        }
+
+        async fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+            let offset = offset as usize;
+
+            if offset % Self::WRITE_SIZE != 0 || bytes.len() % Self::WRITE_SIZE != 0 {
+                return Err(NorFlashErrorKind::NotAligned);
+            }
+
+            let dst = self
+                .bytes
+                .get_mut(offset..offset + bytes.len())
+                .ok_or(NorFlashErrorKind::OutOfBounds)?;
+
+            for (dst, src) in dst.iter_mut().zip(bytes) {
+                *dst &= *src;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn records_frames_in_both_directions() {
+        init_tracing();
+
+        let mut recorder = Recorder::<_, _, 64>::new(MemFlash::new(4), || 42_u64);
+
+        let read = ScriptedReader::new(&[b"Hel", b"lo\r\nHey\r\n"]);
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedRead::new(Lines::new(), read, buffer);
+
+        let mut items = Vec::new();
+
+        while let Some(item) = framer
+            .next_recorded(&mut recorder, |item| String::from_utf8(item.to_vec()))
+            .await
+        {
+            items.push(item.expect("Must decode").expect("Must be utf-8"));
+        }
+
+        assert_eq!(items, ["Hello", "Hey"]);
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Lines::new(), Vec::new(), buffer);
+
+        framer
+            .send_recorded(&mut recorder, b"OK".as_slice())
+            .await
+            .expect("Must send");
+
+        // Coalesced frames are recorded once they are encoded.
+        framer.set_flush_threshold(16);
+
+        framer
+            .send_recorded(&mut recorder, b"A".as_slice())
+            .await
+            .expect("Must send");
+
+        framer
+            .send_recorded(&mut recorder, b"B".as_slice())
+            .await
+            .expect("Must send");
+
+        let records = recorder
+            .storage()
+            .records()
+            .into_iter()
+            .map(|(header, frame)| {
+                assert_eq!(header.timestamp, 42);
+                assert!(!header.truncated);
+
+                (header.direction, frame.to_vec())
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            records,
+            [
+                (Direction::Read, b"Hello\r\n".to_vec()),
+                (Direction::Read, b"Hey\r\n".to_vec()),
+                (Direction::Write, b"OK\r\n".to_vec()),
+                (Direction::Write, b"A\r\n".to_vec()),
+                (Direction::Write, b"B\r\n".to_vec()),
+            ]
+        );
+
+        assert_eq!(recorder.sequence(), 5);
+        assert_eq!(recorder.failures(), 0);
+    }
+
+    #[tokio::test]
+    async fn wraps_around_and_resumes() {
+        init_tracing();
+
+        let mut recorder = Recorder::<_, _, 64>::new(MemFlash::new(3), || 0_u64);
+
+        // One record per sector.
+        for byte in 0..5 {
+            recorder
+                .record(Direction::Read, &[byte; 20])
+                .await
+                .expect("Must record");
+        }
+
+        // The oldest sector was erased and reused.
+        let flash = recorder.storage();
+
+        let sequences = [0, 64, 128].map(|offset| {
+            flash
+                .record(offset)
+                .map(|(header, _)| header.sequence)
+                .expect("Must be a record")
+        });
+
+        assert_eq!(sequences, [3, 4, 2]);
+
+        let offset = recorder.offset();
+        let flash = recorder.into_storage();
+
+        let mut recorder = Recorder::<_, _, 64>::resume(flash, || 0_u64)
+            .await
+            .expect("Must resume");
+
+        assert_eq!(recorder.offset(), offset);
+        assert_eq!(recorder.sequence(), 5);
+
+        // Frames longer than the scratch buffer are truncated.
+        recorder
+            .record(Direction::Write, &[7; 100])
+            .await
+            .expect("Must record");
+
+        let (header, frame) = recorder.storage().record(128).expect("Must be a record");
+
+        assert_eq!(header.sequence, 5);
+        assert!(header.truncated);
+        assert_eq!(frame, [7; 48]);
+    }
+}
@@ -0,0 +1,284 @@
+//! Dispatching of decoded frames to handlers.
+//!
+//! A [`Router`] extracts a key from every frame, e.g. the frame type or a channel id,
+//! and passes the frame to the handler registered for that key. See [`route`](crate::functions::route).
+
+#![allow(async_fn_in_trait)]
+
+use core::marker::PhantomData;
+
+/// Handles a routed frame.
+///
+/// Implemented for async closures, e.g. `async |item: &[u8]| -> Result<(), Error> { ... }`.
+pub trait Handler<Item> {
+    /// The type of error that the handler returns.
+    type Error;
+
+    /// Handles the `item`.
+    async fn handle(&mut self, item: Item) -> Result<(), Self::Error>;
+}
+
+impl<F, Item, E> Handler<Item> for F
+where
+    F: AsyncFnMut(Item) -> Result<(), E>,
+{
+    type Error = E;
+
+    async fn handle(&mut self, item: Item) -> Result<(), Self::Error> {
+        self(item).await
+    }
+}
+
+/// A set of handlers registered by key.
+///
+/// Built using [`Router::route`].
+pub trait Routes<K, Item> {
+    /// The type of error that the handlers return.
+    type Error;
+
+    /// Passes the `item` to the handler registered for `key`.
+    ///
+    /// Returns the `item` back if no handler is registered for `key`.
+    async fn dispatch(&mut self, key: &K, item: Item) -> Result<Option<Item>, Self::Error>;
+}
+
+/// A handler registered for `key`, followed by the previously registered routes.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Route<K, H, N> {
+    key: K,
+    handler: H,
+    next: N,
+}
+
+impl<K, H, N, Item> Routes<K, Item> for Route<K, H, N>
+where
+    K: PartialEq,
+    H: Handler<Item>,
+    N: Routes<K, Item, Error = H::Error>,
+{
+    type Error = H::Error;
+
+    async fn dispatch(&mut self, key: &K, item: Item) -> Result<Option<Item>, Self::Error> {
+        if self.key == *key {
+            return self.handler.handle(item).await.map(|_| None);
+        }
+
+        self.next.dispatch(key, item).await
+    }
+}
+
+/// No routes, or no fallback handler.
+///
+/// As [`Routes`], returns every item back. As [`Handler`], drops every item.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Unrouted<E> {
+    _error: PhantomData<fn() -> E>,
+}
+
+impl<E> Unrouted<E> {
+    /// Creates a new [`Unrouted`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            _error: PhantomData,
+        }
+    }
+}
+
+impl<E> Default for Unrouted<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, Item, E> Routes<K, Item> for Unrouted<E> {
+    type Error = E;
+
+    async fn dispatch(&mut self, _key: &K, item: Item) -> Result<Option<Item>, Self::Error> {
+        Ok(Some(item))
+    }
+}
+
+impl<Item, E> Handler<Item> for Unrouted<E> {
+    type Error = E;
+
+    async fn handle(&mut self, _item: Item) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Dispatches decoded frames to handlers based on a key extracted from each frame.
+///
+/// All handlers return the same error type `E`.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{FramedRead, codec::lines::StrLines, router::Router};
+///
+/// # async fn run() {
+/// let mut pings = 0;
+/// let mut others = 0;
+///
+/// let mut router = Router::new(|item: &&str| item.split(' ').next().unwrap_or_default().len())
+///     .route(4, async |_: &str| -> Result<(), ()> {
+///         pings += 1;
+///         Ok(())
+///     })
+///     .fallback(async |_: &str| {
+///         others += 1;
+///         Ok(())
+///     });
+///
+/// let buffer = &mut [0_u8; 32];
+/// let mut framed = FramedRead::new(StrLines::new(), b"ping 1\nhello\nping 2\n".as_slice(), buffer);
+///
+/// framed.route(&mut router).await.unwrap();
+///
+/// assert_eq!(pings, 2);
+/// assert_eq!(others, 1);
+/// # }
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Router<F, R, D> {
+    key: F,
+    routes: R,
+    fallback: D,
+}
+
+impl<F, E> Router<F, Unrouted<E>, Unrouted<E>> {
+    /// Creates a new [`Router`] that extracts the key of every frame using `key`.
+    ///
+    /// Frames are dropped until routes are registered.
+    #[inline]
+    pub const fn new(key: F) -> Self {
+        Self {
+            key,
+            routes: Unrouted::new(),
+            fallback: Unrouted::new(),
+        }
+    }
+}
+
+impl<F, R, D> Router<F, R, D> {
+    /// Registers `handler` for frames with the given `key`.
+    ///
+    /// If multiple handlers are registered for the same key, the last one handles the frames.
+    #[inline]
+    pub fn route<K, H>(self, key: K, handler: H) -> Router<F, Route<K, H, R>, D> {
+        Router {
+            key: self.key,
+            routes: Route {
+                key,
+                handler,
+                next: self.routes,
+            },
+            fallback: self.fallback,
+        }
+    }
+
+    /// Sets the `handler` for frames with keys that have no registered handler.
+    ///
+    /// Such frames are dropped by default.
+    #[inline]
+    pub fn fallback<H>(self, handler: H) -> Router<F, R, H> {
+        Router {
+            key: self.key,
+            routes: self.routes,
+            fallback: handler,
+        }
+    }
+
+    /// Dispatches the `item` to the handler registered for its key, or to the fallback handler.
+    pub async fn dispatch<K, Item, E>(&mut self, item: Item) -> Result<(), E>
+    where
+        F: FnMut(&Item) -> K,
+        R: Routes<K, Item, Error = E>,
+        D: Handler<Item, Error = E>,
+    {
+        let key = (self.key)(&item);
+
+        match self.routes.dispatch(&key, item).await? {
+            None => Ok(()),
+            Some(item) => self.fallback.handle(item).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use crate::{FramedRead, RouteError, codec::lines::Lines, tests::init_tracing};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn frames_are_dispatched_by_key() {
+        init_tracing();
+
+        let mut a = Vec::<Vec<u8>>::new();
+        let mut b = Vec::<Vec<u8>>::new();
+        let mut others = Vec::<Vec<u8>>::new();
+
+        let mut router = Router::new(|item: &&[u8]| item.first().copied())
+            .route(
+                Some(b'a'),
+                async |item: &[u8]| -> Result<(), &'static str> {
+                    a.push(item.to_vec());
+                    Ok(())
+                },
+            )
+            .route(Some(b'b'), async |item: &[u8]| {
+                if item == b"bad" {
+                    return Err("bad frame");
+                }
+
+                b.push(item.to_vec());
+                Ok(())
+            })
+            .fallback(async |item: &[u8]| {
+                others.push(item.to_vec());
+                Ok(())
+            });
+
+        let read = b"a1\nb1\n\nc1\nbad\na2\nb2\n".as_slice();
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Lines::new(), read, buffer);
+
+        assert!(matches!(
+            framer.route(&mut router).await,
+            Err(RouteError::Handle("bad frame"))
+        ));
+
+        framer.route(&mut router).await.expect("Must route");
+
+        assert_eq!(a, [b"a1".as_slice(), b"a2"]);
+        assert_eq!(b, [b"b1".as_slice(), b"b2"]);
+        assert_eq!(others, [b"".as_slice(), b"c1"]);
+    }
+
+    #[tokio::test]
+    async fn unrouted_frames_are_dropped() {
+        init_tracing();
+
+        let mut handled = 0;
+
+        let mut router =
+            Router::new(|item: &&[u8]| item.len()).route(2, async |_: &[u8]| -> Result<(), ()> {
+                handled += 1;
+                Ok(())
+            });
+
+        let read = b"a\nbb\nccc\ndd\n".as_slice();
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedRead::new(Lines::new(), read, buffer);
+
+        framer.route(&mut router).await.expect("Must route");
+
+        assert_eq!(handled, 2);
+    }
+}
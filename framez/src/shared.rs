@@ -0,0 +1,181 @@
+//! Sharing of a framer between tasks.
+
+use embassy_sync::{
+    blocking_mutex::raw::RawMutex,
+    mutex::{Mutex, MutexGuard},
+};
+use embedded_io_async::{Read, Write};
+
+use crate::{
+    FramedRead, FramedWrite, ReadError, WriteError, buffer::Buffer, decode::Decoder,
+    encode::Encoder,
+};
+
+/// A read half and a write half of a framer, each protected by its own [`Mutex`].
+///
+/// Lets one task receive frames while another task sends frames on the same transport, e.g. the split halves of an embassy UART.
+/// Both halves have their own buffer, so a task waiting for a frame never blocks a task sending a frame.
+///
+/// All methods take `&self`, so the [`SharedFramed`] can be shared between tasks using a `&'static` reference,
+/// e.g. from a `static_cell::StaticCell`.
+///
+/// # Example
+///
+/// ```rust
+/// use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+/// use framez::{FramedRead, FramedWrite, codec::lines::StrLines, shared::SharedFramed};
+///
+/// # async fn run() {
+/// let read_buf = &mut [0_u8; 32];
+/// let write_buf = &mut [0_u8; 32];
+///
+/// let framed = SharedFramed::<NoopRawMutex, _, _, _, _>::new(
+///     FramedRead::new(StrLines::new(), b"Hello\n".as_slice(), read_buf),
+///     FramedWrite::new(StrLines::new(), framez::mock::Noop, write_buf),
+/// );
+///
+/// // Usually called from different tasks.
+/// framed.send("Hello").await.unwrap();
+///
+/// let len = framed.next(|line| line.len()).await;
+///
+/// assert!(matches!(len, Some(Ok(5))));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SharedFramed<'buf, M, D, R, E, W, RB = &'buf mut [u8], WB = &'buf mut [u8]>
+where
+    M: RawMutex,
+{
+    read: Mutex<M, FramedRead<'buf, D, R, RB>>,
+    write: Mutex<M, FramedWrite<'buf, E, W, WB>>,
+}
+
+impl<'buf, M, D, R, E, W, RB, WB> SharedFramed<'buf, M, D, R, E, W, RB, WB>
+where
+    M: RawMutex,
+{
+    /// Creates a new [`SharedFramed`] from the given `read` and `write` halves.
+    #[inline]
+    pub const fn new(read: FramedRead<'buf, D, R, RB>, write: FramedWrite<'buf, E, W, WB>) -> Self {
+        Self {
+            read: Mutex::new(read),
+            write: Mutex::new(write),
+        }
+    }
+
+    /// Locks the read half.
+    ///
+    /// Waits until no other task holds the read half. Sending is not blocked while the lock is held.
+    #[inline]
+    pub async fn lock_read(&self) -> MutexGuard<'_, M, FramedRead<'buf, D, R, RB>> {
+        self.read.lock().await
+    }
+
+    /// Locks the write half.
+    ///
+    /// Waits until no other task holds the write half. Receiving is not blocked while the lock is held.
+    #[inline]
+    pub async fn lock_write(&self) -> MutexGuard<'_, M, FramedWrite<'buf, E, W, WB>> {
+        self.write.lock().await
+    }
+
+    /// Returns mutable reference to the read half.
+    #[inline]
+    pub fn read_mut(&mut self) -> &mut FramedRead<'buf, D, R, RB> {
+        self.read.get_mut()
+    }
+
+    /// Returns mutable reference to the write half.
+    #[inline]
+    pub fn write_mut(&mut self) -> &mut FramedWrite<'buf, E, W, WB> {
+        self.write.get_mut()
+    }
+
+    /// Consumes the [`SharedFramed`] and returns the read and write halves.
+    #[inline]
+    pub fn into_parts(self) -> (FramedRead<'buf, D, R, RB>, FramedWrite<'buf, E, W, WB>) {
+        (self.read.into_inner(), self.write.into_inner())
+    }
+
+    /// Locks the read half and reads the next frame.
+    ///
+    /// See [`Framed::next`](crate::Framed::next).
+    pub async fn next<U>(
+        &self,
+        map: fn(D::Item<'_>) -> U,
+    ) -> Option<Result<U, ReadError<R::Error, D::Error>>>
+    where
+        U: 'static,
+        D: Decoder,
+        R: Read,
+        RB: Buffer,
+    {
+        self.read.lock().await.next(map).await
+    }
+
+    /// Locks the write half and sends a frame.
+    ///
+    /// See [`Framed::send`](crate::Framed::send).
+    pub async fn send<I>(&self, item: I) -> Result<(), WriteError<W::Error, E::Error>>
+    where
+        E: Encoder<I>,
+        W: Write,
+        WB: Buffer,
+    {
+        self.write.lock().await.send(item).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::string::String;
+
+    use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+    use embedded_io_adapters::tokio_1::FromTokio;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use crate::{codec::lines::StrLines, tests::init_tracing};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn receive_does_not_block_send() {
+        init_tracing();
+
+        let (local, mut peer) = tokio::io::duplex(1024);
+        let (read, write) = tokio::io::split(local);
+
+        let read_buf = &mut [0_u8; 32];
+        let write_buf = &mut [0_u8; 32];
+
+        let framed = SharedFramed::<NoopRawMutex, _, _, _, _>::new(
+            FramedRead::new(StrLines::new(), FromTokio::new(read), read_buf),
+            FramedWrite::new(StrLines::new(), FromTokio::new(write), write_buf),
+        );
+
+        // Waits for a frame that the peer only echoes after the send.
+        let receive = async {
+            framed
+                .next(|line| String::from(line))
+                .await
+                .expect("Must be some")
+                .expect("Must decode")
+        };
+
+        let send = async {
+            framed.send("Hello").await.expect("Must send");
+        };
+
+        let echo = async {
+            let buf = &mut [0_u8; 7];
+
+            peer.read_exact(buf).await.expect("Must read");
+            peer.write_all(buf).await.expect("Must write");
+        };
+
+        let (line, _, _) = tokio::join!(receive, send, echo);
+
+        assert_eq!(line, "Hello");
+    }
+}
@@ -0,0 +1,288 @@
+//! Backpressure-aware [`Sink`] over a [`WriteState`].
+
+use core::{
+    pin::{Pin, pin},
+    task::{Context, Poll},
+};
+
+use embedded_io_async::Write;
+use futures::Sink;
+
+use crate::{
+    WriteError,
+    buffer::Buffer,
+    encode::Encoder,
+    functions,
+    logging::{debug, error},
+    record::record,
+    state::WriteState,
+};
+
+#[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
+use crate::logging::Name;
+
+#[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
+const WRITE: &str = "framez::write";
+
+/// Checks whether the writer can accept bytes without blocking.
+pub(crate) type Ready<W> = fn(&mut W) -> Result<bool, <W as embedded_io_async::ErrorType>::Error>;
+
+/// A [`Sink`] that encodes items directly into the write buffer.
+///
+/// [`Sink::poll_ready`] only returns [`Poll::Ready`] once the previous frame is completely written
+/// and `ready` reports that the writer can accept more bytes.
+/// [`Sink::start_send`] only encodes the item, and [`Sink::poll_flush`] writes and flushes it.
+///
+/// The write futures are polled once and dropped if they are pending.
+/// This relies on the cancel safety of [`functions::send`], since the [`WriteState`] tracks the written bytes.
+pub(crate) struct FramedSink<'this, 'buf, C, W, B>
+where
+    W: Write,
+{
+    codec: &'this mut C,
+    write: &'this mut W,
+    state: &'this mut WriteState<'buf, B>,
+    ready: Ready<W>,
+}
+
+impl<'this, 'buf, C, W, B> FramedSink<'this, 'buf, C, W, B>
+where
+    W: Write,
+{
+    pub(crate) fn new(
+        codec: &'this mut C,
+        write: &'this mut W,
+        state: &'this mut WriteState<'buf, B>,
+        ready: Ready<W>,
+    ) -> Self {
+        Self {
+            codec,
+            write,
+            state,
+            ready,
+        }
+    }
+}
+
+impl<C, W, B, I> Sink<I> for FramedSink<'_, '_, C, W, B>
+where
+    C: Encoder<I>,
+    W: Write,
+    B: Buffer,
+{
+    type Error = WriteError<W::Error, C::Error>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+
+        match pin!(functions::resume_pending(this.state, this.write)).poll(cx) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(err.cast_encode())),
+            Poll::Pending => return Poll::Pending,
+        }
+
+        match (this.ready)(this.write) {
+            Ok(true) => Poll::Ready(Ok(())),
+            Ok(false) => {
+                // Readiness has no waker to register, so poll again as soon as possible.
+                cx.waker().wake_by_ref();
+
+                Poll::Pending
+            }
+            Err(err) => {
+                error!(target: WRITE, "{}Failed to check readiness", Name(this.state.name));
+
+                record!(this.state, error);
+
+                Poll::Ready(Err(WriteError::IO(err)))
+            }
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: I) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+
+        if this.state.pending() > 0 {
+            error!(target: WRITE, "{}Sink is not ready", Name(this.state.name));
+
+            record!(this.state, error);
+
+            return Err(WriteError::NotReady);
+        }
+
+        functions::check_encoded_len(this.state, this.codec, &item)?;
+
+        match this.codec.encode(item, this.state.buffer.as_mut_slice()) {
+            Ok(size) => {
+                this.state.encoded = size;
+                this.state.written = 0;
+
+                record!(this.state, frame);
+
+                Ok(())
+            }
+            Err(err) => {
+                error!(target: WRITE, "{}Failed to encode frame", Name(this.state.name));
+
+                record!(this.state, error);
+
+                Err(WriteError::Encode(err))
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+
+        match pin!(functions::write_pending(this.state, this.write)).poll(cx) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(err.cast_encode())),
+            Poll::Pending => return Poll::Pending,
+        }
+
+        match pin!(this.write.flush()).poll(cx) {
+            Poll::Ready(Ok(())) => {
+                debug!(target: WRITE, "{}Flushed. bytes: {}", Name(this.state.name), this.state.encoded);
+
+                Poll::Ready(Ok(()))
+            }
+            Poll::Ready(Err(err)) => {
+                error!(target: WRITE, "{}Failed to flush", Name(this.state.name));
+
+                record!(this.state, error);
+
+                Poll::Ready(Err(WriteError::IO(err)))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Sink::<I>::poll_flush(self, cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use embedded_io_adapters::tokio_1::FromTokio;
+    use embedded_io_async::{ErrorKind, ErrorType, WriteReady};
+    use futures::{FutureExt, SinkExt};
+
+    use crate::{FramedRead, FramedWrite, codec::lines::Lines, next, tests::init_tracing};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn poll_ready_waits_for_previous_frame() {
+        init_tracing();
+
+        // The duplex only accepts 4 bytes until the reader reads.
+        let (read, write) = tokio::io::duplex(4);
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Lines::new(), FromTokio::new(write), buffer);
+
+        {
+            let mut sink = framer.sink();
+
+            // Only encodes the frame.
+            sink.feed(b"Hello, world!".as_slice())
+                .now_or_never()
+                .expect("Must be ready")
+                .expect("Must feed");
+
+            // Writes 4 bytes of the previous frame, then waits for the reader.
+            assert!(sink.feed(b"Hey".as_slice()).now_or_never().is_none());
+        }
+
+        assert_eq!(framer.core.state.write.written, 4);
+        assert_eq!(framer.core.state.write.pending(), 11);
+
+        let reader = tokio::spawn(async move {
+            let buffer = &mut [0_u8; 32];
+            let mut framer = FramedRead::new(Lines::new(), FromTokio::new(read), buffer);
+
+            let mut collected = Vec::<Vec<u8>>::new();
+
+            while let Some(item) = next!(framer) {
+                collected.push(item.expect("Must decode").into());
+            }
+
+            collected
+        });
+
+        framer
+            .sink()
+            .send(b"Hey".as_slice())
+            .await
+            .expect("Must send");
+
+        drop(framer);
+
+        let collected = reader.await.expect("Must join");
+
+        assert_eq!(collected, [b"Hello, world!".as_slice(), b"Hey"]);
+    }
+
+    /// Becomes ready after `not_ready` readiness checks.
+    struct Eventually {
+        not_ready: usize,
+        written: Vec<u8>,
+    }
+
+    impl ErrorType for Eventually {
+        type Error = ErrorKind;
+    }
+
+    impl Write for Eventually {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.written.extend_from_slice(buf);
+
+            Ok(buf.len())
+        }
+    }
+
+    impl WriteReady for Eventually {
+        fn write_ready(&mut self) -> Result<bool, Self::Error> {
+            if self.not_ready == 0 {
+                return Ok(true);
+            }
+
+            self.not_ready -= 1;
+
+            Ok(false)
+        }
+    }
+
+    #[tokio::test]
+    async fn ready_sink_waits_for_write_ready() {
+        init_tracing();
+
+        let writer = Eventually {
+            not_ready: 3,
+            written: Vec::new(),
+        };
+
+        let buffer = &mut [0_u8; 32];
+        let mut framer = FramedWrite::new(Lines::new(), writer, buffer);
+
+        {
+            let mut sink = framer.ready_sink();
+
+            assert!(sink.feed(b"Hello".as_slice()).now_or_never().is_none());
+        }
+
+        assert_eq!(framer.core.state.write.encoded, 0);
+
+        framer
+            .ready_sink()
+            .send(b"Hello".as_slice())
+            .await
+            .expect("Must send");
+
+        assert_eq!(framer.inner().not_ready, 0);
+        assert_eq!(framer.inner().written, b"Hello\r\n");
+    }
+}
@@ -1,105 +1,977 @@
 //! Internal states for reading and writing frames.
 
+use core::marker::PhantomData;
+
+use crate::{buffer::Buffer, quota::Quota};
+
+#[cfg(feature = "metrics")]
+use crate::metrics::{Counters, Direction};
+
+/// Policy that decides when the buffered bytes are shifted to the start of the read buffer.
+///
+/// Shifting makes room for more bytes to be read into the buffer, at the cost of copying the bytes that are not yet framed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ShiftPolicy {
+    /// Shift only when the buffer is full.
+    ///
+    /// Copies as few bytes as possible. Suited for throughput-sensitive links.
+    #[default]
+    WhenFull,
+    /// Shift whenever a frame could not be decoded and some bytes were consumed.
+    ///
+    /// Keeps the free region of the buffer as large as possible, so reads are never cut short by the end of the buffer.
+    /// Suited for latency-sensitive links.
+    Eager,
+    /// Shift when at least the given number of consumed bytes can be reclaimed, or when the buffer is full.
+    AboveThreshold(usize),
+}
+
+impl ShiftPolicy {
+    /// Returns `true` if the buffer should be shifted after a frame could not be decoded.
+    #[inline]
+    pub const fn should_shift(&self, total_consumed: usize, index: usize, capacity: usize) -> bool {
+        if index >= capacity {
+            return true;
+        }
+
+        match self {
+            Self::WhenFull => false,
+            Self::Eager => total_consumed > 0,
+            Self::AboveThreshold(threshold) => total_consumed > 0 && total_consumed >= *threshold,
+        }
+    }
+}
+
+/// The next step of [`maybe_next`](crate::functions::maybe_next) on a [`ReadState`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReadPhase {
+    /// More bytes must be read before decoding.
+    #[default]
+    Read,
+    /// The buffered bytes must be shifted to the start of the buffer before reading more bytes.
+    ///
+    /// Makes room for more bytes to be read into the buffer, keeping the already read bytes.
+    Shift,
+    /// The buffered bytes may contain a frame.
+    Decode,
+    /// EOF was reached. The buffered bytes are decoded using [`Decoder::decode_eof`](crate::decode::Decoder::decode_eof).
+    DecodeEof,
+}
+
+/// Metadata of a decoded frame, e.g. to reference frames by their position in protocol logs.
+///
+/// See [`next_with_meta`](crate::functions::next_with_meta).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrameMeta {
+    /// The offset of the first byte of the frame in the stream.
+    pub offset: u64,
+    /// The number of bytes of the frame in the stream, as consumed by the decoder.
+    pub len: usize,
+    /// The index of the frame, counting the decoded frames of the stream from `0`.
+    pub index: u64,
+}
+
 /// Internal state for reading frames.
+///
+/// The buffer is a `&'buf mut [u8]` by default, but can be any [`Buffer`], e.g. an owned array.
+/// See [`ReadState::with_buffer`].
 #[derive(Debug)]
-pub struct ReadState<'buf> {
+pub struct ReadState<'buf, B = &'buf mut [u8]> {
     /// The current index in the buffer.
     ///
     /// Represents the number of bytes read into the buffer.
     pub index: usize,
-    /// EOF was reached while decoding.
-    pub eof: bool,
-    /// The buffer is currently framable.
-    pub is_framable: bool,
-    /// The buffer must be shifted before reading more bytes.
-    ///
-    /// Makes room for more bytes to be read into the buffer, keeping the already read bytes.
-    pub shift: bool,
+    /// The next step of [`maybe_next`](crate::functions::maybe_next).
+    pub phase: ReadPhase,
     /// Total number of bytes decoded in a framing round.
     pub total_consumed: usize,
+    /// The total length of the frame being read, as reported by [`Decoder::decode_header`](crate::decode::Decoder::decode_header).
+    ///
+    /// Decoding is skipped until this many bytes are framable.
+    pub frame_len: Option<usize>,
+    /// The offset in the stream of the first byte that was not decoded yet.
+    ///
+    /// Advanced by decoded frames, payload chunks and discarded bytes.
+    pub stream_offset: u64,
+    /// The number of frames decoded from the stream.
+    pub frame_count: u64,
+    /// Decides when the [`ReadPhase::Shift`] is entered.
+    pub shift_policy: ShiftPolicy,
+    /// Limits the bytes read from the source.
+    pub quota: Quota,
     /// The underlying buffer to read into.
-    pub buffer: &'buf mut [u8],
+    pub buffer: B,
+    /// The name included in every log line of this state, e.g. `gps`.
+    pub name: Option<&'static str>,
+    /// Called on every decoded frame, e.g. to pet a hardware watchdog.
+    pub on_frame: Option<fn()>,
+    /// Counts the decoded frames, read bytes and errors.
+    #[cfg(feature = "metrics")]
+    pub counters: Counters,
+    _buf: PhantomData<&'buf mut [u8]>,
 }
 
 impl<'buf> ReadState<'buf> {
     /// Creates a new [`ReadState`].
     #[inline]
     pub const fn new(buffer: &'buf mut [u8]) -> Self {
+        Self::with_buffer(buffer)
+    }
+
+    /// Creates an empty [`ReadState`].
+    #[inline]
+    pub const fn empty() -> Self {
+        Self::new(&mut [])
+    }
+}
+
+impl<B> ReadState<'_, B> {
+    /// Creates a new [`ReadState`] with the given [`Buffer`].
+    #[inline]
+    pub const fn with_buffer(buffer: B) -> Self {
         Self {
             index: 0,
-            eof: false,
-            is_framable: false,
-            shift: false,
+            phase: ReadPhase::Read,
             total_consumed: 0,
+            frame_len: None,
+            stream_offset: 0,
+            frame_count: 0,
+            shift_policy: ShiftPolicy::WhenFull,
+            quota: Quota::unlimited(),
             buffer,
+            name: None,
+            on_frame: None,
+            #[cfg(feature = "metrics")]
+            counters: Counters::new(Direction::Read),
+            _buf: PhantomData,
         }
     }
 
+    /// Sets the [`ShiftPolicy`] of the state.
+    #[inline]
+    pub const fn with_shift_policy(mut self, shift_policy: ShiftPolicy) -> Self {
+        self.shift_policy = shift_policy;
+        self
+    }
+
+    /// Sets the [`ReadState::name`] of the state.
+    #[inline]
+    pub const fn with_name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets the [`ReadState::on_frame`] hook of the state.
+    #[inline]
+    pub const fn with_on_frame(mut self, on_frame: fn()) -> Self {
+        self.on_frame = Some(on_frame);
+        self
+    }
+
+    /// Sets the [`Quota`] of the state.
+    #[inline]
+    pub const fn with_quota(mut self, quota: Quota) -> Self {
+        self.quota = quota;
+        self
+    }
+
     /// Resets the state to its initial values.
+    ///
+    /// The [`ShiftPolicy`], the [`Quota`], the [`ReadState::name`], the [`ReadState::on_frame`] hook and the counters of the `metrics` feature are kept.
     #[inline]
-    pub const fn reset(self) -> Self {
-        Self::new(self.buffer)
+    pub fn reset(self) -> Self {
+        let shift_policy = self.shift_policy;
+        let quota = self.quota;
+        let name = self.name;
+        let on_frame = self.on_frame;
+        #[cfg(feature = "metrics")]
+        let counters = self.counters;
+
+        Self {
+            quota,
+            name,
+            on_frame,
+            #[cfg(feature = "metrics")]
+            counters,
+            ..Self::with_buffer(self.buffer).with_shift_policy(shift_policy)
+        }
     }
 
-    /// Creates an empty [`ReadState`].
+    /// Returns the number of bytes that can be framed.
+    #[inline]
+    pub const fn framable(&self) -> usize {
+        self.index.saturating_sub(self.total_consumed)
+    }
+
+    /// Discards the buffered bytes that were not decoded yet and returns their number.
+    ///
+    /// Used to resynchronize after garbage was received. The next call to [`maybe_next`](crate::functions::maybe_next) reads from the source.
+    #[inline]
+    pub const fn discard_buffered(&mut self) -> usize {
+        let discarded = self.framable();
+
+        self.index = 0;
+        self.total_consumed = 0;
+        self.phase = ReadPhase::Read;
+        self.frame_len = None;
+        self.stream_offset = self.stream_offset.wrapping_add(discarded as u64);
+
+        discarded
+    }
+
+    /// Captures the decoding progress of the state, so the frames decoded afterwards can be rolled back with [`ReadState::restore`].
+    ///
+    /// Used to decode speculatively, e.g. to try one codec, roll back on failure and retry with another one,
+    /// or to peek at a frame before dispatching it, without losing bytes between attempts.
+    #[inline]
+    pub const fn snapshot(&self) -> ReadSnapshot {
+        ReadSnapshot {
+            stream_offset: self.stream_offset,
+            frame_count: self.frame_count,
+            frame_len: self.frame_len,
+        }
+    }
+
+    /// Rolls back the bytes consumed since the `snapshot` was taken, so they are decoded again.
+    ///
+    /// Bytes read since the `snapshot` are kept. The internal state of the codec, e.g. its skipped bytes, is not rolled back.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RollbackError`] and leaves the state unchanged if the consumed bytes are no longer buffered,
+    /// e.g. because they were shifted out of the buffer or discarded, or if the `snapshot` was taken after the current position.
+    pub const fn restore(&mut self, snapshot: ReadSnapshot) -> Result<(), RollbackError> {
+        let Some(rewind) = self.stream_offset.checked_sub(snapshot.stream_offset) else {
+            return Err(RollbackError);
+        };
+
+        if rewind > self.total_consumed as u64 {
+            return Err(RollbackError);
+        }
+
+        self.total_consumed = self.total_consumed.saturating_sub(rewind as usize);
+        self.stream_offset = snapshot.stream_offset;
+        self.frame_count = snapshot.frame_count;
+        self.frame_len = snapshot.frame_len;
+
+        // The rolled back bytes are decoded before reading more, on EOF if it was reached.
+        if !matches!(self.phase, ReadPhase::DecodeEof) {
+            self.phase = ReadPhase::Decode;
+        }
+
+        Ok(())
+    }
+}
+
+/// The decoding progress of a [`ReadState`], captured by [`ReadState::snapshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReadSnapshot {
+    stream_offset: u64,
+    frame_count: u64,
+    frame_len: Option<usize>,
+}
+
+/// The bytes consumed since a [`ReadSnapshot`] are no longer buffered, so they can not be rolled back.
+///
+/// Returned by [`ReadState::restore`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RollbackError;
+
+impl core::fmt::Display for RollbackError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Consumed bytes are no longer buffered")
+    }
+}
+
+impl core::error::Error for RollbackError {}
+
+/// More bytes were committed than the writable region can hold.
+///
+/// Returned by [`ReadState::commit`] and [`RingReadState::commit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CommitError {
+    /// The number of committed bytes.
+    pub size: usize,
+    /// The length of the writable region.
+    pub available: usize,
+}
+
+impl core::fmt::Display for CommitError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Committed more bytes than writable. size: {}, available: {}",
+            self.size, self.available
+        )
+    }
+}
+
+impl core::error::Error for CommitError {}
+
+/// More bytes were consumed than buffered.
+///
+/// Returned by [`RingReadState::consume`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ConsumeError {
+    /// The number of consumed bytes.
+    pub size: usize,
+    /// The number of buffered bytes.
+    pub buffered: usize,
+}
+
+impl core::fmt::Display for ConsumeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Consumed more bytes than buffered. size: {}, buffered: {}",
+            self.size, self.buffered
+        )
+    }
+}
+
+impl core::error::Error for ConsumeError {}
+
+impl<B: Buffer> ReadState<'_, B> {
+    /// Discards the buffered bytes, see [`ReadState::discard_buffered`], and wipes the whole buffer.
+    #[cfg(feature = "zeroize")]
+    #[inline]
+    pub fn zeroize(&mut self) {
+        self.discard_buffered();
+
+        zeroize::Zeroize::zeroize(self.buffer.as_mut_slice());
+    }
+
+    /// Returns the free region of the buffer right after the buffered bytes.
+    ///
+    /// Lends the buffer to a driver that fills it directly, e.g. a UART DMA receiver, instead of reading through a [`Read`](embedded_io_async::Read) source.
+    /// Call [`ReadState::commit`] with the number of bytes written into it.
+    ///
+    /// The buffered bytes are shifted to the start of the buffer first if needed, so the region is as large as the [`ShiftPolicy`] allows.
+    pub fn writable_part(&mut self) -> &mut [u8] {
+        if self.phase == ReadPhase::Shift {
+            crate::functions::shift(self);
+        }
+
+        self.buffer
+            .as_mut_slice()
+            .get_mut(self.index..)
+            .unwrap_or_default()
+    }
+
+    /// Marks `size` bytes written into [`ReadState::writable_part`] as read.
+    ///
+    /// The next call to [`maybe_next`](crate::functions::maybe_next) decodes them before reading from the source.
+    ///
+    /// Returns [`CommitError`] and leaves the state unchanged if `size` is greater than the length of [`ReadState::writable_part`],
+    /// e.g. if a driver reports a bad count.
+    #[inline]
+    pub fn commit(&mut self, size: usize) -> Result<(), CommitError> {
+        let available = self.buffer.as_slice().len().saturating_sub(self.index);
+
+        let index = match self.index.checked_add(size) {
+            Some(index) if size <= available => index,
+            _ => return Err(CommitError { size, available }),
+        };
+
+        self.index = index;
+
+        if size > 0 {
+            self.phase = ReadPhase::Decode;
+        }
+
+        Ok(())
+    }
+}
+
+/// Internal state for reading frames into a ring buffer.
+///
+/// An alternative to [`ReadState`] that never shifts the buffered bytes to the start of the buffer.
+/// The buffered bytes may wrap around the end of the buffer and are decoded using a [`RingDecoder`](crate::decode::RingDecoder).
+///
+/// See [`RingFramedRead`](crate::RingFramedRead) and [`maybe_next_ring`](crate::functions::maybe_next_ring).
+#[derive(Debug)]
+pub struct RingReadState<'buf, B = &'buf mut [u8]> {
+    /// The position of the first buffered byte.
+    pub start: usize,
+    /// The number of buffered bytes.
+    pub len: usize,
+    /// EOF was reached while decoding.
+    pub eof: bool,
+    /// The buffer is currently framable.
+    pub is_framable: bool,
+    /// The underlying buffer to read into.
+    pub buffer: B,
+    /// The name included in every log line of this state, e.g. `gps`.
+    pub name: Option<&'static str>,
+    /// Called on every decoded frame, e.g. to pet a hardware watchdog.
+    pub on_frame: Option<fn()>,
+    /// Counts the decoded frames, read bytes and errors.
+    #[cfg(feature = "metrics")]
+    pub counters: Counters,
+    _buf: PhantomData<&'buf mut [u8]>,
+}
+
+impl<'buf> RingReadState<'buf> {
+    /// Creates a new [`RingReadState`].
+    #[inline]
+    pub const fn new(buffer: &'buf mut [u8]) -> Self {
+        Self::with_buffer(buffer)
+    }
+
+    /// Creates an empty [`RingReadState`].
     #[inline]
     pub const fn empty() -> Self {
         Self::new(&mut [])
     }
+}
+
+impl<B> RingReadState<'_, B> {
+    /// Creates a new [`RingReadState`] with the given [`Buffer`].
+    #[inline]
+    pub const fn with_buffer(buffer: B) -> Self {
+        Self {
+            start: 0,
+            len: 0,
+            eof: false,
+            is_framable: false,
+            buffer,
+            name: None,
+            on_frame: None,
+            #[cfg(feature = "metrics")]
+            counters: Counters::new(Direction::Read),
+            _buf: PhantomData,
+        }
+    }
+
+    /// Sets the [`RingReadState::name`] of the state.
+    #[inline]
+    pub const fn with_name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets the [`RingReadState::on_frame`] hook of the state.
+    #[inline]
+    pub const fn with_on_frame(mut self, on_frame: fn()) -> Self {
+        self.on_frame = Some(on_frame);
+        self
+    }
+
+    /// Resets the state to its initial values.
+    ///
+    /// The [`RingReadState::name`], the [`RingReadState::on_frame`] hook and the counters of the `metrics` feature are kept.
+    #[inline]
+    pub fn reset(self) -> Self {
+        let name = self.name;
+        let on_frame = self.on_frame;
+        #[cfg(feature = "metrics")]
+        let counters = self.counters;
+
+        Self {
+            name,
+            on_frame,
+            #[cfg(feature = "metrics")]
+            counters,
+            ..Self::with_buffer(self.buffer)
+        }
+    }
 
     /// Returns the number of bytes that can be framed.
     #[inline]
     pub const fn framable(&self) -> usize {
-        self.index - self.total_consumed
+        self.len
+    }
+}
+
+impl<B: Buffer> RingReadState<'_, B> {
+    /// Returns the number of bytes that can still be read into the buffer.
+    #[inline]
+    pub fn free(&self) -> usize {
+        self.buffer.as_slice().len().saturating_sub(self.len)
+    }
+
+    /// Returns the buffered bytes as two sequential slices.
+    ///
+    /// The second slice is empty if the buffered bytes do not wrap around the end of the buffer.
+    #[inline]
+    pub fn framable_parts(&mut self) -> (&mut [u8], &mut [u8]) {
+        ring_parts(self.buffer.as_mut_slice(), self.start, self.len)
+    }
+
+    /// Returns the contiguous free region of the buffer right after the buffered bytes.
+    pub fn writable(&mut self) -> &mut [u8] {
+        let buffer = self.buffer.as_mut_slice();
+        let capacity = buffer.len();
+        let end = self.start.saturating_add(self.len);
+
+        let writable = if end < capacity {
+            buffer.get_mut(end..)
+        } else {
+            buffer.get_mut(end.saturating_sub(capacity)..self.start)
+        };
+
+        writable.unwrap_or_default()
+    }
+
+    /// Marks `size` buffered bytes as consumed.
+    ///
+    /// Returns [`ConsumeError`] and leaves the state unchanged if `size` is greater than the number of buffered bytes.
+    #[inline]
+    pub fn consume(&mut self, size: usize) -> Result<(), ConsumeError> {
+        if size > self.len {
+            return Err(ConsumeError {
+                size,
+                buffered: self.len,
+            });
+        }
+
+        (self.start, self.len) =
+            ring_consume(self.buffer.as_slice().len(), self.start, self.len, size);
+
+        Ok(())
+    }
+
+    /// Marks `size` bytes written into [`RingReadState::writable`] as buffered.
+    ///
+    /// Returns [`CommitError`] and leaves the state unchanged if `size` is greater than the length of [`RingReadState::writable`].
+    #[inline]
+    pub fn commit(&mut self, size: usize) -> Result<(), CommitError> {
+        let available = self.writable().len();
+
+        if size > available {
+            return Err(CommitError { size, available });
+        }
+
+        self.len = self.len.saturating_add(size);
+
+        Ok(())
+    }
+
+    /// Discards the buffered bytes and wipes the whole buffer.
+    ///
+    /// The next call to [`maybe_next_ring`](crate::functions::maybe_next_ring) reads from the source.
+    #[cfg(feature = "zeroize")]
+    #[inline]
+    pub fn zeroize(&mut self) {
+        self.start = 0;
+        self.len = 0;
+        self.is_framable = false;
+
+        zeroize::Zeroize::zeroize(self.buffer.as_mut_slice());
+    }
+}
+
+/// Splits the `len` bytes starting at `start` of a ring `buffer` into two sequential slices.
+pub(crate) fn ring_parts(buffer: &mut [u8], start: usize, len: usize) -> (&mut [u8], &mut [u8]) {
+    let end = start.saturating_add(len);
+
+    if end <= buffer.len() {
+        return (buffer.get_mut(start..end).unwrap_or_default(), &mut []);
+    }
+
+    let wrapped = end.saturating_sub(buffer.len());
+
+    match buffer.split_at_mut_checked(start) {
+        Some((tail, head)) => (head, tail.get_mut(..wrapped).unwrap_or_default()),
+        None => (&mut [], &mut []),
+    }
+}
+
+/// Returns the new `start` and `len` of a ring buffer with the given `capacity` after consuming `size` bytes.
+///
+/// `size` must not exceed `len`. A larger `size` consumes everything.
+pub(crate) const fn ring_consume(
+    capacity: usize,
+    start: usize,
+    len: usize,
+    size: usize,
+) -> (usize, usize) {
+    let len = len.saturating_sub(size);
+
+    match len {
+        // Nothing is buffered. Start from the beginning to keep the free region contiguous.
+        0 => (0, 0),
+        _ => match start.saturating_add(size).checked_rem(capacity) {
+            Some(start) => (start, len),
+            None => (0, 0),
+        },
     }
 }
 
 /// Internal state for writing frames.
+///
+/// The buffer is a `&'buf mut [u8]` by default, but can be any [`Buffer`], e.g. an owned array.
+/// See [`WriteState::with_buffer`].
 #[derive(Debug)]
-pub struct WriteState<'buf> {
+pub struct WriteState<'buf, B = &'buf mut [u8]> {
+    /// The number of bytes of the current encoded frame in the buffer.
+    pub encoded: usize,
+    /// The number of bytes of the current encoded frame that have been written.
+    ///
+    /// Smaller than [`WriteState::encoded`] if writing the frame was cancelled.
+    pub written: usize,
+    /// A frame with a streamed payload is being written.
+    ///
+    /// Stays `true` if writing the frame failed or was cancelled after some of its bytes were written.
+    /// See [`send_streamed`](crate::functions::send_streamed).
+    pub streaming: bool,
+    /// The number of encoded bytes to accumulate before [`send`](crate::functions::send) writes and flushes them.
+    ///
+    /// `0` writes and flushes every frame as it is sent. Otherwise small frames are coalesced in the buffer
+    /// and written once the threshold is reached, the next frame does not fit, or [`flush`](crate::functions::flush) is called.
+    pub flush_threshold: usize,
     /// The underlying buffer to write to.
-    pub buffer: &'buf mut [u8],
+    pub buffer: B,
+    /// The name included in every log line of this state, e.g. `gps`.
+    pub name: Option<&'static str>,
+    /// Called on every encoded frame, e.g. to pet a hardware watchdog.
+    pub on_frame: Option<fn()>,
+    /// Counts the written frames, written bytes and errors.
+    #[cfg(feature = "metrics")]
+    pub counters: Counters,
+    _buf: PhantomData<&'buf mut [u8]>,
 }
 
 impl<'buf> WriteState<'buf> {
     /// Creates a new [`WriteState`].
     #[inline]
     pub const fn new(buffer: &'buf mut [u8]) -> Self {
-        Self { buffer }
+        Self::with_buffer(buffer)
+    }
+
+    /// Creates an empty [`WriteState`].
+    #[inline]
+    pub const fn empty() -> Self {
+        Self::new(&mut [])
+    }
+}
+
+impl<B> WriteState<'_, B> {
+    /// Creates a new [`WriteState`] with the given [`Buffer`].
+    #[inline]
+    pub const fn with_buffer(buffer: B) -> Self {
+        Self {
+            encoded: 0,
+            written: 0,
+            streaming: false,
+            flush_threshold: 0,
+            buffer,
+            name: None,
+            on_frame: None,
+            #[cfg(feature = "metrics")]
+            counters: Counters::new(Direction::Write),
+            _buf: PhantomData,
+        }
+    }
+
+    /// Sets the [`WriteState::flush_threshold`] of the state.
+    #[inline]
+    pub const fn with_flush_threshold(mut self, flush_threshold: usize) -> Self {
+        self.flush_threshold = flush_threshold;
+        self
+    }
+
+    /// Sets the [`WriteState::name`] of the state.
+    #[inline]
+    pub const fn with_name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets the [`WriteState::on_frame`] hook of the state.
+    #[inline]
+    pub const fn with_on_frame(mut self, on_frame: fn()) -> Self {
+        self.on_frame = Some(on_frame);
+        self
     }
 
     /// Resets the state to its initial values.
+    ///
+    /// The [`WriteState::flush_threshold`], the [`WriteState::name`], the [`WriteState::on_frame`] hook and the counters of the `metrics` feature are kept.
     #[inline]
-    pub const fn reset(self) -> Self {
-        Self::new(self.buffer)
+    pub fn reset(self) -> Self {
+        let flush_threshold = self.flush_threshold;
+        let name = self.name;
+        let on_frame = self.on_frame;
+        #[cfg(feature = "metrics")]
+        let counters = self.counters;
+
+        Self {
+            name,
+            on_frame,
+            #[cfg(feature = "metrics")]
+            counters,
+            ..Self::with_buffer(self.buffer).with_flush_threshold(flush_threshold)
+        }
     }
 
-    /// Creates an empty [`WriteState`].
+    /// Returns the number of bytes of the current encoded frame that are yet to be written.
     #[inline]
-    pub const fn empty() -> Self {
-        Self::new(&mut [])
+    pub const fn pending(&self) -> usize {
+        self.encoded.saturating_sub(self.written)
+    }
+
+    /// Drops the bytes of the current encoded frame that are yet to be written.
+    ///
+    /// The next send will not resume the partially written frame.
+    #[inline]
+    pub const fn discard_pending(&mut self) {
+        self.encoded = 0;
+        self.written = 0;
+    }
+}
+
+impl<B: Buffer> WriteState<'_, B> {
+    /// Discards the pending bytes, see [`WriteState::discard_pending`], and wipes the whole buffer.
+    #[cfg(feature = "zeroize")]
+    #[inline]
+    pub fn zeroize(&mut self) {
+        self.discard_pending();
+
+        zeroize::Zeroize::zeroize(self.buffer.as_mut_slice());
     }
 }
 
 /// Internal state for reading and writing frames.
 #[derive(Debug)]
-pub struct ReadWriteState<'buf> {
+pub struct ReadWriteState<'buf, RB = &'buf mut [u8], WB = &'buf mut [u8]> {
     /// Internal read state.
-    pub read: ReadState<'buf>,
+    pub read: ReadState<'buf, RB>,
     /// Internal write state.
-    pub write: WriteState<'buf>,
+    pub write: WriteState<'buf, WB>,
 }
 
-impl<'buf> ReadWriteState<'buf> {
+impl<'buf, RB, WB> ReadWriteState<'buf, RB, WB> {
     /// Creates a new [`ReadWriteState`] with the given [`ReadState`] and [`WriteState`].
     #[inline]
-    pub const fn new(read: ReadState<'buf>, write: WriteState<'buf>) -> Self {
+    pub const fn new(read: ReadState<'buf, RB>, write: WriteState<'buf, WB>) -> Self {
         Self { read, write }
     }
 
     /// Creates a new [`ReadWriteState`] with empty [`ReadState`] and [`WriteState`].
     #[inline]
-    pub const fn reset(self) -> Self {
+    pub fn reset(self) -> Self {
         Self::new(self.read.reset(), self.write.reset())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        codec::{delimiter::Delimiter, lines::StrLines},
+        functions::decode_pending,
+    };
+
+    use super::*;
+
+    #[test]
+    fn restore_rolls_back_decoded_frames() {
+        let buffer = &mut [0_u8; 16];
+        let mut state = ReadState::new(buffer);
+
+        state.writable_part()[..10].copy_from_slice(b"a\nb\xFF\0c\nd\ne");
+        state.commit(10).expect("Must commit");
+
+        let snapshot = state.snapshot();
+
+        let mut lines = StrLines::new();
+
+        let decoded = decode_pending::<_, (), _>(&mut state, &mut lines);
+        assert!(matches!(decoded, Some(Ok(Some("a")))));
+
+        // Not valid UTF-8.
+        let decoded = decode_pending::<_, (), _>(&mut state, &mut lines);
+        assert!(matches!(decoded, Some(Err(_))));
+
+        state.restore(snapshot).expect("Must restore");
+
+        assert_eq!(state.framable(), 10);
+        assert_eq!((state.stream_offset, state.frame_count), (0, 0));
+
+        let mut delimiter = Delimiter::new(&[0]);
+
+        let decoded = decode_pending::<_, (), _>(&mut state, &mut delimiter);
+        assert!(matches!(decoded, Some(Ok(Some(b"a\nb\xFF")))));
+        assert_eq!(state.frame_count, 1);
+    }
+
+    #[test]
+    fn restore_fails_once_bytes_are_gone() {
+        let buffer = &mut [0_u8; 16];
+        let mut state = ReadState::new(buffer);
+
+        state.writable_part()[..4].copy_from_slice(b"a\nb\n");
+        state.commit(4).expect("Must commit");
+
+        let snapshot = state.snapshot();
+
+        let decoded = decode_pending::<_, (), _>(&mut state, &mut StrLines::new());
+        assert!(matches!(decoded, Some(Ok(Some("a")))));
+
+        state.discard_buffered();
+
+        assert_eq!(state.restore(snapshot), Err(RollbackError));
+        assert_eq!(state.stream_offset, 4);
+    }
+
+    #[test]
+    fn commit_rejects_more_than_writable() {
+        let buffer = &mut [0_u8; 8];
+        let mut state = ReadState::new(buffer);
+
+        state.commit(6).expect("Must commit");
+
+        assert_eq!(
+            state.commit(3),
+            Err(CommitError {
+                size: 3,
+                available: 2
+            })
+        );
+        assert_eq!(
+            state.commit(usize::MAX),
+            Err(CommitError {
+                size: usize::MAX,
+                available: 2
+            })
+        );
+
+        // The state is unchanged.
+        assert_eq!(state.framable(), 6);
+
+        state.commit(2).expect("Must commit");
+
+        assert_eq!(state.framable(), 8);
+    }
+
+    #[test]
+    fn ring_writable_and_commit() {
+        let buffer = &mut [0_u8; 8];
+        let mut state = RingReadState::new(buffer);
+
+        assert_eq!(state.writable().len(), 8);
+
+        state.writable()[..6].copy_from_slice(b"Hello,");
+        state.commit(6).expect("Must commit");
+
+        assert_eq!(state.framable(), 6);
+        assert_eq!(state.free(), 2);
+        assert_eq!(state.writable().len(), 2);
+
+        state.consume(4).expect("Must consume");
+
+        assert_eq!((state.start, state.len), (4, 2));
+
+        // end == capacity: the free region wraps to the start of the buffer.
+        state.writable()[..2].copy_from_slice(b" w");
+        state.commit(2).expect("Must commit");
+
+        assert_eq!((state.start, state.len), (4, 4));
+        assert_eq!(state.writable().len(), 4);
+
+        state.writable().copy_from_slice(b"orld");
+        state.commit(4).expect("Must commit");
+
+        // len == capacity: nothing is writable.
+        assert_eq!(state.free(), 0);
+        assert!(state.writable().is_empty());
+
+        let (head, tail) = state.framable_parts();
+
+        assert_eq!(head, b"o, w");
+        assert_eq!(tail, b"orld");
+    }
+
+    #[test]
+    fn ring_rejects_bad_counts() {
+        let buffer = &mut [0_u8; 8];
+        let mut state = RingReadState::new(buffer);
+
+        state.commit(6).expect("Must commit");
+        state.consume(4).expect("Must consume");
+
+        assert_eq!(
+            state.consume(3),
+            Err(ConsumeError {
+                size: 3,
+                buffered: 2
+            })
+        );
+
+        // Only the region up to the end of the buffer is writable.
+        assert_eq!(
+            state.commit(3),
+            Err(CommitError {
+                size: 3,
+                available: 2
+            })
+        );
+
+        // The state is unchanged.
+        assert_eq!((state.start, state.len), (4, 2));
+    }
+
+    #[test]
+    fn ring_consume_at_boundaries() {
+        // Consuming everything resets the start.
+        assert_eq!(ring_consume(8, 5, 3, 3), (0, 0));
+        assert_eq!(ring_consume(8, 0, 8, 8), (0, 0));
+
+        // Consuming up to the end of the buffer wraps the start to `0`.
+        assert_eq!(ring_consume(8, 5, 6, 3), (0, 3));
+
+        // Consuming across the end of the buffer.
+        assert_eq!(ring_consume(8, 6, 8, 4), (2, 4));
+
+        // Full buffer.
+        assert_eq!(ring_consume(8, 3, 8, 1), (4, 7));
+    }
+
+    #[test]
+    fn ring_parts_at_boundaries() {
+        let buffer = &mut [0, 1, 2, 3, 4, 5, 6, 7];
+
+        // end == capacity: no wrap.
+        let (head, tail) = ring_parts(buffer, 5, 3);
+        assert_eq!(head, [5, 6, 7]);
+        assert!(tail.is_empty());
+
+        // len == capacity, starting in the middle.
+        let (head, tail) = ring_parts(buffer, 3, 8);
+        assert_eq!(head, [3, 4, 5, 6, 7]);
+        assert_eq!(tail, [0, 1, 2]);
+
+        // len == capacity, starting at `0`.
+        let (head, tail) = ring_parts(buffer, 0, 8);
+        assert_eq!(head, [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert!(tail.is_empty());
+    }
+
+    #[test]
+    fn ring_consume_full_buffer() {
+        let buffer = &mut [0_u8; 4];
+        let mut state = RingReadState::new(buffer);
+
+        state.writable().copy_from_slice(b"abcd");
+        state.commit(4).expect("Must commit");
+        state.consume(1).expect("Must consume");
+
+        // end == capacity after the consume, so the freed byte is at the start.
+        assert_eq!((state.start, state.len), (1, 3));
+        assert_eq!(state.writable().len(), 1);
+
+        state.writable().copy_from_slice(b"e");
+        state.commit(1).expect("Must commit");
+        state.consume(4).expect("Must consume");
+
+        assert_eq!((state.start, state.len), (0, 0));
+        assert_eq!(state.writable().len(), 4);
+    }
+}
@@ -0,0 +1,445 @@
+//! Utilities for testing codecs.
+//!
+//! [`Conformance`] checks that a codec, implementing both [`Decoder`] and [`Encoder`], behaves like the framers expect:
+//!
+//! - Encoded items decode back to the same items.
+//! - Decoding does not depend on how the stream is split into reads.
+//! - A stream that ends at a frame boundary ends cleanly, and a stream that ends within a frame ends with an error.
+//! - The reported sizes of decoded frames never exceed the bytes passed to the decoder.
+//!
+//! Checks fail by panicking, like assertions.
+
+#![allow(clippy::panic)]
+
+use core::fmt::Debug;
+use std::{format, vec::Vec};
+
+use crate::{
+    FramedRead, FramedWrite, decode::Decoder, encode::Encoder, mock::ScriptedReader, next,
+};
+
+/// A deterministic pseudo-random number generator, so failing checks can be reproduced.
+#[derive(Debug, Clone)]
+struct XorShift(u64);
+
+impl XorShift {
+    const fn new(seed: u64) -> Self {
+        // A zero state would stay zero.
+        Self(if seed == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            seed
+        })
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number in `1..=max`.
+    fn next_len(&mut self, max: usize) -> usize {
+        (self.next().checked_rem(max as u64).unwrap_or_default() as usize).saturating_add(1)
+    }
+}
+
+/// Splits `data` into chunks with the lengths of `pattern`, repeating the `pattern` until every byte is in a chunk.
+///
+/// Lengths of `0` are treated as `1`, and an empty `pattern` returns `data` as a single chunk.
+/// The chunks can be read using a [`ScriptedReader`], one chunk per read.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::test_util::chunked;
+///
+/// let chunks = chunked(b"Hello, world!", &[1, 5]);
+///
+/// assert_eq!(chunks, [b"H".as_slice(), b"ello,", b" ", b"world", b"!"]);
+/// ```
+pub fn chunked<'a>(data: &'a [u8], pattern: &[usize]) -> Vec<&'a [u8]> {
+    if pattern.is_empty() {
+        return Vec::from([data]);
+    }
+
+    let mut chunks = Vec::new();
+    let mut rest = data;
+
+    for len in pattern.iter().cycle() {
+        if rest.is_empty() {
+            break;
+        }
+
+        let (chunk, tail) = rest.split_at((*len).clamp(1, rest.len()));
+
+        chunks.push(chunk);
+        rest = tail;
+    }
+
+    chunks
+}
+
+/// A plan to split a stream into reads of varying lengths, see [`chunked`].
+///
+/// With the `arbitrary` feature, plans can be generated by fuzzers, to feed a framer through a [`ScriptedReader`]:
+///
+/// ```rust,ignore
+/// #![no_main]
+///
+/// use framez::{FramedRead, codec::lines::StrLines, mock::ScriptedReader, next, test_util::ChunkPlan};
+/// use libfuzzer_sys::fuzz_target;
+///
+/// fuzz_target!(|input: (ChunkPlan, &[u8])| {
+///     let (plan, data) = input;
+///     let chunks = plan.apply(data);
+///
+///     let buffer = &mut [0_u8; 1024];
+///     let mut reader = FramedRead::new(StrLines::new(), ScriptedReader::new(&chunks), buffer);
+///
+///     futures::executor::block_on(async {
+///         while let Some(Ok(_)) = next!(reader) {}
+///     });
+/// });
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChunkPlan {
+    pattern: Vec<usize>,
+}
+
+impl ChunkPlan {
+    /// Creates a new [`ChunkPlan`] with the chunk lengths of `pattern`.
+    #[inline]
+    pub const fn new(pattern: Vec<usize>) -> Self {
+        Self { pattern }
+    }
+
+    /// Returns the chunk lengths.
+    #[inline]
+    pub fn pattern(&self) -> &[usize] {
+        &self.pattern
+    }
+
+    /// Splits `data` into chunks, see [`chunked`].
+    #[inline]
+    pub fn apply<'a>(&self, data: &'a [u8]) -> Vec<&'a [u8]> {
+        chunked(data, &self.pattern)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ChunkPlan {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let pattern = u
+            .arbitrary_iter::<u8>()?
+            .take(16)
+            .map(|len| len.map(|len| usize::from(len.max(1))))
+            .collect::<arbitrary::Result<Vec<_>>>()?;
+
+        Ok(Self::new(pattern))
+    }
+}
+
+/// A conformance check for a codec that implements both [`Decoder`] and [`Encoder`].
+///
+/// See the [module](crate::test_util) documentation.
+///
+/// # Example
+///
+/// ```rust
+/// use framez::{codec::lines::StrLines, test_util::Conformance};
+///
+/// # async fn run() {
+/// Conformance::new(StrLines::new())
+///     .check(&["Hello", "", "How are you?"], |item| String::from(item))
+///     .await;
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Conformance<C> {
+    codec: C,
+    buffer_size: usize,
+    chunk_plans: usize,
+    seed: u64,
+}
+
+impl<C> Conformance<C> {
+    /// Creates a new [`Conformance`] check for the `codec`.
+    ///
+    /// Every check starts with a clone of the `codec`.
+    #[inline]
+    pub const fn new(codec: C) -> Self {
+        Self {
+            codec,
+            buffer_size: 1024,
+            chunk_plans: 32,
+            seed: 0,
+        }
+    }
+
+    /// Sets the size of the read and write buffers of the framers. Defaults to `1024`.
+    ///
+    /// Must fit the largest encoded item.
+    #[inline]
+    pub const fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Sets the number of random chunking plans that are checked, in addition to chunks of fixed sizes. Defaults to `32`.
+    #[inline]
+    pub const fn with_chunk_plans(mut self, chunk_plans: usize) -> Self {
+        self.chunk_plans = chunk_plans;
+        self
+    }
+
+    /// Sets the seed of the random chunking plans. Defaults to `0`.
+    #[inline]
+    pub const fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Encodes the `items`, then runs every check on the encoded bytes.
+    ///
+    /// Decoded items are converted using `map` and compared with the `items`.
+    pub async fn check<I, T>(&self, items: &[I], map: fn(C::Item<'_>) -> T)
+    where
+        C: Decoder + Encoder<I> + Clone,
+        <C as Decoder>::Error: Debug,
+        <C as Encoder<I>>::Error: Debug,
+        I: Clone + Debug,
+        T: PartialEq<I> + Debug,
+    {
+        let (encoded, boundaries) = self.encode(items).await;
+
+        self.check_sizes(&encoded);
+
+        let whole: &[&[u8]] = &[&encoded];
+        self.check_decode(whole, items, map).await;
+
+        for len in 1..=encoded.len().min(8) {
+            self.check_decode(&chunked(&encoded, &[len]), items, map)
+                .await;
+        }
+
+        let mut rng = XorShift::new(self.seed);
+
+        for _ in 0..self.chunk_plans {
+            let plan = ChunkPlan::new(
+                (0..rng.next_len(16))
+                    .map(|_| rng.next_len(encoded.len()))
+                    .collect(),
+            );
+
+            self.check_decode(&plan.apply(&encoded), items, map).await;
+        }
+
+        self.check_eof(&encoded, &boundaries, items, map).await;
+    }
+
+    /// Sends every item and returns the encoded bytes, with the end of every frame.
+    async fn encode<I>(&self, items: &[I]) -> (Vec<u8>, Vec<usize>)
+    where
+        C: Encoder<I> + Clone,
+        <C as Encoder<I>>::Error: Debug,
+        I: Clone + Debug,
+    {
+        let buffer = &mut std::vec![0_u8; self.buffer_size];
+        let mut writer = FramedWrite::new(self.codec.clone(), Vec::new(), buffer);
+
+        let mut boundaries = Vec::new();
+
+        for item in items {
+            if let Err(err) = writer.send(item.clone()).await {
+                panic!("Failed to encode {item:?}: {err:?}");
+            }
+
+            boundaries.push(writer.inner().len());
+        }
+
+        let (_, encoded, _) = writer.into_parts();
+
+        (encoded, boundaries)
+    }
+
+    /// Decodes the growing prefixes of `encoded` like the framers do, and checks the reported sizes.
+    fn check_sizes(&self, encoded: &[u8])
+    where
+        C: Decoder + Clone,
+    {
+        let mut codec = self.codec.clone();
+        let mut buffer = encoded.to_vec();
+        let mut consumed = 0;
+
+        for end in 0..=encoded.len() {
+            while let Some(src) = buffer.get_mut(consumed..end) {
+                let available = src.len();
+
+                let Ok(Some((_, size))) = codec.decode(src) else {
+                    break;
+                };
+
+                assert!(
+                    size <= available,
+                    "Decoded a frame of {size} bytes from {available} bytes at {consumed}"
+                );
+
+                if size == 0 {
+                    break;
+                }
+
+                consumed = consumed.saturating_add(size);
+            }
+        }
+
+        if let Some(src) = buffer.get_mut(consumed..) {
+            let available = src.len();
+
+            if let Ok(Some((_, size))) = codec.decode_eof(src) {
+                assert!(
+                    size <= available,
+                    "Decoded a frame of {size} bytes from {available} bytes at EOF"
+                );
+            }
+        }
+    }
+
+    /// Reads the `chunks` and checks that every item is decoded, then the stream ends cleanly.
+    async fn check_decode<I, T>(&self, chunks: &[&[u8]], items: &[I], map: fn(C::Item<'_>) -> T)
+    where
+        C: Decoder + Clone,
+        <C as Decoder>::Error: Debug,
+        I: Debug,
+        T: PartialEq<I> + Debug,
+    {
+        let lens = chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>();
+        let context = format!("Reading chunks of {lens:?} bytes");
+
+        let (decoded, result) = self.decode(chunks, map).await;
+
+        assert_eq!(decoded, items, "{context}");
+
+        if let Err(err) = result {
+            panic!("{context}: Expected EOF, got {err}");
+        }
+    }
+
+    /// Reads every truncation of `encoded` and checks that the stream ends cleanly only at a frame boundary.
+    async fn check_eof<I, T>(
+        &self,
+        encoded: &[u8],
+        boundaries: &[usize],
+        items: &[I],
+        map: fn(C::Item<'_>) -> T,
+    ) where
+        C: Decoder + Clone,
+        <C as Decoder>::Error: Debug,
+        I: Debug,
+        T: PartialEq<I> + Debug,
+    {
+        for end in 0..encoded.len() {
+            let complete = boundaries
+                .iter()
+                .filter(|boundary| **boundary <= end)
+                .count();
+            let at_boundary = end == 0 || boundaries.contains(&end);
+
+            let chunks: &[&[u8]] = &[encoded.get(..end).unwrap_or_default()];
+            let (decoded, result) = self.decode(chunks, map).await;
+
+            let context = format!("Reading {end} of {} bytes", encoded.len());
+
+            assert_eq!(
+                decoded,
+                items.get(..complete).unwrap_or_default(),
+                "{context}"
+            );
+
+            match result {
+                Ok(()) if !at_boundary => panic!("{context}: Expected an error, got EOF"),
+                Err(err) if at_boundary => panic!("{context}: Expected EOF, got {err}"),
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads the `chunks` until EOF or the first error.
+    async fn decode<T>(
+        &self,
+        chunks: &[&[u8]],
+        map: fn(C::Item<'_>) -> T,
+    ) -> (Vec<T>, Result<(), std::string::String>)
+    where
+        C: Decoder + Clone,
+        <C as Decoder>::Error: Debug,
+    {
+        let buffer = &mut std::vec![0_u8; self.buffer_size];
+        let mut reader = FramedRead::new(self.codec.clone(), ScriptedReader::new(chunks), buffer);
+
+        let mut decoded = Vec::new();
+
+        while let Some(item) = next!(reader) {
+            match item {
+                Ok(item) => decoded.push(map(item)),
+                Err(err) => return (decoded, Err(format!("{err:?}"))),
+            }
+        }
+
+        (decoded, Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{string::String, vec::Vec};
+
+    use crate::{
+        codec::{cobs::Cobs, delimiter::Delimiter, lines::StrLines},
+        tests::init_tracing,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn built_in_codecs_conform() {
+        init_tracing();
+
+        Conformance::new(StrLines::new())
+            .check(&["Hello", "", "How are you?"], |item| String::from(item))
+            .await;
+
+        let items: &[&[u8]] = &[b"\x11\x22\0\x33", b"", b"\0", &[0xAA; 300]];
+
+        Conformance::new(Cobs::new())
+            .check(items, |item| Vec::from(&*item))
+            .await;
+
+        Conformance::new(Delimiter::new(b"#\n#"))
+            .with_seed(7)
+            .check(&[b"Hello".as_slice(), b"", b"How are you?"], |item| {
+                Vec::from(item)
+            })
+            .await;
+    }
+
+    #[test]
+    fn chunked_repeats_pattern() {
+        assert_eq!(chunked(b"Hello", &[2, 0]), [b"He".as_slice(), b"l", b"lo"]);
+        assert_eq!(chunked(b"Hello", &[]), [b"Hello".as_slice()]);
+        assert!(chunked(b"", &[1]).is_empty());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_chunk_plan() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        let plan = ChunkPlan::arbitrary(&mut Unstructured::new(&bytes)).expect("Must generate");
+
+        assert!(plan.pattern().len() <= 16);
+        assert!(plan.pattern().iter().all(|len| *len >= 1));
+    }
+}
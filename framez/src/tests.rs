@@ -1,5 +1,7 @@
 #![allow(missing_docs)]
 
+use std::vec::Vec;
+
 pub fn init_tracing() {
     tracing::subscriber::set_global_default(
         tracing_subscriber::fmt::Subscriber::builder()
@@ -9,17 +11,10 @@ pub fn init_tracing() {
     .ok();
 }
 
-macro_rules! framed_read {
-    ($items:ident, $expected:ident, $decoder:ident) => {
-        framed_read!($items, $expected, $decoder, 1024, 1024);
-    };
-    ($items:ident, $expected:ident, $decoder:ident, $buffer_size:literal) => {
-        framed_read!($items, $expected, $decoder, $buffer_size, 1024);
-    };
-    ($items:ident, $expected:ident, $decoder:ident, $buffer_size:literal $(, $err:ident )?) => {
-        framed_read!($items, $expected, $decoder, $buffer_size, 1024 $(, $err )?);
-    };
-    ($items:ident, $expected:ident, $decoder:ident, $buffer_size:literal, $duplex_max_size:literal $(, $err:ident )?) => {
+/// Reads `$items` through a framer created with `$framer::new` using the `$next` macro,
+/// collects every item using `$collect` and compares the collected items with `$expected`.
+macro_rules! framed_read_with {
+    ($framer:path, $next:ident, $collect:expr, $items:ident, $expected:ident, $decoder:ident, $buffer_size:literal, $duplex_max_size:literal $(, $err:ident )?) => {
         let decoder_clone = $decoder.clone();
         let mut collected = Vec::<Vec<u8>>::new();
 
@@ -32,13 +27,12 @@ macro_rules! framed_read {
         });
 
         let buffer = &mut [0_u8; $buffer_size];
-        let mut framer =
-            crate::FramedRead::new(decoder_clone, embedded_io_adapters::tokio_1::FromTokio::new(read), buffer);
+        let mut framer = <$framer>::new(decoder_clone, embedded_io_adapters::tokio_1::FromTokio::new(read), buffer);
 
-        while let Some(item) = $crate::next!(framer) {
+        while let Some(item) = $crate::$next!(framer) {
             match item {
                 Ok(item) => {
-                    collected.push(item.into());
+                    collected.push($collect(item));
                 }
                 Err(_err) => {
                     #[cfg(not(feature = "defmt"))]
@@ -57,6 +51,43 @@ macro_rules! framed_read {
     };
 }
 
+macro_rules! framed_read {
+    ($items:ident, $expected:ident, $decoder:ident) => {
+        framed_read!($items, $expected, $decoder, 1024, 1024);
+    };
+    ($items:ident, $expected:ident, $decoder:ident, $buffer_size:literal) => {
+        framed_read!($items, $expected, $decoder, $buffer_size, 1024);
+    };
+    ($items:ident, $expected:ident, $decoder:ident, $buffer_size:literal $(, $err:ident )?) => {
+        framed_read!($items, $expected, $decoder, $buffer_size, 1024 $(, $err )?);
+    };
+    ($items:ident, $expected:ident, $decoder:ident, $buffer_size:literal, $duplex_max_size:literal $(, $err:ident )?) => {
+        crate::tests::framed_read_with!(
+            crate::FramedRead<_, _>,
+            next,
+            |item| Vec::<u8>::from(item),
+            $items, $expected, $decoder, $buffer_size, $duplex_max_size $(, $err )?
+        );
+    };
+}
+
+macro_rules! framed_read_ring {
+    ($items:ident, $expected:ident, $decoder:ident) => {
+        framed_read_ring!($items, $expected, $decoder, 1024, 1024);
+    };
+    ($items:ident, $expected:ident, $decoder:ident, $buffer_size:literal $(, $err:ident )?) => {
+        framed_read_ring!($items, $expected, $decoder, $buffer_size, 1024 $(, $err )?);
+    };
+    ($items:ident, $expected:ident, $decoder:ident, $buffer_size:literal, $duplex_max_size:literal $(, $err:ident )?) => {
+        crate::tests::framed_read_with!(
+            crate::RingFramedRead<_, _>,
+            next_ring,
+            |item: crate::decode::RingSlice<'_>| item.iter().collect::<Vec<u8>>(),
+            $items, $expected, $decoder, $buffer_size, $duplex_max_size $(, $err )?
+        );
+    };
+}
+
 macro_rules! sink_stream {
     ($encoder:ident, $decoder:ident, $items:ident, $map:ident) => {
         let items_clone = $items.clone();
@@ -100,4 +131,289 @@ macro_rules! sink_stream {
 }
 
 pub(crate) use framed_read;
+pub(crate) use framed_read_ring;
+pub(crate) use framed_read_with;
 pub(crate) use sink_stream;
+
+/// Returns every input over the `alphabet` with up to `max_len` bytes, including the empty one.
+fn inputs(alphabet: &[u8], max_len: usize) -> Vec<Vec<u8>> {
+    let mut inputs = Vec::from([Vec::new()]);
+    let mut last = inputs.clone();
+
+    for _ in 0..max_len {
+        last = last
+            .iter()
+            .flat_map(|input| {
+                alphabet.iter().map(move |byte| {
+                    let mut input = input.clone();
+                    input.push(*byte);
+                    input
+                })
+            })
+            .collect();
+
+        inputs.extend(last.iter().cloned());
+    }
+
+    inputs
+}
+
+/// Feeds every input over a small alphabet through the built-in codecs.
+///
+/// A panic fails the test. The denied clippy lints in `lib.rs` guard the indexing, this guards the arithmetic.
+mod panic_free {
+    use crate::{
+        codec::{
+            bytes::Bytes,
+            cobs::Cobs,
+            delimiter::Delimiter,
+            lines::{Lines, StrLines},
+        },
+        decode::{Decoder, RingDecoder},
+        encode::Encoder,
+    };
+
+    const ALPHABET: &[u8] = b"a#\r\n\0";
+    const MAX_LEN: usize = 6;
+
+    /// Decodes the growing prefixes of `input` like `maybe_next` does.
+    fn decode<D>(mut decoder: D, input: &[u8])
+    where
+        D: Decoder,
+    {
+        let mut buffer = input.to_vec();
+        let mut consumed = 0;
+
+        for end in 0..=input.len() {
+            while let Ok(Some((_, size))) = decoder.decode(&mut buffer[consumed..end]) {
+                assert!(size <= end - consumed);
+
+                if size == 0 {
+                    break;
+                }
+
+                consumed += size;
+            }
+        }
+
+        if let Ok(Some((_, size))) = decoder.decode_eof(&mut buffer[consumed..]) {
+            assert!(size <= input.len() - consumed);
+        }
+    }
+
+    /// Decodes `input` wrapped around at every possible index.
+    fn decode_ring<D>(mut decoder: D, input: &[u8])
+    where
+        D: RingDecoder + Clone,
+    {
+        for split in 0..=input.len() {
+            let (head, tail) = input.split_at(split);
+            let (mut head, mut tail) = (head.to_vec(), tail.to_vec());
+
+            if let Ok(Some((_, size))) = decoder.clone().decode_ring(&mut head, &mut tail) {
+                assert!(size <= input.len());
+            }
+
+            if let Ok(Some((_, size))) = decoder.decode_ring_eof(&mut head, &mut tail) {
+                assert!(size <= input.len());
+            }
+        }
+    }
+
+    /// Encodes `item` into buffers of every size up to the required one.
+    fn encode<'a, E>(mut encoder: E, item: &'a [u8])
+    where
+        E: Encoder<&'a [u8]>,
+    {
+        let mut buffer = std::vec![0; item.len() + 4];
+
+        for len in 0..=buffer.len() {
+            if let Ok(size) = encoder.encode(item, &mut buffer[..len]) {
+                assert!(size <= len);
+            }
+        }
+    }
+
+    #[test]
+    fn codecs_do_not_panic() {
+        for input in super::inputs(ALPHABET, MAX_LEN) {
+            decode(Bytes::new(), &input);
+            decode(Cobs::new(), &input);
+            decode(Lines::new(), &input);
+            decode(StrLines::new(), &input);
+            decode(Delimiter::new(b""), &input);
+            decode(Delimiter::new(b"#"), &input);
+            decode(Delimiter::new(b"\r\n"), &input);
+            decode(Delimiter::new(b"#\n#"), &input);
+
+            decode_ring(Bytes::new(), &input);
+            decode_ring(Lines::new(), &input);
+            decode_ring(Delimiter::new(b""), &input);
+            decode_ring(Delimiter::new(b"\r\n"), &input);
+            decode_ring(Delimiter::new(b"#\n#"), &input);
+
+            encode(Bytes::new(), &input);
+            encode(Cobs::new(), &input);
+            encode(Lines::new(), &input);
+            encode(Delimiter::new(b""), &input);
+            encode(Delimiter::new(b"\r\n"), &input);
+        }
+    }
+}
+
+/// Feeds every input over a small alphabet to the built-in codecs and to their [`tokio_util`] equivalents, and compares the frames.
+///
+/// The [`tokio_util`] line and delimiter codecs return the unterminated rest of a stream as a last frame.
+/// The framers report it with [`ReadError::BytesRemainingOnStream`](crate::ReadError::BytesRemainingOnStream) instead, so both are compared as [`End::Rest`].
+/// Some [`tokio_util`] codecs end cleanly, although bytes after the last frame were not returned: [`LinesCodec`] drops a lone `\r`,
+/// and [`LengthDelimitedCodec`] drops the header of a truncated frame. The framers report these bytes as remaining too.
+#[cfg(feature = "tokio-util")]
+mod differential {
+    use std::{string::String, vec::Vec};
+
+    use bytes::BytesMut;
+    use tokio_util::codec::{AnyDelimiterCodec, LengthDelimitedCodec, LinesCodec};
+
+    use crate::{
+        FramedRead, ReadError,
+        codec::{
+            delimiter::Delimiter,
+            lines::{Lines, StrLines},
+            tokio_util::FromTokioUtil,
+        },
+        decode::Decoder,
+        mock::ScriptedReader,
+        next,
+    };
+
+    const ALPHABET: &[u8] = b"a#\r\n\0\x01\xFF";
+    const MAX_LEN: usize = 5;
+
+    /// How a stream ended after its last frame.
+    #[derive(Debug, PartialEq)]
+    enum End {
+        /// At a frame boundary.
+        Clean,
+        /// With bytes that do not form a complete frame, returned as a last frame or reported as remaining.
+        Rest,
+        /// With an error of the decoder.
+        Error,
+    }
+
+    /// Decodes `input` like [`tokio_util::codec::FramedRead`] does.
+    fn tokio_util_frames<D>(
+        mut decoder: D,
+        input: &[u8],
+        map: fn(D::Item) -> Vec<u8>,
+    ) -> (Vec<Vec<u8>>, End)
+    where
+        D: tokio_util::codec::Decoder,
+    {
+        let mut src = BytesMut::from(input);
+        let mut frames = Vec::new();
+        let mut frames_end = 0;
+
+        loop {
+            match decoder.decode(&mut src) {
+                Ok(Some(frame)) => {
+                    frames.push(map(frame));
+                    frames_end = input.len() - src.len();
+                }
+                Ok(None) => break,
+                Err(_) => return (frames, End::Error),
+            }
+        }
+
+        match decoder.decode_eof(&mut src) {
+            Ok(None) if frames_end == input.len() => (frames, End::Clean),
+            Ok(_) => (frames, End::Rest),
+            Err(_) => (frames, End::Error),
+        }
+    }
+
+    /// Decodes `input` read in chunks of at most `max_read` bytes.
+    async fn framez_frames<D>(
+        decoder: D,
+        input: &[u8],
+        max_read: usize,
+        map: fn(D::Item<'_>) -> Vec<u8>,
+    ) -> (Vec<Vec<u8>>, End)
+    where
+        D: Decoder,
+    {
+        let chunks: &[&[u8]] = &[input];
+        let read = ScriptedReader::new(chunks).with_max_read(max_read);
+
+        let buffer = &mut [0_u8; 64];
+        let mut reader = FramedRead::new(decoder, read, buffer);
+
+        let mut frames = Vec::new();
+
+        while let Some(frame) = next!(reader) {
+            match frame {
+                Ok(frame) => frames.push(map(frame)),
+                Err(ReadError::BytesRemainingOnStream) => return (frames, End::Rest),
+                Err(_) => return (frames, End::Error),
+            }
+        }
+
+        (frames, End::Clean)
+    }
+
+    #[tokio::test]
+    async fn codecs_match_tokio_util() {
+        for input in super::inputs(ALPHABET, MAX_LEN) {
+            let utf8 = core::str::from_utf8(&input).is_ok();
+
+            let lines = tokio_util_frames(LinesCodec::new(), &input, String::into_bytes);
+            let any_delimiter = tokio_util_frames(
+                AnyDelimiterCodec::new(b"#".to_vec(), b"#".to_vec()),
+                &input,
+                |frame| frame.to_vec(),
+            );
+            let length_delimited =
+                tokio_util_frames(LengthDelimitedCodec::new(), &input, |frame| frame.to_vec());
+
+            for max_read in [1, 2, usize::MAX] {
+                let context = (&input, max_read);
+
+                // `LinesCodec` only decodes valid UTF-8.
+                if utf8 {
+                    let frames = framez_frames(Lines::new(), &input, max_read, |frame: &[u8]| {
+                        frame.to_vec()
+                    })
+                    .await;
+                    assert_eq!(frames, lines, "Lines: {context:?}");
+                }
+
+                let (frames, end) =
+                    framez_frames(StrLines::new(), &input, max_read, |frame| Vec::from(frame))
+                        .await;
+
+                // `LinesCodec` fails on an invalid rest, which `StrLines` reports as remaining.
+                if utf8 {
+                    assert_eq!((frames, end), lines, "StrLines: {context:?}");
+                } else {
+                    assert_eq!(frames, lines.0, "StrLines: {context:?}");
+                    assert_ne!(end, End::Clean, "StrLines: {context:?}");
+                }
+
+                let frames =
+                    framez_frames(Delimiter::new(b"#"), &input, max_read, |frame: &[u8]| {
+                        frame.to_vec()
+                    })
+                    .await;
+                assert_eq!(frames, any_delimiter, "Delimiter: {context:?}");
+
+                let frames = framez_frames(
+                    FromTokioUtil::new(LengthDelimitedCodec::new()),
+                    &input,
+                    max_read,
+                    |frame| frame.to_vec(),
+                )
+                .await;
+                assert_eq!(frames, length_delimited, "FromTokioUtil: {context:?}");
+            }
+        }
+    }
+}
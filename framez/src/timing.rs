@@ -0,0 +1,434 @@
+//! Bus timing around frame boundaries.
+
+/// Silent intervals around frames, e.g. the T3.5 interval of Modbus RTU or the turnaround time of half-duplex instruments.
+///
+/// - On the write path, [`send_gapped`](crate::functions::send_gapped) keeps the bus silent for the [`gap_us`](Timing::gap_us) after every frame.
+/// - On the read path, [`next_quiet`](crate::functions::next_quiet) treats the bus being quiet for the [`quiet_us`](Timing::quiet_us) as the end of a frame.
+/// - On the read path, [`next_deadline`](crate::functions::next_deadline) discards a frame that did not complete within the [`frame_deadline_us`](Timing::frame_deadline_us).
+/// - On the read path, [`next_idle`](crate::functions::next_idle) gives up once no bytes arrived for the [`idle_us`](Timing::idle_us).
+///
+/// The intervals are measured using the [`DelayNs`](embedded_hal_async::delay::DelayNs).
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Timing<D> {
+    /// The delay used to measure the intervals.
+    delay: D,
+    /// The silent interval after every sent frame in microseconds.
+    gap_us: u32,
+    /// The silent interval that ends a received frame in microseconds.
+    quiet_us: u32,
+    /// The time a received frame may take to complete in microseconds.
+    frame_deadline_us: u32,
+    /// The time the link may stay silent before it is considered idle in microseconds.
+    idle_us: u32,
+}
+
+impl<D> Timing<D> {
+    /// Creates a new [`Timing`] with the given `delay` and no silent intervals.
+    #[inline]
+    pub const fn new(delay: D) -> Self {
+        Self {
+            delay,
+            gap_us: 0,
+            quiet_us: 0,
+            frame_deadline_us: 0,
+            idle_us: 0,
+        }
+    }
+
+    /// Sets the silent interval after every sent frame in microseconds.
+    ///
+    /// E.g. the inter-frame gap of the protocol, or the time the bus needs to turn around before the peer may respond.
+    #[inline]
+    pub const fn with_gap_us(mut self, gap_us: u32) -> Self {
+        self.gap_us = gap_us;
+        self
+    }
+
+    /// Sets the silent interval that ends a received frame in microseconds.
+    ///
+    /// E.g. `35 * 1_000_000 / 9600` for the T3.5 interval of Modbus RTU at 9600 baud with 10 bits per byte.
+    #[inline]
+    pub const fn with_quiet_us(mut self, quiet_us: u32) -> Self {
+        self.quiet_us = quiet_us;
+        self
+    }
+
+    /// Sets the time a received frame may take to complete in microseconds, measured from its first byte.
+    ///
+    /// Defends against slow-drip peers that hold buffer space indefinitely by sending a frame byte by byte.
+    #[inline]
+    pub const fn with_frame_deadline_us(mut self, frame_deadline_us: u32) -> Self {
+        self.frame_deadline_us = frame_deadline_us;
+        self
+    }
+
+    /// Sets the time the link may stay silent before it is considered idle in microseconds.
+    ///
+    /// Unlike the [`frame_deadline_us`](Timing::frame_deadline_us), it is measured between any bytes, e.g. to detect an unplugged sensor and reset the link.
+    #[inline]
+    pub const fn with_idle_us(mut self, idle_us: u32) -> Self {
+        self.idle_us = idle_us;
+        self
+    }
+
+    /// Returns the silent interval after every sent frame in microseconds.
+    #[inline]
+    pub const fn gap_us(&self) -> u32 {
+        self.gap_us
+    }
+
+    /// Returns the silent interval that ends a received frame in microseconds.
+    #[inline]
+    pub const fn quiet_us(&self) -> u32 {
+        self.quiet_us
+    }
+
+    /// Returns the time a received frame may take to complete in microseconds.
+    #[inline]
+    pub const fn frame_deadline_us(&self) -> u32 {
+        self.frame_deadline_us
+    }
+
+    /// Returns the time the link may stay silent before it is considered idle in microseconds.
+    #[inline]
+    pub const fn idle_us(&self) -> u32 {
+        self.idle_us
+    }
+
+    /// Returns reference to the delay.
+    #[inline]
+    pub const fn delay(&self) -> &D {
+        &self.delay
+    }
+
+    /// Returns mutable reference to the delay.
+    #[inline]
+    pub const fn delay_mut(&mut self) -> &mut D {
+        &mut self.delay
+    }
+
+    /// Consumes the [`Timing`] and returns the delay.
+    #[inline]
+    pub fn into_delay(self) -> D {
+        self.delay
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::{convert::Infallible, time::Duration};
+    use std::vec::Vec;
+
+    use embedded_hal_async::delay::DelayNs;
+    use embedded_io_adapters::tokio_1::FromTokio;
+    use tokio::io::AsyncWriteExt;
+
+    use crate::{
+        FramedRead, FramedWrite, ReadError, codec::lines::Lines, decode::Decoder,
+        mock::RecordingDelay, tests::init_tracing,
+    };
+
+    use super::*;
+
+    /// Sleeps using tokio.
+    struct TokioDelay;
+
+    impl DelayNs for TokioDelay {
+        async fn delay_ns(&mut self, ns: u32) {
+            tokio::time::sleep(Duration::from_nanos(ns as u64)).await;
+        }
+    }
+
+    /// Frames end only at the end of the stream. Frames are at least 2 bytes long.
+    struct Silence;
+
+    impl Decoder for Silence {
+        type Item<'buf> = &'buf [u8];
+        type Error = Infallible;
+
+        fn decode<'buf>(
+            &mut self,
+            _src: &'buf mut [u8],
+        ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+            Ok(None)
+        }
+
+        fn decode_eof<'buf>(
+            &mut self,
+            src: &'buf mut [u8],
+        ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+            if src.len() < 2 {
+                return Ok(None);
+            }
+
+            let len = src.len();
+
+            Ok(Some((src, len)))
+        }
+    }
+
+    #[tokio::test]
+    async fn quiet_bus_ends_frames() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        let writer = async move {
+            for chunk in [b"a".as_slice(), b"b", b"cd", b"e", b"fg"] {
+                write.write_all(chunk).await.expect("Must write");
+
+                // Frames are separated by a long silence, the bytes of a frame by a short one.
+                let silence = if chunk.len() == 2 { 50 } else { 1 };
+
+                tokio::time::sleep(Duration::from_millis(silence)).await;
+            }
+        };
+
+        let reader = async move {
+            let mut timing = Timing::new(TokioDelay).with_quiet_us(20_000);
+
+            let buffer = &mut [0_u8; 16];
+            let mut framer = FramedRead::new(Silence, FromTokio::new(read), buffer);
+
+            let mut frames = Vec::<Vec<u8>>::new();
+
+            while let Some(frame) = framer.next_quiet(&mut timing, |frame| frame.to_vec()).await {
+                frames.push(frame.expect("Must decode"));
+            }
+
+            frames
+        };
+
+        let (_, frames) = tokio::join!(writer, reader);
+
+        assert_eq!(frames, [b"abcd".as_slice(), b"efg"]);
+    }
+
+    /// Frames start with their length, prefix included. A quiet bus ends a frame early.
+    ///
+    /// Items are the frame and whether it was decoded by [`Decoder::decode_eof`].
+    struct Prefixed;
+
+    impl Decoder for Prefixed {
+        type Item<'buf> = (&'buf [u8], bool);
+        type Error = Infallible;
+
+        fn decode<'buf>(
+            &mut self,
+            src: &'buf mut [u8],
+        ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+            let Some(len) = self.decode_header(src) else {
+                return Ok(None);
+            };
+
+            Ok(src.get(..len).map(|frame| ((frame, false), len)))
+        }
+
+        fn decode_eof<'buf>(
+            &mut self,
+            src: &'buf mut [u8],
+        ) -> Result<Option<(Self::Item<'buf>, usize)>, Self::Error> {
+            let Some(len) = self.decode_header(src) else {
+                return Ok(None);
+            };
+
+            let len = len.min(src.len());
+
+            Ok(src.get(..len).map(|frame| ((frame, true), len)))
+        }
+
+        fn decode_header(&mut self, src: &[u8]) -> Option<usize> {
+            src.first().map(|len| *len as usize)
+        }
+    }
+
+    #[tokio::test]
+    async fn quiet_bus_does_not_keep_the_announced_length() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        let writer = async move {
+            // Announces 5 bytes, but the bus goes quiet after 3.
+            write.write_all(b"\x05ab").await.expect("Must write");
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            write.write_all(b"\x02x").await.expect("Must write");
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        };
+
+        let reader = async move {
+            let mut timing = Timing::new(TokioDelay).with_quiet_us(20_000);
+
+            let buffer = &mut [0_u8; 16];
+            let mut framer = FramedRead::new(Prefixed, FromTokio::new(read), buffer);
+
+            let mut frames = Vec::new();
+
+            while let Some(frame) = framer
+                .next_quiet(&mut timing, |(frame, eof)| (frame.to_vec(), eof))
+                .await
+            {
+                frames.push(frame.expect("Must decode"));
+            }
+
+            frames
+        };
+
+        let (_, frames) = tokio::join!(writer, reader);
+
+        // The second frame is measured by its own length, so it is decoded without waiting for the bus to go quiet.
+        assert_eq!(
+            frames,
+            [(b"\x05ab".to_vec(), true), (b"\x02x".to_vec(), false)]
+        );
+    }
+
+    #[tokio::test]
+    async fn incomplete_frame_is_discarded() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        let writer = async move {
+            write.write_all(b"a").await.expect("Must write");
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            write.write_all(b"bc").await.expect("Must write");
+        };
+
+        let reader = async move {
+            let mut timing = Timing::new(TokioDelay).with_quiet_us(10_000);
+
+            let buffer = &mut [0_u8; 16];
+            let mut framer = FramedRead::new(Silence, FromTokio::new(read), buffer);
+
+            assert!(matches!(
+                framer.next_quiet(&mut timing, |frame| frame.to_vec()).await,
+                Some(Err(ReadError::BytesRemainingOnStream))
+            ));
+
+            let frame = framer
+                .next_quiet(&mut timing, |frame| frame.to_vec())
+                .await
+                .expect("Must be some")
+                .expect("Must decode");
+
+            assert_eq!(frame, b"bc");
+        };
+
+        tokio::join!(writer, reader);
+    }
+
+    #[tokio::test]
+    async fn slow_frame_is_discarded() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        let writer = async move {
+            write.write_all(b"Hello\nHe").await.expect("Must write");
+
+            // The peer stalls in the middle of a frame.
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            write.write_all(b"Hey\n").await.expect("Must write");
+        };
+
+        let reader = async move {
+            let mut timing = Timing::new(TokioDelay).with_frame_deadline_us(20_000);
+
+            let buffer = &mut [0_u8; 16];
+            let mut framer = FramedRead::new(Lines::new(), FromTokio::new(read), buffer);
+
+            let mut frames = Vec::new();
+
+            while let Some(frame) = framer
+                .next_deadline(&mut timing, |frame| frame.to_vec())
+                .await
+            {
+                frames.push(frame.map_err(|err| matches!(err, ReadError::FrameDeadline)));
+            }
+
+            frames
+        };
+
+        let (_, frames) = tokio::join!(writer, reader);
+
+        assert_eq!(
+            frames,
+            [Ok(b"Hello".to_vec()), Err(true), Ok(b"Hey".to_vec())]
+        );
+    }
+
+    #[tokio::test]
+    async fn idle_link_is_reported() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        let writer = async move {
+            // Slow, but never idle.
+            for chunk in [b"He".as_slice(), b"llo", b"\nHe"] {
+                write.write_all(chunk).await.expect("Must write");
+
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+
+            // The sensor is unplugged for longer than one idle time, but not two.
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            write.write_all(b"y\n").await.expect("Must write");
+        };
+
+        let reader = async move {
+            let mut timing = Timing::new(TokioDelay).with_idle_us(40_000);
+
+            let buffer = &mut [0_u8; 16];
+            let mut framer = FramedRead::new(Lines::new(), FromTokio::new(read), buffer);
+
+            let mut frames = Vec::new();
+
+            while let Some(frame) = framer.next_idle(&mut timing, |frame| frame.to_vec()).await {
+                frames.push(frame.map_err(|err| matches!(err, ReadError::Idle)));
+            }
+
+            frames
+        };
+
+        let (_, frames) = tokio::join!(writer, reader);
+
+        // The buffered bytes are kept.
+        assert_eq!(
+            frames,
+            [Ok(b"Hello".to_vec()), Err(true), Ok(b"Hey".to_vec())]
+        );
+    }
+
+    #[tokio::test]
+    async fn gap_is_kept_after_every_frame() {
+        init_tracing();
+
+        let mut timing = Timing::new(RecordingDelay::<4>::new()).with_gap_us(1_750);
+
+        let buffer = &mut [0_u8; 16];
+        let mut framer = FramedWrite::new(Lines::new(), Vec::new(), buffer);
+
+        framer
+            .send_gapped(&mut timing, b"Hello".as_slice())
+            .await
+            .expect("Must send");
+
+        framer
+            .send_gapped(&mut timing, b"world!".as_slice())
+            .await
+            .expect("Must send");
+
+        assert_eq!(timing.delay().delays(), [1_750_000, 1_750_000]);
+        assert_eq!(framer.core.inner, b"Hello\r\nworld!\r\n");
+    }
+}
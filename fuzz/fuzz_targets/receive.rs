@@ -9,7 +9,7 @@
 use std::error::Error;
 
 use embedded_io_adapters::tokio_1::FromTokio;
-use framez::{codec::lines::StrLines, next, FramedRead};
+use framez::{codec::lines::StrLines, next, test_util::chunked, FramedRead};
 use libfuzzer_sys::fuzz_target;
 use tokio::{io::AsyncWriteExt, runtime::Runtime};
 
@@ -39,7 +39,7 @@ async fn fuzz(data: &[u8]) -> Result<(), Box<dyn Error>> {
     };
 
     let writer = async move {
-        for chunk in data.to_vec().chunks(SIZE / 3) {
+        for chunk in chunked(data, &[SIZE / 3]) {
             write.write_all(chunk).await?;
             write.flush().await?;
         }
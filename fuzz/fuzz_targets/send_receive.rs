@@ -19,7 +19,7 @@ use framez::{
         delimiter::Delimiter,
         lines::{Lines, StrLines},
     },
-    decode::{DecodeError, Decoder},
+    decode::Decoder,
     encode::Encoder,
     next, FramedRead, FramedWrite,
 };
@@ -60,9 +60,9 @@ async fn fuzz<'data, D, E, F, T>(
 where
     E: Encoder<T> + 'static,
     <E as Encoder<T>>::Error: Error + Display + 'static,
-    D: for<'buf> Decoder<'buf> + 'static,
-    for<'buf> <D as Decoder<'buf>>::Item: 'buf + Debug + PartialEq<T>,
-    <D as DecodeError>::Error: Error + Display + 'static,
+    D: Decoder + 'static,
+    for<'buf> D::Item<'buf>: Debug + PartialEq<T>,
+    <D as Decoder>::Error: Error + Display + 'static,
     F: FnOnce(&'data [u8]) -> Result<T, ()>,
     T: 'data + Clone + Debug + PartialEq,
 {
@@ -0,0 +1,152 @@
+//! Link-time check that the synchronous decode and encode paths can not panic.
+//!
+//! Every function marked with [`no_panic`] fails to link if the compiler can not prove that it never panics.
+//! The checked functions are called from `main`, so they are linked into the binary.
+//! Overflow checks are enabled in both profiles, so an unchecked arithmetic operation fails the build as well.
+//!
+//! ```not_rust
+//! cargo build --manifest-path no-panic/Cargo.toml
+//! ```
+//!
+//! The decoders of [`Lines`](framez::codec::lines::Lines) and [`Delimiter`](framez::codec::delimiter::Delimiter) are not checked.
+//! They search using `memchr`, which selects its implementation at runtime through a function pointer,
+//! so the compiler can not see through the call. They are covered by the exhaustive `codecs_do_not_panic` test instead.
+
+use core::{convert::Infallible, hint::black_box};
+
+use framez::{
+    ReadError,
+    codec::{bytes::Bytes, cobs::Cobs, delimiter::Delimiter, lines::Lines},
+    decode::{Decoder, RingDecoder},
+    encode::Encoder,
+    functions,
+    state::{CommitError, ConsumeError, ReadState, RingReadState},
+};
+use no_panic::no_panic;
+
+/// The length and the consumed size of a decoded frame.
+type Decoded = Option<(usize, usize)>;
+
+#[no_panic]
+fn decode_bytes(codec: &mut Bytes, src: &mut [u8]) -> Decoded {
+    let Ok(Some((item, size))) = codec.decode(src) else {
+        return None;
+    };
+
+    Some((item.len(), size))
+}
+
+#[no_panic]
+fn decode_cobs(codec: &mut Cobs, src: &mut [u8]) -> Decoded {
+    let Ok(Some((item, size))) = codec.decode(src) else {
+        return None;
+    };
+
+    Some((item.len(), size))
+}
+
+#[no_panic]
+fn decode_ring_bytes(codec: &mut Bytes, head: &mut [u8], tail: &mut [u8]) -> Decoded {
+    let Ok(Some((item, size))) = codec.decode_ring(head, tail) else {
+        return None;
+    };
+
+    Some((item.len(), size))
+}
+
+#[no_panic]
+fn decode_pending_bytes(
+    state: &mut ReadState<'_>,
+    codec: &mut Bytes,
+) -> Option<Result<Option<usize>, ReadError<Infallible, Infallible>>> {
+    // The hook is user code, so it may panic.
+    state.on_frame = None;
+
+    functions::decode_pending(state, codec)
+        .map(|result| result.map(|item| item.map(|item| item.len())))
+}
+
+#[no_panic]
+fn decode_pending_cobs(
+    state: &mut ReadState<'_>,
+    codec: &mut Cobs,
+) -> Option<Result<Option<usize>, ReadError<Infallible, Infallible>>> {
+    // The hook is user code, so it may panic.
+    state.on_frame = None;
+
+    functions::decode_pending(state, codec)
+        .map(|result| result.map(|item| item.map(|item| item.len())))
+}
+
+#[no_panic]
+fn encode_lines(codec: &mut Lines, item: &[u8], dst: &mut [u8]) -> Option<usize> {
+    codec.encode(item, dst).ok()
+}
+
+#[no_panic]
+fn encode_delimiter(codec: &mut Delimiter<'_>, item: &[u8], dst: &mut [u8]) -> Option<usize> {
+    codec.encode(item, dst).ok()
+}
+
+#[no_panic]
+fn encode_bytes(codec: &mut Bytes, item: &[u8], dst: &mut [u8]) -> Option<usize> {
+    codec.encode(item, dst).ok()
+}
+
+#[no_panic]
+fn encode_cobs(codec: &mut Cobs, item: &[u8], dst: &mut [u8]) -> Option<usize> {
+    codec.encode(item, dst).ok()
+}
+
+#[no_panic]
+fn commit(state: &mut ReadState<'_>, size: usize) -> Result<(), CommitError> {
+    state.commit(size)
+}
+
+#[no_panic]
+fn ring_commit(state: &mut RingReadState<'_>, size: usize) -> Result<(), CommitError> {
+    state.commit(size)
+}
+
+#[no_panic]
+fn ring_consume(state: &mut RingReadState<'_>, size: usize) -> Result<(), ConsumeError> {
+    state.consume(size)
+}
+
+fn main() {
+    let src = &mut black_box([0_u8; 64]);
+    let tail = &mut black_box([0_u8; 64]);
+    let dst = &mut black_box([0_u8; 64]);
+    let item = black_box(b"Hello".as_slice());
+    let size = black_box(usize::MAX);
+
+    // Every input is opaque, so the checks do not depend on the values known in `main`.
+    black_box(decode_bytes(&mut black_box(Bytes::new()), src));
+    black_box(decode_cobs(&mut black_box(Cobs::new()), src));
+    black_box(decode_ring_bytes(&mut black_box(Bytes::new()), src, tail));
+
+    black_box(encode_lines(&mut black_box(Lines::new()), item, dst));
+    black_box(encode_delimiter(
+        &mut black_box(Delimiter::new(b"\r\n")),
+        item,
+        dst,
+    ));
+    black_box(encode_bytes(&mut black_box(Bytes::new()), item, dst));
+    black_box(encode_cobs(&mut black_box(Cobs::new()), item, dst));
+
+    let buffer = &mut black_box([0_u8; 64]);
+    let mut state = black_box(ReadState::new(buffer));
+
+    black_box(commit(&mut state, size).is_ok());
+    black_box(decode_pending_bytes(
+        &mut state,
+        &mut black_box(Bytes::new()),
+    ));
+    black_box(decode_pending_cobs(&mut state, &mut black_box(Cobs::new())));
+
+    let buffer = &mut black_box([0_u8; 64]);
+    let mut state = black_box(RingReadState::new(buffer));
+
+    black_box(ring_commit(&mut state, size).is_ok());
+    black_box(ring_consume(&mut state, size).is_ok());
+}